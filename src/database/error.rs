@@ -12,6 +12,7 @@
 use std::{error::Error, fmt};
 
 use crate::database::Datatype;
+use crate::model::State;
 
 /* ERROR WRAPPER */
 
@@ -43,6 +44,47 @@ pub enum DatabaseError {
         data: Datatype,
         table: Option<String>,
     },
+
+    /// An error to indicate that there was an attempt to select or delete a
+    /// table that does not exist within a database.
+    MissingTable { table: String },
+
+    /// An error to indicate that there was an attempt to perform an operation
+    /// on the selected table of a database while no table was selected.
+    NoTableSelected,
+
+    /// An error to indicate that there was an attempt to concurrently insert a
+    /// record under a `key` of `table` which had not been reserved beforehand.
+    UnreservedKey { key: State, table: String },
+
+    /// An error to indicate that there was an attempt to concurrently insert a
+    /// record under a `key` of `table` which already had a record.
+    RecordOverwrite { key: State, table: String },
+
+    /// An error to indicate that there was an attempt to persist or load a
+    /// table of a database which was not bound to a directory.
+    UnboundDatabase,
+
+    /// An error to indicate that there was an attempt to bind a database to a
+    /// `path` which is not an existing directory.
+    InvalidDirectory { path: String },
+
+    /// An error to indicate that the persisted contents of `table` could not
+    /// be deserialized, as they are truncated or malformed.
+    CorruptedTable { table: String },
+
+    /// An error to indicate that a database and its client use approximately
+    /// `usage` bytes of memory, which is more than the `limit` they were given
+    /// even after evicting all possible contents from memory.
+    MemoryLimitExceeded { usage: usize, limit: usize },
+
+    /// An error to indicate that the evicted `page` of records of `table` could
+    /// not be loaded back into memory when it was accessed, due to `cause`.
+    UnloadablePage {
+        table: String,
+        page: usize,
+        cause: String,
+    },
 }
 
 impl Error for DatabaseError {}
@@ -135,6 +177,79 @@ impl fmt::Display for DatabaseError {
                     )
                 }
             },
+            Self::MissingTable { table } => {
+                write!(
+                    f,
+                    "Attempted to access a table with identifier '{}', but no \
+                    such table exists in the database.",
+                    table,
+                )
+            },
+            Self::NoTableSelected => {
+                write!(
+                    f,
+                    "Attempted to perform a table operation, but no table is \
+                    currently selected in the database.",
+                )
+            },
+            Self::UnreservedKey { key, table } => {
+                write!(
+                    f,
+                    "Attempted to concurrently insert a record under the key \
+                    {} of table '{}', but the key was not reserved.",
+                    key, table,
+                )
+            },
+            Self::RecordOverwrite { key, table } => {
+                write!(
+                    f,
+                    "Attempted to concurrently insert a record under the key \
+                    {} of table '{}', which already has a record.",
+                    key, table,
+                )
+            },
+            Self::UnboundDatabase => {
+                write!(
+                    f,
+                    "Attempted to access the persisted contents of a table, \
+                    but the database is not bound to any directory.",
+                )
+            },
+            Self::InvalidDirectory { path } => {
+                write!(
+                    f,
+                    "Attempted to bind a database to the path '{}', which is \
+                    not an existing directory.",
+                    path,
+                )
+            },
+            Self::CorruptedTable { table } => {
+                write!(
+                    f,
+                    "The persisted contents of table '{}' are truncated or \
+                    malformed, so the table could not be loaded.",
+                    table,
+                )
+            },
+            Self::MemoryLimitExceeded { usage, limit } => {
+                write!(
+                    f,
+                    "Approximately {} bytes of memory are in use by a database \
+                    and its client, which exceeds the memory limit of {} bytes \
+                    even after evicting all the records it could. A higher \
+                    memory limit (or a path to evict records to) is needed to \
+                    continue.",
+                    usage, limit,
+                )
+            },
+            Self::UnloadablePage { table, page, cause } => {
+                write!(
+                    f,
+                    "The evicted page {} of records of table '{}' could not be \
+                    loaded back into memory ({}), so its records were lost.",
+                    page, table, cause,
+                )
+            },
         }
     }
 }
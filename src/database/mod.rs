@@ -17,12 +17,14 @@ use crate::model::State;
 /* UTILITY MODULES */
 
 mod error;
+mod paging;
 mod util;
 
 /* IMPLEMENTATION MODULES */
 
 pub mod volatile;
 pub mod vector;
+pub mod sharded;
 pub mod lsmt;
 
 /* DATABASE PARAMETERS */
@@ -41,7 +43,7 @@ pub enum Persistence<'a> {
 pub trait KVStore<R: Record> {
     fn put(&mut self, key: State, record: &R);
     fn get(&self, key: State) -> Option<&BitSlice<u8, Msb0>>;
-    fn del(&self, key: State);
+    fn del(&mut self, key: State);
 }
 
 /* FEATURE ADDITIONS */
@@ -52,9 +54,60 @@ pub trait KVStore<R: Record> {
 /// consistency. In terms of file structure, each implementation decides how to
 /// organize its persistent content. The only overarching requisite is that it
 /// be provided an existing directory's path.
+///
+/// Persisting a table through `materialize` should be atomic, such that if the
+/// program is interrupted while doing so, loading the table later yields either
+/// its previous or its new contents (and never a mix of both).
 pub trait Persistent {
-    fn bind_path(&self, path: &Path) -> Result<()>;
-    fn materialize(&self) -> Result<()>;
+    fn bind_path(&mut self, path: &Path) -> Result<()>;
+    fn materialize(&mut self) -> Result<()>;
+    fn load_table(&mut self, id: &str) -> Result<()>;
+}
+
+/// Allows a database to keep track of the approximate amount of memory used by
+/// the contents it holds in memory, and to reduce it by evicting contents to
+/// persistent media. The tables which are not selected are evicted as a whole,
+/// and are loaded back into memory once they are selected again. The selected
+/// table is instead evicted one page of records at a time, starting from the
+/// pages which were accessed the longest time ago, and each page is loaded back
+/// into memory once any of its records are accessed again (see `paging`).
+pub trait Budgeted
+where
+    Self: Persistent,
+{
+    /// Returns the approximate number of bytes used by the contents of this
+    /// database which are currently held in memory.
+    fn usage(&self) -> usize;
+
+    /// Persists the contents of the tables which are not selected through
+    /// `materialize` and evicts them from memory, and then evicts pages of the
+    /// selected table until at most `target` bytes are used (or there are no
+    /// more pages to evict). Does nothing if the database is not bound to a
+    /// path.
+    fn evict(&mut self, target: usize) -> Result<()>;
+
+    /// Fails if any contents of this database were lost since it was created,
+    /// such as when evicted contents could not be loaded back into memory as
+    /// they were accessed (which `KVStore` operations cannot report).
+    fn check(&self) -> Result<()>;
+
+    /// Evicts contents from memory (see `evict`) down to half of `limit` bytes
+    /// if the memory used by this database plus `external` bytes (used by its
+    /// client) comes near `limit`. Fails if the memory used exceeds `limit`
+    /// even after evicting, or if any contents were lost (see `check`).
+    fn enforce(&mut self, limit: usize, external: usize) -> Result<()> {
+        self.check()?;
+        if self.usage() + external < limit - limit / 10 {
+            return Ok(());
+        }
+
+        self.evict((limit / 2).saturating_sub(external))?;
+        let usage = self.usage() + external;
+        if usage > limit {
+            Err(error::DatabaseError::MemoryLimitExceeded { usage, limit })?
+        }
+        Ok(())
+    }
 }
 
 /// Allows for grouping data into collections of fixed-length records called
@@ -62,10 +115,18 @@ pub trait Persistent {
 /// a database should be optimized for inter-table operations. In fact, this
 /// interface's semantics are such that its implementations optimize performance
 /// for cases of sequential operations on a single table.
+///
+/// Tables can also hold textual metadata under string keys, which is meant to
+/// describe how their contents were generated (e.g., the parameters a solver
+/// was run with), so that they can be reproduced. Metadata operations act on
+/// the currently selected table.
 pub trait Tabular {
-    fn create_table(&self, id: &str, schema: Schema) -> Result<()>;
-    fn select_table(&self, id: &str) -> Result<()>;
-    fn delete_table(&self, id: &str) -> Result<()>;
+    fn create_table(&mut self, id: &str, schema: Schema) -> Result<()>;
+    fn select_table(&mut self, id: &str) -> Result<()>;
+    fn delete_table(&mut self, id: &str) -> Result<()>;
+    fn set_metadata(&mut self, key: &str, value: &str) -> Result<()>;
+    fn get_metadata(&self, key: &str) -> Option<&str>;
+    fn schema(&self) -> Option<&Schema>;
 }
 
 /// Allows a database implementation to read raw data from a record buffer.
@@ -181,6 +242,7 @@ impl SchemaBuilder {
     pub fn add(mut self, attr: Attribute) -> Result<Self> {
         util::check_attribute_validity(&self.attributes, &attr)?;
         self.size += attr.size();
+        self.attributes.push(attr);
         Ok(self)
     }
 
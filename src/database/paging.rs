@@ -0,0 +1,587 @@
+//! # Database Paging Module
+//!
+//! This module provides the means for in-memory databases to split the records
+//! of a table into pages, such that the pages which have gone unused for the
+//! longest can be evicted to files individually (see `Budgeted`). An evicted
+//! page is loaded back into memory the next time it is accessed, even through
+//! a shared reference, so records can still be borrowed from any page.
+//!
+//! The files of evicted pages are not part of a persisted table; they are only
+//! kept while their pages are out of memory, and are read from when the table
+//! is materialized so that it is persisted along with the rest of its records.
+//!
+//! Since records are accessed through the infallible `KVStore` interface, an
+//! evicted page which cannot be loaded back (or a record stored while no table
+//! is selected) is recorded as a fault of the database instead, which is then
+//! reported by `Budgeted::check` and by every subsequent call to `enforce` or
+//! `materialize`.
+
+use anyhow::{Context, Result};
+use bitvec::{order::Msb0, slice::BitSlice, vec::BitVec};
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::iter;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use crate::database::error::DatabaseError;
+use crate::database::{util, Schema};
+use crate::database::{Budgeted, KVStore, Persistent, Record, Tabular};
+use crate::model::State;
+
+/* DEFINITIONS */
+
+/// A subset of the records of a table, each of which is `width` bits long.
+pub trait Page: Sized {
+    /// Returns the index of the page of a table which houses the record of
+    /// `key`.
+    fn index(key: State) -> usize;
+
+    /// Returns the page at `index` of a table with `width`-bit records, which
+    /// holds no records.
+    fn empty(index: usize, width: usize) -> Self;
+
+    /// Stores `record` under `key` in this page, replacing any previous one,
+    /// or removes the record under `key` if `record` is `None`.
+    fn set(&mut self, key: State, record: Option<&BitSlice<u8, Msb0>>);
+
+    /// Returns the record stored under `key` in this page, if there is one.
+    fn get(&self, key: State) -> Option<&BitSlice<u8, Msb0>>;
+
+    /// Returns the records held in this page along with their keys.
+    fn records(&self) -> impl Iterator<Item = (State, &BitSlice<u8, Msb0>)>;
+
+    /// Returns the approximate number of bytes used by this page in memory.
+    fn usage(&self) -> usize;
+}
+
+/// The tables of an in-memory database whose records are split into pages of
+/// type **P**, which can be persisted to files in the `directory` that the
+/// database is bound to. If a record was stored or deleted while no table was
+/// `selected`, the database is marked as `unselected` (see `Budgeted::check`).
+pub struct Tables<P> {
+    tables: HashMap<String, Table<P>>,
+    selected: Option<String>,
+    directory: Option<PathBuf>,
+    unselected: bool,
+}
+
+/// A table whose records are held in `pages`, which is `dirty` if it has
+/// changes that were not persisted yet, and `evicted` if its pages were
+/// dropped from memory after persisting it.
+struct Table<P> {
+    dirty: bool,
+    evicted: bool,
+    schema: Schema,
+    metadata: HashMap<String, String>,
+    pages: Pages<P>,
+}
+
+/// The pages of a table with `width`-bit records, some of which may have been
+/// evicted to files in the `spill` directory under the table's ID. The `clock`
+/// is advanced every time a page is accessed, and the `resident` bytes are the
+/// usage of the pages which are held in memory. The first evicted page which
+/// could not be loaded back is kept as a `fault`, along with the reason why.
+struct Pages<P> {
+    width: usize,
+    slots: Vec<Slot<P>>,
+    spill: Option<(PathBuf, String)>,
+    clock: AtomicU64,
+    resident: AtomicUsize,
+    fault: OnceLock<(usize, String)>,
+}
+
+/// A page which is evicted if it is not set, along with the time that it was
+/// last `used` according to the clock of its table.
+struct Slot<P> {
+    page: OnceLock<P>,
+    used: AtomicU64,
+}
+
+/* TABLES IMPLEMENTATION */
+
+impl<P: Page> Tables<P> {
+    /// Returns a database without any tables, which is not bound to a path.
+    pub fn new() -> Self {
+        Self {
+            tables: HashMap::new(),
+            selected: None,
+            directory: None,
+            unselected: false,
+        }
+    }
+
+    /// Adds empty pages to the selected table until it has `count` pages, so
+    /// that no reallocations are needed when storing the records they house.
+    pub fn extend(&mut self, count: usize) -> Result<()> {
+        self.table_mut()?
+            .pages
+            .extend(count);
+        Ok(())
+    }
+
+    fn table(&self) -> Option<&Table<P>> {
+        self.selected
+            .as_ref()
+            .and_then(|id| self.tables.get(id))
+    }
+
+    fn table_mut(&mut self) -> Result<&mut Table<P>, DatabaseError> {
+        self.selected
+            .as_ref()
+            .and_then(|id| self.tables.get_mut(id))
+            .ok_or(DatabaseError::NoTableSelected)
+    }
+}
+
+impl<R: Record, P: Page> KVStore<R> for Tables<P> {
+    fn put(&mut self, key: State, value: &R) {
+        match self.table_mut() {
+            Ok(table) => {
+                if table
+                    .pages
+                    .update(P::index(key), |page| {
+                        page.set(key, Some(value.raw()))
+                    })
+                    .is_some()
+                {
+                    table.dirty = true;
+                }
+            },
+            Err(_) => self.unselected = true,
+        }
+    }
+
+    fn get(&self, key: State) -> Option<&BitSlice<u8, Msb0>> {
+        self.table()?
+            .pages
+            .get(P::index(key))?
+            .get(key)
+    }
+
+    fn del(&mut self, key: State) {
+        match self.table_mut() {
+            Ok(table) => {
+                if table
+                    .pages
+                    .get(P::index(key))
+                    .is_some()
+                {
+                    table
+                        .pages
+                        .update(P::index(key), |page| page.set(key, None));
+                    table.dirty = true;
+                }
+            },
+            Err(_) => self.unselected = true,
+        }
+    }
+}
+
+impl<P: Page> Persistent for Tables<P> {
+    fn bind_path(&mut self, path: &Path) -> Result<()> {
+        util::check_directory(path)?;
+        self.directory = Some(path.to_owned());
+        for table in self.tables.values_mut() {
+            table.dirty = !table.evicted;
+        }
+        Ok(())
+    }
+
+    fn materialize(&mut self) -> Result<()> {
+        self.check()?;
+        let directory = self
+            .directory
+            .as_ref()
+            .ok_or(DatabaseError::UnboundDatabase)?;
+
+        for (id, table) in &mut self.tables {
+            if table.dirty {
+                let mut failure = None;
+                let bytes = util::encode_table(
+                    &table.schema,
+                    &table.metadata,
+                    table
+                        .pages
+                        .records()
+                        .map_while(|record| {
+                            record
+                                .map_err(|error| failure = Some(error))
+                                .ok()
+                        }),
+                );
+                if let Some(error) = failure {
+                    return Err(error);
+                }
+                util::write_atomically(
+                    &util::table_path(directory, id),
+                    &bytes,
+                )?;
+                table.dirty = false;
+            }
+        }
+        Ok(())
+    }
+
+    fn load_table(&mut self, id: &str) -> Result<()> {
+        let directory = self
+            .directory
+            .as_ref()
+            .ok_or(DatabaseError::UnboundDatabase)?;
+
+        let path = util::table_path(directory, id);
+        if !path.is_file() {
+            Err(DatabaseError::MissingTable {
+                table: id.to_owned(),
+            })?
+        }
+
+        let bytes = fs::read(path)?;
+        let contents = util::decode_table(id, &bytes)?;
+        let mut pages = Pages::<P>::new(contents.schema.size());
+        for (key, record) in contents.records {
+            pages.update(P::index(key), |page| page.set(key, Some(&record)));
+        }
+
+        let table = Table {
+            dirty: false,
+            evicted: false,
+            schema: contents.schema,
+            metadata: contents.metadata,
+            pages,
+        };
+
+        self.tables
+            .insert(id.to_owned(), table);
+        Ok(())
+    }
+}
+
+impl<P: Page> Budgeted for Tables<P> {
+    fn usage(&self) -> usize {
+        self.tables
+            .values()
+            .map(|table| table.pages.usage())
+            .sum()
+    }
+
+    fn evict(&mut self, target: usize) -> Result<()> {
+        let directory = match &self.directory {
+            Some(directory) => directory.clone(),
+            None => return Ok(()),
+        };
+
+        let selected = self.selected.clone();
+        if self
+            .tables
+            .iter()
+            .any(|(id, table)| !table.evicted && Some(id) != selected.as_ref())
+        {
+            self.materialize()?;
+            for (id, table) in &mut self.tables {
+                if Some(id) != selected.as_ref() {
+                    table.pages = Pages::new(table.schema.size());
+                    table.evicted = true;
+                }
+            }
+        }
+
+        let usage = self.usage();
+        if let Some(id) = selected.filter(|_| usage > target) {
+            let table = self
+                .tables
+                .get_mut(&id)
+                .ok_or(DatabaseError::NoTableSelected)?;
+            table
+                .pages
+                .evict(directory, &id, &table.schema, usage - target)?;
+        }
+        Ok(())
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.unselected {
+            Err(DatabaseError::NoTableSelected)?
+        }
+        for (id, table) in &self.tables {
+            if let Some((page, cause)) = table.pages.fault.get() {
+                Err(DatabaseError::UnloadablePage {
+                    table: id.to_owned(),
+                    page: *page,
+                    cause: cause.to_owned(),
+                })?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<P: Page> Tabular for Tables<P> {
+    fn create_table(&mut self, id: &str, schema: Schema) -> Result<()> {
+        let table = Table {
+            dirty: true,
+            evicted: false,
+            pages: Pages::new(schema.size()),
+            schema,
+            metadata: HashMap::new(),
+        };
+        self.tables
+            .insert(id.to_owned(), table);
+        Ok(())
+    }
+
+    fn select_table(&mut self, id: &str) -> Result<()> {
+        if let Some(table) = self.tables.get(id) {
+            if table.evicted {
+                self.load_table(id)?;
+            }
+            self.selected = Some(id.to_owned());
+            Ok(())
+        } else {
+            Err(DatabaseError::MissingTable {
+                table: id.to_owned(),
+            })?
+        }
+    }
+
+    fn delete_table(&mut self, id: &str) -> Result<()> {
+        if self.tables.remove(id).is_none() {
+            Err(DatabaseError::MissingTable {
+                table: id.to_owned(),
+            })?
+        }
+        if self.selected.as_deref() == Some(id) {
+            self.selected = None;
+        }
+        Ok(())
+    }
+
+    fn set_metadata(&mut self, key: &str, value: &str) -> Result<()> {
+        let table = self.table_mut()?;
+        table
+            .metadata
+            .insert(key.to_owned(), value.to_owned());
+        table.dirty = true;
+        Ok(())
+    }
+
+    fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.table()?
+            .metadata
+            .get(key)
+            .map(|value| value.as_str())
+    }
+
+    fn schema(&self) -> Option<&Schema> {
+        self.table().map(|t| &t.schema)
+    }
+}
+
+/* PAGES IMPLEMENTATION */
+
+impl<P: Page> Pages<P> {
+    /// Returns a table of `width`-bit records without any pages.
+    pub fn new(width: usize) -> Self {
+        Self {
+            width,
+            slots: Vec::new(),
+            spill: None,
+            clock: AtomicU64::new(0),
+            resident: AtomicUsize::new(0),
+            fault: OnceLock::new(),
+        }
+    }
+
+    /// Adds empty pages to this table until it has `count` pages.
+    pub fn extend(&mut self, count: usize) {
+        while self.slots.len() < count {
+            let page = P::empty(self.slots.len(), self.width);
+            *self.resident.get_mut() += page.usage();
+            self.slots.push(Slot {
+                page: OnceLock::from(page),
+                used: AtomicU64::new(0),
+            });
+        }
+    }
+
+    /// Returns the page at `index`, loading it back into memory if it was
+    /// evicted, or `None` if this table has no such page. If an evicted page
+    /// cannot be loaded, `None` is returned and the fault is recorded.
+    pub fn get(&self, index: usize) -> Option<&P> {
+        let slot = self.slots.get(index)?;
+        slot.used.store(
+            self.clock
+                .fetch_add(1, Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+
+        if slot.page.get().is_none() {
+            match self.load(index) {
+                Ok(page) => {
+                    let usage = page.usage();
+                    if slot.page.set(page).is_ok() {
+                        self.resident
+                            .fetch_add(usage, Ordering::Relaxed);
+                    }
+                },
+                Err(error) => {
+                    self.fault
+                        .get_or_init(|| (index, format!("{:#}", error)));
+                    return None;
+                },
+            }
+        }
+        slot.page.get()
+    }
+
+    /// Applies `f` to the page at `index` (see `get`), adding empty pages to
+    /// this table until it has such a page. Returns `None` without applying
+    /// `f` if the page was evicted and cannot be loaded.
+    pub fn update<R>(
+        &mut self,
+        index: usize,
+        f: impl FnOnce(&mut P) -> R,
+    ) -> Option<R> {
+        self.extend(index + 1);
+        self.get(index)?;
+        let page = self.slots[index].page.get_mut()?;
+
+        let before = page.usage();
+        let result = f(page);
+        let after = page.usage();
+        let resident = self.resident.get_mut();
+        *resident = *resident + after - before;
+        Some(result)
+    }
+
+    /// Returns the records of all pages along with their keys, where those of
+    /// evicted pages are read from their files one page at a time (without
+    /// loading the pages back into memory).
+    pub fn records(
+        &self,
+    ) -> impl Iterator<Item = Result<(State, Cow<'_, BitSlice<u8, Msb0>>)>> + '_
+    {
+        self.slots
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, slot)| {
+                let records: Box<dyn Iterator<Item = _>> =
+                    match slot.page.get() {
+                        Some(page) => {
+                            Box::new(page.records().map(|(key, bits)| {
+                                Ok((key, Cow::Borrowed(bits)))
+                            }))
+                        },
+                        None => match self.read(index) {
+                            Ok(records) => Box::new(records.into_iter().map(
+                                |(key, bits)| Ok((key, Cow::Owned(bits))),
+                            )),
+                            Err(error) => Box::new(iter::once(Err(error))),
+                        },
+                    };
+                records
+            })
+    }
+
+    /// Returns the approximate number of bytes used by this table in memory.
+    pub fn usage(&self) -> usize {
+        self.slots.capacity() * mem::size_of::<Slot<P>>()
+            + self
+                .resident
+                .load(Ordering::Relaxed)
+    }
+
+    /// Evicts the pages of this table which were used the longest time ago to
+    /// files in `directory` under the table's `id` (written with `schema`),
+    /// until `excess` bytes are freed or no pages in memory use any bytes.
+    pub fn evict(
+        &mut self,
+        directory: PathBuf,
+        id: &str,
+        schema: &Schema,
+        excess: usize,
+    ) -> Result<()> {
+        let mut order = (0..self.slots.len())
+            .filter(|&index| {
+                self.slots[index]
+                    .page
+                    .get()
+                    .is_some_and(|page| page.usage() > 0)
+            })
+            .collect::<Vec<usize>>();
+
+        order.sort_by_key(|&index| {
+            self.slots[index]
+                .used
+                .load(Ordering::Relaxed)
+        });
+
+        let mut freed = 0;
+        for index in order {
+            if freed >= excess {
+                break;
+            }
+
+            let slot = &mut self.slots[index];
+            let bytes = util::encode_table(
+                schema,
+                &HashMap::new(),
+                slot.page.get().unwrap().records(),
+            );
+            util::write_atomically(
+                &util::page_path(&directory, id, index),
+                &bytes,
+            )
+            .context("Failed to evict page of records.")?;
+
+            let usage = slot.page.take().unwrap().usage();
+            *self.resident.get_mut() -= usage;
+            freed += usage;
+        }
+
+        self.spill = Some((directory, id.to_owned()));
+        Ok(())
+    }
+
+    /// Returns the page at `index` loaded from its file, which is removed.
+    fn load(&self, index: usize) -> Result<P> {
+        let mut page = P::empty(index, self.width);
+        for (key, record) in self.read(index)? {
+            page.set(key, Some(&record));
+        }
+
+        if let Some((directory, id)) = &self.spill {
+            fs::remove_file(util::page_path(directory, id, index))?;
+        }
+        Ok(page)
+    }
+
+    /// Returns the records in the file of the evicted page at `index`.
+    fn read(&self, index: usize) -> Result<Vec<(State, BitVec<u8, Msb0>)>> {
+        let (directory, id) = self
+            .spill
+            .as_ref()
+            .ok_or(DatabaseError::UnboundDatabase)?;
+
+        let bytes = fs::read(util::page_path(directory, id, index))
+            .context("Failed to read evicted page of records.")?;
+        Ok(util::decode_table(id, &bytes)?.records)
+    }
+}
+
+impl<P> Drop for Pages<P> {
+    fn drop(&mut self) {
+        if let Some((directory, id)) = &self.spill {
+            for (index, slot) in self.slots.iter().enumerate() {
+                if slot.page.get().is_none() {
+                    let _ =
+                        fs::remove_file(util::page_path(directory, id, index));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! # Sharded Database
+//!
+//! This module provides an in-memory database implementation which can be
+//! written to by multiple threads at once. The keys of each table are split
+//! across a fixed number of shards by their hash, and each shard is an index of
+//! write-once slots. Keys must be reserved (which can be done for all shards in
+//! parallel) before records can be inserted under them concurrently, and each
+//! record can only be inserted once in this way. Exclusive access through the
+//! `KVStore` interface lifts these restrictions.
+
+use anyhow::Result;
+use bitvec::{order::Msb0, slice::BitSlice, vec::BitVec};
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
+use std::thread;
+
+use crate::{
+    database::error::DatabaseError,
+    database::{KVStore, Record, Schema, Tabular},
+    model::State,
+};
+
+/* DATABASE DEFINITION */
+
+pub struct Database {
+    tables: HashMap<String, Table>,
+    selected: Option<String>,
+    shards: usize,
+}
+
+struct Table {
+    schema: Schema,
+    metadata: HashMap<String, String>,
+    shards: Vec<Shard>,
+}
+
+/// A subset of the records of a table, each of which can be written once while
+/// the shard is shared.
+type Shard = HashMap<State, OnceLock<BitVec<u8, Msb0>>>;
+
+/// Specifies the number of shards that the keys of each table are split into,
+/// which is also the maximum number of threads used to reserve keys.
+pub struct Parameters {
+    pub shards: usize,
+}
+
+/* IMPLEMENTATION */
+
+impl Database {
+    pub fn initialize(params: Parameters) -> Self {
+        Self {
+            tables: HashMap::new(),
+            selected: None,
+            shards: params.shards.max(1),
+        }
+    }
+
+    /// Creates empty slots in the selected table for the records of `keys`,
+    /// such that they can be inserted concurrently through `insert`. Keys are
+    /// distributed across shards, and the slots of each shard are created by a
+    /// different thread. Slots which already exist are left untouched.
+    pub fn reserve(&mut self, keys: &[State]) {
+        let table = self.table_mut();
+        let mut split = vec![Vec::new(); table.shards.len()];
+        for &key in keys {
+            split[shard(key, table.shards.len())].push(key);
+        }
+
+        thread::scope(|s| {
+            for (shard, keys) in table.shards.iter_mut().zip(split) {
+                s.spawn(move || {
+                    shard.reserve(keys.len());
+                    for key in keys {
+                        shard.entry(key).or_default();
+                    }
+                });
+            }
+        });
+    }
+
+    /// Inserts `record` under `key` in the selected table through a shared
+    /// reference. Fails if `key` was not reserved beforehand, or if a record
+    /// was already inserted under `key`.
+    pub fn insert<R: Record>(&self, key: State, record: &R) -> Result<()> {
+        let (id, table) = self
+            .selected
+            .as_ref()
+            .and_then(|id| Some((id, self.tables.get(id)?)))
+            .ok_or(DatabaseError::NoTableSelected)?;
+
+        let slot = table.shards[shard(key, table.shards.len())]
+            .get(&key)
+            .ok_or_else(|| DatabaseError::UnreservedKey {
+                key,
+                table: id.to_owned(),
+            })?;
+
+        slot.set(record.raw().to_bitvec())
+            .map_err(|_| DatabaseError::RecordOverwrite {
+                key,
+                table: id.to_owned(),
+            })?;
+
+        Ok(())
+    }
+
+    fn table(&self) -> Option<&Table> {
+        self.selected
+            .as_ref()
+            .and_then(|id| self.tables.get(id))
+    }
+
+    fn table_mut(&mut self) -> &mut Table {
+        self.selected
+            .as_ref()
+            .and_then(|id| self.tables.get_mut(id))
+            .expect("Attempted to access records without selecting a table.")
+    }
+}
+
+impl<R: Record> KVStore<R> for Database {
+    fn put(&mut self, key: State, value: &R) {
+        let table = self.table_mut();
+        let shard = shard(key, table.shards.len());
+        table.shards[shard]
+            .insert(key, OnceLock::from(value.raw().to_bitvec()));
+    }
+
+    fn get(&self, key: State) -> Option<&BitSlice<u8, Msb0>> {
+        let table = self.table()?;
+        table.shards[shard(key, table.shards.len())]
+            .get(&key)?
+            .get()
+            .map(|bits| bits.as_bitslice())
+    }
+
+    fn del(&mut self, key: State) {
+        let table = self.table_mut();
+        let shard = shard(key, table.shards.len());
+        table.shards[shard].remove(&key);
+    }
+}
+
+impl Tabular for Database {
+    fn create_table(&mut self, id: &str, schema: Schema) -> Result<()> {
+        let table = Table {
+            schema,
+            metadata: HashMap::new(),
+            shards: vec![HashMap::new(); self.shards],
+        };
+        self.tables
+            .insert(id.to_owned(), table);
+        Ok(())
+    }
+
+    fn select_table(&mut self, id: &str) -> Result<()> {
+        if self.tables.contains_key(id) {
+            self.selected = Some(id.to_owned());
+            Ok(())
+        } else {
+            Err(DatabaseError::MissingTable {
+                table: id.to_owned(),
+            })?
+        }
+    }
+
+    fn delete_table(&mut self, id: &str) -> Result<()> {
+        if self.tables.remove(id).is_none() {
+            Err(DatabaseError::MissingTable {
+                table: id.to_owned(),
+            })?
+        }
+        if self.selected.as_deref() == Some(id) {
+            self.selected = None;
+        }
+        Ok(())
+    }
+
+    fn set_metadata(&mut self, key: &str, value: &str) -> Result<()> {
+        let table = self
+            .selected
+            .as_ref()
+            .and_then(|id| self.tables.get_mut(id))
+            .ok_or(DatabaseError::NoTableSelected)?;
+        table
+            .metadata
+            .insert(key.to_owned(), value.to_owned());
+        Ok(())
+    }
+
+    fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.table()?
+            .metadata
+            .get(key)
+            .map(|value| value.as_str())
+    }
+
+    fn schema(&self) -> Option<&Schema> {
+        self.table().map(|t| &t.schema)
+    }
+}
+
+/* HELPER FUNCTIONS */
+
+/// Returns the index of the shard out of `shards` which houses `key`.
+fn shard(key: State, shards: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % shards as u64) as usize
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::solver::record::mur::{self, Layout, RecordBuffer};
+
+    fn database() -> Database {
+        let mut db = Database::initialize(Parameters { shards: 4 });
+        db.create_table("test", mur::schema(2).unwrap())
+            .unwrap();
+        db.select_table("test").unwrap();
+        db
+    }
+
+    fn record(remoteness: u64) -> RecordBuffer {
+        let mut record = RecordBuffer::new(2).unwrap();
+        record
+            .set_remoteness(remoteness)
+            .unwrap();
+        record
+    }
+
+    #[test]
+    fn records_are_inserted_concurrently_once() {
+        let mut db = database();
+        let keys = (0..1000).collect::<Vec<State>>();
+        db.reserve(&keys);
+        assert!(KVStore::<RecordBuffer>::get(&db, 5).is_none());
+
+        thread::scope(|s| {
+            for chunk in keys.chunks(100) {
+                let db = &db;
+                s.spawn(move || {
+                    for &key in chunk {
+                        db.insert(key, &record(key))
+                            .unwrap();
+                    }
+                });
+            }
+        });
+
+        for key in keys {
+            let bits = KVStore::<RecordBuffer>::get(&db, key).unwrap();
+            let record = RecordBuffer::parse(bits, Layout::new(2)).unwrap();
+            assert_eq!(record.get_remoteness(), key);
+        }
+
+        assert!(db.insert(5, &record(0)).is_err());
+        assert!(db
+            .insert(1000, &record(0))
+            .is_err());
+    }
+
+    #[test]
+    fn exclusive_access_overwrites_records() {
+        let mut db = database();
+        db.put(7, &record(1));
+        db.put(7, &record(2));
+        let bits = KVStore::<RecordBuffer>::get(&db, 7).unwrap();
+        assert_eq!(
+            RecordBuffer::parse(bits, Layout::new(2))
+                .unwrap()
+                .get_remoteness(),
+            2
+        );
+
+        KVStore::<RecordBuffer>::del(&mut db, 7);
+        assert!(KVStore::<RecordBuffer>::get(&db, 7).is_none());
+    }
+}
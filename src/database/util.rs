@@ -9,11 +9,37 @@
 //!
 //! - Max Fierro, 2/24/2024 (maxfierro@berkeley.edu)
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use bitvec::{order::Msb0, slice::BitSlice, vec::BitVec};
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use crate::database::error::DatabaseError;
 use crate::database::Attribute;
 use crate::database::Datatype;
+use crate::database::{Schema, SchemaBuilder};
+use crate::model::State;
+
+/* DEFINITIONS */
+
+/// The contents of a table which was persisted through `encode_table`.
+pub struct Contents {
+    pub schema: Schema,
+    pub metadata: HashMap<String, String>,
+    pub records: Vec<(State, BitVec<u8, Msb0>)>,
+}
+
+/// Reads the fields of a persisted table in the order they were written, such
+/// that truncated contents are reported as a corruption of `table`.
+struct Reader<'a> {
+    table: &'a str,
+    bytes: &'a [u8],
+}
+
+/* SCHEMA VALIDATION */
 
 /// Verifies that adding a `new` attribute to an `existing` set of attributes
 /// would not result in an invalid state for the schema who owns `existing`,
@@ -47,9 +73,7 @@ fn check_datatype_validity(new: &Attribute) -> Result<(), DatabaseError> {
         Datatype::SPFP => s != 32,
         Datatype::DPFP => s != 64,
         Datatype::CSTR => s % 8 != 0,
-        Datatype::UINT | Datatype::ENUM => {
-            unreachable!("UINTs and ENUMs can be of any nonzero size.")
-        },
+        Datatype::UINT | Datatype::ENUM => false,
     } {
         Err(DatabaseError::InvalidSize {
             size: new.size(),
@@ -62,6 +86,177 @@ fn check_datatype_validity(new: &Attribute) -> Result<(), DatabaseError> {
     }
 }
 
+/* TABLE PERSISTENCE */
+
+/// Returns the path of the file where the table with `id` is persisted within
+/// `directory`.
+pub fn table_path(directory: &Path, id: &str) -> PathBuf {
+    directory.join(format!("{}.table", id))
+}
+
+/// Returns the path of the file where the page at `index` of the table with
+/// `id` is kept within `directory` while it is evicted from memory.
+pub fn page_path(directory: &Path, id: &str, index: usize) -> PathBuf {
+    directory.join(format!("{}.{}.page", id, index))
+}
+
+/// Verifies that `path` is an existing directory that a database can persist
+/// its tables in.
+pub fn check_directory(path: &Path) -> Result<(), DatabaseError> {
+    if path.is_dir() {
+        Ok(())
+    } else {
+        Err(DatabaseError::InvalidDirectory {
+            path: path.display().to_string(),
+        })
+    }
+}
+
+/// Writes `contents` to the file at `path` by first writing them to a sibling
+/// temporary file, and then renaming it to `path`. Since renaming replaces the
+/// previous file atomically, an interrupted write never corrupts `path`.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, contents)
+        .with_context(|| format!("Failed to write to {:?}.", temporary))?;
+    fs::rename(&temporary, path)
+        .with_context(|| format!("Failed to replace {}.", path.display()))?;
+    Ok(())
+}
+
+/// Serializes a table with `schema` and `metadata` holding `records` (which are
+/// pairs of keys and record data) into bytes, which can be deserialized back
+/// through `decode_table`. All integers are encoded in little-endian order, and
+/// each record is padded to a whole number of bytes.
+pub fn encode_table<I, B>(
+    schema: &Schema,
+    metadata: &HashMap<String, String>,
+    records: I,
+) -> Vec<u8>
+where
+    I: Iterator<Item = (State, B)>,
+    B: Borrow<BitSlice<u8, Msb0>>,
+{
+    fn write_str(bytes: &mut Vec<u8>, string: &str) {
+        bytes.extend((string.len() as u64).to_le_bytes());
+        bytes.extend(string.as_bytes());
+    }
+
+    let mut bytes = Vec::new();
+
+    bytes.extend((schema.attributes.len() as u64).to_le_bytes());
+    for attribute in schema.iter() {
+        write_str(&mut bytes, attribute.name());
+        bytes.push(attribute.datatype() as u8);
+        bytes.extend((attribute.size() as u64).to_le_bytes());
+    }
+
+    bytes.extend((metadata.len() as u64).to_le_bytes());
+    for (key, value) in metadata {
+        write_str(&mut bytes, key);
+        write_str(&mut bytes, value);
+    }
+
+    let mut count = 0u64;
+    let mut data = Vec::new();
+    for (key, record) in records {
+        data.extend(key.to_le_bytes());
+        // Records sliced from a larger buffer may not start on a byte.
+        let mut record = record.borrow().to_bitvec();
+        record.force_align();
+        record.set_uninitialized(false);
+        data.extend(record.into_vec());
+        count += 1;
+    }
+
+    bytes.extend(count.to_le_bytes());
+    bytes.extend(data);
+    bytes
+}
+
+/// Deserializes the contents of the table with `id` from `bytes`, which must
+/// have been obtained through `encode_table`.
+pub fn decode_table(id: &str, bytes: &[u8]) -> Result<Contents> {
+    let mut reader = Reader { table: id, bytes };
+    let mut schema = SchemaBuilder::new();
+    for _ in 0..reader.u64()? {
+        let name = reader.string()?;
+        let data = reader.datatype()?;
+        let size = reader.u64()? as usize;
+        schema = schema
+            .add(Attribute::new(&name, data, size))
+            .map_err(|_| reader.corrupted())?;
+    }
+
+    let schema = schema.build();
+    let mut metadata = HashMap::new();
+    for _ in 0..reader.u64()? {
+        let key = reader.string()?;
+        let value = reader.string()?;
+        metadata.insert(key, value);
+    }
+
+    let width = schema.size().div_ceil(8);
+    let mut records = Vec::new();
+    for _ in 0..reader.u64()? {
+        let key = reader.u64()?;
+        let mut record = BitVec::from_slice(reader.take(width)?);
+        record.truncate(schema.size());
+        records.push((key, record));
+    }
+
+    if reader.bytes.is_empty() {
+        Ok(Contents {
+            schema,
+            metadata,
+            records,
+        })
+    } else {
+        Err(reader.corrupted())?
+    }
+}
+
+impl Reader<'_> {
+    fn corrupted(&self) -> DatabaseError {
+        DatabaseError::CorruptedTable {
+            table: self.table.to_owned(),
+        }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&[u8], DatabaseError> {
+        if self.bytes.len() < count {
+            return Err(self.corrupted());
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u64(&mut self) -> Result<u64, DatabaseError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, DatabaseError> {
+        let length = self.u64()? as usize;
+        let bytes = self.take(length)?.to_vec();
+        String::from_utf8(bytes).map_err(|_| self.corrupted())
+    }
+
+    fn datatype(&mut self) -> Result<Datatype, DatabaseError> {
+        match self.take(1)?[0] {
+            0 => Ok(Datatype::ENUM),
+            1 => Ok(Datatype::UINT),
+            2 => Ok(Datatype::SINT),
+            3 => Ok(Datatype::SPFP),
+            4 => Ok(Datatype::DPFP),
+            5 => Ok(Datatype::CSTR),
+            _ => Err(self.corrupted()),
+        }
+    }
+}
+
 /* UTILITY IMPLEMENTATIONS */
 
 impl ToString for Datatype {
@@ -76,3 +271,35 @@ impl ToString for Datatype {
         }
     }
 }
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use bitvec::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn unaligned_records_survive_encoding() {
+        let schema = SchemaBuilder::new()
+            .add(Attribute::new("value", Datatype::UINT, 10))
+            .unwrap()
+            .build();
+
+        let mut buffer = bitvec![u8, Msb0; 0; 24];
+        buffer[3..13].store_be(0b10_1100_1101u16);
+        let record = &buffer[3..13];
+
+        let bytes = encode_table(
+            &schema,
+            &HashMap::new(),
+            [(4, record)].into_iter(),
+        );
+        let contents = decode_table("test", &bytes).unwrap();
+        assert_eq!(contents.records.len(), 1);
+        assert_eq!(contents.records[0].0, 4);
+        assert_eq!(contents.records[0].1, record);
+    }
+}
@@ -7,10 +7,13 @@
 //! allocated by the operating system, without considering the usage of virtual
 //! memory.
 //!
-//! For persistence, a file is created containing a bit-accurate representation
-//! of the in-memory vector. Table logic is handled by switching which of these
-//! files is currently being targeted, with the understanding that the contents
-//! of memory may be materialized on arbitrary operations.
+//! For persistence, a file is created for each table containing its schema, its
+//! metadata, and the records present in its in-memory vector (along with their
+//! keys). Table logic is handled by switching which of these files is currently
+//! being targeted, with the understanding that the contents of memory may be
+//! materialized on arbitrary operations. The vector of each table is split into
+//! segments of consecutive keys, which are the pages that the selected table
+//! is evicted by (see `Budgeted`).
 //!
 //! #### Authorship
 //!
@@ -19,67 +22,373 @@
 use anyhow::Result;
 use bitvec::order::Msb0;
 use bitvec::slice::BitSlice;
+use bitvec::vec::BitVec;
 
-use crate::database::Persistence;
-use crate::database::Schema;
-use crate::database::{KVStore, Record, Tabular};
-use crate::model::State;
+use crate::database::paging::{Page, Tables};
+use crate::database::{Persistence, Persistent};
+use crate::model::{State, StateCount};
 
 /* CONSTANTS */
 
 const METADATA_TABLE: &'static str = ".metadata";
 
+/// The number of consecutive keys whose records are kept in each segment.
+const SEGMENT_KEYS: usize = 1 << 12;
+
 /* DATABASE DEFINITION */
 
-pub struct Database<'a> {
-    buffer: Vec<u8>,
-    table: Table<'a>,
-    mode: Persistence<'a>,
-}
+pub type Database = Tables<Segment>;
 
-struct Table<'a> {
-    dirty: bool,
-    width: u32,
-    name: &'a str,
-    size: u128,
+/// The records of the `SEGMENT_KEYS` consecutive keys starting at `base`, each
+/// of which is `width` bits long, along with which of them are `present`.
+pub struct Segment {
+    base: State,
+    width: usize,
+    present: BitVec<u8, Msb0>,
+    buffer: BitVec<u8, Msb0>,
 }
 
 pub struct Parameters<'a> {
-    persistence: Persistence<'a>,
+    pub persistence: Persistence<'a>,
 }
 
 /* IMPLEMENTATION */
 
-impl Database<'_> {
-    fn initialize(params: Parameters) -> Result<Self> {
-        todo!()
+impl Database {
+    pub fn initialize(params: Parameters) -> Result<Self> {
+        let mut db = Tables::new();
+        if let Persistence::On(path) = params.persistence {
+            db.bind_path(path)?;
+        }
+        Ok(db)
+    }
+
+    /// Allocates enough memory in the selected table to house the records of
+    /// keys up to (but not including) `records`, so that no reallocations are
+    /// needed when storing them. This is most useful when keys are dense, such
+    /// as when they are state ranks. Fails if no table is selected.
+    pub fn reserve(&mut self, records: StateCount) -> Result<()> {
+        self.extend((records as usize).div_ceil(SEGMENT_KEYS))
     }
 }
 
-impl<R: Record> KVStore<R> for Database<'_> {
-    fn put(&mut self, key: State, value: &R) {
-        todo!()
+impl Page for Segment {
+    fn index(key: State) -> usize {
+        segment(key)
+    }
+
+    fn empty(index: usize, width: usize) -> Self {
+        Self {
+            base: (index * SEGMENT_KEYS) as State,
+            width,
+            present: BitVec::repeat(false, SEGMENT_KEYS),
+            buffer: BitVec::repeat(false, SEGMENT_KEYS * width),
+        }
+    }
+
+    fn set(&mut self, key: State, record: Option<&BitSlice<u8, Msb0>>) {
+        let index = (key - self.base) as usize;
+        if let Some(bits) = record {
+            let start = index * self.width;
+            self.buffer[start..start + self.width].copy_from_bitslice(bits);
+        }
+        self.present
+            .set(index, record.is_some());
     }
 
     fn get(&self, key: State) -> Option<&BitSlice<u8, Msb0>> {
-        todo!()
+        let index = (key - self.base) as usize;
+        if self.present[index] {
+            let start = index * self.width;
+            Some(&self.buffer[start..start + self.width])
+        } else {
+            None
+        }
     }
 
-    fn del(&self, key: State) {
-        todo!()
+    fn records(&self) -> impl Iterator<Item = (State, &BitSlice<u8, Msb0>)> {
+        self.present
+            .iter_ones()
+            .map(|index| {
+                let start = index * self.width;
+                (
+                    self.base + index as State,
+                    &self.buffer[start..start + self.width],
+                )
+            })
+    }
+
+    fn usage(&self) -> usize {
+        (self.present.capacity() + self.buffer.capacity()) / 8
     }
 }
 
-impl Tabular for Database<'_> {
-    fn create_table(&self, id: &str, schema: Schema) -> Result<()> {
-        todo!()
+/* HELPER FUNCTIONS */
+
+/// Returns the index of the segment which houses `key`.
+fn segment(key: State) -> usize {
+    key as usize / SEGMENT_KEYS
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::error::DatabaseError;
+    use crate::database::{util, Budgeted, KVStore, Record, Tabular};
+    use crate::solver::record::mur::{self, Layout, RecordBuffer};
+
+    use std::{env, fs, process};
+
+    #[test]
+    fn records_survive_round_trip() {
+        let mut db = database();
+        let mut record = RecordBuffer::new(2).unwrap();
+        record
+            .set_utility([3, -3])
+            .unwrap();
+        record.set_remoteness(7).unwrap();
+        db.put(5, &record);
+
+        let stored = RecordBuffer::parse(
+            KVStore::<RecordBuffer>::get(&db, 5).unwrap(),
+            Layout::new(2),
+        )
+        .unwrap();
+        assert_eq!(stored.get_utility(0).unwrap(), 3);
+        assert_eq!(stored.get_utility(1).unwrap(), -3);
+        assert_eq!(stored.get_remoteness(), 7);
+    }
+
+    #[test]
+    fn missing_and_deleted_keys_have_no_record() {
+        let mut db = database();
+        let record = RecordBuffer::new(2).unwrap();
+        assert!(KVStore::<RecordBuffer>::get(&db, 0).is_none());
+
+        db.put(3, &record);
+        assert!(KVStore::<RecordBuffer>::get(&db, 2).is_none());
+        assert!(KVStore::<RecordBuffer>::get(&db, 3).is_some());
+        assert!(KVStore::<RecordBuffer>::get(&db, 100).is_none());
+
+        KVStore::<RecordBuffer>::del(&mut db, 3);
+        assert!(KVStore::<RecordBuffer>::get(&db, 3).is_none());
     }
 
-    fn select_table(&self, id: &str) -> Result<()> {
-        todo!()
+    #[test]
+    fn reserving_does_not_create_records() {
+        let mut db = database();
+        db.reserve(64).unwrap();
+        assert!((0..64).all(|k| KVStore::<RecordBuffer>::get(&db, k).is_none()));
     }
 
-    fn delete_table(&self, id: &str) -> Result<()> {
-        todo!()
+    #[test]
+    fn tables_survive_materialization() {
+        let directory =
+            env::temp_dir().join(format!("nova-vector-test-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let mut db = database();
+        let mut record = RecordBuffer::new(2).unwrap();
+        record
+            .set_utility([1, -1])
+            .unwrap();
+        record.set_remoteness(9).unwrap();
+        db.put(12, &record);
+        db.set_metadata("key", "value")
+            .unwrap();
+        db.bind_path(&directory).unwrap();
+        db.materialize().unwrap();
+
+        let mut loaded = Database::initialize(Parameters {
+            persistence: Persistence::On(&directory),
+        })
+        .unwrap();
+
+        loaded.load_table("test").unwrap();
+        loaded
+            .select_table("test")
+            .unwrap();
+        assert_eq!(loaded.get_metadata("key"), Some("value"));
+        assert_eq!(
+            loaded.schema().unwrap().size(),
+            record.raw().len()
+        );
+        assert!(KVStore::<RecordBuffer>::get(&loaded, 11).is_none());
+        assert_eq!(
+            KVStore::<RecordBuffer>::get(&loaded, 12),
+            KVStore::<RecordBuffer>::get(&db, 12),
+        );
+
+        let path = util::table_path(&directory, "test");
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        let error = loaded
+            .load_table("test")
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DatabaseError>(),
+            Some(DatabaseError::CorruptedTable { .. })
+        ));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn unselected_tables_are_evicted_under_memory_limit() {
+        let directory = env::temp_dir()
+            .join(format!("nova-eviction-test-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let mut db = database();
+        let record = RecordBuffer::new(2).unwrap();
+        for key in 0..1000 {
+            db.put(key, &record);
+        }
+
+        db.create_table("other", mur::schema(2).unwrap())
+            .unwrap();
+        db.select_table("other").unwrap();
+        db.put(0, &record);
+
+        let usage = db.usage();
+        assert!(db.enforce(usage, 0).is_ok());
+        assert_eq!(db.usage(), usage);
+
+        db.evict(0).unwrap();
+        assert_eq!(db.usage(), usage);
+
+        db.bind_path(&directory).unwrap();
+        assert!(db.enforce(usage, 0).is_ok());
+        assert!(db.usage() < usage);
+
+        let error = db.enforce(1, 0).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DatabaseError>(),
+            Some(DatabaseError::MemoryLimitExceeded { limit: 1, .. })
+        ));
+
+        db.select_table("test").unwrap();
+        assert!(
+            (0..1000).all(|k| KVStore::<RecordBuffer>::get(&db, k).is_some())
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn selected_table_is_evicted_by_segment() {
+        let directory =
+            env::temp_dir().join(format!("nova-paging-test-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let mut db = database();
+        db.bind_path(&directory).unwrap();
+        let keys = 8 * SEGMENT_KEYS as State;
+        for key in 0..keys {
+            let mut record = RecordBuffer::new(2).unwrap();
+            record
+                .set_remoteness(key % 100)
+                .unwrap();
+            db.put(key, &record);
+        }
+
+        let usage = db.usage();
+        db.enforce(usage, 0).unwrap();
+        assert!(db.usage() <= usage / 2);
+
+        let remoteness = |db: &Database, key| {
+            RecordBuffer::parse(
+                KVStore::<RecordBuffer>::get(db, key).unwrap(),
+                Layout::new(2),
+            )
+            .unwrap()
+            .get_remoteness()
+        };
+
+        assert!((0..keys).all(|key| remoteness(&db, key) == key % 100));
+        assert_eq!(db.usage(), usage);
+
+        db.evict(0).unwrap();
+        db.materialize().unwrap();
+        drop(db);
+        assert_eq!(
+            fs::read_dir(&directory)
+                .unwrap()
+                .count(),
+            1
+        );
+
+        let mut loaded = Database::initialize(Parameters {
+            persistence: Persistence::On(&directory),
+        })
+        .unwrap();
+
+        loaded.load_table("test").unwrap();
+        loaded
+            .select_table("test")
+            .unwrap();
+        assert!((0..keys).all(|key| remoteness(&loaded, key) == key % 100));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn lost_segments_are_reported_instead_of_panicking() {
+        let directory =
+            env::temp_dir().join(format!("nova-fault-test-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let mut db = database();
+        db.bind_path(&directory).unwrap();
+        let record = RecordBuffer::new(2).unwrap();
+        db.put(0, &record);
+        db.evict(0).unwrap();
+        assert!(db.check().is_ok());
+
+        fs::remove_file(util::page_path(&directory, "test", 0)).unwrap();
+        assert!(KVStore::<RecordBuffer>::get(&db, 0).is_none());
+        db.put(1, &record);
+
+        let error = db
+            .enforce(usize::MAX, 0)
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DatabaseError>(),
+            Some(DatabaseError::UnloadablePage { page: 0, .. })
+        ));
+        assert!(db.materialize().is_err());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn records_without_selected_table_are_reported() {
+        let mut db = Database::initialize(Parameters {
+            persistence: Persistence::Off,
+        })
+        .unwrap();
+
+        assert!(db.reserve(64).is_err());
+        db.put(0, &RecordBuffer::new(2).unwrap());
+        let error = db.check().unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DatabaseError>(),
+            Some(DatabaseError::NoTableSelected)
+        ));
+    }
+
+    fn database() -> Database {
+        let mut db = Database::initialize(Parameters {
+            persistence: Persistence::Off,
+        })
+        .unwrap();
+
+        db.create_table("test", mur::schema(2).unwrap())
+            .unwrap();
+        db.select_table("test").unwrap();
+        db
     }
 }
@@ -1,58 +1,101 @@
 //! # Volatile Database
 //!
 //! This module provides a trivial database implementation backed by a volatile
-//! in-memory hashmap.
+//! in-memory hashmap. Its tables can be persisted to (and loaded from) files in
+//! a directory that the database is bound to, which are replaced as a whole
+//! each time the table is materialized. The records of each table are split
+//! across a fixed number of hashmaps by the hash of their keys, which are the
+//! pages that the selected table is evicted by (see `Budgeted`).
 //!
 //! #### Authorship
 //!
 //! - Max Fierro, 2/24/2024 (maxfierro@berkeley.edu)
 
-use anyhow::Result;
-use bitvec::{order::Msb0, slice::BitSlice, store::BitStore};
+use bitvec::{order::Msb0, slice::BitSlice, vec::BitVec};
 
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::mem;
 
 use crate::{
-    database::{KVStore, Record, Schema, Tabular},
+    database::paging::{Page, Tables},
     model::State,
 };
 
-pub struct Database<'a> {
-    memory: HashMap<State, &'a [u8]>,
+/* CONSTANTS */
+
+/// The number of hashmaps that the records of each table are split across.
+const PARTITIONS: usize = 64;
+
+/* DATABASE DEFINITION */
+
+pub type Database = Tables<Partition>;
+
+/// The records of a table whose keys hash to the same partition, each of which
+/// is `width` bits long.
+pub struct Partition {
+    width: usize,
+    memory: HashMap<State, BitVec<u8, Msb0>>,
 }
 
-impl Database<'_> {
+/* IMPLEMENTATION */
+
+impl Database {
     pub fn initialize() -> Self {
+        Tables::new()
+    }
+}
+
+impl Page for Partition {
+    fn index(key: State) -> usize {
+        partition(key)
+    }
+
+    fn empty(_: usize, width: usize) -> Self {
         Self {
+            width,
             memory: HashMap::new(),
         }
     }
-}
 
-impl<R: Record> KVStore<R> for Database<'_> {
-    fn put(&mut self, key: State, value: &R) {
-        todo!()
+    fn set(&mut self, key: State, record: Option<&BitSlice<u8, Msb0>>) {
+        match record {
+            Some(bits) => self
+                .memory
+                .insert(key, bits.to_bitvec()),
+            None => self.memory.remove(&key),
+        };
     }
 
     fn get(&self, key: State) -> Option<&BitSlice<u8, Msb0>> {
-        todo!()
+        self.memory
+            .get(&key)
+            .map(|bits| bits.as_bitslice())
     }
 
-    fn del(&self, key: State) {
-        todo!()
+    fn records(&self) -> impl Iterator<Item = (State, &BitSlice<u8, Msb0>)> {
+        self.memory
+            .iter()
+            .map(|(&key, bits)| (key, bits.as_bitslice()))
     }
-}
 
-impl Tabular for Database<'_> {
-    fn create_table(&self, id: &str, schema: Schema) -> Result<()> {
-        todo!()
+    fn usage(&self) -> usize {
+        let entry =
+            mem::size_of::<State>() + mem::size_of::<BitVec<u8, Msb0>>() + 1;
+        let record = self
+            .width
+            .div_ceil(usize::BITS as usize)
+            * mem::size_of::<usize>();
+        self.memory.capacity() * entry + self.memory.len() * record
     }
+}
 
-    fn select_table(&self, id: &str) -> Result<()> {
-        todo!()
-    }
+/* HELPER FUNCTIONS */
 
-    fn delete_table(&self, id: &str) -> Result<()> {
-        todo!()
-    }
+/// Returns the index of the partition which houses `key`.
+fn partition(key: State) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % PARTITIONS as u64) as usize
 }
@@ -21,9 +21,11 @@
 
 use anyhow::{Context, Result};
 
+use crate::game::error::GameError;
 use crate::game::Acyclic;
 use crate::game::Bounded;
 use crate::game::DTransition;
+use crate::game::Entry;
 use crate::game::Game;
 use crate::game::GameData;
 use crate::game::Legible;
@@ -33,6 +35,7 @@ use crate::interface::IOMode;
 use crate::interface::SolutionMode;
 use crate::model::State;
 use crate::model::Utility;
+use crate::solver::util::{Policy, Resources};
 use variants::*;
 
 /* SUBMODULES */
@@ -102,8 +105,24 @@ impl Game for Session {
         todo!()
     }
 
-    fn solve(&self, mode: IOMode, method: SolutionMode) -> Result<()> {
-        todo!()
+    fn solve(
+        &self,
+        _: IOMode,
+        _: SolutionMode,
+        _: Policy,
+        _: &Resources,
+    ) -> Result<()> {
+        Err(GameError::SolverNotFound {
+            input_game_name: NAME,
+        })
+        .context("Solver not found.")
+    }
+
+    fn query(&self, _: IOMode, _: Option<String>) -> Result<Entry> {
+        Err(GameError::SolverNotFound {
+            input_game_name: NAME,
+        })
+        .context("Solver not found.")
     }
 
     fn forward(&mut self, history: Vec<String>) -> Result<()> {
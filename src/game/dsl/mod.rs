@@ -0,0 +1,738 @@
+//! # Game Description Language Module
+//!
+//! This module provides an interpreter for a small language which describes
+//! two-player board games, such that simple games can be solved without
+//! implementing a module for each of them. A description (which is the variant
+//! of this game) specifies the dimensions of a rectangular board, the types of
+//! pieces each player owns, the ways in which pieces can be moved, and the
+//! conditions under which the game is won.
+//!
+//! Descriptions consist of one directive per line, where anything after a `#`
+//! is a comment. For example, the following is a description of Tic-Tac-Toe:
+//!
+//! ```text
+//! name tic-tac-toe
+//! board 3 3
+//! piece X 0
+//! piece O 1
+//! move place X
+//! move place O
+//! win line 3
+//! ```
+//!
+//! See `variants::VARIANT_PROTOCOL` for the full list of directives.
+
+use anyhow::{Context, Result};
+use states::*;
+use variants::*;
+
+use std::path::Path;
+
+use crate::game::error::GameError;
+use crate::game::misere::Misere;
+use crate::game::{util, Acyclic, Bounded, DTransition, Entry, Legible};
+use crate::game::{Game, GameData, Limited, Solvable};
+use crate::interface::{IOMode, SolutionMode};
+use crate::model::{State, Turn, Utility};
+use crate::solver::strong;
+use crate::solver::util::{Policy, Resources};
+use crate::solver::verification::{Audit, Report};
+use crate::{implement, register};
+
+/* SUBMODULES */
+
+mod states;
+mod variants;
+
+/* GAME DATA */
+
+const NAME: &str = "dsl";
+const AUTHORS: &str = "GamesmanNova contributors";
+const ABOUT: &str =
+"Two players take turns moving pieces on a rectangular board, according to the \
+rules in a description of the game written in a small language. The game \
+variant is the description itself, which determines the dimensions of the \
+board, the pieces owned by each player, the moves that can be made with each \
+piece (placing, sliding, and jumping), and the conditions under which a player \
+wins (making a line, capturing all opposing pieces, or leaving the opponent \
+without moves). Games which are not won by any player end in a draw.";
+
+/* REGISTRATION */
+
+const DATA: GameData = GameData {
+    variant: VARIANT_DEFAULT,
+
+    name: NAME,
+    authors: AUTHORS,
+    about: ABOUT,
+
+    variant_protocol: VARIANT_PROTOCOL,
+    variant_pattern: VARIANT_PATTERN,
+    variant_default: VARIANT_DEFAULT,
+
+    state_default: STATE_DEFAULT,
+    state_pattern: STATE_PATTERN,
+    state_protocol: STATE_PROTOCOL,
+
+    move_protocol: None,
+    move_pattern: None,
+};
+
+register! { DATA => session }
+
+/// Initializes a session of this game with `variant` for the game registry,
+/// which is of its misère version if `misere` is set.
+fn session(variant: Option<String>, misere: bool) -> Result<Box<dyn Game>> {
+    let session = Session::initialize(variant)
+        .context("Failed to initialize described game session.")?;
+    if misere && session.acyclic {
+        Ok(Box::new(Misere::<2, _>::new(session)))
+    } else if misere {
+        Err(GameError::MisereNotFound {
+            input_game_name: NAME,
+        })
+        .context("Misère version not found.")
+    } else {
+        Ok(Box::new(session))
+    }
+}
+
+/* DEFINITIONS */
+
+/// A displacement on the board, in rows and columns.
+type Direction = (isize, isize);
+
+/// A type of piece, which is represented by `symbol` in state encodings and is
+/// owned by the player `owner`.
+struct Piece {
+    symbol: char,
+    owner: Turn,
+}
+
+/// A way in which a player can move the pieces of a type (the index of which
+/// is the first element of each variant) they own.
+enum Rule {
+    /// Placing a new piece on any empty cell.
+    Place(usize),
+    /// Moving a piece to an adjacent empty cell in one of the directions.
+    Slide(usize, Vec<Direction>),
+    /// Jumping with a piece over an adjacent opposing piece in one of the
+    /// directions, onto the empty cell right behind it, capturing it.
+    Jump(usize, Vec<Direction>),
+}
+
+/// A condition under which a player wins the game.
+enum Win {
+    /// Having a line of at least this many pieces owned by the player, which
+    /// can be horizontal, vertical, or diagonal.
+    Line(usize),
+    /// The opponent having no pieces left on the board on their turn.
+    CaptureAll,
+    /// The opponent being unable to make a move on their turn.
+    NoMoves,
+}
+
+/* GAME IMPLEMENTATION */
+
+pub struct Session {
+    variant: String,
+    name: String,
+    rows: usize,
+    cols: usize,
+    pieces: Vec<Piece>,
+    rules: Vec<Rule>,
+    wins: Vec<Win>,
+    acyclic: bool,
+    start: State,
+}
+
+impl Game for Session {
+    fn initialize(variant: Option<String>) -> Result<Self> {
+        if let Some(v) = variant {
+            parse_variant(v).context("Malformed game variant.")
+        } else {
+            Ok(parse_variant(VARIANT_DEFAULT.to_owned()).unwrap())
+        }
+    }
+
+    fn id(&self) -> String {
+        // Descriptions with the same name may still describe different games.
+        let hash = util::fingerprint(&self.variant);
+        format!("{}.{}.{:016x}", NAME, self.name, hash)
+    }
+
+    fn forward(&mut self, history: Vec<String>) -> Result<()> {
+        self.start = util::verify_history_dynamic(self, history)
+            .context("Malformed game state encoding.")?;
+        Ok(())
+    }
+
+    fn info(&self) -> GameData<'_> {
+        GameData {
+            variant: &self.variant,
+            ..DATA
+        }
+    }
+
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()> {
+        match method {
+            SolutionMode::Strong if self.acyclic && resources.parallel() => {
+                strong::acyclic::parallel_dynamic_solver::<2, Self>(
+                    self, policy, resources,
+                )
+                .context("Failed solver run.")?;
+            },
+            SolutionMode::Strong => {
+                self.strong_solver(mode, policy, resources)?;
+            },
+            _ => {
+                return Err(GameError::SolverNotFound {
+                    input_game_name: NAME,
+                })
+                .context("Solver not found.");
+            },
+        }
+        Ok(())
+    }
+
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+        let db =
+            self.strong_solver(mode, Policy::default(), &Resources::default())?;
+        util::query_entry(self, &db, state)
+    }
+
+    fn verify(&self, directory: &Path, audit: Audit) -> Result<Report> {
+        if self.acyclic {
+            strong::acyclic::dynamic_verifier::<2, Self>(self, directory, audit)
+                .context("Failed verifier run.")
+        } else {
+            Err(GameError::VerifierNotFound {
+                input_game_name: NAME,
+            })
+            .context(
+                "Verifier not found. Only games where all moves are \
+                placements or all moves are jumps can be verified.",
+            )
+        }
+    }
+}
+
+/* TRAVERSAL DECLARATIONS */
+
+impl Bounded<State> for Session {
+    fn start(&self) -> State {
+        self.start
+    }
+
+    fn end(&self, state: State) -> bool {
+        let (cells, turn) = self.unpack(state);
+        self.winner(&cells, turn).is_some()
+            || self
+                .successors(&cells, turn)
+                .is_empty()
+    }
+}
+
+impl DTransition<State> for Session {
+    fn prograde(&self, state: State) -> Vec<State> {
+        let (cells, turn) = self.unpack(state);
+        if self.winner(&cells, turn).is_some() {
+            Vec::new()
+        } else {
+            self.successors(&cells, turn)
+        }
+    }
+
+    fn retrograde(&self, state: State) -> Vec<State> {
+        let (cells, turn) = self.unpack(state);
+        let last = 1 - turn;
+        let mut parents = Vec::new();
+        for rule in &self.rules {
+            match rule {
+                Rule::Place(p) if self.pieces[*p].owner == last => {
+                    for i in self.locate(&cells, *p) {
+                        let mut prev = cells.clone();
+                        prev[i] = 0;
+                        parents.push(prev);
+                    }
+                },
+                Rule::Slide(p, directions) if self.pieces[*p].owner == last => {
+                    for i in self.locate(&cells, *p) {
+                        for &(dr, dc) in directions {
+                            if let Some(j) = self.step(i, (-dr, -dc)) {
+                                if cells[j] == 0 {
+                                    let mut prev = cells.clone();
+                                    prev[j] = p + 1;
+                                    prev[i] = 0;
+                                    parents.push(prev);
+                                }
+                            }
+                        }
+                    }
+                },
+                Rule::Jump(p, directions) if self.pieces[*p].owner == last => {
+                    for i in self.locate(&cells, *p) {
+                        for &(dr, dc) in directions {
+                            let over = self.step(i, (-dr, -dc));
+                            let from =
+                                over.and_then(|m| self.step(m, (-dr, -dc)));
+                            if let (Some(m), Some(j)) = (over, from) {
+                                if cells[m] != 0 || cells[j] != 0 {
+                                    continue;
+                                }
+                                for (q, piece) in self.pieces.iter().enumerate()
+                                {
+                                    if piece.owner == turn {
+                                        let mut prev = cells.clone();
+                                        prev[j] = p + 1;
+                                        prev[m] = q + 1;
+                                        prev[i] = 0;
+                                        parents.push(prev);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        // Inverting the rules can produce boards from which the game would
+        // have already ended, so each candidate is checked against the rules.
+        let mut parents = parents
+            .into_iter()
+            .map(|prev| self.pack(&prev, last))
+            .filter(|&prev| {
+                self.prograde(prev)
+                    .contains(&state)
+            })
+            .collect::<Vec<State>>();
+        parents.sort();
+        parents.dedup();
+        parents
+    }
+}
+
+/* SUPPLEMENTAL DECLARATIONS */
+
+impl Legible<State> for Session {
+    fn decode(&self, string: String) -> Result<State> {
+        Ok(parse_state(self, string)?)
+    }
+
+    fn encode(&self, state: State) -> String {
+        let (cells, turn) = self.unpack(state);
+        let board =
+            cells
+                .chunks(self.cols)
+                .map(|row| {
+                    row.iter()
+                        .map(|&c| {
+                            if c == 0 {
+                                EMPTY
+                            } else {
+                                self.pieces[c - 1].symbol
+                            }
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<String>>()
+                .join("/");
+
+        format!("{}-{}", board, turn)
+    }
+}
+
+/* SOLVING DECLARATIONS */
+
+implement! { for Session =>
+    Limited
+}
+
+impl Acyclic<2> for Session {
+    fn acyclic(&self) -> bool {
+        self.acyclic
+    }
+}
+
+impl Solvable<2> for Session {
+    fn utility(&self, state: State) -> [Utility; 2] {
+        let (cells, turn) = self.unpack(state);
+        let loser = if let Some(winner) = self.winner(&cells, turn) {
+            1 - winner
+        } else if self
+            .wins
+            .iter()
+            .any(|w| matches!(w, Win::NoMoves))
+            && self
+                .successors(&cells, turn)
+                .is_empty()
+        {
+            turn
+        } else {
+            return [0, 0];
+        };
+
+        let mut payoffs = [1; 2];
+        payoffs[loser] = -1;
+        payoffs
+    }
+
+    fn turn(&self, state: State) -> Turn {
+        util::unpack_turn(state, 2).1
+    }
+}
+
+impl Session {
+    /// Strongly solves this session, which is only possible if the rules of
+    /// the game guarantee that no position can be repeated. Ties are broken
+    /// by `policy`.
+    fn strong_solver(
+        &self,
+        mode: IOMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<crate::database::volatile::Database> {
+        if self.acyclic {
+            strong::acyclic::dynamic_solver::<2, Self>(
+                self, mode, policy, resources,
+            )
+            .context("Failed solver run.")
+        } else {
+            Err(GameError::SolverNotFound {
+                input_game_name: NAME,
+            })
+            .context(
+                "Solver not found. Only games where all moves are placements \
+                or all moves are jumps can be solved.",
+            )
+        }
+    }
+}
+
+/* HELPER FUNCTIONS */
+
+impl Session {
+    /// Returns the contents of each cell of the board in `state` (in row-major
+    /// order), where 0 is an empty cell and `k` is a piece of type `k - 1`,
+    /// along with the player whose turn it is.
+    fn unpack(&self, state: State) -> (Vec<usize>, Turn) {
+        let (mut board, turn) = util::unpack_turn(state, 2);
+        let radix = self.pieces.len() as State + 1;
+        let mut cells = Vec::with_capacity(self.rows * self.cols);
+        for _ in 0..(self.rows * self.cols) {
+            cells.push((board % radix) as usize);
+            board /= radix;
+        }
+        (cells, turn)
+    }
+
+    /// Returns the state with the board described by `cells` (in the same
+    /// format as `unpack`) where it is the turn of `turn`.
+    fn pack(&self, cells: &[usize], turn: Turn) -> State {
+        let radix = self.pieces.len() as State + 1;
+        let board = cells
+            .iter()
+            .rev()
+            .fold(0, |board, &c| board * radix + c as State);
+
+        util::pack_turn(board, turn, 2)
+    }
+
+    /// Returns the states which result from each move that `turn` can make
+    /// on the board described by `cells`, regardless of whether the game has
+    /// already been won.
+    fn successors(&self, cells: &[usize], turn: Turn) -> Vec<State> {
+        let mut next = Vec::new();
+        for rule in &self.rules {
+            match rule {
+                Rule::Place(p) if self.pieces[*p].owner == turn => {
+                    for i in self.locate(cells, usize::MAX) {
+                        let mut child = cells.to_vec();
+                        child[i] = p + 1;
+                        next.push(child);
+                    }
+                },
+                Rule::Slide(p, directions) if self.pieces[*p].owner == turn => {
+                    for i in self.locate(cells, *p) {
+                        for &d in directions {
+                            if let Some(j) = self.step(i, d) {
+                                if cells[j] == 0 {
+                                    let mut child = cells.to_vec();
+                                    child[j] = p + 1;
+                                    child[i] = 0;
+                                    next.push(child);
+                                }
+                            }
+                        }
+                    }
+                },
+                Rule::Jump(p, directions) if self.pieces[*p].owner == turn => {
+                    for i in self.locate(cells, *p) {
+                        for &d in directions {
+                            let over = self.step(i, d);
+                            let onto = over.and_then(|m| self.step(m, d));
+                            if let (Some(m), Some(j)) = (over, onto) {
+                                if self.owner(cells[m]) == Some(1 - turn)
+                                    && cells[j] == 0
+                                {
+                                    let mut child = cells.to_vec();
+                                    child[j] = p + 1;
+                                    child[m] = 0;
+                                    child[i] = 0;
+                                    next.push(child);
+                                }
+                            }
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        let mut next = next
+            .into_iter()
+            .map(|child| self.pack(&child, 1 - turn))
+            .collect::<Vec<State>>();
+        next.sort();
+        next.dedup();
+        next
+    }
+
+    /// Returns the player who has won on the board described by `cells` where
+    /// it is the turn of `turn`, if any. Does not account for `Win::NoMoves`.
+    fn winner(&self, cells: &[usize], turn: Turn) -> Option<Turn> {
+        let last = 1 - turn;
+        for win in &self.wins {
+            match win {
+                Win::Line(length) => {
+                    for player in [last, turn] {
+                        if self.line(cells, player, *length) {
+                            return Some(player);
+                        }
+                    }
+                },
+                Win::CaptureAll => {
+                    let count = |player| {
+                        cells
+                            .iter()
+                            .filter(|&&c| self.owner(c) == Some(player))
+                            .count()
+                    };
+                    if count(turn) == 0 && count(last) > 0 {
+                        return Some(last);
+                    }
+                },
+                Win::NoMoves => (),
+            }
+        }
+        None
+    }
+
+    /// Returns true if `player` owns `length` consecutive cells in a line on
+    /// the board described by `cells`.
+    fn line(&self, cells: &[usize], player: Turn, length: usize) -> bool {
+        let directions = [(0, 1), (1, 0), (1, 1), (1, -1)];
+        (0..cells.len()).any(|start| {
+            directions.iter().any(|&d| {
+                let mut curr = Some(start);
+                for _ in 0..length {
+                    match curr {
+                        Some(i) if self.owner(cells[i]) == Some(player) => {
+                            curr = self.step(i, d);
+                        },
+                        _ => return false,
+                    }
+                }
+                true
+            })
+        })
+    }
+
+    /// Returns the indices of the cells containing a piece of type `piece`, or
+    /// of the empty cells if `piece` is `usize::MAX`.
+    fn locate(&self, cells: &[usize], piece: usize) -> Vec<usize> {
+        let target = piece.wrapping_add(1);
+        (0..cells.len())
+            .filter(|&i| cells[i] == target)
+            .collect()
+    }
+
+    /// Returns the index of the cell reached by moving from the cell at index
+    /// `cell` in `direction`, if it is within the board.
+    fn step(&self, cell: usize, direction: Direction) -> Option<usize> {
+        let row = (cell / self.cols) as isize + direction.0;
+        let col = (cell % self.cols) as isize + direction.1;
+        if row < 0
+            || col < 0
+            || row >= self.rows as isize
+            || col >= self.cols as isize
+        {
+            None
+        } else {
+            Some(row as usize * self.cols + col as usize)
+        }
+    }
+
+    /// Returns the owner of the contents of a cell, if it is not empty.
+    fn owner(&self, cell: usize) -> Option<Turn> {
+        if cell == 0 {
+            None
+        } else {
+            Some(self.pieces[cell - 1].owner)
+        }
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::testing;
+    use crate::solver::error::SolverError;
+
+    /// A game where pieces can only jump over each other, and where the
+    /// player left without moves loses.
+    const JUMPING: &str = "
+        name jumping
+        board 3 3
+        piece X 0
+        piece O 1
+        move jump X all
+        move jump O all
+        win no-moves
+        start 2 0 X
+        start 1 1 O
+        start 0 0 O
+    ";
+
+    /// A game where players slide their pieces, which might repeat positions.
+    const SLIDING: &str = "
+        name sliding
+        board 2 2
+        piece X 0
+        piece O 1
+        move slide X orthogonal
+        move slide O orthogonal
+        start 0 0 X
+        start 1 1 O
+    ";
+
+    #[test]
+    fn tic_tac_toe_is_a_draw() {
+        let session = Session::initialize(None).unwrap();
+        let entry = session
+            .query(IOMode::Write, None)
+            .unwrap();
+
+        assert_eq!(entry.utility, vec![0, 0]);
+        assert_eq!(entry.remoteness, 9);
+    }
+
+    #[test]
+    fn tic_tac_toe_outcomes_are_correct() {
+        let session = Session::initialize(None).unwrap();
+        let query = |s: &str| {
+            session
+                .query(IOMode::Write, Some(s.to_owned()))
+                .unwrap()
+        };
+
+        // The first player can complete the top row right away.
+        let entry = query("XX./OO./...-0");
+        assert_eq!(entry.utility, vec![1, -1]);
+        assert_eq!(entry.remoteness, 1);
+
+        // The second player has already completed the middle row.
+        let entry = query("XX./OOO/X..-0");
+        assert_eq!(entry.utility, vec![-1, 1]);
+        assert_eq!(entry.remoteness, 0);
+    }
+
+    #[test]
+    fn described_games_conform_to_game_interfaces() {
+        let ttt = Session::initialize(None).unwrap();
+        let jumping = Session::initialize(Some(JUMPING.into())).unwrap();
+        let sliding = Session::initialize(Some(SLIDING.into())).unwrap();
+        testing::verify_acyclic::<2, _>(&ttt, 1_000).unwrap();
+        testing::verify_acyclic::<2, _>(&jumping, 1_000).unwrap();
+        testing::verify_traversal(&sliding, 1_000).unwrap();
+    }
+
+    #[test]
+    fn jumping_captures_and_no_moves_loses() {
+        let session = Session::initialize(Some(JUMPING.into())).unwrap();
+        let children = session
+            .prograde(session.start())
+            .into_iter()
+            .map(|s| session.encode(s))
+            .collect::<Vec<String>>();
+
+        // The X can only jump over the center O, after which the remaining O
+        // has nothing to jump over.
+        assert_eq!(children, vec!["O.X/.../...-1"]);
+        let entry = session
+            .query(IOMode::Write, None)
+            .unwrap();
+
+        assert_eq!(entry.utility, vec![1, -1]);
+        assert_eq!(entry.remoteness, 1);
+    }
+
+    #[test]
+    fn descriptions_with_the_same_name_have_distinct_ids() {
+        let jumping = Session::initialize(Some(JUMPING.to_owned())).unwrap();
+        let variant = JUMPING.replace("board 3 3", "board 3 4");
+        let wider = Session::initialize(Some(variant)).unwrap();
+
+        assert_eq!(jumping.name, wider.name);
+        assert_ne!(jumping.id(), wider.id());
+        assert_eq!(
+            jumping.id(),
+            Session::initialize(Some(JUMPING.to_owned()))
+                .unwrap()
+                .id()
+        );
+    }
+
+    #[test]
+    fn games_with_repeatable_positions_are_not_solved() {
+        let session = Session::initialize(Some(SLIDING.into())).unwrap();
+        assert!(session
+            .solve(
+                IOMode::Write,
+                SolutionMode::Strong,
+                Policy::default(),
+                &Resources::default(),
+            )
+            .is_err());
+        assert!(super::session(Some(SLIDING.into()), true).is_err());
+    }
+
+    #[test]
+    fn acyclic_solvers_refuse_games_with_repeatable_positions() {
+        let session = Session::initialize(Some(SLIDING.into())).unwrap();
+        let Err(error) = strong::acyclic::dynamic_solver::<2, _>(
+            &session,
+            IOMode::Write,
+            Policy::default(),
+            &Resources::default(),
+        ) else {
+            panic!("Solved a game with repeatable positions.");
+        };
+
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::CyclicGame { .. })
+        ));
+    }
+}
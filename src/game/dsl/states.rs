@@ -0,0 +1,173 @@
+//! # Game Description State Handling Module
+//!
+//! This module helps parse the string encoding of a board in a described game
+//! into a more efficient binary representation, verifying that it is coherent
+//! with the dimensions of the board and the pieces in the game description.
+
+use regex::Regex;
+
+use crate::game::dsl::{Session, NAME};
+use crate::game::error::GameError;
+use crate::model::{State, Turn};
+
+/* GAME DESCRIPTION STATE ENCODING */
+
+/// The character which represents an empty cell in state encodings.
+pub const EMPTY: char = '.';
+
+pub const STATE_DEFAULT: &str = ".../.../...-0";
+pub const STATE_PATTERN: &str = r"^[.0-9A-Za-z]+(?:/[.0-9A-Za-z]+)*-\d+$";
+pub const STATE_PROTOCOL: &str =
+"The state string should be the rows of the board from top to bottom separated \
+by slashes, followed by a dash and the player whose turn it is (0 or 1). Each \
+row is a sequence of characters, one for each cell from left to right, where \
+empty cells are represented by a dot and cells with a piece are represented by \
+the symbol of its type. For example, 'X../.O./...-0' is a Tic-Tac-Toe board \
+with an X in the top-left corner and an O in the center, where it is the turn \
+of player 0.";
+
+/* API */
+
+/// Returns the state encoded by `from` in the game described for `session`.
+/// This does not verify that the provided `from` is reachable in `session`'s
+/// game variant.
+pub fn parse_state(
+    session: &Session,
+    from: String,
+) -> Result<State, GameError> {
+    check_state_pattern(&from)?;
+    let (board, turn) = from.rsplit_once('-').unwrap();
+    let turn = parse_turn(turn)?;
+    let rows = board
+        .split('/')
+        .collect::<Vec<&str>>();
+    check_dimensions(&rows, session)?;
+
+    let cells = rows
+        .iter()
+        .flat_map(|row| row.chars())
+        .map(|c| parse_cell(c, session))
+        .collect::<Result<Vec<usize>, GameError>>()?;
+
+    Ok(session.pack(&cells, turn))
+}
+
+/* STATE STRING VERIFICATION */
+
+fn check_state_pattern(from: &str) -> Result<(), GameError> {
+    let re = Regex::new(STATE_PATTERN).unwrap();
+    if !re.is_match(from) {
+        Err(malformed(format!(
+            "String does not match the pattern '{}'.",
+            STATE_PATTERN
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_turn(turn: &str) -> Result<Turn, GameError> {
+    match turn.parse::<Turn>() {
+        Ok(turn) if turn < 2 => Ok(turn),
+        Ok(turn) => Err(malformed(format!(
+            "Specified a turn ({}) too high for this (2-player) game.",
+            turn,
+        ))),
+        Err(e) => Err(malformed(e.to_string())),
+    }
+}
+
+fn check_dimensions(rows: &[&str], session: &Session) -> Result<(), GameError> {
+    if rows.len() != session.rows {
+        Err(malformed(format!(
+            "Specified {} rows, but the board has {} rows.",
+            rows.len(),
+            session.rows,
+        )))
+    } else if let Some(row) = rows
+        .iter()
+        .find(|r| r.chars().count() != session.cols)
+    {
+        Err(malformed(format!(
+            "The row '{}' does not have exactly {} cells.",
+            row, session.cols,
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_cell(cell: char, session: &Session) -> Result<usize, GameError> {
+    if cell == EMPTY {
+        Ok(0)
+    } else {
+        session
+            .pieces
+            .iter()
+            .position(|p| p.symbol == cell)
+            .map(|p| p + 1)
+            .ok_or_else(|| {
+                malformed(format!(
+                    "There is no piece with the symbol '{}'.",
+                    cell
+                ))
+            })
+    }
+}
+
+fn malformed(hint: String) -> GameError {
+    GameError::StateMalformed {
+        game_name: NAME,
+        hint,
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::{Bounded, Game, Legible};
+
+    #[test]
+    fn state_pattern_is_valid_regex() {
+        assert!(Regex::new(STATE_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_state_is_start() {
+        let session = Session::initialize(None).unwrap();
+        let state = session
+            .decode(STATE_DEFAULT.to_owned())
+            .unwrap();
+
+        assert_eq!(state, session.start());
+        assert_eq!(session.encode(state), STATE_DEFAULT);
+    }
+
+    #[test]
+    fn invalid_states_fail_checks() {
+        let session = Session::initialize(None).unwrap();
+        let s = |v: &str| parse_state(&session, v.to_owned());
+        assert!(s("").is_err());
+        assert!(s(".../.../...").is_err());
+        assert!(s(".../.../...-2").is_err());
+        assert!(s(".../...-0").is_err());
+        assert!(s(".../..../...-0").is_err());
+        assert!(s(".../.Y./...-0").is_err());
+        assert!(s(".../.-./...-0").is_err());
+    }
+
+    #[test]
+    fn valid_states_pass_checks() {
+        let session = Session::initialize(None).unwrap();
+        let s = |v: &str| parse_state(&session, v.to_owned());
+        assert!(s("X../.O./...-0").is_ok());
+        assert!(s("XXX/OOO/XXX-1").is_ok());
+        assert_eq!(
+            session.encode(s("X../.O./..X-1").unwrap()),
+            "X../.O./..X-1"
+        );
+    }
+}
@@ -0,0 +1,434 @@
+//! # Game Description Variant Handling Module
+//!
+//! This module parses the description of a game (which is the variant string
+//! of the game description language interpreter) into a game session, checking
+//! that the description is coherent and that its states fit in 64 bits.
+
+use regex::Regex;
+
+use crate::game::dsl::{Direction, Piece, Rule, Session, Win, NAME};
+use crate::game::error::GameError;
+use crate::model::State;
+
+/* GAME DESCRIPTION VARIANT ENCODING */
+
+pub const VARIANT_DEFAULT: &str = "\
+# Tic-Tac-Toe, where the first player to get three in a row wins.
+name tic-tac-toe
+board 3 3
+piece X 0
+piece O 1
+move place X
+move place O
+win line 3
+";
+
+pub const VARIANT_PATTERN: &str = r"(?m)^\s*board\s+\d+\s+\d+\s*(?:#.*)?$";
+pub const VARIANT_PROTOCOL: &str =
+"The variant string should be a description of the game, with one directive \
+per line. Blank lines and anything after a '#' are ignored. The directives are \
+'name <name>', giving the game a name made of lowercase letters, digits, and \
+dashes; 'board <rows> <columns>', giving the dimensions of the board; 'piece \
+<symbol> <player>', declaring a type of piece represented by an alphanumeric \
+character and owned by player 0 or 1; 'move place <symbol>', allowing the \
+owner of a type of piece to place one on any empty cell; 'move slide <symbol> \
+<directions>', allowing a piece to be moved to an adjacent empty cell; 'move \
+jump <symbol> <directions>', allowing a piece to jump over an adjacent opposing \
+piece onto the empty cell behind it, capturing it; 'win line <length>', making \
+a player win when they own that many pieces in a row, column, or diagonal; \
+'win capture-all', making a player win when their opponent has no pieces left \
+on their turn; 'win no-moves', making a player win when their opponent cannot \
+move on their turn; and 'start <row> <column> <symbol>', placing a piece on \
+the board at the start of the game. Directions can be 'orthogonal', \
+'diagonal', or 'all'. The name and board directives are required, and each \
+player must own at least one type of piece. Player 0 moves first, and games \
+which end without a winner are draws. Note that the number of cells and the \
+number of types of pieces are limited by the need to fit all states in 64 \
+bits. Only games where all moves are placements or all moves are jumps can be \
+solved.";
+
+/* API */
+
+/// Returns a session of the game described by `variant`. Returns a
+/// `GameError::VariantMalformed` if any directive in the description is not
+/// valid, if the description is incomplete, or if the states of the game
+/// could not be encoded in 64 bits.
+pub fn parse_variant(variant: String) -> Result<Session, GameError> {
+    check_variant_pattern(&variant)?;
+    let mut description = Description::default();
+    for (number, line) in variant.lines().enumerate() {
+        let line = line
+            .split('#')
+            .next()
+            .unwrap_or_default();
+
+        let words = line
+            .split_whitespace()
+            .collect::<Vec<&str>>();
+
+        if !words.is_empty() {
+            description
+                .directive(&words)
+                .map_err(|hint| {
+                    malformed(format!("Line {}: {}", number + 1, hint))
+                })?;
+        }
+    }
+
+    description.build(variant)
+}
+
+/* DESCRIPTION PARSING */
+
+/// The contents of a game description, accumulated one directive at a time.
+#[derive(Default)]
+struct Description {
+    name: Option<String>,
+    board: Option<(usize, usize)>,
+    pieces: Vec<Piece>,
+    rules: Vec<Rule>,
+    wins: Vec<Win>,
+    start: Vec<(usize, usize, usize)>,
+}
+
+impl Description {
+    /// Adds the directive made up of `words` to this description, returning a
+    /// hint on what is wrong with the directive if it is not valid.
+    fn directive(&mut self, words: &[&str]) -> Result<(), String> {
+        match words {
+            ["name", name] => {
+                let re = Regex::new(r"^[a-z0-9-]+$").unwrap();
+                if self.name.is_some() {
+                    Err("The game already has a name.".into())
+                } else if !re.is_match(name) {
+                    Err(format!("The name '{}' is not valid.", name))
+                } else {
+                    self.name = Some(name.to_string());
+                    Ok(())
+                }
+            },
+            ["board", rows, cols] => {
+                let rows = positive(rows)?;
+                let cols = positive(cols)?;
+                if self.board.is_some() {
+                    Err("The board was already specified.".into())
+                } else {
+                    self.board = Some((rows, cols));
+                    Ok(())
+                }
+            },
+            ["piece", symbol, player] => {
+                let symbol = symbol_char(symbol)?;
+                let owner = match *player {
+                    "0" => 0,
+                    "1" => 1,
+                    _ => {
+                        return Err(format!("There is no player '{}'.", player))
+                    },
+                };
+
+                if self
+                    .pieces
+                    .iter()
+                    .any(|p| p.symbol == symbol)
+                {
+                    Err(format!(
+                        "The piece '{}' was already declared.",
+                        symbol
+                    ))
+                } else {
+                    self.pieces
+                        .push(Piece { symbol, owner });
+                    Ok(())
+                }
+            },
+            ["move", "place", symbol] => {
+                let piece = self.piece(symbol)?;
+                self.rules.push(Rule::Place(piece));
+                Ok(())
+            },
+            ["move", "slide", symbol, directions] => {
+                let piece = self.piece(symbol)?;
+                self.rules
+                    .push(Rule::Slide(piece, parse_directions(directions)?));
+                Ok(())
+            },
+            ["move", "jump", symbol, directions] => {
+                let piece = self.piece(symbol)?;
+                self.rules
+                    .push(Rule::Jump(piece, parse_directions(directions)?));
+                Ok(())
+            },
+            ["win", "line", length] => {
+                self.wins
+                    .push(Win::Line(positive(length)?));
+                Ok(())
+            },
+            ["win", "capture-all"] => {
+                self.wins.push(Win::CaptureAll);
+                Ok(())
+            },
+            ["win", "no-moves"] => {
+                self.wins.push(Win::NoMoves);
+                Ok(())
+            },
+            ["start", row, col, symbol] => {
+                let piece = self.piece(symbol)?;
+                let (rows, cols) = self.board.ok_or(
+                    "The board must be specified before any pieces are \
+                    placed on it.",
+                )?;
+
+                let row = row
+                    .parse::<usize>()
+                    .map_err(|e| e.to_string())?;
+                let col = col
+                    .parse::<usize>()
+                    .map_err(|e| e.to_string())?;
+
+                if row >= rows || col >= cols {
+                    Err(format!(
+                        "The cell ({}, {}) is not on the board.",
+                        row, col
+                    ))
+                } else if self
+                    .start
+                    .iter()
+                    .any(|&(r, c, _)| r == row && c == col)
+                {
+                    Err(format!(
+                        "The cell ({}, {}) is already occupied.",
+                        row, col
+                    ))
+                } else {
+                    self.start.push((row, col, piece));
+                    Ok(())
+                }
+            },
+            _ => Err(format!(
+                "The directive '{}' is not valid.",
+                words.join(" ")
+            )),
+        }
+    }
+
+    /// Returns the index of the type of piece represented by `symbol`.
+    fn piece(&self, symbol: &str) -> Result<usize, String> {
+        let symbol = symbol_char(symbol)?;
+        self.pieces
+            .iter()
+            .position(|p| p.symbol == symbol)
+            .ok_or(format!(
+                "The piece '{}' was not declared.",
+                symbol
+            ))
+    }
+
+    /// Returns a session of the game described, verifying that the description
+    /// is complete and that its states can be encoded in 64 bits.
+    fn build(self, variant: String) -> Result<Session, GameError> {
+        let name = self
+            .name
+            .ok_or_else(|| malformed("The game must have a name.".into()))?;
+
+        let (rows, cols) = self
+            .board
+            .ok_or_else(|| malformed("The board must be specified.".into()))?;
+
+        for player in 0..2 {
+            if !self
+                .pieces
+                .iter()
+                .any(|p| p.owner == player)
+            {
+                return Err(malformed(format!(
+                    "Player {} must own at least one type of piece.",
+                    player
+                )));
+            }
+        }
+
+        if self.rules.is_empty() {
+            return Err(malformed(
+                "There must be at least one move.".into(),
+            ));
+        }
+
+        let cells = rows
+            .checked_mul(cols)
+            .ok_or_else(|| {
+                malformed(format!(
+                    "A board with {} rows and {} columns has too many cells.",
+                    rows, cols,
+                ))
+            })?;
+
+        check_state_size(cells, self.pieces.len())?;
+        let acyclic = self
+            .rules
+            .iter()
+            .all(|r| matches!(r, Rule::Place(_)))
+            || self
+                .rules
+                .iter()
+                .all(|r| matches!(r, Rule::Jump(..)));
+
+        let mut session = Session {
+            variant,
+            name,
+            rows,
+            cols,
+            pieces: self.pieces,
+            rules: self.rules,
+            wins: self.wins,
+            acyclic,
+            start: 0,
+        };
+
+        let mut cells = vec![0; rows * cols];
+        for (row, col, piece) in self.start {
+            cells[row * cols + col] = piece + 1;
+        }
+        session.start = session.pack(&cells, 0);
+        Ok(session)
+    }
+}
+
+/* VARIANT STRING VERIFICATION */
+
+fn check_variant_pattern(variant: &str) -> Result<(), GameError> {
+    let re = Regex::new(VARIANT_PATTERN).unwrap();
+    if !re.is_match(variant) {
+        Err(malformed(format!(
+            "String does not match the pattern '{}'.",
+            VARIANT_PATTERN
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Fails if the states of a game with `cells` cells on its board and `pieces`
+/// types of pieces (and a turn bit) cannot be encoded in a `State`.
+fn check_state_size(cells: usize, pieces: usize) -> Result<(), GameError> {
+    let mut product: State = 2;
+    for _ in 0..cells {
+        product = product
+            .checked_mul(pieces as State + 1)
+            .ok_or_else(|| {
+                malformed(format!(
+                    "A board with {} cells and {} types of pieces has too many \
+                    states for all of them to be encoded in 64 bits.",
+                    cells, pieces,
+                ))
+            })?;
+    }
+    Ok(())
+}
+
+/* HELPER FUNCTIONS */
+
+fn malformed(hint: String) -> GameError {
+    GameError::VariantMalformed {
+        game_name: NAME,
+        hint,
+    }
+}
+
+fn positive(word: &str) -> Result<usize, String> {
+    match word.parse::<usize>() {
+        Ok(0) => Err("Expected a positive integer, but found 0.".into()),
+        Ok(n) => Ok(n),
+        Err(e) => Err(format!("Expected a positive integer ({}).", e)),
+    }
+}
+
+fn symbol_char(word: &str) -> Result<char, String> {
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => Ok(c),
+        _ => Err(format!(
+            "The symbol '{}' is not a single alphanumeric character.",
+            word
+        )),
+    }
+}
+
+fn parse_directions(word: &str) -> Result<Vec<Direction>, String> {
+    let orthogonal = vec![(-1, 0), (1, 0), (0, -1), (0, 1)];
+    let diagonal = vec![(-1, -1), (-1, 1), (1, -1), (1, 1)];
+    match word {
+        "orthogonal" => Ok(orthogonal),
+        "diagonal" => Ok(diagonal),
+        "all" => Ok([orthogonal, diagonal].concat()),
+        _ => Err(format!(
+            "The directions '{}' are not valid.",
+            word
+        )),
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn variant_pattern_is_valid_regex() {
+        assert!(Regex::new(VARIANT_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_variant_matches_variant_pattern() {
+        let re = Regex::new(VARIANT_PATTERN).unwrap();
+        assert!(re.is_match(VARIANT_DEFAULT));
+    }
+
+    #[test]
+    fn invalid_variants_fail_checks() {
+        let v = |s: &str| parse_variant(s.replace("; ", "\n"));
+        let base = "name a; board 3 3; piece X 0; piece O 1";
+        let with = |s: &str| v(&format!("{}; {}", base, s));
+        assert!(v("").is_err());
+        assert!(v("name a; piece X 0; piece O 1; move place X").is_err());
+        assert!(v("board 3 3; piece X 0; piece O 1; move place X").is_err());
+        assert!(v("name a; board 3 3; piece X 0; move place X").is_err());
+        assert!(v("name a; board 0 3; piece X 0; piece O 1").is_err());
+        assert!(v("name A; board 3 3; piece X 0; piece O 1").is_err());
+        assert!(v(base).is_err());
+        assert!(with("move place Y").is_err());
+        assert!(with("move slide X sideways").is_err());
+        assert!(with("move hop X all").is_err());
+        assert!(with("piece X 1; move place X").is_err());
+        assert!(with("piece Z 2; move place X").is_err());
+        assert!(with("piece .. 0; move place X").is_err());
+        assert!(with("move place X; win line 0").is_err());
+        assert!(with("move place X; start 3 0 X").is_err());
+        assert!(with("move place X; start 0 0 X; start 0 0 O").is_err());
+        assert!(
+            v("name a; board 8 8; piece X 0; piece O 1; move place X").is_err()
+        );
+        assert!(v(
+            "name a; board 4294967296 4294967296; piece X 0; piece O 1; \
+            move place X"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn valid_variants_pass_checks() {
+        let v = |s: &str| parse_variant(s.replace("; ", "\n"));
+        let base = "name a; board 3 3; piece X 0; piece O 1";
+        let with = |s: &str| v(&format!("{}; {}", base, s));
+        assert!(v(VARIANT_DEFAULT).is_ok());
+        assert!(with("move place X # comment").is_ok());
+        assert!(with("move slide X all; move jump O diagonal").is_ok());
+        assert!(with("move jump X orthogonal; win capture-all").is_ok());
+        assert!(with("move place O; win no-moves; start 1 1 X").is_ok());
+        assert!(
+            v("name a; board 5 5; piece X 0; piece O 1; move place X").is_ok()
+        );
+    }
+}
@@ -9,6 +9,8 @@
 
 use std::{error::Error, fmt};
 
+use crate::interface::Analyzer;
+
 /* ERROR WRAPPER */
 
 /// Wrapper for all game-related errors that could happen during runtime. Note
@@ -16,10 +18,33 @@ use std::{error::Error, fmt};
 /// of interface elements in `crate::game::mod`.
 #[derive(Debug)]
 pub enum GameError {
+    /// An error to indicate that a user attempted to use a game which is not
+    /// in the game registry (see `crate::game::registry`).
+    GameNotFound { input_game_name: String },
+
     /// An error to indicate that a user attempted to solve a game variant
     /// which is valid, but has no solver available to solve it.
     SolverNotFound { input_game_name: &'static str },
 
+    /// An error to indicate that a user attempted to play the misère version
+    /// of a game variant for which misère play is not supported.
+    MisereNotFound { input_game_name: &'static str },
+
+    /// An error to indicate that a user attempted to enumerate the variants of
+    /// a game which does not provide a way to enumerate its variants.
+    VariantsNotFound { input_game_name: &'static str },
+
+    /// An error to indicate that a user attempted to verify a solution set of a
+    /// game variant which provides no way of verifying its solution sets.
+    VerifierNotFound { input_game_name: &'static str },
+
+    /// An error to indicate that a user attempted to run an `analyzer` on a
+    /// game variant which does not support it.
+    AnalyzerNotFound {
+        input_game_name: &'static str,
+        analyzer: Analyzer,
+    },
+
     /// An error to indicate that the variant passed to the game with
     /// `game_name` was not in a format the game could parse. Includes a
     /// message from the game implementation on exactly what went wrong. Note
@@ -40,6 +65,16 @@ pub enum GameError {
         hint: String,
     },
 
+    /// An error to indicate that the move string passed to the game with
+    /// `game_name` was not in a format the game could parse, or that it does
+    /// not correspond to a legal move. Includes a message from the game
+    /// implementation on exactly what went wrong. Note that `game_name` should
+    /// be a valid argument to the `--target` parameter in the CLI.
+    MoveMalformed {
+        game_name: &'static str,
+        hint: String,
+    },
+
     /// An error to indicate that a sequence of states in string form would
     /// be impossible to reproduce in real play. Includes a message from the
     /// game implementation on exactly what went wrong. Note that `game_name`
@@ -48,6 +83,26 @@ pub enum GameError {
         game_name: &'static str,
         hint: String,
     },
+
+    /// An error to indicate that the external game process spawned through
+    /// `command` could not be reached, or that it did not respond according to
+    /// the external game protocol (see `crate::game::external`). Includes a
+    /// message on exactly what went wrong.
+    ExternalFailure { command: String, hint: String },
+
+    /// An error to indicate that the plugin at `path` could not be used
+    /// because it does not implement the expected version of the plugin
+    /// interface (see `crate::game::plugin`). Includes a message on exactly
+    /// what is incompatible.
+    PluginIncompatible { path: String, hint: String },
+
+    /// An error to indicate that the plugin implementing `game_name` did not
+    /// behave as required by the plugin interface (see `crate::game::plugin`)
+    /// while it was being used. Includes a message on exactly what went wrong.
+    PluginFailure {
+        game_name: &'static str,
+        hint: String,
+    },
 }
 
 impl Error for GameError {}
@@ -63,6 +118,49 @@ impl fmt::Display for GameError {
                     input_game_name
                 )
             },
+            Self::GameNotFound { input_game_name } => {
+                write!(
+                    f,
+                    "There is no game called '{}'. A list of the available \
+                    games can be found with 'nova info'.",
+                    input_game_name
+                )
+            },
+            Self::MisereNotFound { input_game_name } => {
+                write!(
+                    f,
+                    "The variant you specified for the game {} has no misère \
+                    version associated with it.",
+                    input_game_name
+                )
+            },
+            Self::VariantsNotFound { input_game_name } => {
+                write!(
+                    f,
+                    "The game {} does not enumerate any variants within the \
+                    state limit you specified.",
+                    input_game_name
+                )
+            },
+            Self::VerifierNotFound { input_game_name } => {
+                write!(
+                    f,
+                    "The variant you specified for the game {} has no verifier \
+                    associated with its solution sets.",
+                    input_game_name
+                )
+            },
+            Self::AnalyzerNotFound {
+                input_game_name,
+                analyzer,
+            } => {
+                write!(
+                    f,
+                    "The variant you specified for the game {} does not \
+                    support the '{}' analyzer.",
+                    input_game_name, analyzer,
+                )
+            },
             Self::VariantMalformed { game_name, hint } => {
                 write!(
                     f,
@@ -79,6 +177,14 @@ impl fmt::Display for GameError {
                     hint, game_name
                 )
             },
+            Self::MoveMalformed { game_name, hint } => {
+                write!(
+                    f,
+                    "{}\n\n\tMore information on how the game expects you to \
+                    format it can be found with 'nova info {} --output extra'.",
+                    hint, game_name
+                )
+            },
             Self::InvalidHistory { game_name, hint } => {
                 write!(
                     f,
@@ -87,6 +193,29 @@ impl fmt::Display for GameError {
                     hint, game_name
                 )
             },
+            Self::PluginIncompatible { path, hint } => {
+                write!(
+                    f,
+                    "The plugin '{}' is not compatible with this version of \
+                    Nova: {}",
+                    path, hint
+                )
+            },
+            Self::PluginFailure { game_name, hint } => {
+                write!(
+                    f,
+                    "The plugin game '{}' failed to follow the plugin \
+                    interface: {}",
+                    game_name, hint
+                )
+            },
+            Self::ExternalFailure { command, hint } => {
+                write!(
+                    f,
+                    "The external game '{}' failed to follow the protocol: {}",
+                    command, hint
+                )
+            },
         }
     }
 }
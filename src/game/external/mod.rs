@@ -0,0 +1,504 @@
+//! # External Game Module
+//!
+//! This module provides an adapter for games implemented outside of this
+//! project (possibly in other languages), which run as separate processes and
+//! communicate with the adapter through a line-delimited JSON protocol over
+//! their standard input and output. This allows the existing solvers to be used
+//! on such games without any changes to them.
+//!
+//! ## Protocol
+//!
+//! Each request is a JSON object with a `message` field indicating its type,
+//! sent as a single line. The process must answer each request with a single
+//! line containing a JSON object, which is either a response with the fields
+//! below or an object with an `error` field explaining what went wrong. States
+//! are represented as unsigned 64-bit integers.
+//!
+//! | Message      | Request fields | Response fields                          |
+//! |--------------|----------------|------------------------------------------|
+//! | `info`       |                | `name`, `authors`, `about`, `players`,   |
+//! |              |                | `acyclic`, `state_protocol`,             |
+//! |              |                | `state_pattern`, `state_default`         |
+//! | `start`      |                | `state`                                  |
+//! | `end`        | `state`        | `end` (boolean)                          |
+//! | `prograde`   | `state`        | `states` (array)                         |
+//! | `retrograde` | `state`        | `states` (array)                         |
+//! | `encode`     | `state`        | `string`                                 |
+//! | `decode`     | `string`       | `state`                                  |
+//! | `utility`    | `state`        | `utility` (array of integers)            |
+//! | `turn`       | `state`        | `turn`                                   |
+//!
+//! Since the traversal interfaces cannot fail, the first violation of the
+//! protocol after a session is initialized is recorded, and the traversal is
+//! cut short by treating the offending state as an end state. The violation is
+//! then reported as a `GameError::ExternalFailure` by the operation which led
+//! to it (such as solving or querying the game).
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use std::cell::RefCell;
+
+use crate::database::volatile;
+use crate::game::error::GameError;
+use crate::game::external::protocol::Channel;
+use crate::game::{util, Acyclic, Bounded, DTransition, Entry, Legible};
+use crate::game::{Game, GameData, Limited, Solvable};
+use crate::interface::{IOMode, SolutionMode};
+use crate::model::{PlayerCount, State, Turn, Utility};
+use crate::solver::strong;
+use crate::solver::util::{Policy, Resources};
+use crate::solver::SOLVABLE_PLAYERS;
+use crate::{register, solvable};
+
+/* SUBMODULES */
+
+mod protocol;
+
+/* GAME DATA */
+
+const NAME: &str = "external";
+const VARIANT_DEFAULT: &str = "";
+const VARIANT_PATTERN: &str = r"^\S.*$";
+const VARIANT_PROTOCOL: &str =
+"The variant string should be the path to an executable which implements the \
+external game protocol, optionally followed by whitespace-separated arguments \
+to be passed to it. For example, 'python3 game.py' would spawn a Python \
+interpreter running the script 'game.py' as the external game.";
+
+/* REGISTRATION */
+
+const DATA: GameData = GameData {
+    variant: VARIANT_DEFAULT,
+
+    name: NAME,
+    authors: "Provided by each external game.",
+    about: "Adapter for games running as separate processes, which can be \
+    implemented in any language through the external game protocol. The game \
+    variant determines which executable is spawned.",
+
+    variant_protocol: VARIANT_PROTOCOL,
+    variant_pattern: VARIANT_PATTERN,
+    variant_default: VARIANT_DEFAULT,
+
+    state_default: "",
+    state_pattern: "",
+    state_protocol: "Provided by each external game.",
+
+    move_protocol: None,
+    move_pattern: None,
+};
+
+register! { DATA => session }
+
+/// Initializes a session of an external game with `variant` for the game
+/// registry. Misère play is not supported for external games.
+fn session(variant: Option<String>, misere: bool) -> Result<Box<dyn Game>> {
+    let session = Session::initialize(variant)
+        .context("Failed to initialize external game session.")?;
+    if misere {
+        Err(GameError::MisereNotFound {
+            input_game_name: NAME,
+        })
+        .context("Misère version not found.")
+    } else {
+        Ok(Box::new(session))
+    }
+}
+
+/* GAME IMPLEMENTATION */
+
+pub struct Session {
+    variant: String,
+    data: GameData<'static>,
+    players: PlayerCount,
+    acyclic: bool,
+    start: State,
+    channel: Channel,
+    fault: RefCell<Option<GameError>>,
+}
+
+impl Game for Session {
+    fn initialize(variant: Option<String>) -> Result<Self> {
+        let variant = variant
+            .ok_or(GameError::VariantMalformed {
+                game_name: NAME,
+                hint: "An external game must be specified.".to_owned(),
+            })
+            .context("Malformed game variant.")?;
+
+        let channel = Channel::spawn(&variant)
+            .context("Failed to start external game process.")?;
+
+        let info = channel.request("info", json!({}))?;
+        let players: PlayerCount = channel.field(&info, "players")?;
+        if !SOLVABLE_PLAYERS.contains(&players) {
+            return Err(GameError::ExternalFailure {
+                command: variant,
+                hint: format!(
+                    "The game has {} players, but only 1 to {} are supported.",
+                    players,
+                    SOLVABLE_PLAYERS[SOLVABLE_PLAYERS.len() - 1],
+                ),
+            })
+            .context("Unsupported external game.");
+        }
+
+        // Game data is kept for the lifetime of the program, as is expected of
+        // the information provided by all other games (see `util::intern`).
+        let text = |key| -> Result<&'static str> {
+            Ok(util::intern(channel.field(&info, key)?))
+        };
+
+        let data = GameData {
+            name: text("name")?,
+            authors: text("authors")?,
+            about: text("about")?,
+
+            state_default: text("state_default")?,
+            state_pattern: text("state_pattern")?,
+            state_protocol: text("state_protocol")?,
+
+            ..DATA
+        };
+
+        let acyclic = channel.field(&info, "acyclic")?;
+        let start =
+            channel.field(&channel.request("start", json!({}))?, "state")?;
+        Ok(Session {
+            variant,
+            data,
+            players,
+            acyclic,
+            start,
+            channel,
+            fault: RefCell::new(None),
+        })
+    }
+
+    fn id(&self) -> String {
+        // The same program may play different variants under other commands.
+        let hash = util::fingerprint(&self.variant);
+        format!("{}.{}.{:016x}", NAME, self.data.name, hash)
+    }
+
+    fn forward(&mut self, history: Vec<String>) -> Result<()> {
+        let start = util::verify_history_dynamic(self, history);
+        self.check()?;
+        self.start = start.context("Malformed game state encoding.")?;
+        Ok(())
+    }
+
+    fn info(&self) -> GameData<'_> {
+        GameData {
+            variant: &self.variant,
+            ..self.data
+        }
+    }
+
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()> {
+        match method {
+            SolutionMode::Strong => {
+                self.strong_solver(mode, policy, resources)?;
+            },
+            _ => {
+                return Err(GameError::SolverNotFound {
+                    input_game_name: NAME,
+                })
+                .context("Solver not found.");
+            },
+        }
+        Ok(())
+    }
+
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+        let db =
+            self.strong_solver(mode, Policy::default(), &Resources::default())?;
+        let entry = util::query_entry(self, &db, state);
+        self.check()?;
+        entry
+    }
+}
+
+/* TRAVERSAL DECLARATIONS */
+
+impl Bounded<State> for Session {
+    fn start(&self) -> State {
+        self.start
+    }
+
+    fn end(&self, state: State) -> bool {
+        self.ask("end", json!({ "state": state }), "end", true)
+    }
+}
+
+impl DTransition<State> for Session {
+    fn prograde(&self, state: State) -> Vec<State> {
+        self.ask(
+            "prograde",
+            json!({ "state": state }),
+            "states",
+            Vec::new(),
+        )
+    }
+
+    fn retrograde(&self, state: State) -> Vec<State> {
+        self.ask(
+            "retrograde",
+            json!({ "state": state }),
+            "states",
+            Vec::new(),
+        )
+    }
+}
+
+/* SUPPLEMENTAL DECLARATIONS */
+
+impl Legible<State> for Session {
+    fn decode(&self, string: String) -> Result<State> {
+        let response = self
+            .channel
+            .request("decode", json!({ "string": string }))
+            .map_err(|e| GameError::StateMalformed {
+                game_name: NAME,
+                hint: e.root_cause().to_string(),
+            })?;
+
+        self.channel
+            .field(&response, "state")
+    }
+
+    fn encode(&self, state: State) -> String {
+        self.ask(
+            "encode",
+            json!({ "state": state }),
+            "string",
+            String::new(),
+        )
+    }
+}
+
+/* SOLVING DECLARATIONS */
+
+impl<const N: PlayerCount> Acyclic<N> for Session {
+    fn acyclic(&self) -> bool {
+        self.acyclic
+    }
+}
+
+impl Limited for Session {}
+
+impl<const N: PlayerCount> Solvable<N> for Session {
+    fn utility(&self, state: State) -> [Utility; N] {
+        let utility: Vec<Utility> = self.ask(
+            "utility",
+            json!({ "state": state }),
+            "utility",
+            vec![0; N],
+        );
+
+        utility
+            .try_into()
+            .unwrap_or_else(|u: Vec<Utility>| {
+                self.fail(GameError::ExternalFailure {
+                    command: self.channel.command().to_owned(),
+                    hint: format!(
+                        "The game provided {} utility values at state {}, \
+                        but it has {} players.",
+                        u.len(),
+                        state,
+                        N,
+                    ),
+                });
+                [0; N]
+            })
+    }
+
+    fn turn(&self, state: State) -> Turn {
+        self.ask("turn", json!({ "state": state }), "turn", 0)
+    }
+}
+
+/// Generates the routine which dispatches to the solver whose generic player
+/// count matches that of the session, for each player count provided.
+macro_rules! players {
+    ($($n:literal),+) => {
+        impl Session {
+            /// Strongly solves this session with the solver whose generic
+            /// player count matches that of the external game, which must be
+            /// acyclic unless it is a puzzle. Ties are broken by `policy`.
+            fn strong_solver(
+                &self,
+                mode: IOMode,
+                policy: Policy,
+                resources: &Resources,
+            ) -> Result<volatile::Database> {
+                let db = match (self.players, self.acyclic) {
+                    (1, false) => strong::cyclic::puzzle_solver(
+                        self, mode, policy, resources,
+                    )
+                    .context("Failed solver run."),
+                    $(($n, true) => strong::acyclic::dynamic_solver::<$n, Self>(
+                        self, mode, policy, resources,
+                    )
+                    .context("Failed solver run."),)+
+                    _ => Err(GameError::SolverNotFound {
+                        input_game_name: NAME,
+                    })
+                    .context("Solver not found."),
+                };
+
+                self.check()?;
+                db
+            }
+        }
+    };
+}
+
+solvable! { players }
+
+/* HELPER FUNCTIONS */
+
+impl Session {
+    /// Sends a request of type `message` with `fields` to the external game,
+    /// and returns the field `key` of its response. If the external game fails
+    /// to respond according to the protocol, the failure is recorded (see
+    /// `check`) and `fallback` is returned instead.
+    fn ask<T>(&self, message: &str, fields: Value, key: &str, fallback: T) -> T
+    where
+        T: DeserializeOwned,
+    {
+        self.channel
+            .request(message, fields)
+            .and_then(|response| self.channel.field(&response, key))
+            .unwrap_or_else(|error| {
+                self.fail(match error.downcast::<GameError>() {
+                    Ok(error) => error,
+                    Err(error) => GameError::ExternalFailure {
+                        command: self.channel.command().to_owned(),
+                        hint: error.to_string(),
+                    },
+                });
+                fallback
+            })
+    }
+
+    /// Records `error` as a violation of the protocol by the external game,
+    /// unless an earlier violation was already recorded.
+    fn fail(&self, error: GameError) {
+        self.fault
+            .borrow_mut()
+            .get_or_insert(error);
+    }
+
+    /// Fails with the earliest violation of the protocol by the external game
+    /// since the last call, if there was any.
+    fn check(&self) -> Result<()> {
+        match self.fault.borrow_mut().take() {
+            Some(error) => {
+                Err(error).context("External game violated the protocol.")
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::testing;
+    use crate::game::zero_by;
+
+    /// Reference external game implementing 2-player ten to zero by one or two
+    /// (the default variant of zero-by) through the external game protocol.
+    const STUB: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/game/external/stub.py"
+    );
+
+    fn stub() -> Session {
+        Session::initialize(Some(format!("python3 {}", STUB))).unwrap()
+    }
+
+    #[test]
+    fn stub_provides_game_data() {
+        let session = stub();
+        assert_eq!(session.info().name, "ten-to-zero");
+        assert!(session
+            .id()
+            .starts_with("external.ten-to-zero."));
+        assert_eq!(session.encode(session.start()), "10-0");
+        assert!(session
+            .info()
+            .variant
+            .ends_with("stub.py"));
+    }
+
+    #[test]
+    fn stub_conforms_to_game_interfaces() {
+        let session = stub();
+        testing::verify_traversal(&session, 100).unwrap();
+        testing::verify_cycles::<2, _>(&session, 100).unwrap();
+    }
+
+    #[test]
+    fn stub_errors_are_reported() {
+        let session = stub();
+        assert!(session
+            .decode("11-0".to_owned())
+            .is_err());
+        assert!(session
+            .decode("nonsense".to_owned())
+            .is_err());
+        assert!(Session::initialize(None).is_err());
+        assert!(Session::initialize(Some("./does-not-exist".into())).is_err());
+    }
+
+    #[test]
+    fn variants_of_the_same_program_have_distinct_ids() {
+        let faulty = format!("python3 {} --faulty", STUB);
+        let faulty = Session::initialize(Some(faulty)).unwrap();
+        assert_eq!(stub().info().name, faulty.info().name);
+        assert_ne!(stub().id(), faulty.id());
+        assert_eq!(stub().id(), stub().id());
+    }
+
+    #[test]
+    fn protocol_violations_are_reported() {
+        let command = format!("python3 {} --faulty", STUB);
+        let session = Session::initialize(Some(command)).unwrap();
+        let error = session
+            .solve(
+                IOMode::Write,
+                SolutionMode::Strong,
+                Policy::default(),
+                &Resources::default(),
+            )
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<GameError>(),
+            Some(GameError::ExternalFailure { .. })
+        ));
+        assert!(error
+            .root_cause()
+            .to_string()
+            .contains("provided 1 utility values"));
+    }
+
+    #[test]
+    fn stub_solution_agrees_with_zero_by() {
+        let zero_by = zero_by::Session::initialize(None).unwrap();
+        testing::verify_agreement::<2, _, _>(&stub(), &zero_by, 100).unwrap();
+    }
+}
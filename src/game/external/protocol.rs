@@ -0,0 +1,148 @@
+//! # External Game Protocol Module
+//!
+//! This module implements the communication channel between the external game
+//! adapter and the process of an external game. Messages are exchanged as JSON
+//! objects, one per line, through the standard input and output of the process.
+//! Each request is answered by exactly one response, in order.
+
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use crate::game::error::GameError;
+
+/* DEFINITIONS */
+
+/// A running external game process, along with the pipes used to send requests
+/// to it and to receive its responses. The process is killed when the channel
+/// is dropped.
+pub struct Channel {
+    command: String,
+    process: Child,
+    pipes: RefCell<(ChildStdin, BufReader<ChildStdout>)>,
+}
+
+/* IMPLEMENTATION */
+
+impl Channel {
+    /// Spawns the process described by `command`, which is the path to an
+    /// executable followed by any whitespace-separated arguments to it.
+    pub fn spawn(command: &str) -> Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| anyhow!("No executable was specified."))?;
+
+        let mut process = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to spawn '{}'.", command))?;
+
+        let stdin = process.stdin.take().unwrap();
+        let stdout = BufReader::new(process.stdout.take().unwrap());
+        Ok(Channel {
+            command: command.to_owned(),
+            process,
+            pipes: RefCell::new((stdin, stdout)),
+        })
+    }
+
+    /// Sends a request of type `message` with the fields in `fields` to the
+    /// process, and returns its response. Fails if the process could not be
+    /// reached, if its response is not a JSON object, or if the response
+    /// contains an `error` field (whose contents are included in the error).
+    pub fn request(&self, message: &str, fields: Value) -> Result<Value> {
+        let mut request = json!({ "message": message });
+        if let (Some(request), Value::Object(fields)) =
+            (request.as_object_mut(), fields)
+        {
+            request.extend(fields);
+        }
+
+        let mut pipes = self.pipes.borrow_mut();
+        let (stdin, stdout) = &mut *pipes;
+        writeln!(stdin, "{}", request)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| self.failure(e.to_string()))?;
+
+        let mut line = String::new();
+        let read = stdout
+            .read_line(&mut line)
+            .map_err(|e| self.failure(e.to_string()))?;
+
+        if read == 0 {
+            return Err(self
+                .failure(format!(
+                    "The process exited before responding to a '{}' request.",
+                    message
+                ))
+                .into());
+        }
+
+        let response: Value = serde_json::from_str(&line)
+            .map_err(|e| self.failure(format!("Invalid response: {}", e)))?;
+
+        if !response.is_object() {
+            Err(self
+                .failure(format!(
+                    "The response '{}' is not an object.",
+                    line
+                ))
+                .into())
+        } else if let Some(error) = response.get("error") {
+            Err(self
+                .failure(format!(
+                    "The '{}' request failed: {}",
+                    message,
+                    error
+                        .as_str()
+                        .unwrap_or(&error.to_string())
+                ))
+                .into())
+        } else {
+            Ok(response)
+        }
+    }
+
+    /// Returns the field `key` of `response` as an instance of `T`, failing if
+    /// it is missing or of the wrong type.
+    pub fn field<T>(&self, response: &Value, key: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let value = response.get(key).ok_or_else(|| {
+            self.failure(format!("The response lacks the field '{}'.", key))
+        })?;
+
+        serde_json::from_value(value.clone())
+            .map_err(|e| {
+                self.failure(format!("The field '{}' is invalid: {}", key, e))
+            })
+            .map_err(Into::into)
+    }
+
+    /// Returns the command used to spawn the process of this channel.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    fn failure(&self, hint: String) -> GameError {
+        GameError::ExternalFailure {
+            command: self.command.clone(),
+            hint,
+        }
+    }
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
@@ -0,0 +1,97 @@
+#!/usr/bin/env python3
+"""Reference external game for the external game protocol.
+
+Implements 2-player ten to zero by one or two, where states are encoded as
+`elements * 2 + turn`. Used by the tests of the external game adapter. When
+passed `--faulty`, the utility of end states is malformed.
+"""
+
+import json
+import re
+import sys
+
+START = 10
+CHOICES = (1, 2)
+FAULTY = "--faulty" in sys.argv[1:]
+
+
+def pack(elements, turn):
+    return elements * 2 + turn
+
+
+def unpack(state):
+    return state // 2, state % 2
+
+
+def prograde(state):
+    elements, turn = unpack(state)
+    if elements == 0:
+        return []
+    amounts = {min(c, elements) for c in CHOICES}
+    return sorted(pack(elements - a, 1 - turn) for a in amounts)
+
+
+def retrograde(state):
+    elements, turn = unpack(state)
+    parents = set()
+    for c in CHOICES:
+        if elements == 0:
+            parents.update(range(1, min(c, START) + 1))
+        elif elements + c <= START:
+            parents.add(elements + c)
+    return sorted(pack(p, 1 - turn) for p in parents)
+
+
+def decode(string):
+    match = re.fullmatch(r"(\d+)-(\d+)", string)
+    if not match:
+        raise ValueError("String does not match the pattern '^\\d+-\\d+$'.")
+    elements, turn = int(match.group(1)), int(match.group(2))
+    if elements > START or turn > 1:
+        raise ValueError(f"The state '{string}' is out of range.")
+    return pack(elements, turn)
+
+
+def respond(request):
+    message = request["message"]
+    if message == "info":
+        return {
+            "name": "ten-to-zero",
+            "authors": "GamesmanNova contributors",
+            "about": "Ten to zero by one or two, as an external game.",
+            "players": 2,
+            "acyclic": True,
+            "state_protocol": "Dash-separated elements left and turn.",
+            "state_pattern": r"^\d+-\d+$",
+            "state_default": "10-0",
+        }
+    if message == "start":
+        return {"state": pack(START, 0)}
+    if message == "decode":
+        return {"state": decode(request["string"])}
+
+    state = request["state"]
+    elements, turn = unpack(state)
+    if message == "end":
+        return {"end": elements == 0}
+    if message == "prograde":
+        return {"states": prograde(state)}
+    if message == "retrograde":
+        return {"states": retrograde(state)}
+    if message == "encode":
+        return {"string": f"{elements}-{turn}"}
+    if message == "utility":
+        if FAULTY:
+            return {"utility": [1]}
+        return {"utility": [-1, 1] if turn == 0 else [1, -1]}
+    if message == "turn":
+        return {"turn": turn}
+    raise ValueError(f"Unknown message '{message}'.")
+
+
+for line in sys.stdin:
+    try:
+        response = respond(json.loads(line))
+    except Exception as e:
+        response = {"error": str(e)}
+    print(json.dumps(response), flush=True)
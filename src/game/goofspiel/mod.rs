@@ -0,0 +1,449 @@
+//! # Goofspiel Module
+//!
+//! Goofspiel (also known as the Game of Pure Strategy) is a two-player card
+//! game played with three suits of N cards each, numbered 1 through N. Each
+//! player is dealt one of the suits as their hand, and the third suit is used
+//! as a pile of prizes. In each round, a prize is revealed and both players
+//! simultaneously bid on it by playing a card from their hand; the player who
+//! bid the higher card wins the prize, adding its number to their score. If
+//! the bids are equal, nobody wins the prize. After N rounds, the player with
+//! the higher score wins the game.
+//!
+//! In this implementation, the prizes are revealed in increasing order, which
+//! makes the game deterministic. Since players move simultaneously, optimal
+//! play generally requires mixed strategies, so the game is solved through
+//! the equilibria of the matrix games at each state (see `Simultaneous`).
+
+use anyhow::{Context, Result};
+
+use std::cmp::Ordering;
+
+use crate::database::KVStore;
+use crate::game::error::GameError;
+use crate::game::goofspiel::states::*;
+use crate::game::goofspiel::variants::*;
+use crate::game::{util, Acyclic, Bounded, Entry, Legible, Simultaneous};
+use crate::game::{Game, GameData};
+use crate::interface::{IOMode, SolutionMode};
+use crate::model::{Move, State, Utility};
+use crate::register;
+use crate::solver::record::mes::RecordBuffer;
+use crate::solver::strong;
+use crate::solver::util::{Policy, Resources};
+
+/* SUBMODULES */
+
+mod states;
+mod variants;
+
+/* GAME DATA */
+
+const NAME: &str = "goofspiel";
+const AUTHORS: &str = "GamesmanNova contributors";
+const ABOUT: &str =
+"Two players each hold a hand of cards numbered 1 through N, and compete for a \
+pile of prize cards numbered in the same way. In each round, the lowest prize \
+left is put up for grabs, and both players simultaneously bid on it by playing \
+a card from their hand. The player who bids the higher card wins the prize and \
+adds its number to their score, while nobody wins it if the bids are equal. \
+After all cards have been played, the player with the higher score wins. The \
+game variant determines the number of cards N.";
+
+/* REGISTRATION */
+
+const DATA: GameData = GameData {
+    variant: VARIANT_DEFAULT,
+
+    name: NAME,
+    authors: AUTHORS,
+    about: ABOUT,
+
+    variant_protocol: VARIANT_PROTOCOL,
+    variant_pattern: VARIANT_PATTERN,
+    variant_default: VARIANT_DEFAULT,
+
+    state_default: STATE_DEFAULT,
+    state_pattern: STATE_PATTERN,
+    state_protocol: STATE_PROTOCOL,
+
+    move_protocol: None,
+    move_pattern: None,
+};
+
+register! { DATA => session }
+
+/// Initializes a session of this game with `variant` for the game registry,
+/// which is of its misère version if `misere` is set.
+fn session(variant: Option<String>, misere: bool) -> Result<Box<dyn Game>> {
+    let session = Session::initialize(variant)
+        .context("Failed to initialize Goofspiel session.")?;
+    if misere {
+        Err(GameError::MisereNotFound {
+            input_game_name: NAME,
+        })
+        .context("Misère version not found.")
+    } else {
+        Ok(Box::new(session))
+    }
+}
+
+/* GAME IMPLEMENTATION */
+
+/// Represents an instance of a Goofspiel session, which is specific to a valid
+/// variant of the game. States are encoded as the bit sets of the cards in the
+/// hands of both players and of the prizes left (where the card `c` is the bit
+/// `c - 1`), followed by the score of player 0 minus that of player 1 (offset
+/// so that it is never negative).
+pub struct Session {
+    variant: String,
+    cards: usize,
+    start: State,
+}
+
+impl Game for Session {
+    fn initialize(variant: Option<String>) -> Result<Self> {
+        if let Some(v) = variant {
+            parse_variant(v).context("Malformed game variant.")
+        } else {
+            Ok(parse_variant(VARIANT_DEFAULT.to_owned()).unwrap())
+        }
+    }
+
+    fn id(&self) -> String {
+        format!("{}.{}", NAME, self.variant)
+    }
+
+    fn forward(&mut self, history: Vec<String>) -> Result<()> {
+        self.start = util::verify_history_simultaneous(self, history)
+            .context("Malformed game state encoding.")?;
+        Ok(())
+    }
+
+    fn info(&self) -> GameData<'_> {
+        GameData {
+            variant: &self.variant,
+            ..DATA
+        }
+    }
+
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()> {
+        match method {
+            SolutionMode::Strong => {
+                strong::simultaneous::zero_sum_solver(
+                    self, mode, policy, resources,
+                )
+                .context("Failed solver run.")?;
+            },
+            _ => {
+                return Err(GameError::SolverNotFound {
+                    input_game_name: NAME,
+                })
+                .context("Solver not found.");
+            },
+        }
+        Ok(())
+    }
+
+    /// Returns the entry of `state` (or of the starting state if it is not
+    /// provided), where the utility of each player is their expected utility
+    /// under the equilibrium strategies rounded to the nearest integer, the
+    /// supports are the cards that each player bids with non-zero probability
+    /// under those strategies, and the remoteness is the number of rounds left
+    /// to play.
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+        let db = strong::simultaneous::zero_sum_solver(
+            self,
+            mode,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .context("Failed solver run.")?;
+
+        let state =
+            if let Some(s) = state { self.decode(s)? } else { self.start };
+
+        let bits = KVStore::<RecordBuffer>::get(&db, state)
+            .ok_or_else(|| self.unreachable(state))
+            .context("State not found in solution set.")?;
+
+        let record = RecordBuffer::from(bits)
+            .context("Failed to parse solution set record.")?;
+        let value = record.get_value().round() as Utility;
+        let supports = self
+            .actions(state)
+            .iter()
+            .enumerate()
+            .map(|(player, actions)| {
+                Ok(record
+                    .get_support(player)?
+                    .into_iter()
+                    .filter_map(|i| actions.get(i))
+                    .map(|action| action.to_string())
+                    .collect())
+            })
+            .collect::<Result<Vec<Vec<String>>>>()
+            .context("Failed to get supports from record.")?;
+
+        let ([p0, _], _, _) = unpack(self, state);
+        Ok(Entry {
+            state: self.encode(state),
+            utility: vec![value, -value],
+            remoteness: p0.count_ones() as _,
+            adjusted: Vec::new(),
+            moves: Vec::new(),
+            supports,
+            nim_value: None,
+        })
+    }
+}
+
+/* TRAVERSAL DECLARATIONS */
+
+impl Bounded<State> for Session {
+    fn start(&self) -> State {
+        self.start
+    }
+
+    fn end(&self, state: State) -> bool {
+        let (_, prizes, _) = unpack(self, state);
+        prizes == 0
+    }
+}
+
+/* SUPPLEMENTAL DECLARATIONS */
+
+impl Legible<State> for Session {
+    fn decode(&self, string: String) -> Result<State> {
+        Ok(parse_state(self, string)?)
+    }
+
+    fn encode(&self, state: State) -> String {
+        let ([p0, p1], prizes, difference) = unpack(self, state);
+        let cards = |set: u64| {
+            (0..self.cards)
+                .filter(|&i| set & (1 << i) != 0)
+                .map(|i| (i + 1).to_string())
+                .collect::<Vec<String>>()
+                .join("-")
+        };
+
+        format!(
+            "{}/{}/{}/{}",
+            cards(p0),
+            cards(p1),
+            cards(prizes),
+            difference
+        )
+    }
+}
+
+/* SOLVING DECLARATIONS */
+
+impl Acyclic<2> for Session {}
+
+impl Simultaneous<2> for Session {
+    fn actions(&self, state: State) -> [Vec<Move>; 2] {
+        let (hands, _, _) = unpack(self, state);
+        hands.map(|hand| {
+            (0..self.cards as Move)
+                .filter(|&i| hand & (1 << i) != 0)
+                .map(|i| i + 1)
+                .collect()
+        })
+    }
+
+    fn transition(&self, state: State, joint: [Move; 2]) -> State {
+        let ([p0, p1], prizes, difference) = unpack(self, state);
+        let prize = prizes.trailing_zeros() as i64 + 1;
+        let difference = match joint[0].cmp(&joint[1]) {
+            Ordering::Greater => difference + prize,
+            Ordering::Less => difference - prize,
+            Ordering::Equal => difference,
+        };
+
+        pack(
+            self,
+            [
+                p0 & !(1 << (joint[0] - 1)),
+                p1 & !(1 << (joint[1] - 1)),
+            ],
+            prizes & (prizes - 1),
+            difference,
+        )
+    }
+
+    fn utility(&self, state: State) -> [Utility; 2] {
+        let (_, _, difference) = unpack(self, state);
+        let sign = difference.signum() as Utility;
+        [sign, -sign]
+    }
+}
+
+/* HELPER METHODS */
+
+impl Session {
+    /// Returns an error indicating that `state` has no record in the solution
+    /// set of this session because it is not reachable from its start.
+    fn unreachable(&self, state: State) -> GameError {
+        GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "The state '{}' is not reachable from the starting state of \
+                the current game variant ({}), which is '{}'.",
+                self.encode(state),
+                self.variant,
+                self.encode(self.start),
+            ),
+        }
+    }
+}
+
+/* STATE ENCODING */
+
+/// Returns the state of `session` where players 0 and 1 hold the cards in
+/// `hands`, the cards in `prizes` are left to be won, and the score of player
+/// 0 minus that of player 1 is `difference`.
+fn pack(
+    session: &Session,
+    hands: [u64; 2],
+    prizes: u64,
+    difference: i64,
+) -> State {
+    let n = session.cards;
+    let offset = (difference + total(n)) as State;
+    hands[0] | (hands[1] << n) | (prizes << (2 * n)) | (offset << (3 * n))
+}
+
+/// Returns the hands of both players, the prizes left, and the difference
+/// between the scores of the players at `state`, such that it is the inverse
+/// of `pack`.
+fn unpack(session: &Session, state: State) -> ([u64; 2], u64, i64) {
+    let n = session.cards;
+    let mask = (1 << n) - 1;
+    let hands = [state & mask, (state >> n) & mask];
+    let prizes = (state >> (2 * n)) & mask;
+    let difference = (state >> (3 * n)) as i64 - total(n);
+    (hands, prizes, difference)
+}
+
+/// Returns the sum of the numbers of the cards in a suit of `cards` cards.
+fn total(cards: usize) -> i64 {
+    (cards * (cards + 1) / 2) as i64
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn query(session: &Session, state: &str) -> Entry {
+        session
+            .query(IOMode::Write, Some(state.to_owned()))
+            .unwrap()
+    }
+
+    #[test]
+    fn unpack_is_inverse_of_pack() {
+        let session = Session::initialize(Some("16".to_owned())).unwrap();
+        let hands = [0b1010_0000_0000_0001, 0b1111_1111_1111_1111];
+        for difference in [-136, -7, 0, 1, 136] {
+            let state = pack(&session, hands, 0b0110, difference);
+            assert_eq!(
+                unpack(&session, state),
+                (hands, 0b0110, difference)
+            );
+        }
+    }
+
+    #[test]
+    fn bids_award_the_lowest_prize_left() {
+        let session = Session::initialize(Some("4".to_owned())).unwrap();
+        let state = session
+            .decode("2-3/1-3/3-4/-1".to_owned())
+            .unwrap();
+
+        let encode = |joint| session.encode(session.transition(state, joint));
+        assert_eq!(encode([3, 1]), "2/3/4/2");
+        assert_eq!(encode([2, 3]), "3/1/4/-4");
+        assert_eq!(encode([3, 3]), "2/1/4/-1");
+    }
+
+    #[test]
+    fn symmetric_games_have_zero_value() {
+        let session = Session::initialize(Some("3".to_owned())).unwrap();
+        let entry = session
+            .query(IOMode::Write, None)
+            .unwrap();
+
+        assert_eq!(entry.utility, vec![0, 0]);
+        assert_eq!(entry.remoteness, 3);
+        assert!(entry
+            .supports
+            .iter()
+            .all(|support| !support.is_empty()));
+    }
+
+    #[test]
+    fn endgames_are_solved() {
+        let session = Session::initialize(Some("4".to_owned())).unwrap();
+
+        // Player 0 outbids player 1 on the last prize.
+        let entry = query(&session, "4/1/4/-3");
+        assert_eq!(entry.utility, vec![1, -1]);
+        assert_eq!(entry.remoteness, 1);
+        assert_eq!(entry.supports, vec![vec!["4"], vec!["1"]]);
+
+        // Player 1 is too far ahead for the last prize to matter.
+        let entry = query(&session, "4/1/4/-6");
+        assert_eq!(entry.utility, vec![-1, 1]);
+
+        // The game is over, and the players are tied.
+        let entry = query(&session, "///0");
+        assert_eq!(entry.utility, vec![0, 0]);
+        assert_eq!(entry.remoteness, 0);
+        assert!(entry
+            .supports
+            .iter()
+            .all(|support| support.is_empty()));
+    }
+
+    #[test]
+    fn forwarding_follows_joint_actions() {
+        let mut session = Session::initialize(Some("2".to_owned())).unwrap();
+        let history = vec!["1-2/1-2/1-2/0".to_owned(), "2/1/2/-1".to_owned()];
+        assert!(session.forward(history).is_ok());
+        assert_eq!(session.encode(session.start()), "2/1/2/-1");
+
+        let mut session = Session::initialize(Some("2".to_owned())).unwrap();
+        let history = vec!["1-2/1-2/1-2/0".to_owned(), "1/1/2/1".to_owned()];
+        assert!(session.forward(history).is_err());
+    }
+
+    #[test]
+    fn only_strong_solutions_are_computed() {
+        let session = Session::initialize(Some("2".to_owned())).unwrap();
+        let solve = |method| {
+            session.solve(
+                IOMode::Write,
+                method,
+                Policy::default(),
+                &Resources::default(),
+            )
+        };
+
+        assert!(solve(SolutionMode::Strong).is_ok());
+        let error = solve(SolutionMode::Weak).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<GameError>(),
+            Some(GameError::SolverNotFound { .. })
+        ));
+    }
+}
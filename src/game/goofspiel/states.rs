@@ -0,0 +1,221 @@
+//! # Goofspiel State Handling Module
+//!
+//! This module helps parse the string encoding of a Goofspiel state into a
+//! more efficient binary representation, performing a series of checks which
+//! ensure compatibility with a game variant.
+
+use regex::Regex;
+
+use crate::game::error::GameError;
+use crate::game::goofspiel::{pack, total, Session, NAME};
+use crate::model::State;
+
+/* GOOFSPIEL STATE ENCODING */
+
+pub const STATE_DEFAULT: &str = "1-2-3-4-5/1-2-3-4-5/1-2-3-4-5/0";
+pub const STATE_PATTERN: &str =
+    r"^(\d+(-\d+)*)?/(\d+(-\d+)*)?/(\d+(-\d+)*)?/-?\d+$";
+pub const STATE_PROTOCOL: &str =
+    "The state string should be four slash-separated fields H0/H1/P/D, where H0 \
+and H1 are the dash-separated cards left in the hands of players 0 and 1, P are \
+the dash-separated prize cards left to be won, and D is the score of player 0 \
+minus the score of player 1. The first three fields must have the same number \
+of distinct cards (between 1 and N, where N is the variant), and are empty once \
+the game is over. The absolute value of D cannot exceed the sum of the prizes \
+which are no longer left. For example, '2-3/1-3/3-4/-1' is a state of the \
+4-card variant where player 1 is ahead by one point, and the 3 is up for grabs.";
+
+/* API */
+
+/// Returns a Goofspiel state encoding using the parameters specified by a
+/// pre-verified game variant combined with the cards and score provided in
+/// `from`. Returns a `GameError::StateMalformed` if the cards or the score are
+/// not consistent with each other or with the variant.
+pub fn parse_state(
+    session: &Session,
+    from: String,
+) -> Result<State, GameError> {
+    check_state_pattern(&from)?;
+    let fields: Vec<&str> = from.split('/').collect();
+    let p0 = parse_cards(session, fields[0])?;
+    let p1 = parse_cards(session, fields[1])?;
+    let prizes = parse_cards(session, fields[2])?;
+    let difference = parse_difference(fields[3])?;
+    check_card_counts(p0, p1, prizes)?;
+    check_difference(session, prizes, difference)?;
+    Ok(pack(session, [p0, p1], prizes, difference))
+}
+
+/* STATE STRING VERIFICATION */
+
+fn check_state_pattern(from: &str) -> Result<(), GameError> {
+    let re = Regex::new(STATE_PATTERN).unwrap();
+    if !re.is_match(from) {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String does not match the pattern '{}'.",
+                STATE_PATTERN
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the set of cards listed in `field` as a bit set where the card `c`
+/// corresponds to the bit `c - 1`.
+fn parse_cards(session: &Session, field: &str) -> Result<u64, GameError> {
+    let mut cards = 0;
+    for card in field
+        .split('-')
+        .filter(|s| !s.is_empty())
+    {
+        let card = card
+            .parse::<usize>()
+            .map_err(|e| GameError::StateMalformed {
+                game_name: NAME,
+                hint: e.to_string(),
+            })?;
+
+        if card == 0 || card > session.cards {
+            return Err(GameError::StateMalformed {
+                game_name: NAME,
+                hint: format!(
+                    "Cards must be numbered between 1 and {}, but found {}.",
+                    session.cards, card,
+                ),
+            });
+        } else if cards & (1 << (card - 1)) != 0 {
+            return Err(GameError::StateMalformed {
+                game_name: NAME,
+                hint: format!("The card {} appears more than once.", card),
+            });
+        }
+
+        cards |= 1 << (card - 1);
+    }
+    Ok(cards)
+}
+
+fn parse_difference(field: &str) -> Result<i64, GameError> {
+    field
+        .parse::<i64>()
+        .map_err(|e| GameError::StateMalformed {
+            game_name: NAME,
+            hint: e.to_string(),
+        })
+}
+
+fn check_card_counts(p0: u64, p1: u64, prizes: u64) -> Result<(), GameError> {
+    let counts = [p0, p1, prizes].map(u64::count_ones);
+    if counts[0] != counts[1] || counts[1] != counts[2] {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "Both hands and the prizes must have the same number of \
+                cards, but they have {}, {}, and {} cards, respectively.",
+                counts[0], counts[1], counts[2],
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn check_difference(
+    session: &Session,
+    prizes: u64,
+    difference: i64,
+) -> Result<(), GameError> {
+    let awarded = total(session.cards) - total_of(prizes);
+    if difference.abs() > awarded {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "The score difference cannot exceed {} in absolute value, as \
+                that is the sum of the prizes that are no longer left.",
+                awarded,
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the sum of the values of the cards in the bit set `cards`.
+fn total_of(cards: u64) -> i64 {
+    (0..64)
+        .filter(|&i| cards & (1 << i) != 0)
+        .map(|i| i + 1)
+        .sum()
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::Game;
+
+    #[test]
+    fn state_pattern_is_valid_regex() {
+        assert!(Regex::new(STATE_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_state_matches_state_pattern() {
+        let re = Regex::new(STATE_PATTERN).unwrap();
+        assert!(re.is_match(STATE_DEFAULT));
+    }
+
+    #[test]
+    fn no_state_equals_default_state() {
+        let session = Session::initialize(None).unwrap();
+        assert_eq!(
+            session.start,
+            parse_state(&session, STATE_DEFAULT.to_owned()).unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed_states_fail_checks() {
+        let s1 = "1-2-3-4-5/1-2-3-4-5/1-2-3-4-5".to_owned(); // Missing score
+        let s2 = "1-2-3-4/1-2-3-4-5/1-2-3-4-5/0".to_owned(); // Unequal counts
+        let s3 = "1-2-3-4-6/1-2-3-4-5/1-2-3-4-5/0".to_owned(); // Out of range
+        let s4 = "1-1-3-4-5/1-2-3-4-5/1-2-3-4-5/0".to_owned(); // Repeated card
+        let s5 = "1-2/1-2/1-2/13".to_owned(); // Score too large
+        let s6 = "0-1/1-2/1-2/0".to_owned(); // Card zero
+        let s7 = "".to_owned(); // Empty string
+
+        fn f() -> Session {
+            Session::initialize(None).unwrap()
+        }
+
+        assert!(parse_state(&f(), s1).is_err());
+        assert!(parse_state(&f(), s2).is_err());
+        assert!(parse_state(&f(), s3).is_err());
+        assert!(parse_state(&f(), s4).is_err());
+        assert!(parse_state(&f(), s5).is_err());
+        assert!(parse_state(&f(), s6).is_err());
+        assert!(parse_state(&f(), s7).is_err());
+    }
+
+    #[test]
+    fn well_formed_states_pass_checks() {
+        let s1 = "2-3/1-3/3-4/-1".to_owned();
+        let s2 = "5/1/5/10".to_owned();
+        let s3 = "///-15".to_owned();
+        let s4 = "4-2/5-3/1-5/-9".to_owned();
+
+        fn f() -> Session {
+            Session::initialize(None).unwrap()
+        }
+
+        assert!(parse_state(&f(), s1).is_ok());
+        assert!(parse_state(&f(), s2).is_ok());
+        assert!(parse_state(&f(), s3).is_ok());
+        assert!(parse_state(&f(), s4).is_ok());
+    }
+}
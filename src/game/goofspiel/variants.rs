@@ -0,0 +1,138 @@
+//! # Goofspiel Variant Handling Module
+//!
+//! This module helps parse the variant string provided to Goofspiel into
+//! parameters that can help build a game session.
+
+use regex::Regex;
+
+use crate::game::error::GameError;
+use crate::game::goofspiel::{pack, Session, NAME};
+
+/* GOOFSPIEL VARIANT ENCODING */
+
+pub const VARIANT_DEFAULT: &str = "5";
+pub const VARIANT_PATTERN: &str = r"^[1-9]\d*$";
+pub const VARIANT_PROTOCOL: &str =
+    "The variant string should be a positive integer N indicating the number \
+of cards in each suit, such that each player starts with a hand of the cards \
+numbered 1 through N, and the prize cards are numbered 1 through N as well. \
+There can be at most 16 cards in each suit so that game states can be encoded \
+in 64 bits.";
+
+/// The maximum number of cards in a suit for which the hands of both players,
+/// the remaining prizes, and the score difference all fit in a `State`.
+pub const MAX_CARDS: usize = 16;
+
+/* API */
+
+/// Returns a Goofspiel session set up using the parameters specified by
+/// `variant`. Returns a `GameError::VariantMalformed` if the variant string
+/// does not conform to the variant protocol.
+pub fn parse_variant(variant: String) -> Result<Session, GameError> {
+    check_variant_pattern(&variant)?;
+    let cards = parse_cards(&variant)?;
+    let suit = (1 << cards) - 1;
+    let mut session = Session {
+        variant,
+        cards,
+        start: 0,
+    };
+
+    session.start = pack(&session, [suit, suit], suit, 0);
+    Ok(session)
+}
+
+/* VARIANT STRING VERIFICATION */
+
+fn check_variant_pattern(variant: &str) -> Result<(), GameError> {
+    let re = Regex::new(VARIANT_PATTERN).unwrap();
+    if !re.is_match(variant) {
+        Err(GameError::VariantMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String does not match the pattern '{}'.",
+                VARIANT_PATTERN
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_cards(variant: &str) -> Result<usize, GameError> {
+    let cards = variant
+        .parse::<usize>()
+        .map_err(|e| GameError::VariantMalformed {
+            game_name: NAME,
+            hint: e.to_string(),
+        })?;
+
+    if cards > MAX_CARDS {
+        Err(GameError::VariantMalformed {
+            game_name: NAME,
+            hint: format!(
+                "There can be at most {} cards in each suit, but {} were \
+                specified.",
+                MAX_CARDS, cards,
+            ),
+        })
+    } else {
+        Ok(cards)
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::Game;
+
+    #[test]
+    fn variant_pattern_is_valid_regex() {
+        assert!(Regex::new(VARIANT_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_variant_matches_variant_pattern() {
+        let re = Regex::new(VARIANT_PATTERN).unwrap();
+        assert!(re.is_match(VARIANT_DEFAULT));
+    }
+
+    #[test]
+    fn no_variant_equals_default_variant() {
+        let with_none = Session::initialize(None).unwrap();
+        let with_default =
+            Session::initialize(Some(VARIANT_DEFAULT.to_owned())).unwrap();
+        assert_eq!(with_none.variant, with_default.variant);
+        assert_eq!(with_none.cards, with_default.cards);
+        assert_eq!(with_none.start, with_default.start);
+    }
+
+    #[test]
+    fn invalid_variants_fail_checks() {
+        fn wrapper(v: &'static str) -> Result<Session, GameError> {
+            parse_variant(v.to_owned())
+        }
+
+        assert!(wrapper("0").is_err());
+        assert!(wrapper("17").is_err());
+        assert!(wrapper("-3").is_err());
+        assert!(wrapper("3-3").is_err());
+        assert!(wrapper("three").is_err());
+        assert!(wrapper("").is_err());
+    }
+
+    #[test]
+    fn valid_variants_pass_checks() {
+        fn wrapper(v: &'static str) -> Result<Session, GameError> {
+            parse_variant(v.to_owned())
+        }
+
+        assert!(wrapper("1").is_ok());
+        assert!(wrapper("5").is_ok());
+        assert!(wrapper("13").is_ok());
+        assert!(wrapper("16").is_ok());
+    }
+}
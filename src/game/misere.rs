@@ -0,0 +1,375 @@
+//! # Misère Play Module
+//!
+//! Under the normal play convention, the player who makes the last move in a
+//! game wins. The misère version of a game is played with the same rules, but
+//! under the opposite convention; the player who makes the last move loses.
+//! This module provides a wrapper which can transform any game into its misère
+//! version without modifying its implementation.
+
+use anyhow::{Context, Result};
+
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use crate::game::error::GameError;
+use crate::game::util;
+use crate::game::{Acyclic, Analysis, Bounded, DTransition, Entry, Game};
+use crate::game::{GameData, Legible, Limited, Solvable};
+use crate::interface::{Analyzer, IOMode, SolutionMode};
+use crate::model::Utility;
+use crate::model::{PlayerCount, Remoteness, State, StateCount, Turn};
+use crate::solver::strong;
+use crate::solver::util::{Policy, Resources};
+use crate::solver::verification::{Audit, Report};
+
+/* CONSTANTS */
+
+/// Suffix appended to the ID of a game to obtain the ID of its misère version.
+const ID_SUFFIX: &str = "misere";
+
+/* WRAPPER IMPLEMENTATION */
+
+/// Represents the misère version of an `N`-player game `G`. All of the states
+/// and transitions of `G` are forwarded unchanged; only the utility vectors of
+/// its terminal states are inverted (negated), such that the outcomes which
+/// were most desirable to each player become the least desirable.
+pub struct Misere<const N: PlayerCount, G> {
+    game: G,
+}
+
+impl<const N: PlayerCount, G> Misere<N, G>
+where
+    G: Solvable<N> + Bounded<State> + DTransition<State>,
+{
+    /// Returns the misère version of `game`.
+    pub fn new(game: G) -> Self {
+        Self { game }
+    }
+}
+
+impl<const N: PlayerCount, G> Game for Misere<N, G>
+where
+    G: Acyclic<N>
+        + Solvable<N>
+        + Limited
+        + Sync
+        + Bounded<State>
+        + DTransition<State>
+        + Legible<State>,
+{
+    fn initialize(variant: Option<String>) -> Result<Self> {
+        Ok(Self::new(G::initialize(variant)?))
+    }
+
+    fn id(&self) -> String {
+        format!("{}.{}", self.game.id(), ID_SUFFIX)
+    }
+
+    fn forward(&mut self, history: Vec<String>) -> Result<()> {
+        self.game.forward(history)
+    }
+
+    fn variants(&self, max_states: StateCount) -> Vec<String> {
+        self.game.variants(max_states)
+    }
+
+    fn info(&self) -> GameData<'_> {
+        self.game.info()
+    }
+
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()> {
+        match method {
+            SolutionMode::Strong if resources.parallel() => {
+                strong::acyclic::parallel_dynamic_solver::<N, Self>(
+                    self, policy, resources,
+                )
+                .context("Failed solver run.")?;
+            },
+            SolutionMode::Strong => {
+                strong::acyclic::dynamic_solver::<N, Self>(
+                    self, mode, policy, resources,
+                )
+                .context("Failed solver run.")?;
+            },
+            _ => {
+                return Err(GameError::SolverNotFound {
+                    input_game_name: self.info().name,
+                })
+                .context("Solver not found.");
+            },
+        }
+        Ok(())
+    }
+
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+        let db = strong::acyclic::dynamic_solver::<N, Self>(
+            self,
+            mode,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .context("Failed solver run.")?;
+        util::query_entry(self, &db, state)
+    }
+
+    fn verify(&self, directory: &Path, audit: Audit) -> Result<Report> {
+        strong::acyclic::dynamic_verifier::<N, Self>(self, directory, audit)
+            .context("Failed verifier run.")
+    }
+
+    /// Misère play does not change the states of a game or the transitions
+    /// between them, so the analysis of the state graph of the game is that
+    /// of its misère version as well.
+    fn analyze(&self, analyzer: Analyzer) -> Result<Analysis> {
+        self.game.analyze(analyzer)
+    }
+}
+
+/* TRAVERSAL DECLARATIONS */
+
+impl<const N: PlayerCount, G> Bounded<State> for Misere<N, G>
+where
+    G: Bounded<State>,
+    Self: Game,
+{
+    fn start(&self) -> State {
+        self.game.start()
+    }
+
+    fn end(&self, state: State) -> bool {
+        self.game.end(state)
+    }
+}
+
+impl<const N: PlayerCount, G> DTransition<State> for Misere<N, G>
+where
+    G: DTransition<State>,
+{
+    fn prograde(&self, state: State) -> Vec<State> {
+        self.game.prograde(state)
+    }
+
+    fn retrograde(&self, state: State) -> Vec<State> {
+        self.game.retrograde(state)
+    }
+}
+
+/* SUPPLEMENTAL DECLARATIONS */
+
+impl<const N: PlayerCount, G> Legible<State> for Misere<N, G>
+where
+    G: Legible<State>,
+    Self: Bounded<State>,
+{
+    fn decode(&self, string: String) -> Result<State> {
+        self.game.decode(string)
+    }
+
+    fn encode(&self, state: State) -> String {
+        self.game.encode(state)
+    }
+}
+
+/* SOLVING DECLARATIONS */
+
+impl<const N: PlayerCount, G> Acyclic<N> for Misere<N, G>
+where
+    G: Acyclic<N>,
+{
+    fn acyclic(&self) -> bool {
+        self.game.acyclic()
+    }
+}
+
+impl<const N: PlayerCount, G> Limited for Misere<N, G>
+where
+    G: Limited,
+    Self: Game,
+{
+    fn utility_range(&self) -> RangeInclusive<Utility> {
+        let range = self.game.utility_range();
+        -*range.end()..=-*range.start()
+    }
+
+    fn max_remoteness(&self) -> Remoteness {
+        self.game.max_remoteness()
+    }
+}
+
+impl<const N: PlayerCount, G> Solvable<N> for Misere<N, G>
+where
+    G: Solvable<N>,
+    Self: Game,
+{
+    fn utility(&self, state: State) -> [Utility; N] {
+        self.game
+            .utility(state)
+            .map(|u| -u)
+    }
+
+    fn turn(&self, state: State) -> Turn {
+        self.game.turn(state)
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::{octal, sum, testing, zero_by};
+    use crate::solver::checkpoint::Checkpoint;
+    use crate::solver::error::SolverError;
+
+    use std::env;
+
+    #[test]
+    fn misere_id_has_suffix() {
+        let game = zero_by::Session::initialize(None).unwrap();
+        let id = game.id();
+        let misere = Misere::<2, _>::new(game);
+        assert_eq!(misere.id(), format!("{}.misere", id));
+    }
+
+    #[test]
+    fn misere_analysis_is_that_of_normal_play() {
+        let game = sum::Session::initialize(None).unwrap();
+        let normal = game
+            .analyze(Analyzer::Symmetry)
+            .unwrap();
+        let misere = Misere::<2, _>::new(game)
+            .analyze(Analyzer::Symmetry)
+            .unwrap();
+
+        assert_eq!(misere.states, normal.states);
+        assert_eq!(misere.stored, normal.stored);
+    }
+
+    #[test]
+    fn misere_games_are_only_solved_strongly() {
+        let game = zero_by::Session::initialize(None).unwrap();
+        let misere = Misere::<2, _>::new(game);
+        let error = misere
+            .solve(
+                IOMode::Write,
+                SolutionMode::Weak,
+                Policy::default(),
+                &Resources::default(),
+            )
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<GameError>(),
+            Some(GameError::SolverNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn parallel_solves_reject_unsupported_resources() {
+        let game = octal::Session::initialize(None).unwrap();
+        let misere = Misere::<2, _>::new(game);
+        let checkpoint = Resources {
+            threads: 2,
+            checkpoint: Some(Checkpoint {
+                directory: env::temp_dir(),
+                interval: 1,
+            }),
+            ..Resources::default()
+        };
+
+        let limited = Resources {
+            threads: 2,
+            memory_limit: Some(1 << 20),
+            ..Resources::default()
+        };
+
+        for resources in [checkpoint, limited] {
+            let error = misere
+                .solve(
+                    IOMode::Write,
+                    SolutionMode::Strong,
+                    Policy::default(),
+                    &resources,
+                )
+                .unwrap_err();
+
+            assert!(matches!(
+                error.downcast_ref::<SolverError>(),
+                Some(SolverError::UnsupportedResource { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn misere_conforms_to_game_interfaces() {
+        let game = octal::Session::initialize(None).unwrap();
+        let misere = Misere::<2, _>::new(game);
+        testing::verify_acyclic::<2, _>(&misere, 10_000).unwrap();
+    }
+
+    #[test]
+    fn misere_inverts_terminal_utility() {
+        let game = zero_by::Session::initialize(None).unwrap();
+        let end = game
+            .decode("0-1".to_owned())
+            .unwrap();
+        let normal = game.utility(end);
+        let misere = Misere::<2, _>::new(game);
+        assert_eq!(misere.utility(end), normal.map(|u| -u));
+        assert_eq!(misere.turn(end), 1);
+    }
+
+    #[test]
+    fn misere_zero_by_outcomes_are_correct() {
+        // Under normal play, multiples of 3 lose for the player to move in
+        // zero-by games by 1 or 2. Under misère play, the losing positions
+        // are instead those that are one above a multiple of 3.
+        for n in 1..=12 {
+            let variant = format!("2-{}-1-2", n);
+            let game = zero_by::Session::initialize(Some(variant)).unwrap();
+            let normal = game
+                .query(IOMode::Write, None)
+                .unwrap();
+
+            let misere = Misere::<2, _>::new(game);
+            let inverse = misere
+                .query(IOMode::Write, None)
+                .unwrap();
+
+            assert_eq!(normal.utility[0] < 0, n % 3 == 0);
+            assert_eq!(inverse.utility[0] < 0, n % 3 == 1);
+        }
+    }
+
+    #[test]
+    fn misere_kayles_differs_from_normal_kayles() {
+        // A single pin is a win for the player to move in normal Kayles, but a
+        // loss in misère Kayles, while two pins are a win in both.
+        let f = |v: &str| octal::Session::initialize(Some(v.to_owned()));
+        let one = Misere::<2, _>::new(f("0.77-1").unwrap());
+        let two = Misere::<2, _>::new(f("0.77-2").unwrap());
+
+        let outcome = |entry: Entry| entry.utility[0] > 0;
+        assert!(outcome(
+            f("0.77-1")
+                .unwrap()
+                .query(IOMode::Write, None)
+                .unwrap()
+        ));
+        assert!(!outcome(
+            one.query(IOMode::Write, None)
+                .unwrap()
+        ));
+        assert!(outcome(
+            two.query(IOMode::Write, None)
+                .unwrap()
+        ));
+    }
+}
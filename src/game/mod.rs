@@ -11,23 +11,46 @@
 //!
 //! - Max Fierro, 4/6/2023 (maxfierro@berkeley.edu)
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use nalgebra::SMatrix;
 
+use std::ops::RangeInclusive;
+use std::path::Path;
+
 use crate::{
-    interface::{IOMode, SolutionMode},
-    model::{Partition, PlayerCount, State, StateCount, Turn, Utility},
+    interface::{Analyzer, IOMode, SolutionMode},
+    model::{MinimumExcludedValue, Utility},
+    model::{
+        Move, Partition, PlayerCount, Remoteness, State, StateCount, Turn,
+    },
+    solver::util::{Policy, Resources},
+    solver::verification::{Audit, Report},
 };
 
 /* UTILITY MODULES */
 
-mod error;
+pub mod error;
+pub mod registry;
 mod util;
 
+#[cfg(test)]
+pub mod testing;
+
 /* IMPLEMENTED GAMES */
 
 pub mod zero_by;
 pub mod crossteaser;
+pub mod sliding;
+pub mod octal;
+pub mod external;
+pub mod plugin;
+pub mod dsl;
+pub mod goofspiel;
+
+/* GAME TRANSFORMATIONS */
+
+pub mod misere;
+pub mod sum;
 
 /* DATA CONSTRUCTS */
 
@@ -38,7 +61,7 @@ pub struct GameData<'a> {
     /* INSTANCE */
     /// The variant string used to initialize the `Game` instance which returned
     /// this `GameData` object from its `info` associated method.
-    pub variant: &'a String,
+    pub variant: &'a str,
 
     /* GENERAL */
     /// Known name for the game. This should return a string that can be used as
@@ -69,6 +92,61 @@ pub struct GameData<'a> {
     pub state_pattern: &'static str,
     /// Default state encoding to be used when none is specified.
     pub state_default: &'static str,
+
+    /* MOVES */
+    /// Explanation of how to use a string to encode a move, or `None` if the
+    /// game does not provide move encodings (see `Moves`).
+    pub move_protocol: Option<&'static str>,
+    /// Regular expression pattern that all move encodings must match, or `None`
+    /// if the game does not provide move encodings (see `Moves`).
+    pub move_pattern: Option<&'static str>,
+}
+
+/// Contains the information computed by a solving algorithm about a single
+/// state of a game, intended to be displayed to users who wish to know how a
+/// specific position plays out under optimal play.
+pub struct Entry {
+    /// The string encoding of the state this entry pertains to, conforming to
+    /// the `state_protocol` of the game which produced it.
+    pub state: String,
+    /// The utility that each player obtains by playing optimally from `state`,
+    /// where `utility[i]` is the utility obtained by player `i`.
+    pub utility: Vec<Utility>,
+    /// The number of moves that will be made from `state` until the game ends
+    /// under optimal play.
+    pub remoteness: Remoteness,
+    /// The effective utility that each player obtains by playing optimally
+    /// from `state` after accounting for externalities between players, where
+    /// players optimize for effective utility instead of `utility`. This is
+    /// empty unless the solution was computed that way (see `External`).
+    pub adjusted: Vec<Utility>,
+    /// The string encodings of the legal moves from `state`, conforming to the
+    /// `move_protocol` of the game which produced it. This is empty for games
+    /// which do not provide move encodings (see `Moves`).
+    pub moves: Vec<String>,
+    /// The string encodings of the actions in the support of the equilibrium
+    /// strategy of each player at `state` (the actions they choose with
+    /// non-zero probability), where `supports[i]` are those of player `i`. This
+    /// is empty unless the solution was computed under mixed strategies (see
+    /// `Simultaneous`).
+    pub supports: Vec<Vec<String>>,
+    /// The nim-value of `state` under normal play, which is the XOR of the
+    /// nim-values of its components for disjunctive sums (see `Impartial`).
+    /// This is `None` for games which do not compute nim-values.
+    pub nim_value: Option<MinimumExcludedValue>,
+}
+
+/// Contains statistics about the state graph of a game variant computed by an
+/// analyzer (see `Analyzer`), intended to be displayed to users who wish to
+/// know how a game variant could be solved more efficiently.
+pub struct Analysis {
+    /// The analyzer which computed these statistics.
+    pub analyzer: Analyzer,
+    /// The number of states reachable from the starting state of the variant.
+    pub states: StateCount,
+    /// The number of states reachable from the starting state of the variant
+    /// that a solver stores records for, which is at most `states`.
+    pub stored: StateCount,
 }
 
 /* ACCESS INTERFACES */
@@ -113,6 +191,16 @@ pub trait Game {
     /// to have a starting state whose string encoding is `history.pop()`.
     fn forward(&mut self, history: Vec<String>) -> Result<()>;
 
+    /// Returns the variant strings of the variants in the same family as this
+    /// game's variant (under a notion of family defined by the game) which
+    /// have at most `max_states` states, in increasing order of size. This is
+    /// useful for solving many small variants in batch to find patterns. The
+    /// default implementation returns no variants, which is appropriate for
+    /// games whose variants cannot be enumerated in a meaningful way.
+    fn variants(&self, _max_states: StateCount) -> Vec<String> {
+        Vec::new()
+    }
+
     /* MAIN PLUGINS */
 
     /// Returns useful information about the game, such as the type of game it
@@ -125,7 +213,51 @@ pub trait Game {
     /// if solving the specific game variant is not supported (among other
     /// possibilities for an error), and a unit type if everything goes per
     /// specification. See `IOMode` for specifics on intended side effects.
-    fn solve(&self, mode: IOMode, method: SolutionMode) -> Result<()>;
+    /// Solvers that are sensitive to how ties between equally good moves are
+    /// broken use `policy` to do so (see `TieBreak`), while others ignore it.
+    /// Solvers may also make use of the execution `resources` provided, such
+    /// as multiple threads or a checkpoint directory (see `Resources`).
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()>;
+
+    /// Runs a strong solving algorithm in the same way as `solve`, and returns
+    /// the entry associated with the state encoded by `state` in the resulting
+    /// solution set (or the entry of the starting state if `state` is `None`).
+    /// The `state` string should conform to the `state_protocol` specified in
+    /// the `GameData` object returned by `info`, and should be reachable from
+    /// the starting state of the game variant.
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry>;
+
+    /// Checks that the strong solution set of this game variant persisted in
+    /// `directory` (such as by solving with a checkpoint) is consistent with
+    /// the game, visiting its states as specified by `audit`. Returns a report
+    /// of the inconsistencies found, where states are encoded according to the
+    /// `state_protocol` specified in the `GameData` object returned by `info`.
+    /// The default implementation fails, which is appropriate for games whose
+    /// solution sets cannot be verified.
+    fn verify(&self, _directory: &Path, _audit: Audit) -> Result<Report> {
+        Err(error::GameError::VerifierNotFound {
+            input_game_name: self.info().name,
+        })
+        .context("Verifier not found.")
+    }
+
+    /// Runs `analyzer` on the states reachable from the starting state of this
+    /// game variant, returning the statistics it computes. The default
+    /// implementation fails, which is appropriate for games which support no
+    /// analyzers.
+    fn analyze(&self, analyzer: Analyzer) -> Result<Analysis> {
+        Err(error::GameError::AnalyzerNotFound {
+            input_game_name: self.info().name,
+            analyzer,
+        })
+        .context("Analyzer not found.")
+    }
 }
 
 /* INTERFACING */
@@ -165,6 +297,44 @@ where
     fn encode(&self, state: S) -> String;
 }
 
+/// Provides a way to make the transitions between states of type **S** of the
+/// underlying game explicit, by naming the moves that players can make. This is
+/// related to the `GameData` object, which should contain information about how
+/// moves can be represented using a string.
+///
+/// ## Explanation
+///
+/// Transition interfaces only provide the states which result from each move,
+/// which are not always easy to tell apart for humans (e.g., two boards which
+/// only differ in one piece). Interfaces can use this to show what players
+/// actually do at each state instead, such as "take 2" or "drop in column 4".
+/// The rules for string encodings of moves are the same as for states.
+pub trait Moves<S>
+where
+    Self: Legible<S>,
+{
+    /// Returns all of the moves that can be made from `state`, which should be
+    /// empty if and only if `state` is an end state. No two moves returned
+    /// should result in the same state. The order of the moves is insignificant.
+    fn moves(&self, state: S) -> Vec<Move>;
+
+    /// Returns the state which results from making `choice` at `state`. Making
+    /// a move which is not in `moves(state)` is undefined behavior.
+    fn apply(&self, state: S, choice: Move) -> S;
+
+    /// Transforms a string representation of a move into a `Move`. The `string`
+    /// should conform to the `move_protocol` specified in the `GameData` object
+    /// returned by `Game::info`. If it does not, an error containing a message
+    /// with a brief explanation on what is wrong with `string` should be
+    /// returned.
+    fn decode_move(&self, string: String) -> Result<Move>;
+
+    /// Transforms a move into a string representation which conforms to the
+    /// `move_protocol` specified in the `GameData` object returned by
+    /// `Game::info`. No two moves should have the same string representation.
+    fn encode_move(&self, choice: Move) -> String;
+}
+
 /* DETERMINISTIC TRAVERSAL INTERFACES */
 
 /// Provides a way to retrieve a unique starting state from which to begin a
@@ -298,6 +468,24 @@ pub trait STransition<S, const F: usize> {
     fn retrograde(&self, state: S) -> [Option<S>; F];
 }
 
+/// Provides a way to list the end states of a traversal, such that it can be
+/// carried out in retrograde by starting from them (see `DTransition`). Generic
+/// over a state type **S**.
+///
+/// Solvers which start from these states only reach the states from which all
+/// traversals finish at one of them, so for a traversal of the structure to be
+/// complete, the returned states must include every end state reachable from
+/// the starting state. They may also include unreachable end states, which
+/// only makes such traversals cover more of the structure than needed.
+pub trait TerminalEnumerable<S>
+where
+    Self: Bounded<S>,
+{
+    /// Returns the end states of the underlying structure, each of which must
+    /// satisfy `end`. The order of the values returned is insignificant.
+    fn terminals(&self) -> Vec<S>;
+}
+
 /* SOLVING INTERFACES */
 
 /// Indicates that an economic game object can have utility associated with
@@ -337,6 +525,69 @@ where
     }
 }
 
+/// Indicates that the utility values and remoteness of the states of an
+/// economic game object are bounded, which allows solvers to decide how much
+/// space to dedicate to each of them in the records they store. The provided
+/// bounds are the ones assumed for games which do not declare their own, so
+/// games with small bounds can save a lot of space by overriding them, and
+/// games which exceed them must override them to be solved.
+///
+/// Solvers fail if they encounter a state whose utility or remoteness exceeds
+/// the bounds declared by its game, as opposed to silently storing corrupted
+/// records for it.
+pub trait Limited
+where
+    Self: Game,
+{
+    /// Returns the range of values containing the utility of every player at
+    /// every state of the underlying game variant under optimal play.
+    #[inline(always)]
+    fn utility_range(&self) -> RangeInclusive<Utility> {
+        -127..=127
+    }
+
+    /// Returns the maximum remoteness of any state reachable from the starting
+    /// state of the underlying game variant.
+    #[inline(always)]
+    fn max_remoteness(&self) -> Remoteness {
+        (1 << 16) - 1
+    }
+}
+
+/// Indicates that the players of an economic game object move simultaneously,
+/// such that each state offers a set of actions to every player and the next
+/// state is determined by the actions chosen by all of them (a joint action).
+/// This is an alternative to `Solvable` for games where there is no single
+/// player whose turn it is at each state.
+///
+/// ## Explanation
+///
+/// Since players cannot react to the actions of others when they move at the
+/// same time, there is not necessarily a best action for each player at each
+/// state. Instead, optimal play can require mixed strategies (probability
+/// distributions over actions), which is the case in games like Goofspiel. This
+/// is why simultaneous-move games are solved by computing an equilibrium of
+/// mixed strategies at each state, rather than by choosing optimal children.
+pub trait Simultaneous<const N: PlayerCount>
+where
+    Self: Game,
+{
+    /// Returns the actions available to each player at `state`, where
+    /// `actions[i]` is the set of actions available to player `i`. Every player
+    /// should have at least one action at each non-terminal state, and no two
+    /// actions available to the same player should be the same.
+    fn actions(&self, state: State) -> [Vec<Move>; N];
+
+    /// Returns the state reached from `state` when each player `i` chooses the
+    /// action `joint[i]`, which should be one of the actions that `actions`
+    /// provides for player `i` at `state`.
+    fn transition(&self, state: State, joint: [Move; N]) -> State;
+
+    /// If `state` is terminal, returns the utility vector associated with that
+    /// state in the same way as `Solvable::utility`.
+    fn utility(&self, state: State) -> [Utility; N];
+}
+
 /// Indicates that the directed graph _G_ induced by the structure of the
 /// underlying game can be partitioned into partitions which themselves induce a
 /// directed acyclic graph. Note that this does not necessarily mean that all
@@ -368,16 +619,99 @@ where
     fn size(&self, partition: Partition) -> StateCount;
 }
 
+/// Indicates that the underlying game is impartial, meaning that the moves
+/// available from each of its positions do not depend on which player is to
+/// move. By the Sprague-Grundy theorem, every position of a finite impartial
+/// game played under the normal play convention is equivalent to a single Nim
+/// heap, whose size is called the nim-value of the position.
+///
+/// This is useful because the nim-value of a disjunctive sum of such games is
+/// the XOR of the nim-values of its components, which means that sums can be
+/// analyzed without exploring their (much larger) state spaces. Since turn
+/// information is irrelevant to the positions of impartial games, this trait
+/// also provides the means to erase it, so that components of a sum can be
+/// encoded with no redundancy.
+pub trait Impartial
+where
+    Self: Acyclic<2>
+        + Bounded<State>
+        + DTransition<State>
+        + Legible<State>
+        + Sync,
+{
+    /// Returns the nim-value (or Grundy value) of the position in `state`. The
+    /// player whose turn it is at `state` can force a win under normal play if
+    /// and only if this value is not zero.
+    fn nim_value(&self, state: State) -> MinimumExcludedValue;
+
+    /// Returns the state which encodes the same position as `state`, but where
+    /// it is player `0`'s turn. Two states which only differ in whose turn it
+    /// is should be mapped to the same value.
+    fn normalize(&self, state: State) -> State;
+
+    /// Returns the minimum number of bits necessary to express every state of
+    /// the form `normalize(s)`, where `s` is reachable from the starting state
+    /// of the underlying game variant.
+    fn width(&self) -> usize;
+}
+
+/// Indicates that the underlying game has symmetries, meaning that there are
+/// groups of distinct states from which the game plays out identically (such as
+/// reflections or rotations of the same board). Each of these groups can be
+/// represented by a single canonical state, so that solvers only need to store
+/// information about canonical states, and look up the canonical state of any
+/// other state when queried about it. Generic over a state type **S**.
+///
+/// For this to be sound, all states in the same group must agree on whether
+/// they are end states, on their utility if they are, and on whose turn it is.
+pub trait Symmetric<S> {
+    /// Returns the canonical representative of the group of states which are
+    /// symmetric to `state`. This should return the same state for all states
+    /// in the same group, which means that it should be idempotent.
+    fn canonical(&self, state: S) -> S;
+}
+
+/// Indicates that the states of the underlying game which are reachable from
+/// its starting state can be mapped to a dense range of integers (and back),
+/// which is to say that there exists a perfect hash function for them.
+/// This is useful because `State` encodings are often sparse, which wastes a
+/// lot of space in database implementations which index records by key.
+/// Generic over a state type **S**.
+pub trait Ranked<S> {
+    /// Returns the rank of `state`, which is an integer in `0..count()` that is
+    /// different for each state reachable from the starting state.
+    fn rank(&self, state: S) -> StateCount;
+
+    /// Returns the state whose rank is `rank`. This is the inverse of `rank`
+    /// over the states reachable from the starting state; passing it a rank not
+    /// associated with any such state is undefined behavior.
+    fn unrank(&self, rank: StateCount) -> S;
+
+    /// Returns the number of distinct ranks, which is an upper bound on the
+    /// number of states reachable from the starting state. This may be larger
+    /// than the number of reachable states, in which case some of the ranks in
+    /// `0..count()` are not associated with any reachable state.
+    fn count(&self) -> StateCount;
+}
+
 /* GAME STRUCTURE MARKERS */
 
 /// Indicates that the graph induced by the underlying game's states is acyclic.
 /// This intuitively means that no state will appear twice in a single session
 /// of game play. It is very practical for a game to exhibit this structure, as
-/// performing backwards induction is quite natural on acyclic graphs. Note that
-/// there is no behavior associated with this trait; it is used as a marker for
-/// providing blanket implementations from solvers which require games' state
-/// graphs to not have any cycles.
-pub trait Acyclic<const N: PlayerCount> {}
+/// performing backwards induction is quite natural on acyclic graphs. This trait
+/// is mostly used as a marker for providing blanket implementations from
+/// solvers which require games' state graphs to not have any cycles.
+pub trait Acyclic<const N: PlayerCount> {
+    /// Returns true if the state graph of this game is acyclic. This is always
+    /// the case for games whose structure is known at compile time, but games
+    /// which are only described at runtime (such as those loaded from plugins
+    /// or external programs) can override this to declare that they may have
+    /// cycles, which solvers check before running.
+    fn acyclic(&self) -> bool {
+        true
+    }
+}
 
 /* UTILITY INTERFACES */
 
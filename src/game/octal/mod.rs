@@ -0,0 +1,370 @@
+//! # Octal Game Module
+//!
+//! Octal games are a family of impartial two-player games played on heaps of
+//! tokens, where on each turn a player removes some tokens from a single heap
+//! and optionally splits what remains of it into two heaps. The moves that are
+//! allowed are specified by an octal code `d0.d1d2d3...`, where each digit
+//! `dk` is the sum of the following values:
+//!
+//! - `1` if it is allowed to remove `k` tokens, leaving no heaps behind.
+//! - `2` if it is allowed to remove `k` tokens, leaving one heap behind.
+//! - `4` if it is allowed to remove `k` tokens, leaving two heaps behind.
+//!
+//! Many classical combinatorial games are octal games. For example, Kayles is
+//! the octal game `0.77`, Dawson's Kayles is `0.07`, and Nim with a maximum of
+//! three tokens removed per turn is `0.333`. Under normal play, the player who
+//! is unable to move on their turn loses.
+
+use anyhow::{Context, Result};
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::game::error::GameError;
+use crate::game::misere::Misere;
+use crate::game::octal::states::*;
+use crate::game::octal::variants::*;
+use crate::game::{util, Acyclic, Bounded, DTransition, Entry, Legible};
+use crate::game::{Game, GameData, Impartial, Limited, Solvable};
+use crate::interface::{IOMode, SolutionMode};
+use crate::model::{MinimumExcludedValue, State, Turn, Utility};
+use crate::solver::strong;
+use crate::solver::util::{min_ubits, Policy, Resources};
+use crate::solver::verification::{Audit, Report};
+use crate::{implement, register};
+
+/* SUBMODULES */
+
+mod states;
+mod variants;
+
+/* GAME DATA */
+
+const NAME: &str = "octal";
+const AUTHORS: &str = "GamesmanNova contributors";
+const ABOUT: &str =
+"Two players take turns removing tokens from one of several heaps, optionally \
+splitting the remainder of the heap into two smaller heaps. The moves allowed \
+are determined by an octal code which is part of the game variant, where the \
+k-th digit after the point indicates whether it is allowed to remove k tokens \
+leaving zero (1), one (2), or two (4) heaps behind, or any sum of these. The \
+game variant also determines the sizes of the heaps at the start of the game. \
+The player who is unable to make a move on their turn loses.";
+
+/* REGISTRATION */
+
+const DATA: GameData = GameData {
+    variant: VARIANT_DEFAULT,
+
+    name: NAME,
+    authors: AUTHORS,
+    about: ABOUT,
+
+    variant_protocol: VARIANT_PROTOCOL,
+    variant_pattern: VARIANT_PATTERN,
+    variant_default: VARIANT_DEFAULT,
+
+    state_default: STATE_DEFAULT,
+    state_pattern: STATE_PATTERN,
+    state_protocol: STATE_PROTOCOL,
+
+    move_protocol: None,
+    move_pattern: None,
+};
+
+register! { DATA => session }
+
+/// Initializes a session of this game with `variant` for the game registry,
+/// which is of its misère version if `misere` is set.
+fn session(variant: Option<String>, misere: bool) -> Result<Box<dyn Game>> {
+    let session = Session::initialize(variant)
+        .context("Failed to initialize octal game session.")?;
+    if misere {
+        Ok(Box::new(Misere::<2, _>::new(session)))
+    } else {
+        Ok(Box::new(session))
+    }
+}
+
+/* GAME IMPLEMENTATION */
+
+/// Represents an instance of an octal game session, which is specific to a
+/// valid variant of the game. States are canonical representations of the
+/// multiset of heaps in the game, encoded by counting how many heaps of each
+/// size there are using a mixed-radix number system (whose radices are bounded
+/// by the maximum number of heaps of each size there could be in the game),
+/// which is packed with turn information.
+pub struct Session {
+    variant: String,
+    start: State,
+    tokens: u64,
+    largest: u64,
+    radices: Vec<State>,
+    options: Vec<Vec<Vec<u64>>>,
+    origins: HashMap<Vec<u64>, Vec<u64>>,
+    nimbers: Vec<MinimumExcludedValue>,
+}
+
+impl Game for Session {
+    fn initialize(variant: Option<String>) -> Result<Self> {
+        if let Some(v) = variant {
+            parse_variant(v).context("Malformed game variant.")
+        } else {
+            Ok(parse_variant(VARIANT_DEFAULT.to_owned()).unwrap())
+        }
+    }
+
+    fn id(&self) -> String {
+        format!("{}.{}", NAME, self.variant)
+    }
+
+    fn forward(&mut self, history: Vec<String>) -> Result<()> {
+        self.start = util::verify_history_dynamic(self, history)
+            .context("Malformed game state encoding.")?;
+        Ok(())
+    }
+
+    fn info(&self) -> GameData<'_> {
+        GameData {
+            variant: &self.variant,
+            ..DATA
+        }
+    }
+
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()> {
+        match method {
+            SolutionMode::Strong if resources.parallel() => {
+                strong::acyclic::parallel_dynamic_solver::<2, Self>(
+                    self, policy, resources,
+                )
+                .context("Failed solver run.")?;
+            },
+            SolutionMode::Strong => {
+                strong::acyclic::dynamic_solver::<2, Self>(
+                    self, mode, policy, resources,
+                )
+                .context("Failed solver run.")?;
+            },
+            _ => {
+                return Err(GameError::SolverNotFound {
+                    input_game_name: NAME,
+                })
+                .context("Solver not found.");
+            },
+        }
+        Ok(())
+    }
+
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+        let db = strong::acyclic::dynamic_solver::<2, Self>(
+            self,
+            mode,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .context("Failed solver run.")?;
+        util::query_entry(self, &db, state)
+    }
+
+    fn verify(&self, directory: &Path, audit: Audit) -> Result<Report> {
+        strong::acyclic::dynamic_verifier::<2, Self>(self, directory, audit)
+            .context("Failed verifier run.")
+    }
+}
+
+/* TRAVERSAL DECLARATIONS */
+
+impl Bounded<State> for Session {
+    fn start(&self) -> State {
+        self.start
+    }
+
+    fn end(&self, state: State) -> bool {
+        let (counts, _) = self.unpack(state);
+        counts
+            .iter()
+            .enumerate()
+            .all(|(size, &count)| count == 0 || self.options[size].is_empty())
+    }
+}
+
+impl DTransition<State> for Session {
+    fn prograde(&self, state: State) -> Vec<State> {
+        let (counts, turn) = self.unpack(state);
+        let mut next = Vec::new();
+        for (size, &count) in counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            for option in &self.options[size] {
+                let mut child = counts.clone();
+                child[size] -= 1;
+                for &heap in option {
+                    child[heap as usize] += 1;
+                }
+                next.push(self.pack(&child, (turn + 1) % 2));
+            }
+        }
+        next.sort();
+        next.dedup();
+        next
+    }
+
+    fn retrograde(&self, state: State) -> Vec<State> {
+        let (counts, turn) = self.unpack(state);
+        let total: u64 = counts
+            .iter()
+            .enumerate()
+            .map(|(size, &count)| size as u64 * count)
+            .sum();
+
+        let mut results = vec![vec![]];
+        for (a, &count) in counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            results.push(vec![a as u64]);
+            for (b, &other) in counts.iter().enumerate().skip(a) {
+                if other > 0 && (a != b || count > 1) {
+                    results.push(vec![a as u64, b as u64]);
+                }
+            }
+        }
+
+        let mut next = Vec::new();
+        for result in results {
+            let Some(origins) = self.origins.get(&result) else {
+                continue;
+            };
+            let remaining = total - result.iter().sum::<u64>();
+            for &size in origins {
+                if remaining + size > self.tokens {
+                    continue;
+                }
+                let mut parent = counts.clone();
+                for &heap in &result {
+                    parent[heap as usize] -= 1;
+                }
+                parent[size as usize] += 1;
+                next.push(self.pack(&parent, (turn + 1) % 2));
+            }
+        }
+        next.sort();
+        next.dedup();
+        next
+    }
+}
+
+/* SUPPLEMENTAL DECLARATIONS */
+
+impl Legible<State> for Session {
+    fn decode(&self, string: String) -> Result<State> {
+        Ok(parse_state(self, string)?)
+    }
+
+    fn encode(&self, state: State) -> String {
+        let (counts, turn) = self.unpack(state);
+        let heaps = counts
+            .iter()
+            .enumerate()
+            .flat_map(|(size, &count)| {
+                std::iter::repeat_n(size.to_string(), count as usize)
+            })
+            .collect::<Vec<String>>();
+
+        if heaps.is_empty() {
+            format!("0-{}", turn)
+        } else {
+            format!("{}-{}", heaps.join(","), turn)
+        }
+    }
+}
+
+/* SOLVING DECLARATIONS */
+
+implement! { for Session =>
+    Acyclic<2>,
+    Limited
+}
+
+impl Solvable<2> for Session {
+    fn utility(&self, state: State) -> [Utility; 2] {
+        let (_, turn) = util::unpack_turn(state, 2);
+        let mut payoffs = [1; 2];
+        payoffs[turn] = -1;
+        payoffs
+    }
+
+    fn turn(&self, state: State) -> Turn {
+        util::unpack_turn(state, 2).1
+    }
+}
+
+/* IMPARTIAL GAME THEORY */
+
+impl Impartial for Session {
+    fn nim_value(&self, state: State) -> MinimumExcludedValue {
+        let (counts, _) = self.unpack(state);
+        counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count % 2 == 1)
+            .fold(0, |acc, (size, _)| acc ^ self.nimbers[size])
+    }
+
+    fn normalize(&self, state: State) -> State {
+        let (counts, _) = self.unpack(state);
+        self.pack(&counts, 0)
+    }
+
+    fn width(&self) -> usize {
+        let counts = self
+            .radices
+            .iter()
+            .map(|&radix| radix - 1)
+            .collect::<Vec<u64>>();
+
+        min_ubits(self.pack(&counts, 0))
+    }
+}
+
+/* HELPER METHODS */
+
+impl Session {
+    /// Returns the number of heaps of each size in `state` (indexed by heap
+    /// size), along with the player whose turn it is at `state`.
+    fn unpack(&self, state: State) -> (Vec<u64>, Turn) {
+        let (mut state, turn) = util::unpack_turn(state, 2);
+        let mut counts = vec![0; self.radices.len()];
+        for (size, &radix) in self
+            .radices
+            .iter()
+            .enumerate()
+            .skip(1)
+        {
+            counts[size] = state % radix;
+            state /= radix;
+        }
+        (counts, turn)
+    }
+
+    /// Returns the canonical encoding of the multiset of heaps where there are
+    /// `counts[i]` heaps of size `i`, with information that it is `turn`'s turn
+    /// to move. This is the inverse of `unpack`.
+    fn pack(&self, counts: &[u64], turn: Turn) -> State {
+        let state = self
+            .radices
+            .iter()
+            .zip(counts)
+            .skip(1)
+            .rev()
+            .fold(0, |acc, (&radix, &count)| acc * radix + count);
+        util::pack_turn(state, turn, 2)
+    }
+}
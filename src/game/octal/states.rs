@@ -0,0 +1,259 @@
+//! # Octal Game State Handling Module
+//!
+//! This module helps parse the string encoding of an octal game state into a
+//! more efficient binary representation, performing a series of checks which
+//! partially ensure compatibility with a game variant.
+
+use regex::Regex;
+
+use crate::game::error::GameError;
+use crate::game::octal::{Session, NAME};
+use crate::model::{State, Turn};
+
+/* OCTAL GAME STATE ENCODING */
+
+pub const STATE_DEFAULT: &str = "5,7-0";
+pub const STATE_PATTERN: &str = r"^\d+(?:,\d+)*-\d+$";
+pub const STATE_PROTOCOL: &str =
+"The state string should be a comma-separated list of non-negative integers \
+indicating the sizes of the heaps in the game, followed by a dash and the turn \
+of the player who is next to move (either 0 or 1). The order of the heaps is \
+not significant, and heaps of size 0 are ignored; for example, '0-1' is a game \
+with no tokens left where it is player 1's turn. No heap can be larger than \
+the largest heap at the start of the game variant, and the total number of \
+tokens cannot exceed that of the starting heaps of the game variant.";
+
+/* API */
+
+/// Returns an octal game state encoding using the parameters specified by a
+/// pre-verified game variant combined with the heaps provided in `from`. This
+/// does not verify that the provided `from` is reachable in `session`'s game
+/// variant.
+pub fn parse_state(
+    session: &Session,
+    from: String,
+) -> Result<State, GameError> {
+    check_state_pattern(&from)?;
+    let (heaps, turn) = parse_parameters(&from)?;
+    check_variant_coherence(&heaps, turn, session)?;
+    let mut counts = vec![0; session.largest as usize + 1];
+    for heap in heaps
+        .into_iter()
+        .filter(|&h| h > 0)
+    {
+        counts[heap as usize] += 1;
+    }
+    Ok(session.pack(&counts, turn))
+}
+
+/* STATE STRING VERIFICATION */
+
+fn check_state_pattern(from: &str) -> Result<(), GameError> {
+    let re = Regex::new(STATE_PATTERN).unwrap();
+    if !re.is_match(from) {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String does not match the pattern '{}'.",
+                STATE_PATTERN
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_parameters(from: &str) -> Result<(Vec<u64>, Turn), GameError> {
+    let error = |e: std::num::ParseIntError| GameError::StateMalformed {
+        game_name: NAME,
+        hint: e.to_string(),
+    };
+
+    let (heaps, turn) = from.split_once('-').unwrap();
+    let heaps = heaps
+        .split(',')
+        .map(|int_string| int_string.parse::<u64>())
+        .collect::<Result<Vec<u64>, _>>()
+        .map_err(error)?;
+
+    let turn = turn
+        .parse::<Turn>()
+        .map_err(error)?;
+
+    Ok((heaps, turn))
+}
+
+fn check_variant_coherence(
+    heaps: &[u64],
+    turn: Turn,
+    session: &Session,
+) -> Result<(), GameError> {
+    let total = heaps
+        .iter()
+        .try_fold(0u64, |acc, &h| acc.checked_add(h));
+
+    if let Some(&heap) = heaps
+        .iter()
+        .find(|&&h| h > session.largest)
+    {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "Specified a heap of size {}, but no heap can be larger than \
+                the largest starting heap of this variant ({}).",
+                heap, session.largest,
+            ),
+        })
+    } else if total.is_none_or(|t| t > session.tokens) {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "Specified more tokens than there are at the start of this \
+                variant ({}).",
+                session.tokens,
+            ),
+        })
+    } else if turn >= 2 {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "Specified a turn ({}) too high for this (2-player) game.",
+                turn,
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::testing;
+    use crate::game::util::verify_history_dynamic;
+    use crate::game::{Bounded, DTransition, Game, Impartial, Legible};
+
+    use std::collections::HashSet;
+
+    #[test]
+    fn state_pattern_is_valid_regex() {
+        assert!(Regex::new(STATE_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_state_matches_state_pattern() {
+        let re = Regex::new(STATE_PATTERN).unwrap();
+        assert!(re.is_match(STATE_DEFAULT));
+    }
+
+    #[test]
+    fn no_state_equals_default_state() {
+        let with_none = Session::initialize(None).unwrap();
+        let with_default = Session::initialize(None).unwrap();
+
+        assert_eq!(
+            with_none.start,
+            parse_state(&with_default, STATE_DEFAULT.to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn heap_order_is_canonicalized() {
+        let session = session("0.77-3-5-7");
+        let s1 = parse_state(&session, "3,5,7-0".to_owned()).unwrap();
+        let s2 = parse_state(&session, "7,3,5-0".to_owned()).unwrap();
+        let s3 = parse_state(&session, "5,0,7,3,0-0".to_owned()).unwrap();
+
+        assert_eq!(s1, s2);
+        assert_eq!(s1, s3);
+        assert_eq!(session.encode(s1), "3,5,7-0");
+    }
+
+    #[test]
+    fn malformed_states_fail_checks() {
+        let s1 = "3,5-2".to_owned(); // Turn too high
+        let s2 = "8-0".to_owned(); // Heap too large
+        let s3 = "7,7-1".to_owned(); // Too many tokens
+        let s4 = "3,-1".to_owned(); // Malformed
+        let s5 = "-0".to_owned(); // No heaps
+        let s6 = "3 5-0".to_owned(); // Whitespace
+
+        let f = || session("0.77-5-7");
+        assert!(parse_state(&f(), s1).is_err());
+        assert!(parse_state(&f(), s2).is_err());
+        assert!(parse_state(&f(), s3).is_err());
+        assert!(parse_state(&f(), s4).is_err());
+        assert!(parse_state(&f(), s5).is_err());
+        assert!(parse_state(&f(), s6).is_err());
+    }
+
+    #[test]
+    fn verify_kayles_history() {
+        let c1 = vec!["5,7-0", "2,2,7-1", "2,2,2,3-0", "1,2,2,2-1"];
+        let c2 = vec!["5,7-0", "5,6-1", "1,2,6-0", "1,6-1"];
+        let i1 = vec!["5,7-0", "0-1"]; // Illegal move
+        let i2 = vec!["5,7-0", "5,6-0"]; // Turns don't switch
+
+        let f = || session("0.77-5-7");
+        assert!(verify_history_dynamic(&f(), owned(c1)).is_ok());
+        assert!(verify_history_dynamic(&f(), owned(c2)).is_ok());
+        assert!(verify_history_dynamic(&f(), owned(i1)).is_err());
+        assert!(verify_history_dynamic(&f(), owned(i2)).is_err());
+    }
+
+    #[test]
+    fn retrograde_inverts_prograde() {
+        for variant in ["0.77-4-5", "0.07-3-6", "4.07-5-2", "0.333-3-4"] {
+            let session = session(variant);
+            let mut seen = HashSet::new();
+            let mut stack = vec![session.start()];
+            while let Some(state) = stack.pop() {
+                if !seen.insert(state) || session.end(state) {
+                    continue;
+                }
+                for child in session.prograde(state) {
+                    assert!(session
+                        .retrograde(child)
+                        .contains(&state));
+                    stack.push(child);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nim_values_predict_winners() {
+        for variant in ["0.77-4-5", "0.07-3-6", "4.07-5-2", "0.137-4-4-1"] {
+            let session = session(variant);
+            let entry = session
+                .query(crate::interface::IOMode::Write, None)
+                .unwrap();
+
+            let winning = session.nim_value(session.start()) != 0;
+            assert_eq!(entry.utility[0] > 0, winning);
+        }
+    }
+
+    #[test]
+    fn octal_conforms_to_game_interfaces() {
+        for variant in ["0.77-5-7", "0.07-3-6", "4.07-5-2", "0.137-4-4-1"] {
+            let session = session(variant);
+            testing::verify_acyclic::<2, _>(&session, 10_000).unwrap();
+        }
+    }
+
+    /* UTILITIES */
+
+    fn session(v: &str) -> Session {
+        Session::initialize(Some(v.to_owned())).unwrap()
+    }
+
+    fn owned(v: Vec<&str>) -> Vec<String> {
+        v.iter()
+            .map(|&s| s.to_owned())
+            .collect()
+    }
+}
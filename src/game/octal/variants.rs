@@ -0,0 +1,304 @@
+//! # Octal Game Variant Handling Module
+//!
+//! This module helps parse the variant string provided to the octal game into
+//! parameters that can help build a game session, which includes computing the
+//! options available from each heap size as specified by the octal code.
+
+use regex::Regex;
+
+use std::collections::HashMap;
+
+use crate::game::error::GameError;
+use crate::game::octal::{Session, NAME};
+use crate::game::util::mex;
+use crate::model::{MinimumExcludedValue, State};
+
+/* OCTAL GAME VARIANT ENCODING */
+
+pub const VARIANT_DEFAULT: &str = "0.77-5-7";
+pub const VARIANT_PATTERN: &str = r"^[0-7]\.[0-7]+(?:-[1-9]\d*)+$";
+pub const VARIANT_PROTOCOL: &str =
+"The variant string should be an octal code followed by one or more positive \
+integers, all separated by dashes. The octal code is of the form d0.d1d2...dn, \
+where each dk is an octal digit (0-7) indicating which moves that remove k \
+tokens from a heap are allowed, as the sum of 1 (removing the entire heap), 2 \
+(leaving one heap behind), and 4 (leaving two non-empty heaps behind). Because \
+no tokens are removed by d0, it must be either 0 or 4. The integers after the \
+octal code are the sizes of the heaps at the start of the game. For example, \
+'0.77-5-7' is Kayles played on rows of 5 and 7 pins, and '0.07-10' is Dawson's \
+Kayles played on a single row of 10 pins. Note that the total number of tokens \
+in the heaps is limited by the need to fit all states in 64 bits.";
+
+/* API */
+
+/// Returns an octal game session set up using the parameters specified by
+/// `variant`. Returns a `GameError::VariantMalformed` if the variant string
+/// does not conform to the variant protocol, or if its states could not be
+/// encoded in 64 bits.
+pub fn parse_variant(variant: String) -> Result<Session, GameError> {
+    check_variant_pattern(&variant)?;
+    let (code, heaps) = parse_parameters(&variant)?;
+    check_splitting_digit(&code)?;
+    let tokens = heaps.iter().sum::<u64>();
+    let largest = *heaps.iter().max().unwrap();
+    let radices = compute_radices(tokens, largest)?;
+    let options = compute_options(&code, largest);
+    let origins = compute_origins(&options);
+    let nimbers = compute_nimbers(&options);
+
+    let mut session = Session {
+        variant,
+        start: 0,
+        tokens,
+        largest,
+        radices,
+        options,
+        origins,
+        nimbers,
+    };
+
+    let mut counts = vec![0; largest as usize + 1];
+    for heap in heaps {
+        counts[heap as usize] += 1;
+    }
+    session.start = session.pack(&counts, 0);
+    Ok(session)
+}
+
+/* VARIANT STRING VERIFICATION */
+
+fn check_variant_pattern(variant: &str) -> Result<(), GameError> {
+    let re = Regex::new(VARIANT_PATTERN).unwrap();
+    if !re.is_match(variant) {
+        Err(GameError::VariantMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String does not match the pattern '{}'.",
+                VARIANT_PATTERN
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_parameters(variant: &str) -> Result<(Vec<u8>, Vec<u64>), GameError> {
+    let mut params = variant.split('-');
+    let code = params
+        .next()
+        .unwrap()
+        .chars()
+        .filter(|&c| c != '.')
+        .map(|c| c.to_digit(8).unwrap() as u8)
+        .collect();
+
+    let heaps = params
+        .map(|int_string| {
+            int_string
+                .parse::<u64>()
+                .map_err(|e| GameError::VariantMalformed {
+                    game_name: NAME,
+                    hint: e.to_string(),
+                })
+        })
+        .collect::<Result<Vec<u64>, GameError>>()?;
+
+    Ok((code, heaps))
+}
+
+fn check_splitting_digit(code: &[u8]) -> Result<(), GameError> {
+    if code[0] != 0 && code[0] != 4 {
+        Err(GameError::VariantMalformed {
+            game_name: NAME,
+            hint: format!(
+                "The digit before the point in the octal code must be either \
+                0 or 4, but it was {}.",
+                code[0],
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/* SESSION PARAMETER COMPUTATION */
+
+/// Returns the radices used to count the number of heaps of each size in a
+/// game with `tokens` total tokens and heaps of size at most `largest`, where
+/// the radix at index `i` bounds the number of heaps of size `i`. Fails if the
+/// product of all radices (and the turn bit) does not fit in a `State`.
+fn compute_radices(tokens: u64, largest: u64) -> Result<Vec<State>, GameError> {
+    let mut radices = vec![1];
+    let mut product: State = 2;
+    for size in 1..=largest {
+        let radix = tokens / size + 1;
+        product = product
+            .checked_mul(radix)
+            .ok_or_else(|| GameError::VariantMalformed {
+                game_name: NAME,
+                hint: format!(
+                    "The heaps specified contain too many tokens ({}) for all \
+                    of the states of the game to be encoded in 64 bits.",
+                    tokens,
+                ),
+            })?;
+        radices.push(radix);
+    }
+    Ok(radices)
+}
+
+/// Returns the options available from each heap size up to `largest` under
+/// `code`, where each option is a sorted list of the sizes of the heaps left
+/// behind by a move (which can have zero, one, or two elements).
+fn compute_options(code: &[u8], largest: u64) -> Vec<Vec<Vec<u64>>> {
+    let mut options = vec![vec![]; largest as usize + 1];
+    for (size, result) in options.iter_mut().enumerate() {
+        let size = size as u64;
+        for (removed, &digit) in code.iter().enumerate() {
+            let removed = removed as u64;
+            if removed > size {
+                break;
+            }
+
+            let left = size - removed;
+            if digit & 1 != 0 && removed > 0 && left == 0 {
+                result.push(vec![]);
+            }
+            if digit & 2 != 0 && removed > 0 && left > 0 {
+                result.push(vec![left]);
+            }
+            if digit & 4 != 0 {
+                for a in 1..=(left / 2) {
+                    result.push(vec![a, left - a]);
+                }
+            }
+        }
+    }
+    options
+}
+
+/// Returns a mapping from each possible sorted list of heaps left behind by a
+/// move to the heap sizes from which such a move can be made.
+fn compute_origins(options: &[Vec<Vec<u64>>]) -> HashMap<Vec<u64>, Vec<u64>> {
+    let mut origins: HashMap<Vec<u64>, Vec<u64>> = HashMap::new();
+    for (size, results) in options.iter().enumerate() {
+        for result in results {
+            origins
+                .entry(result.clone())
+                .or_default()
+                .push(size as u64);
+        }
+    }
+    origins
+}
+
+/// Returns the nim-value of a single heap of each size up to the largest size
+/// in `options`, which is the minimum excluded value of the nim-values of the
+/// positions reachable from it in a single move.
+fn compute_nimbers(options: &[Vec<Vec<u64>>]) -> Vec<MinimumExcludedValue> {
+    let mut nimbers: Vec<MinimumExcludedValue> = Vec::new();
+    for results in options {
+        let values = results
+            .iter()
+            .map(|result| {
+                result
+                    .iter()
+                    .fold(0, |acc, &heap| acc ^ nimbers[heap as usize])
+            })
+            .collect::<Vec<MinimumExcludedValue>>();
+        nimbers.push(mex(&values));
+    }
+    nimbers
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::Game;
+
+    #[test]
+    fn variant_pattern_is_valid_regex() {
+        assert!(Regex::new(VARIANT_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_variant_matches_variant_pattern() {
+        let re = Regex::new(VARIANT_PATTERN).unwrap();
+        assert!(re.is_match(VARIANT_DEFAULT));
+    }
+
+    #[test]
+    fn no_variant_equals_default_variant() {
+        let with_none = Session::initialize(None).unwrap();
+        let with_default =
+            Session::initialize(Some(VARIANT_DEFAULT.to_owned())).unwrap();
+        assert_eq!(with_none.variant, with_default.variant);
+        assert_eq!(with_none.start, with_default.start);
+        assert_eq!(with_none.options, with_default.options);
+    }
+
+    #[test]
+    fn invalid_variants_fail_checks() {
+        let v1 = "0.77";
+        let v2 = "0.78-5";
+        let v3 = "77-5-7";
+        let v4 = "1.77-5";
+        let v5 = "0.77-0-5";
+        let v6 = "0.77--5";
+        let v7 = "0.3-60-60-60-60-60";
+
+        fn wrapper(v: &'static str) -> Result<Session, GameError> {
+            parse_variant(v.to_owned())
+        }
+
+        assert!(wrapper(v1).is_err());
+        assert!(wrapper(v2).is_err());
+        assert!(wrapper(v3).is_err());
+        assert!(wrapper(v4).is_err());
+        assert!(wrapper(v5).is_err());
+        assert!(wrapper(v6).is_err());
+        assert!(wrapper(v7).is_err());
+    }
+
+    #[test]
+    fn valid_variants_pass_checks() {
+        let v1 = "0.77-5-7";
+        let v2 = "0.07-10";
+        let v3 = "0.333-3-4-5";
+        let v4 = "4.0-9";
+        let v5 = "0.137-8-1-1";
+
+        fn wrapper(v: &'static str) -> Result<Session, GameError> {
+            parse_variant(v.to_owned())
+        }
+
+        assert!(wrapper(v1).is_ok());
+        assert!(wrapper(v2).is_ok());
+        assert!(wrapper(v3).is_ok());
+        assert!(wrapper(v4).is_ok());
+        assert!(wrapper(v5).is_ok());
+    }
+
+    #[test]
+    fn heap_nim_values_match_known_sequences() {
+        let kayles = parse_variant("0.77-16".to_owned()).unwrap();
+        let dawson = parse_variant("0.07-16".to_owned()).unwrap();
+        let nim = parse_variant("0.333-16".to_owned()).unwrap();
+
+        assert_eq!(
+            kayles.nimbers,
+            vec![0, 1, 2, 3, 1, 4, 3, 2, 1, 4, 2, 6, 4, 1, 2, 7, 1]
+        );
+        assert_eq!(
+            dawson.nimbers,
+            vec![0, 0, 1, 1, 2, 0, 3, 1, 1, 0, 3, 3, 2, 2, 4, 0, 5]
+        );
+        assert_eq!(
+            nim.nimbers,
+            vec![0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3, 0]
+        );
+    }
+}
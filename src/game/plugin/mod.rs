@@ -0,0 +1,653 @@
+//! # Game Plugin Module
+//!
+//! This module provides an adapter for games which are compiled separately
+//! into shared libraries (e.g., as a `cdylib` crate), and loaded at runtime.
+//! Plugins communicate with the adapter through a stable C ABI, which allows
+//! them to be written in any language that can export C functions.
+//!
+//! ## Interface
+//!
+//! A plugin must export the following two symbols:
+//!
+//! - `uint32_t nova_plugin_version(void)`, returning `INTERFACE_VERSION`.
+//! - `const Interface *nova_plugin_interface(void)`, returning a pointer to an
+//!   `Interface` which is valid for as long as the library is loaded.
+//!
+//! The version is checked before anything else is used, so plugins built
+//! against a different version of the interface are rejected. Sessions are
+//! opaque pointers created by `Interface::initialize` and destroyed through
+//! `Interface::finalize`. Functions which produce a variable amount of data
+//! write at most `capacity` elements into a buffer provided by the caller, and
+//! return the total amount of elements available (the caller retries with a
+//! larger buffer if necessary). Strings are not NUL-terminated in buffers, but
+//! must be NUL-terminated everywhere else.
+//!
+//! Since the traversal interfaces cannot fail, the first violation of these
+//! rules after a session is initialized is recorded, and the offending call
+//! returns placeholder values (such as zero utility for every player). The
+//! violation is then reported as a `GameError::PluginFailure` once the solver
+//! run which led to it finishes.
+
+use anyhow::{anyhow, Context, Result};
+use libloading::Library;
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::Path;
+
+use crate::database::volatile;
+use crate::game::error::GameError;
+use crate::game::{util, Acyclic, Bounded, DTransition, Entry, Legible};
+use crate::game::{Game, GameData, Limited, Solvable};
+use crate::interface::{IOMode, SolutionMode};
+use crate::model::{PlayerCount, State, Turn, Utility};
+use crate::solvable;
+use crate::solver::strong;
+use crate::solver::util::{Policy, Resources};
+use crate::solver::{MAX_TRANSITIONS, SOLVABLE_PLAYERS};
+
+/* C ABI */
+
+/// The version of the plugin interface implemented by this module, which must
+/// be returned by the `nova_plugin_version` function of all plugins. This must
+/// be incremented whenever `Interface` or `Metadata` change.
+pub const INTERFACE_VERSION: u32 = 1;
+
+const VERSION_SYMBOL: &[u8] = b"nova_plugin_version\0";
+const INTERFACE_SYMBOL: &[u8] = b"nova_plugin_interface\0";
+
+/// Static information about a plugin game, mirroring `GameData`. All strings
+/// must be NUL-terminated and UTF-8 encoded.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Metadata {
+    pub name: *const c_char,
+    pub authors: *const c_char,
+    pub about: *const c_char,
+    pub variant_protocol: *const c_char,
+    pub variant_pattern: *const c_char,
+    pub variant_default: *const c_char,
+    pub state_protocol: *const c_char,
+    pub state_pattern: *const c_char,
+    pub state_default: *const c_char,
+}
+
+/// The functions provided by a plugin, which mirror the `Game`, `Bounded`,
+/// `DTransition`, `Legible`, and `Solvable` interfaces over opaque sessions.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Interface {
+    /// Static information about the game.
+    pub metadata: Metadata,
+    /// Writes a description of the last error that happened in the calling
+    /// thread into a buffer.
+    pub error: unsafe extern "C" fn(*mut c_char, usize) -> usize,
+    /// Returns a new session of a variant (or the default one if given a null
+    /// pointer), or a null pointer if the variant is malformed.
+    pub initialize: unsafe extern "C" fn(*const c_char) -> *mut c_void,
+    /// Destroys a session returned by `initialize`.
+    pub finalize: unsafe extern "C" fn(*mut c_void),
+    /// Returns the number of players in a session's variant.
+    pub players: unsafe extern "C" fn(*const c_void) -> usize,
+    /// Returns whether a session's variant has no cycles in its state graph.
+    pub acyclic: unsafe extern "C" fn(*const c_void) -> bool,
+    /// Returns the starting state of a session.
+    pub start: unsafe extern "C" fn(*const c_void) -> State,
+    /// Returns whether a state is an end state.
+    pub end: unsafe extern "C" fn(*const c_void, State) -> bool,
+    /// Writes the children of a state into a buffer.
+    pub prograde:
+        unsafe extern "C" fn(*const c_void, State, *mut State, usize) -> usize,
+    /// Writes the parents of a state into a buffer.
+    pub retrograde:
+        unsafe extern "C" fn(*const c_void, State, *mut State, usize) -> usize,
+    /// Writes the string encoding of a state into a buffer.
+    pub encode:
+        unsafe extern "C" fn(*const c_void, State, *mut c_char, usize) -> usize,
+    /// Parses a NUL-terminated string encoding into a state, returning whether
+    /// it was possible to do so.
+    pub decode:
+        unsafe extern "C" fn(*const c_void, *const c_char, *mut State) -> bool,
+    /// Writes the utility of an end state for each player into a buffer.
+    pub utility: unsafe extern "C" fn(
+        *const c_void,
+        State,
+        *mut Utility,
+        usize,
+    ) -> usize,
+    /// Returns the player whose turn it is at a state.
+    pub turn: unsafe extern "C" fn(*const c_void, State) -> Turn,
+}
+
+/* GAME DATA */
+
+const NAME: &str = "plugin";
+
+/* GAME IMPLEMENTATION */
+
+pub struct Session {
+    variant: String,
+    data: GameData<'static>,
+    players: PlayerCount,
+    acyclic: bool,
+    start: State,
+    handle: *mut c_void,
+    interface: Interface,
+    fault: RefCell<Option<GameError>>,
+    _library: Library,
+}
+
+impl Session {
+    /// Loads the plugin at `path`, and initializes a session of it with
+    /// `variant` (or its default variant if `None` is provided). Fails with a
+    /// `GameError::PluginIncompatible` if the plugin does not implement the
+    /// same version of the plugin interface as this module.
+    pub fn load(path: &Path, variant: Option<String>) -> Result<Self> {
+        let display = path.display().to_string();
+        let incompatible = |hint: String| GameError::PluginIncompatible {
+            path: display.clone(),
+            hint,
+        };
+
+        // SAFETY: Loading a library runs its initialization routines, which is
+        // inherent to supporting plugins; they are trusted as much as Nova.
+        let library = unsafe { Library::new(path) }
+            .with_context(|| format!("Failed to load plugin '{}'.", display))?;
+
+        let interface = unsafe {
+            let version = library
+                .get::<unsafe extern "C" fn() -> u32>(VERSION_SYMBOL)
+                .map_err(|e| incompatible(e.to_string()))?;
+
+            if version() != INTERFACE_VERSION {
+                return Err(incompatible(format!(
+                    "It implements version {} of the plugin interface, but \
+                    only version {} is supported.",
+                    version(),
+                    INTERFACE_VERSION,
+                )))
+                .context("Incompatible plugin.");
+            }
+
+            let interface = library
+                .get::<unsafe extern "C" fn() -> *const Interface>(
+                    INTERFACE_SYMBOL,
+                )
+                .map_err(|e| incompatible(e.to_string()))?;
+
+            interface()
+                .as_ref()
+                .copied()
+                .ok_or_else(|| incompatible("No interface provided.".into()))?
+        };
+
+        let data = metadata(&interface.metadata)
+            .map_err(|e| incompatible(e.to_string()))?;
+
+        let name = data.name;
+        let variant = variant.unwrap_or(data.variant_default.to_owned());
+        let argument = CString::new(variant.clone())?;
+        let handle = unsafe { (interface.initialize)(argument.as_ptr()) };
+        if handle.is_null() {
+            return Err(GameError::VariantMalformed {
+                game_name: name,
+                hint: unsafe { read(|b, c| (interface.error)(b, c)) },
+            })
+            .context("Malformed game variant.");
+        }
+
+        // The session is built before validating it so that it is finalized
+        // when dropped if it is rejected.
+        let session = Session {
+            variant,
+            data,
+            players: unsafe { (interface.players)(handle) },
+            acyclic: unsafe { (interface.acyclic)(handle) },
+            start: unsafe { (interface.start)(handle) },
+            handle,
+            interface,
+            fault: RefCell::new(None),
+            _library: library,
+        };
+
+        if !SOLVABLE_PLAYERS.contains(&session.players) {
+            return Err(incompatible(format!(
+                "The game has {} players, but only 1 to {} are supported.",
+                session.players,
+                SOLVABLE_PLAYERS[SOLVABLE_PLAYERS.len() - 1],
+            )))
+            .context("Unsupported plugin game.");
+        }
+
+        Ok(session)
+    }
+}
+
+impl Game for Session {
+    fn initialize(_: Option<String>) -> Result<Self> {
+        Err(anyhow!(
+            "Plugin sessions can only be initialized from a shared library."
+        ))
+    }
+
+    fn id(&self) -> String {
+        format!("{}.{}.{}", NAME, self.data.name, self.variant)
+    }
+
+    fn forward(&mut self, history: Vec<String>) -> Result<()> {
+        self.start = util::verify_history_dynamic(self, history)
+            .context("Malformed game state encoding.")?;
+        Ok(())
+    }
+
+    fn info(&self) -> GameData<'_> {
+        GameData {
+            variant: &self.variant,
+            ..self.data
+        }
+    }
+
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()> {
+        match method {
+            SolutionMode::Strong => {
+                self.strong_solver(mode, policy, resources)?;
+            },
+            _ => {
+                return Err(GameError::SolverNotFound {
+                    input_game_name: self.data.name,
+                })
+                .context("Solver not found.");
+            },
+        }
+        Ok(())
+    }
+
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+        let db =
+            self.strong_solver(mode, Policy::default(), &Resources::default())?;
+        util::query_entry(self, &db, state)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // SAFETY: The handle was returned by `initialize` of this interface,
+        // and the library is unloaded only after this (fields drop later).
+        unsafe { (self.interface.finalize)(self.handle) }
+    }
+}
+
+/* TRAVERSAL DECLARATIONS */
+
+impl Bounded<State> for Session {
+    fn start(&self) -> State {
+        self.start
+    }
+
+    fn end(&self, state: State) -> bool {
+        unsafe { (self.interface.end)(self.handle, state) }
+    }
+}
+
+impl DTransition<State> for Session {
+    fn prograde(&self, state: State) -> Vec<State> {
+        self.states(self.interface.prograde, state)
+    }
+
+    fn retrograde(&self, state: State) -> Vec<State> {
+        self.states(self.interface.retrograde, state)
+    }
+}
+
+/* SUPPLEMENTAL DECLARATIONS */
+
+impl Legible<State> for Session {
+    fn decode(&self, string: String) -> Result<State> {
+        let malformed = |hint: String| GameError::StateMalformed {
+            game_name: self.data.name,
+            hint,
+        };
+
+        let string =
+            CString::new(string).map_err(|e| malformed(e.to_string()))?;
+        let mut state = 0;
+        if unsafe {
+            (self.interface.decode)(self.handle, string.as_ptr(), &mut state)
+        } {
+            Ok(state)
+        } else {
+            let hint = unsafe { read(|b, c| (self.interface.error)(b, c)) };
+            Err(malformed(hint)).context("Malformed game state encoding.")
+        }
+    }
+
+    fn encode(&self, state: State) -> String {
+        unsafe {
+            read(|b, c| (self.interface.encode)(self.handle, state, b, c))
+        }
+    }
+}
+
+/* SOLVING DECLARATIONS */
+
+impl<const N: PlayerCount> Acyclic<N> for Session {
+    fn acyclic(&self) -> bool {
+        self.acyclic
+    }
+}
+
+impl Limited for Session {}
+
+impl<const N: PlayerCount> Solvable<N> for Session {
+    fn utility(&self, state: State) -> [Utility; N] {
+        let mut utility = [0; N];
+        let count = unsafe {
+            (self.interface.utility)(
+                self.handle,
+                state,
+                utility.as_mut_ptr(),
+                N,
+            )
+        };
+
+        if count != N {
+            self.fail(GameError::PluginFailure {
+                game_name: self.data.name,
+                hint: format!(
+                    "The game provided {} utility values at state {}, but it \
+                    has {} players.",
+                    count, state, N,
+                ),
+            });
+            return [0; N];
+        }
+        utility
+    }
+
+    fn turn(&self, state: State) -> Turn {
+        unsafe { (self.interface.turn)(self.handle, state) }
+    }
+}
+
+/// Generates the routine which dispatches to the solver whose generic player
+/// count matches that of the session, for each player count provided.
+macro_rules! players {
+    ($($n:literal),+) => {
+        impl Session {
+            /// Strongly solves this session with the solver whose generic
+            /// player count matches that of the plugin game, which must be
+            /// acyclic unless it is a puzzle. Ties are broken by `policy`.
+            fn strong_solver(
+                &self,
+                mode: IOMode,
+                policy: Policy,
+                resources: &Resources,
+            ) -> Result<volatile::Database> {
+                let db = match (self.players, self.acyclic) {
+                    (1, false) => strong::cyclic::puzzle_solver(
+                        self, mode, policy, resources,
+                    )
+                    .context("Failed solver run."),
+                    $(($n, true) => strong::acyclic::dynamic_solver::<$n, Self>(
+                        self, mode, policy, resources,
+                    )
+                    .context("Failed solver run."),)+
+                    _ => Err(GameError::SolverNotFound {
+                        input_game_name: self.data.name,
+                    })
+                    .context("Solver not found."),
+                };
+
+                self.check()?;
+                db
+            }
+        }
+    };
+}
+
+solvable! { players }
+
+/* HELPER FUNCTIONS */
+
+impl Session {
+    /// Returns the states written by `function` (either the prograde or the
+    /// retrograde of the plugin) for `state`.
+    fn states(
+        &self,
+        function: unsafe extern "C" fn(
+            *const c_void,
+            State,
+            *mut State,
+            usize,
+        ) -> usize,
+        state: State,
+    ) -> Vec<State> {
+        let mut buffer = vec![0; MAX_TRANSITIONS];
+        let write = |buffer: &mut Vec<State>| unsafe {
+            function(
+                self.handle,
+                state,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+            )
+        };
+
+        // Solvers reject states with more than `MAX_TRANSITIONS` transitions,
+        // so they are all returned for them to report it.
+        let count = write(&mut buffer);
+        if count > buffer.len() {
+            buffer.resize(count, 0);
+            write(&mut buffer);
+        }
+        buffer.truncate(count);
+        buffer
+    }
+
+    /// Records `error` as a violation of the plugin interface by the plugin,
+    /// unless an earlier violation was already recorded.
+    fn fail(&self, error: GameError) {
+        self.fault
+            .borrow_mut()
+            .get_or_insert(error);
+    }
+
+    /// Fails with the earliest violation of the plugin interface by the plugin
+    /// since the last call, if there was any.
+    fn check(&self) -> Result<()> {
+        match self.fault.borrow_mut().take() {
+            Some(error) => {
+                Err(error).context("Plugin game violated the interface.")
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the string written by `function` into a buffer of a given capacity,
+/// retrying with a larger buffer if the first one is not large enough.
+unsafe fn read<F>(function: F) -> String
+where
+    F: Fn(*mut c_char, usize) -> usize,
+{
+    let mut buffer: Vec<u8> = vec![0; 64];
+    let length = function(buffer.as_mut_ptr() as *mut c_char, buffer.len());
+    if length > buffer.len() {
+        buffer.resize(length, 0);
+        function(buffer.as_mut_ptr() as *mut c_char, buffer.len());
+    }
+    buffer.truncate(length);
+    String::from_utf8_lossy(&buffer).into_owned()
+}
+
+/// Returns the game data described by `metadata`, where the variant is the
+/// default one. The strings are copied once, and kept for the lifetime of the
+/// program as is expected of the information provided by all other games (see
+/// `util::intern`).
+fn metadata(metadata: &Metadata) -> Result<GameData<'static>> {
+    let text = |string: *const c_char| -> Result<&'static str> {
+        if string.is_null() {
+            return Err(anyhow!("The plugin metadata is incomplete."));
+        }
+        // SAFETY: Plugins must provide NUL-terminated strings in metadata.
+        let string = unsafe { CStr::from_ptr(string) }
+            .to_str()?
+            .to_owned();
+        Ok(util::intern(string))
+    };
+
+    let variant_default = text(metadata.variant_default)?;
+    Ok(GameData {
+        variant: variant_default,
+
+        name: text(metadata.name)?,
+        authors: text(metadata.authors)?,
+        about: text(metadata.about)?,
+
+        variant_protocol: text(metadata.variant_protocol)?,
+        variant_pattern: text(metadata.variant_pattern)?,
+        variant_default,
+
+        state_default: text(metadata.state_default)?,
+        state_pattern: text(metadata.state_pattern)?,
+        state_protocol: text(metadata.state_protocol)?,
+
+        move_protocol: None,
+        move_pattern: None,
+    })
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::{testing, zero_by};
+
+    use std::path::PathBuf;
+    use std::process::Command;
+    use std::sync::OnceLock;
+
+    /// Source of a reference plugin implementing 2-player ten to zero by one
+    /// or two (the default variant of zero-by).
+    const STUB: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/game/plugin/stub.rs"
+    );
+
+    /// Compiles the reference plugin into a shared library with `cfg` set (if
+    /// provided), returning the path to the library.
+    fn build(cfg: Option<&str>) -> PathBuf {
+        let name = format!("stub-{}", cfg.unwrap_or("compatible"));
+        let directory = std::env::temp_dir()
+            .join(format!("nova-plugin-{}", std::process::id()));
+
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join(format!(
+            "{}{}{}",
+            std::env::consts::DLL_PREFIX,
+            name,
+            std::env::consts::DLL_SUFFIX
+        ));
+
+        let mut command = Command::new("rustc");
+        command
+            .args([
+                "--edition",
+                "2021",
+                "--crate-type",
+                "cdylib",
+                "-o",
+            ])
+            .arg(&path)
+            .arg(STUB);
+
+        if let Some(cfg) = cfg {
+            command.args(["--cfg", cfg]);
+        }
+
+        assert!(command.status().unwrap().success());
+        path
+    }
+
+    fn stub() -> Session {
+        static PATH: OnceLock<PathBuf> = OnceLock::new();
+        Session::load(PATH.get_or_init(|| build(None)), None).unwrap()
+    }
+
+    #[test]
+    fn stub_provides_game_data() {
+        let session = stub();
+        assert_eq!(session.info().name, "ten-to-zero");
+        assert_eq!(session.info().variant, "default");
+        assert_eq!(session.id(), "plugin.ten-to-zero.default");
+        assert_eq!(session.encode(session.start()), "10-0");
+    }
+
+    #[test]
+    fn stub_conforms_to_game_interfaces() {
+        let session = stub();
+        testing::verify_traversal(&session, 100).unwrap();
+        testing::verify_cycles::<2, _>(&session, 100).unwrap();
+    }
+
+    #[test]
+    fn stub_errors_are_reported() {
+        let session = stub();
+        assert!(session
+            .decode("11-0".to_owned())
+            .is_err());
+        assert!(session
+            .decode("nonsense".to_owned())
+            .is_err());
+        assert!(Session::initialize(None).is_err());
+        assert!(Session::load(Path::new("./does-not-exist.so"), None).is_err());
+    }
+
+    #[test]
+    fn stub_solution_agrees_with_zero_by() {
+        let zero_by = zero_by::Session::initialize(None).unwrap();
+        testing::verify_agreement::<2, _, _>(&stub(), &zero_by, 100).unwrap();
+    }
+
+    #[test]
+    fn interface_violations_are_reported() {
+        let session = Session::load(&build(Some("faulty")), None).unwrap();
+        let error = session
+            .solve(
+                IOMode::Write,
+                SolutionMode::Strong,
+                Policy::default(),
+                &Resources::default(),
+            )
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<GameError>(),
+            Some(GameError::PluginFailure { .. })
+        ));
+        assert!(error
+            .root_cause()
+            .to_string()
+            .contains("provided 1 utility values"));
+    }
+
+    #[test]
+    fn incompatible_plugins_are_rejected() {
+        let path = build(Some("incompatible"));
+        let error = Session::load(&path, None)
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            error.downcast_ref::<GameError>(),
+            Some(GameError::PluginIncompatible { .. })
+        ));
+    }
+}
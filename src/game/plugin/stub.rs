@@ -0,0 +1,259 @@
+//! # Reference Game Plugin
+//!
+//! Implements 2-player ten to zero by one or two as a game plugin, where states
+//! are encoded as `elements * 2 + turn`. This file is not part of the crate; it
+//! is compiled separately into a shared library by the tests of the plugin
+//! adapter. Compiling it with `--cfg incompatible` produces a plugin which
+//! reports an unsupported interface version, and compiling it with `--cfg
+//! faulty` produces one which provides malformed utility for end states.
+
+use std::cell::Cell;
+use std::ffi::{c_char, c_void, CStr};
+
+const START: u64 = 10;
+const CHOICES: [u64; 2] = [1, 2];
+
+#[cfg(not(incompatible))]
+const VERSION: u32 = 1;
+#[cfg(incompatible)]
+const VERSION: u32 = 0;
+
+#[repr(C)]
+pub struct Metadata {
+    name: *const c_char,
+    authors: *const c_char,
+    about: *const c_char,
+    variant_protocol: *const c_char,
+    variant_pattern: *const c_char,
+    variant_default: *const c_char,
+    state_protocol: *const c_char,
+    state_pattern: *const c_char,
+    state_default: *const c_char,
+}
+
+#[repr(C)]
+pub struct Interface {
+    metadata: Metadata,
+    error: unsafe extern "C" fn(*mut c_char, usize) -> usize,
+    initialize: unsafe extern "C" fn(*const c_char) -> *mut c_void,
+    finalize: unsafe extern "C" fn(*mut c_void),
+    players: unsafe extern "C" fn(*const c_void) -> usize,
+    acyclic: unsafe extern "C" fn(*const c_void) -> bool,
+    start: unsafe extern "C" fn(*const c_void) -> u64,
+    end: unsafe extern "C" fn(*const c_void, u64) -> bool,
+    prograde:
+        unsafe extern "C" fn(*const c_void, u64, *mut u64, usize) -> usize,
+    retrograde:
+        unsafe extern "C" fn(*const c_void, u64, *mut u64, usize) -> usize,
+    encode:
+        unsafe extern "C" fn(*const c_void, u64, *mut c_char, usize) -> usize,
+    decode:
+        unsafe extern "C" fn(*const c_void, *const c_char, *mut u64) -> bool,
+    utility: unsafe extern "C" fn(*const c_void, u64, *mut i64, usize) -> usize,
+    turn: unsafe extern "C" fn(*const c_void, u64) -> usize,
+}
+
+unsafe impl Sync for Interface {}
+
+static INTERFACE: Interface = Interface {
+    metadata: Metadata {
+        name: c"ten-to-zero".as_ptr(),
+        authors: c"GamesmanNova contributors".as_ptr(),
+        about: c"Ten to zero by one or two, as a game plugin.".as_ptr(),
+        variant_protocol: c"There is only one variant.".as_ptr(),
+        variant_pattern: c"^default$".as_ptr(),
+        variant_default: c"default".as_ptr(),
+        state_protocol: c"Dash-separated elements left and turn.".as_ptr(),
+        state_pattern: c"^\\d+-\\d+$".as_ptr(),
+        state_default: c"10-0".as_ptr(),
+    },
+    error,
+    initialize,
+    finalize,
+    players,
+    acyclic,
+    start,
+    end,
+    prograde,
+    retrograde,
+    encode,
+    decode,
+    utility,
+    turn,
+};
+
+thread_local! {
+    static ERROR: Cell<&'static str> = const { Cell::new("") };
+}
+
+#[no_mangle]
+pub extern "C" fn nova_plugin_version() -> u32 {
+    VERSION
+}
+
+#[no_mangle]
+pub extern "C" fn nova_plugin_interface() -> *const Interface {
+    &INTERFACE
+}
+
+fn pack(elements: u64, turn: u64) -> u64 {
+    elements * 2 + turn
+}
+
+fn unpack(state: u64) -> (u64, u64) {
+    (state / 2, state % 2)
+}
+
+unsafe fn write<T: Copy>(
+    values: &[T],
+    buffer: *mut T,
+    capacity: usize,
+) -> usize {
+    for (i, &v) in values
+        .iter()
+        .take(capacity)
+        .enumerate()
+    {
+        *buffer.add(i) = v;
+    }
+    values.len()
+}
+
+unsafe extern "C" fn error(buffer: *mut c_char, capacity: usize) -> usize {
+    let message = ERROR.get().as_bytes();
+    write(
+        &*(message as *const [u8] as *const [c_char]),
+        buffer,
+        capacity,
+    )
+}
+
+unsafe extern "C" fn initialize(variant: *const c_char) -> *mut c_void {
+    if variant.is_null() || CStr::from_ptr(variant).to_bytes() == b"default" {
+        Box::into_raw(Box::new(START)) as *mut c_void
+    } else {
+        ERROR.set("There is only one variant.");
+        std::ptr::null_mut()
+    }
+}
+
+unsafe extern "C" fn finalize(session: *mut c_void) {
+    drop(Box::from_raw(session as *mut u64));
+}
+
+unsafe extern "C" fn players(_: *const c_void) -> usize {
+    2
+}
+
+unsafe extern "C" fn acyclic(_: *const c_void) -> bool {
+    true
+}
+
+unsafe extern "C" fn start(session: *const c_void) -> u64 {
+    pack(*(session as *const u64), 0)
+}
+
+unsafe extern "C" fn end(_: *const c_void, state: u64) -> bool {
+    unpack(state).0 == 0
+}
+
+unsafe extern "C" fn prograde(
+    _: *const c_void,
+    state: u64,
+    buffer: *mut u64,
+    capacity: usize,
+) -> usize {
+    let (elements, turn) = unpack(state);
+    let mut next = Vec::new();
+    if elements > 0 {
+        for c in CHOICES {
+            next.push(pack(elements - c.min(elements), 1 - turn));
+        }
+    }
+    next.sort();
+    next.dedup();
+    write(&next, buffer, capacity)
+}
+
+unsafe extern "C" fn retrograde(
+    _: *const c_void,
+    state: u64,
+    buffer: *mut u64,
+    capacity: usize,
+) -> usize {
+    let (elements, turn) = unpack(state);
+    let mut prev = Vec::new();
+    for c in CHOICES {
+        if elements == 0 {
+            prev.extend(1..=c.min(START));
+        } else if elements + c <= START {
+            prev.push(elements + c);
+        }
+    }
+    let mut prev = prev
+        .into_iter()
+        .map(|p| pack(p, 1 - turn))
+        .collect::<Vec<u64>>();
+    prev.sort();
+    prev.dedup();
+    write(&prev, buffer, capacity)
+}
+
+unsafe extern "C" fn encode(
+    _: *const c_void,
+    state: u64,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> usize {
+    let (elements, turn) = unpack(state);
+    let string = format!("{}-{}", elements, turn);
+    let bytes = string.as_bytes();
+    write(
+        &*(bytes as *const [u8] as *const [c_char]),
+        buffer,
+        capacity,
+    )
+}
+
+unsafe extern "C" fn decode(
+    _: *const c_void,
+    string: *const c_char,
+    state: *mut u64,
+) -> bool {
+    let parsed = CStr::from_ptr(string)
+        .to_str()
+        .ok()
+        .and_then(|s| s.split_once('-'))
+        .and_then(|(e, t)| {
+            Some((e.parse::<u64>().ok()?, t.parse::<u64>().ok()?))
+        });
+
+    match parsed {
+        Some((elements, turn)) if elements <= START && turn < 2 => {
+            *state = pack(elements, turn);
+            true
+        },
+        _ => {
+            ERROR.set("The state is malformed or out of range.");
+            false
+        },
+    }
+}
+
+unsafe extern "C" fn utility(
+    _: *const c_void,
+    state: u64,
+    buffer: *mut i64,
+    capacity: usize,
+) -> usize {
+    let utility = if unpack(state).1 == 0 { [-1, 1] } else { [1, -1] };
+    if cfg!(faulty) {
+        write(&utility[..1], buffer, capacity)
+    } else {
+        write(&utility, buffer, capacity)
+    }
+}
+
+unsafe extern "C" fn turn(_: *const c_void, state: u64) -> usize {
+    unpack(state).1 as usize
+}
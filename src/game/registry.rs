@@ -0,0 +1,105 @@
+//! # Game Registry Module
+//!
+//! This module keeps track of the games available through all interfaces. Each
+//! game module registers itself at compile time through the `register!` macro,
+//! providing its static `GameData` and a way to initialize its sessions, so
+//! that adding a game does not require editing any central list of games.
+
+use anyhow::Result;
+
+use crate::game::{Game, GameData};
+
+/* DEFINITIONS */
+
+/// The entry of a game in the registry, submitted by its module through the
+/// `register!` macro.
+pub struct Registration {
+    /// The information about the game, where the variant is its default one.
+    pub data: GameData<'static>,
+    /// Initializes a session of the game with a variant (or the default one if
+    /// `None` is provided), which is of its misère version if the flag is set.
+    pub session: fn(Option<String>, bool) -> Result<Box<dyn Game>>,
+}
+
+inventory::collect!(Registration);
+
+/* API */
+
+/// Returns the registrations of all available games, sorted by name.
+pub fn games() -> Vec<&'static Registration> {
+    let mut games = inventory::iter::<Registration>
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    games.sort_by_key(|g| g.data.name);
+    games
+}
+
+/// Returns the registration of the game called `name`, if there is one.
+pub fn find(name: &str) -> Option<&'static Registration> {
+    inventory::iter::<Registration>
+        .into_iter()
+        .find(|g| g.data.name == name)
+}
+
+/// Returns the names of all available games, which are the valid targets of
+/// the commands in each interface, sorted alphabetically.
+pub fn names() -> Vec<&'static str> {
+    games()
+        .into_iter()
+        .map(|g| g.data.name)
+        .collect()
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn game_names_are_unique() {
+        let mut names = names();
+        let count = names.len();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn registered_data_matches_default_sessions() {
+        for game in games() {
+            // Games without a default variant cannot be initialized without
+            // being given one (e.g., external games).
+            if game
+                .data
+                .variant_default
+                .is_empty()
+            {
+                assert!((game.session)(None, false).is_err());
+                continue;
+            }
+
+            let session = (game.session)(None, false).unwrap();
+            let info = session.info();
+            assert_eq!(info.name, game.data.name);
+            assert_eq!(info.variant, game.data.variant_default);
+            assert_eq!(info.state_default, game.data.state_default);
+        }
+    }
+
+    #[test]
+    fn all_games_are_registered() {
+        for name in [
+            "dsl",
+            "external",
+            "goofspiel",
+            "octal",
+            "sliding",
+            "sum",
+            "zero-by",
+        ] {
+            assert!(find(name).is_some());
+        }
+    }
+}
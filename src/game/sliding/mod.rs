@@ -0,0 +1,408 @@
+//! # Sliding Tile Puzzle Module
+//!
+//! The sliding tile puzzle (of which the 8-puzzle and 15-puzzle are the most
+//! famous instances) is a single-player game played on a rectangular board of
+//! R rows and C columns, which holds R * C - 1 numbered tiles and a single
+//! blank space. On each move, the player slides a tile that is orthogonally
+//! adjacent to the blank space into it. The objective is to arrange the tiles
+//! in increasing order (in row-major order), leaving the blank space in the
+//! bottom-right corner of the board.
+//!
+//! Since every move can be undone by sliding the same tile back, the graph of
+//! states of this puzzle contains cycles. Its remoteness is the length of an
+//! optimal solution to the puzzle from each state.
+
+use anyhow::{Context, Result};
+
+use crate::game::error::GameError;
+use crate::game::sliding::states::*;
+use crate::game::sliding::variants::*;
+use crate::game::{util, Bounded, DTransition, Entry, Legible};
+use crate::game::{Game, GameData, Solvable};
+use crate::interface::{IOMode, SolutionMode};
+use crate::model::{State, Turn, Utility};
+use crate::register;
+use crate::solver::strong;
+use crate::solver::util::{Policy, Resources};
+
+/* SUBMODULES */
+
+mod states;
+mod variants;
+
+/* GAME DATA */
+
+const NAME: &str = "sliding";
+const AUTHORS: &str = "GamesmanNova contributors";
+const ABOUT: &str =
+"A single player slides numbered tiles on a rectangular board with a single \
+blank space, where on each move a tile adjacent to the blank space is slid \
+into it. The goal is to arrange the tiles in increasing order from left to \
+right and top to bottom, with the blank space in the bottom-right corner. The \
+game variant determines the number of rows and columns of the board. Only half \
+of all tile arrangements can be solved, which is determined by a parity \
+invariant of the arrangement.";
+
+/* REGISTRATION */
+
+const DATA: GameData = GameData {
+    variant: VARIANT_DEFAULT,
+
+    name: NAME,
+    authors: AUTHORS,
+    about: ABOUT,
+
+    variant_protocol: VARIANT_PROTOCOL,
+    variant_pattern: VARIANT_PATTERN,
+    variant_default: VARIANT_DEFAULT,
+
+    state_default: STATE_DEFAULT,
+    state_pattern: STATE_PATTERN,
+    state_protocol: STATE_PROTOCOL,
+
+    move_protocol: None,
+    move_pattern: None,
+};
+
+register! { DATA => session }
+
+/// Initializes a session of this game with `variant` for the game registry,
+/// which is of its misère version if `misere` is set.
+fn session(variant: Option<String>, misere: bool) -> Result<Box<dyn Game>> {
+    let session = Session::initialize(variant)
+        .context("Failed to initialize sliding puzzle session.")?;
+    if misere {
+        Err(GameError::MisereNotFound {
+            input_game_name: NAME,
+        })
+        .context("Misère version not found.")
+    } else {
+        Ok(Box::new(session))
+    }
+}
+
+/* GAME IMPLEMENTATION */
+
+/// Represents an instance of a sliding tile puzzle session, which is specific
+/// to a valid variant of the game. States are encoded as the lexicographic
+/// rank of the permutation of tiles on the board in row-major order (with the
+/// blank space encoded as tile `0`), which requires that the board have no
+/// more than 20 cells to fit into a `State`.
+pub struct Session {
+    variant: String,
+    rows: usize,
+    cols: usize,
+    start: State,
+    goal: State,
+}
+
+impl Game for Session {
+    fn initialize(variant: Option<String>) -> Result<Self> {
+        if let Some(v) = variant {
+            parse_variant(v).context("Malformed game variant.")
+        } else {
+            Ok(parse_variant(VARIANT_DEFAULT.to_owned()).unwrap())
+        }
+    }
+
+    fn id(&self) -> String {
+        format!("{}.{}", NAME, self.variant)
+    }
+
+    fn forward(&mut self, history: Vec<String>) -> Result<()> {
+        self.start = util::verify_history_dynamic(self, history)
+            .context("Malformed game state encoding.")?;
+        Ok(())
+    }
+
+    fn info(&self) -> GameData<'_> {
+        GameData {
+            variant: &self.variant,
+            ..DATA
+        }
+    }
+
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()> {
+        match method {
+            SolutionMode::Strong => {
+                strong::cyclic::puzzle_solver(self, mode, policy, resources)
+                    .context("Failed solver run.")?;
+            },
+            _ => {
+                return Err(GameError::SolverNotFound {
+                    input_game_name: NAME,
+                })
+                .context("Solver not found.");
+            },
+        }
+        Ok(())
+    }
+
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+        let db = strong::cyclic::puzzle_solver(
+            self,
+            mode,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .context("Failed solver run.")?;
+        util::query_entry(self, &db, state)
+    }
+}
+
+/* TRAVERSAL DECLARATIONS */
+
+impl Bounded<State> for Session {
+    fn start(&self) -> State {
+        self.start
+    }
+
+    fn end(&self, state: State) -> bool {
+        state == self.goal
+    }
+}
+
+impl DTransition<State> for Session {
+    fn prograde(&self, state: State) -> Vec<State> {
+        if self.end(state) {
+            Vec::new()
+        } else {
+            self.slides(state)
+        }
+    }
+
+    fn retrograde(&self, state: State) -> Vec<State> {
+        self.slides(state)
+            .into_iter()
+            .filter(|&parent| !self.end(parent))
+            .collect()
+    }
+}
+
+/* SUPPLEMENTAL DECLARATIONS */
+
+impl Legible<State> for Session {
+    fn decode(&self, string: String) -> Result<State> {
+        Ok(parse_state(self, string)?)
+    }
+
+    fn encode(&self, state: State) -> String {
+        unrank(state, self.rows * self.cols)
+            .iter()
+            .map(|tile| tile.to_string())
+            .collect::<Vec<String>>()
+            .join("-")
+    }
+}
+
+/* SOLVING DECLARATIONS */
+
+impl Solvable<1> for Session {
+    fn utility(&self, _: State) -> [Utility; 1] {
+        [1]
+    }
+
+    fn turn(&self, _: State) -> Turn {
+        0
+    }
+}
+
+/* HELPER METHODS */
+
+impl Session {
+    /// Returns the states that result from sliding each of the tiles adjacent
+    /// to the blank space into it. Since slides are their own inverse, these
+    /// are both the children and the possible parents of `state`, except that
+    /// the goal state has no children (and is therefore nobody's parent).
+    fn slides(&self, state: State) -> Vec<State> {
+        let tiles = unrank(state, self.rows * self.cols);
+        let blank = tiles
+            .iter()
+            .position(|&t| t == 0)
+            .expect("Sliding puzzle state has no blank space.");
+
+        let (row, col) = (blank / self.cols, blank % self.cols);
+        let mut adjacent = Vec::with_capacity(4);
+        if row > 0 {
+            adjacent.push(blank - self.cols);
+        }
+        if row + 1 < self.rows {
+            adjacent.push(blank + self.cols);
+        }
+        if col > 0 {
+            adjacent.push(blank - 1);
+        }
+        if col + 1 < self.cols {
+            adjacent.push(blank + 1);
+        }
+
+        adjacent
+            .into_iter()
+            .map(|tile| {
+                let mut next = tiles.clone();
+                next.swap(blank, tile);
+                rank(&next)
+            })
+            .collect()
+    }
+}
+
+/* PERMUTATION RANKING */
+
+/// Returns the solved arrangement of tiles for a board of `rows` by `cols`,
+/// which has tiles in increasing order and the blank space at the end.
+fn goal(rows: usize, cols: usize) -> Vec<u8> {
+    let cells = (rows * cols) as u8;
+    let mut tiles: Vec<u8> = (1..cells).collect();
+    tiles.push(0);
+    tiles
+}
+
+/// Returns the quantity which determines whether an arrangement of `tiles` on
+/// a board with `cols` columns is solvable. Sliding a tile horizontally does
+/// not change the number of inversions between tiles, and sliding one tile
+/// vertically changes it by `cols - 1` while moving the blank space by one row.
+/// Hence, the value returned here is invariant under all moves, and two tile
+/// arrangements are reachable from each other if and only if it is equal.
+fn parity(tiles: &[u8], cols: usize) -> usize {
+    let blank = tiles
+        .iter()
+        .position(|&t| t == 0)
+        .unwrap_or(0);
+
+    let mut inversions = 0;
+    for (i, &a) in tiles.iter().enumerate() {
+        inversions += tiles[i + 1..]
+            .iter()
+            .filter(|&&b| a != 0 && b != 0 && b < a)
+            .count();
+    }
+
+    if cols % 2 == 1 {
+        inversions % 2
+    } else {
+        (inversions + blank / cols) % 2
+    }
+}
+
+/// Returns the lexicographic rank of the permutation of `tiles` among all of
+/// the permutations of its elements, assuming they are `0..tiles.len()`.
+fn rank(tiles: &[u8]) -> State {
+    let mut result = 0;
+    for (i, &a) in tiles.iter().enumerate() {
+        let smaller = tiles[i + 1..]
+            .iter()
+            .filter(|&&b| b < a)
+            .count() as State;
+        result = result * (tiles.len() - i) as State + smaller;
+    }
+    result
+}
+
+/// Returns the permutation of `0..cells` whose lexicographic rank among all of
+/// the permutations of those elements is `state`. This is the inverse of the
+/// `rank` function.
+fn unrank(mut state: State, cells: usize) -> Vec<u8> {
+    let mut available: Vec<u8> = (0..cells as u8).collect();
+    let mut factorial: State = (1..cells as State).product();
+    let mut tiles = Vec::with_capacity(cells);
+    for i in 0..cells {
+        let index = (state / factorial) as usize;
+        state %= factorial;
+        tiles.push(available.remove(index));
+        if i + 1 < cells {
+            factorial /= (cells - i - 1) as State;
+        }
+    }
+    tiles
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::testing;
+
+    #[test]
+    fn unrank_is_inverse_of_rank() {
+        let cells = 9;
+        for state in (0..362880).step_by(997) {
+            assert_eq!(state, rank(&unrank(state, cells)));
+        }
+        assert_eq!(rank(&unrank(0, cells)), 0);
+        assert_eq!(unrank(0, 4), vec![0, 1, 2, 3]);
+        assert_eq!(unrank(23, 4), vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn sliding_conforms_to_game_interfaces() {
+        for variant in ["2x2", "3x3", "2x4"] {
+            let session =
+                Session::initialize(Some(variant.to_owned())).unwrap();
+            testing::verify(&session, 1_000).unwrap();
+        }
+    }
+
+    #[test]
+    fn slides_preserve_parity() {
+        let session = Session::initialize(Some("3x4".to_owned())).unwrap();
+        let start = unrank(session.start(), 12);
+        for child in session.prograde(session.start()) {
+            assert_eq!(parity(&start, 4), parity(&unrank(child, 12), 4));
+        }
+    }
+
+    #[test]
+    fn goal_is_not_a_parent() {
+        let session = Session::initialize(Some("2x2".to_owned())).unwrap();
+        let goal = session.goal;
+        for child in session.slides(goal) {
+            assert!(!session
+                .retrograde(child)
+                .contains(&goal));
+            for parent in session.retrograde(child) {
+                assert!(session
+                    .prograde(parent)
+                    .contains(&child));
+            }
+        }
+    }
+
+    #[test]
+    fn remoteness_is_optimal_solution_length() {
+        let session = Session::initialize(Some("2x2".to_owned())).unwrap();
+        let query = |s: &str| {
+            session
+                .query(IOMode::Write, Some(s.to_owned()))
+                .unwrap()
+                .remoteness
+        };
+
+        assert_eq!(query("1-2-3-0"), 0);
+        assert_eq!(query("1-2-0-3"), 1);
+        assert_eq!(query("1-0-3-2"), 1);
+        assert_eq!(query("0-1-3-2"), 2);
+        assert_eq!(query("2-3-1-0"), 4);
+        assert_eq!(query("0-3-2-1"), 6);
+    }
+
+    #[test]
+    fn default_start_has_known_optimal_solution() {
+        let session = Session::initialize(None).unwrap();
+        let entry = session
+            .query(IOMode::Write, None)
+            .unwrap();
+
+        assert_eq!(entry.state, STATE_DEFAULT);
+        assert_eq!(entry.utility, vec![1]);
+        assert_eq!(entry.remoteness, 30);
+    }
+}
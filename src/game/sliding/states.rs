@@ -0,0 +1,241 @@
+//! # Sliding Tile Puzzle State Handling Module
+//!
+//! This module helps parse the string encoding of a sliding tile puzzle state
+//! into a more efficient binary representation, performing a series of checks
+//! which ensure compatibility with a game variant.
+
+use regex::Regex;
+
+use crate::game::error::GameError;
+use crate::game::sliding::{goal, parity, rank, Session, NAME};
+use crate::model::State;
+
+/* SLIDING PUZZLE STATE ENCODING */
+
+pub const STATE_DEFAULT: &str = "8-7-6-5-4-3-2-1-0";
+pub const STATE_PATTERN: &str = r"^\d+(?:-\d+)+$";
+pub const STATE_PROTOCOL: &str =
+    "The state string should be a dash-separated list of R * C non-negative \
+integers, where R and C are the number of rows and columns specified by the \
+game variant. The integers indicate the tiles on the board in row-major order \
+(left to right, then top to bottom), with 0 standing for the blank space and \
+the rest for numbered tiles. Each integer from 0 to R * C - 1 must appear \
+exactly once. Additionally, the arrangement must be solvable; exactly half of \
+all arrangements are, which is determined by the parity of the number of \
+inversions between tiles (plus the row of the blank space, if C is even).";
+
+/* API */
+
+/// Returns a sliding puzzle state encoding using the parameters specified by
+/// a pre-verified game variant combined with the tile arrangement provided in
+/// `from`. Returns a `GameError::StateMalformed` if the arrangement cannot be
+/// reached from (or brought to) the solved arrangement of the puzzle.
+pub fn parse_state(
+    session: &Session,
+    from: String,
+) -> Result<State, GameError> {
+    check_state_pattern(&from)?;
+    let tiles = parse_tiles(&from)?;
+    check_tiles_are_permutation(&tiles, session)?;
+    check_tiles_are_solvable(&tiles, session)?;
+    Ok(rank(&tiles))
+}
+
+/* STATE STRING VERIFICATION */
+
+fn check_state_pattern(from: &str) -> Result<(), GameError> {
+    let re = Regex::new(STATE_PATTERN).unwrap();
+    if !re.is_match(from) {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String does not match the pattern '{}'.",
+                STATE_PATTERN
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_tiles(from: &str) -> Result<Vec<u8>, GameError> {
+    from.split('-')
+        .map(|int_string| {
+            int_string
+                .parse::<u8>()
+                .map_err(|e| GameError::StateMalformed {
+                    game_name: NAME,
+                    hint: e.to_string(),
+                })
+        })
+        .collect()
+}
+
+fn check_tiles_are_permutation(
+    tiles: &[u8],
+    session: &Session,
+) -> Result<(), GameError> {
+    let cells = session.rows * session.cols;
+    let mut sorted = tiles.to_vec();
+    sorted.sort();
+    if tiles.len() != cells {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String contains {} integers, but this ({}x{}) variant has \
+                exactly {} cells.",
+                tiles.len(),
+                session.rows,
+                session.cols,
+                cells,
+            ),
+        })
+    } else if sorted
+        .iter()
+        .enumerate()
+        .any(|(i, &t)| t as usize != i)
+    {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "Each integer from 0 to {} must appear exactly once.",
+                cells - 1,
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn check_tiles_are_solvable(
+    tiles: &[u8],
+    session: &Session,
+) -> Result<(), GameError> {
+    let solved = goal(session.rows, session.cols);
+    if parity(tiles, session.cols) != parity(&solved, session.cols) {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: "The tile arrangement has the wrong parity, so it cannot be \
+                brought to the solved arrangement through any sequence of \
+                moves."
+                .to_owned(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::{util::verify_history_dynamic, Game};
+
+    #[test]
+    fn state_pattern_is_valid_regex() {
+        assert!(Regex::new(STATE_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_state_matches_state_pattern() {
+        let re = Regex::new(STATE_PATTERN).unwrap();
+        assert!(re.is_match(STATE_DEFAULT));
+    }
+
+    #[test]
+    fn no_state_equals_default_state() {
+        let with_none = Session::initialize(None).unwrap();
+        let with_default = Session::initialize(None).unwrap();
+
+        assert_eq!(
+            with_none.start,
+            parse_state(&with_default, STATE_DEFAULT.to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn malformed_states_fail_checks() {
+        let s1 = "1-2-3-4-5-6-7-8".to_owned(); // Too few tiles
+        let s2 = "1-2-3-4-5-6-7-8-0-9".to_owned(); // Too many tiles
+        let s3 = "1-1-3-4-5-6-7-8-0".to_owned(); // Repeated tile
+        let s4 = "1-2-3-4-5-6-7-9-0".to_owned(); // Out of range tile
+        let s5 = "-1-2-3-4-5-6-7-8-0".to_owned(); // Malformed
+        let s6 = "".to_owned(); // Empty string
+
+        fn f() -> Session {
+            Session::initialize(None).unwrap()
+        }
+
+        assert!(parse_state(&f(), s1).is_err());
+        assert!(parse_state(&f(), s2).is_err());
+        assert!(parse_state(&f(), s3).is_err());
+        assert!(parse_state(&f(), s4).is_err());
+        assert!(parse_state(&f(), s5).is_err());
+        assert!(parse_state(&f(), s6).is_err());
+    }
+
+    #[test]
+    fn unsolvable_states_fail_checks() {
+        let s1 = "2-1-3-4-5-6-7-8-0".to_owned(); // One swap from solved
+        let s2 = "1-2-3-4-5-6-8-7-0".to_owned(); // One swap from solved
+        let s3 = "8-7-6-5-4-3-1-2-0".to_owned(); // One swap from start
+
+        fn f() -> Session {
+            Session::initialize(None).unwrap()
+        }
+
+        assert!(matches!(
+            parse_state(&f(), s1),
+            Err(GameError::StateMalformed { .. })
+        ));
+        assert!(matches!(
+            parse_state(&f(), s2),
+            Err(GameError::StateMalformed { .. })
+        ));
+        assert!(matches!(
+            parse_state(&f(), s3),
+            Err(GameError::StateMalformed { .. })
+        ));
+    }
+
+    #[test]
+    fn solvable_states_pass_checks() {
+        let s1 = "1-2-3-4-5-6-7-8-0".to_owned(); // Solved
+        let s2 = "1-2-3-4-5-6-7-0-8".to_owned(); // One move from solved
+        let s3 = "0-1-2-3-4-5-6-7-8".to_owned();
+        let s4 = "8-6-7-2-5-4-3-0-1".to_owned();
+
+        fn f() -> Session {
+            Session::initialize(None).unwrap()
+        }
+
+        assert!(parse_state(&f(), s1).is_ok());
+        assert!(parse_state(&f(), s2).is_ok());
+        assert!(parse_state(&f(), s3).is_ok());
+        assert!(parse_state(&f(), s4).is_ok());
+    }
+
+    #[test]
+    fn verify_sliding_history() {
+        let session = Session::initialize(Some("2x3".to_owned())).unwrap();
+        let h1 = vec!["5-4-3-2-1-0", "5-4-0-2-1-3", "5-0-4-2-1-3"];
+        let h2 = vec!["5-4-3-2-1-0", "5-4-3-2-0-1", "5-4-3-0-2-1"];
+        let i1 = vec!["5-4-3-2-1-0", "5-0-3-2-1-4"]; // Not adjacent
+        let i2 = vec!["4-5-3-2-1-0"]; // Not starting state
+
+        assert!(verify_history_dynamic(&session, owned(h1)).is_ok());
+        assert!(verify_history_dynamic(&session, owned(h2)).is_ok());
+        assert!(verify_history_dynamic(&session, owned(i1)).is_err());
+        assert!(verify_history_dynamic(&session, owned(i2)).is_err());
+    }
+
+    /* UTILITIES */
+
+    fn owned(v: Vec<&str>) -> Vec<String> {
+        v.iter()
+            .map(|&s| s.to_owned())
+            .collect()
+    }
+}
@@ -0,0 +1,195 @@
+//! # Sliding Tile Puzzle Variant Handling Module
+//!
+//! This module helps parse the variant string provided to the sliding tile
+//! puzzle into parameters that can help build a game session.
+
+use regex::Regex;
+
+use crate::game::error::GameError;
+use crate::game::sliding::{goal, parity, rank, Session, NAME};
+
+/* SLIDING PUZZLE VARIANT ENCODING */
+
+pub const VARIANT_DEFAULT: &str = "3x3";
+pub const VARIANT_PATTERN: &str = r"^[1-9]\d*x[1-9]\d*$";
+pub const VARIANT_PROTOCOL: &str =
+    "The variant string should be of the form RxC, where R and C are positive \
+integers indicating the number of rows and columns of the puzzle board, \
+respectively. For example, '3x3' corresponds to the 8-puzzle, and '4x4' to the \
+15-puzzle. Both R and C must be greater than 1, and the board can have at most \
+20 cells (R * C <= 20) so that its states can be encoded in 64 bits. The \
+starting arrangement of a variant has its tiles in decreasing order, which is \
+adjusted by swapping the first two tiles when needed for it to be solvable.";
+
+/// The maximum number of cells a board can have while being able to encode
+/// all permutations of its tiles in a `State` (since 20! < 2^64 < 21!).
+const MAX_CELLS: usize = 20;
+
+/* API */
+
+/// Returns a sliding puzzle session set up using the parameters specified by
+/// `variant`. Returns a `GameError::VariantMalformed` if the variant string
+/// does not conform to the variant protocol.
+pub fn parse_variant(variant: String) -> Result<Session, GameError> {
+    check_variant_pattern(&variant)?;
+    let params = parse_parameters(&variant)?;
+    check_board_dimensions(&params)?;
+    let (rows, cols) = (params[0], params[1]);
+    Ok(Session {
+        variant,
+        rows,
+        cols,
+        start: rank(&scramble(rows, cols)),
+        goal: rank(&goal(rows, cols)),
+    })
+}
+
+/* VARIANT STRING VERIFICATION */
+
+fn check_variant_pattern(variant: &str) -> Result<(), GameError> {
+    let re = Regex::new(VARIANT_PATTERN).unwrap();
+    if !re.is_match(variant) {
+        Err(GameError::VariantMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String does not match the pattern '{}'.",
+                VARIANT_PATTERN
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_parameters(variant: &str) -> Result<Vec<usize>, GameError> {
+    variant
+        .split('x')
+        .map(|int_string| {
+            int_string
+                .parse::<usize>()
+                .map_err(|e| GameError::VariantMalformed {
+                    game_name: NAME,
+                    hint: e.to_string(),
+                })
+        })
+        .collect()
+}
+
+fn check_board_dimensions(params: &[usize]) -> Result<(), GameError> {
+    if params.iter().any(|&x| x <= 1) {
+        Err(GameError::VariantMalformed {
+            game_name: NAME,
+            hint: "R and C must both be strictly greater than 1.".to_owned(),
+        })
+    } else if params[0].saturating_mul(params[1]) > MAX_CELLS {
+        Err(GameError::VariantMalformed {
+            game_name: NAME,
+            hint: format!(
+                "The board can have at most {} cells, but {} were specified.",
+                MAX_CELLS,
+                params[0].saturating_mul(params[1]),
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/* STARTING STATE */
+
+/// Returns the starting arrangement of tiles of a board of `rows` by `cols`,
+/// which has all tiles in decreasing order followed by the blank space. If
+/// this arrangement is not solvable, its first two tiles are swapped (which
+/// flips its parity, making it solvable).
+fn scramble(rows: usize, cols: usize) -> Vec<u8> {
+    let cells = (rows * cols) as u8;
+    let mut tiles: Vec<u8> = (1..cells).rev().collect();
+    tiles.push(0);
+    if parity(&tiles, cols) != parity(&goal(rows, cols), cols) {
+        tiles.swap(0, 1);
+    }
+    tiles
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::Game;
+
+    #[test]
+    fn variant_pattern_is_valid_regex() {
+        assert!(Regex::new(VARIANT_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_variant_matches_variant_pattern() {
+        let re = Regex::new(VARIANT_PATTERN).unwrap();
+        assert!(re.is_match(VARIANT_DEFAULT));
+    }
+
+    #[test]
+    fn no_variant_equals_default_variant() {
+        let with_none = Session::initialize(None).unwrap();
+        let with_default =
+            Session::initialize(Some(VARIANT_DEFAULT.to_owned())).unwrap();
+        assert_eq!(with_none.variant, with_default.variant);
+        assert_eq!(with_none.start, with_default.start);
+        assert_eq!(with_none.goal, with_default.goal);
+    }
+
+    #[test]
+    fn invalid_variants_fail_checks() {
+        let v1 = "1x5";
+        let v2 = "3x1";
+        let v3 = "5x5";
+        let v4 = "3-3";
+        let v5 = "0x4";
+        let v6 = "three-by-three";
+        let v7 = "3x3x3";
+
+        fn wrapper(v: &'static str) -> Result<Session, GameError> {
+            parse_variant(v.to_owned())
+        }
+
+        assert!(wrapper(v1).is_err());
+        assert!(wrapper(v2).is_err());
+        assert!(wrapper(v3).is_err());
+        assert!(wrapper(v4).is_err());
+        assert!(wrapper(v5).is_err());
+        assert!(wrapper(v6).is_err());
+        assert!(wrapper(v7).is_err());
+    }
+
+    #[test]
+    fn valid_variants_pass_checks() {
+        let v1 = "2x2";
+        let v2 = "3x3";
+        let v3 = "4x4";
+        let v4 = "4x5";
+        let v5 = "2x10";
+
+        fn wrapper(v: &'static str) -> Result<Session, GameError> {
+            parse_variant(v.to_owned())
+        }
+
+        assert!(wrapper(v1).is_ok());
+        assert!(wrapper(v2).is_ok());
+        assert!(wrapper(v3).is_ok());
+        assert!(wrapper(v4).is_ok());
+        assert!(wrapper(v5).is_ok());
+    }
+
+    #[test]
+    fn starting_arrangements_are_solvable() {
+        for (rows, cols) in [(2, 2), (2, 3), (3, 3), (3, 4), (4, 4), (4, 5)] {
+            let start = scramble(rows, cols);
+            assert_eq!(
+                parity(&start, cols),
+                parity(&goal(rows, cols), cols)
+            );
+        }
+    }
+}
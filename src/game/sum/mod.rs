@@ -0,0 +1,598 @@
+//! # Disjunctive Sum Module
+//!
+//! The disjunctive sum of two or more impartial games is the game where both
+//! players share all of the components' positions, and where each move is made
+//! in exactly one of the components (of the player's choosing). The sum ends
+//! when no moves are possible in any of its components, and under normal play,
+//! the player who is unable to move on their turn loses.
+//!
+//! By the Sprague-Grundy theorem, the nim-value of a sum is the XOR of the
+//! nim-values of its components. This module provides a combinator which plays
+//! the sum of any impartial games, which can be solved either by exploring its
+//! states directly or by using this fact.
+
+use anyhow::{Context, Result};
+
+use std::path::Path;
+
+use crate::game::error::GameError;
+use crate::game::misere::Misere;
+use crate::game::sum::states::*;
+use crate::game::sum::variants::*;
+use crate::game::{util, Acyclic, Analysis, Bounded, DTransition, Entry, Game};
+use crate::game::{GameData, Impartial, Legible, Limited, Solvable, Symmetric};
+use crate::interface::{Analyzer, IOMode, SolutionMode};
+use crate::model::{MinimumExcludedValue, State, Turn, Utility};
+use crate::solver::strong;
+use crate::solver::util::{Policy, Resources};
+use crate::solver::verification::{Audit, Report};
+use crate::{implement, register};
+
+/* SUBMODULES */
+
+mod states;
+mod variants;
+
+/* GAME DATA */
+
+const NAME: &str = "sum";
+const AUTHORS: &str = "GamesmanNova contributors";
+const ABOUT: &str =
+"Two players take turns making a move in exactly one of several impartial \
+games, which are all played at the same time. The game variant determines which \
+games are being played, and the variants they are played in. The player who is \
+unable to make a move in any of the games on their turn loses.";
+
+/* REGISTRATION */
+
+const DATA: GameData = GameData {
+    variant: VARIANT_DEFAULT,
+
+    name: NAME,
+    authors: AUTHORS,
+    about: ABOUT,
+
+    variant_protocol: VARIANT_PROTOCOL,
+    variant_pattern: VARIANT_PATTERN,
+    variant_default: VARIANT_DEFAULT,
+
+    state_default: STATE_DEFAULT,
+    state_pattern: STATE_PATTERN,
+    state_protocol: STATE_PROTOCOL,
+
+    move_protocol: None,
+    move_pattern: None,
+};
+
+register! { DATA => session }
+
+/// Initializes a session of this game with `variant` for the game registry,
+/// which is of its misère version if `misere` is set.
+fn session(variant: Option<String>, misere: bool) -> Result<Box<dyn Game>> {
+    let session = Session::initialize(variant)
+        .context("Failed to initialize disjunctive sum session.")?;
+    if misere {
+        Ok(Box::new(Misere::<2, _>::new(session)))
+    } else {
+        Ok(Box::new(session))
+    }
+}
+
+/* GAME IMPLEMENTATION */
+
+/// Represents a session of the disjunctive sum of two or more impartial games.
+/// States are encoded by concatenating the bits of the normalized states of
+/// each component (see `Impartial::normalize`), which are then packed with the
+/// turn information of the sum. It is therefore only possible to add games
+/// whose normalized states fit in a `State` when put together. Components which
+/// are identical sessions are grouped into `classes`, as the states of the sum
+/// which only differ by a permutation of them are symmetric.
+pub struct Session {
+    variant: String,
+    start: State,
+    width: usize,
+    offsets: Vec<usize>,
+    classes: Vec<Vec<usize>>,
+    components: Vec<Box<dyn Impartial>>,
+}
+
+impl Session {
+    /// Returns the disjunctive sum of `components`. Fails if there are less
+    /// than two components, or if the states of the sum could not be encoded
+    /// in a single `State`.
+    pub fn new(components: Vec<Box<dyn Impartial>>) -> Result<Self, GameError> {
+        if components.len() < 2 {
+            return Err(GameError::VariantMalformed {
+                game_name: NAME,
+                hint: format!(
+                    "A sum must have at least 2 components, but {} were \
+                    provided.",
+                    components.len()
+                ),
+            });
+        }
+
+        let mut offsets = Vec::with_capacity(components.len());
+        let mut width = 0;
+        for component in &components {
+            offsets.push(width);
+            width += component.width();
+        }
+
+        if width >= State::BITS as usize {
+            return Err(GameError::VariantMalformed {
+                game_name: NAME,
+                hint: format!(
+                    "The states of the components require {} bits to encode, \
+                    but at most {} are available.",
+                    width,
+                    State::BITS - 1
+                ),
+            });
+        }
+
+        let variant = components
+            .iter()
+            .map(|c| c.id())
+            .collect::<Vec<String>>()
+            .join("+");
+
+        let mut classes: Vec<Vec<usize>> = Vec::new();
+        for (i, component) in components.iter().enumerate() {
+            let identical = |&j: &usize| {
+                let other = &components[j];
+                other.id() == component.id()
+                    && other.width() == component.width()
+                    && other.normalize(other.start())
+                        == component.normalize(component.start())
+            };
+
+            if let Some(class) = classes
+                .iter_mut()
+                .find(|class| identical(&class[0]))
+            {
+                class.push(i);
+            } else {
+                classes.push(vec![i]);
+            }
+        }
+
+        classes.retain(|class| class.len() > 1);
+        let mut session = Session {
+            variant,
+            start: 0,
+            width: width + 1,
+            offsets,
+            classes,
+            components,
+        };
+
+        let start = session
+            .components
+            .iter()
+            .map(|c| c.normalize(c.start()))
+            .collect::<Vec<State>>();
+
+        session.start = session.pack(&start, 0);
+        Ok(session)
+    }
+}
+
+impl Game for Session {
+    fn initialize(variant: Option<String>) -> Result<Self> {
+        if let Some(v) = variant {
+            parse_variant(v).context("Malformed game variant.")
+        } else {
+            Ok(parse_variant(VARIANT_DEFAULT.to_owned()).unwrap())
+        }
+    }
+
+    fn id(&self) -> String {
+        format!("{}.{}", NAME, self.variant)
+    }
+
+    fn forward(&mut self, history: Vec<String>) -> Result<()> {
+        self.start = util::verify_history_dynamic(self, history)
+            .context("Malformed game state encoding.")?;
+        Ok(())
+    }
+
+    fn info(&self) -> GameData<'_> {
+        GameData {
+            variant: &self.variant,
+            ..DATA
+        }
+    }
+
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()> {
+        match method {
+            SolutionMode::Strong => {
+                strong::acyclic::symmetric_dynamic_solver::<2, Self>(
+                    self, mode, policy, resources,
+                )
+                .context("Failed solver run.")?;
+            },
+            _ => {
+                return Err(GameError::SolverNotFound {
+                    input_game_name: NAME,
+                })
+                .context("Solver not found.");
+            },
+        }
+        Ok(())
+    }
+
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+        let db = strong::acyclic::symmetric_dynamic_solver::<2, Self>(
+            self,
+            mode,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .context("Failed solver run.")?;
+        let nim_value = match &state {
+            Some(string) => self.nim_value(self.decode(string.clone())?),
+            None => self.nim_value(self.start),
+        };
+
+        let mut entry = util::query_canonical_entry(self, &db, state)?;
+        entry.nim_value = Some(nim_value);
+        Ok(entry)
+    }
+
+    fn verify(&self, directory: &Path, audit: Audit) -> Result<Report> {
+        strong::acyclic::symmetric_dynamic_verifier::<2, Self>(
+            self, directory, audit,
+        )
+        .context("Failed verifier run.")
+    }
+
+    fn analyze(&self, analyzer: Analyzer) -> Result<Analysis> {
+        match analyzer {
+            Analyzer::Symmetry => Ok(util::analyze_symmetries(self)),
+        }
+    }
+}
+
+/* TRAVERSAL DECLARATIONS */
+
+impl Bounded<State> for Session {
+    fn start(&self) -> State {
+        self.start
+    }
+
+    fn end(&self, state: State) -> bool {
+        let (states, _) = self.unpack(state);
+        self.components
+            .iter()
+            .zip(states)
+            .all(|(c, s)| c.end(s))
+    }
+}
+
+impl DTransition<State> for Session {
+    fn prograde(&self, state: State) -> Vec<State> {
+        let (states, turn) = self.unpack(state);
+        let mut next = Vec::new();
+        for (i, component) in self.components.iter().enumerate() {
+            if component.end(states[i]) {
+                continue;
+            }
+            for child in component.prograde(states[i]) {
+                let mut states = states.clone();
+                states[i] = component.normalize(child);
+                next.push(self.pack(&states, (turn + 1) % 2));
+            }
+        }
+        next.sort();
+        next.dedup();
+        next
+    }
+
+    fn retrograde(&self, state: State) -> Vec<State> {
+        let (states, turn) = self.unpack(state);
+        let mut next = Vec::new();
+        for (i, component) in self.components.iter().enumerate() {
+            for parent in component.retrograde(states[i]) {
+                let mut states = states.clone();
+                states[i] = component.normalize(parent);
+                next.push(self.pack(&states, (turn + 1) % 2));
+            }
+        }
+        next.sort();
+        next.dedup();
+        next
+    }
+}
+
+/* SUPPLEMENTAL DECLARATIONS */
+
+impl Legible<State> for Session {
+    fn decode(&self, string: String) -> Result<State> {
+        Ok(parse_state(self, string)?)
+    }
+
+    fn encode(&self, state: State) -> String {
+        let (states, turn) = self.unpack(state);
+        let components = self
+            .components
+            .iter()
+            .zip(states)
+            .map(|(c, s)| c.encode(s))
+            .collect::<Vec<String>>();
+
+        format!("{}/{}", components.join("+"), turn)
+    }
+}
+
+/* SOLVING DECLARATIONS */
+
+implement! { for Session =>
+    Acyclic<2>,
+    Limited
+}
+
+impl Solvable<2> for Session {
+    fn utility(&self, state: State) -> [Utility; 2] {
+        let (_, turn) = util::unpack_turn(state, 2);
+        let mut payoffs = [1; 2];
+        payoffs[turn] = -1;
+        payoffs
+    }
+
+    fn turn(&self, state: State) -> Turn {
+        util::unpack_turn(state, 2).1
+    }
+}
+
+impl Symmetric<State> for Session {
+    fn canonical(&self, state: State) -> State {
+        let (mut states, turn) = self.unpack(state);
+        for class in &self.classes {
+            let mut sorted = class
+                .iter()
+                .map(|&i| states[i])
+                .collect::<Vec<State>>();
+
+            sorted.sort();
+            for (&i, s) in class.iter().zip(sorted) {
+                states[i] = s;
+            }
+        }
+        self.pack(&states, turn)
+    }
+}
+
+/* IMPARTIAL GAME THEORY */
+
+impl Impartial for Session {
+    fn nim_value(&self, state: State) -> MinimumExcludedValue {
+        let (states, _) = self.unpack(state);
+        self.components
+            .iter()
+            .zip(states)
+            .fold(0, |acc, (c, s)| acc ^ c.nim_value(s))
+    }
+
+    fn normalize(&self, state: State) -> State {
+        let (states, _) = self.unpack(state);
+        self.pack(&states, 0)
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+}
+
+/* HELPER METHODS */
+
+impl Session {
+    /// Returns the normalized states of each component of the sum in `state`,
+    /// along with the player whose turn it is at `state`.
+    fn unpack(&self, state: State) -> (Vec<State>, Turn) {
+        let (state, turn) = util::unpack_turn(state, 2);
+        let states = self
+            .components
+            .iter()
+            .zip(&self.offsets)
+            .map(|(c, &offset)| (state >> offset) & ((1 << c.width()) - 1))
+            .collect();
+
+        (states, turn)
+    }
+
+    /// Returns the encoding of the sum whose components are at the normalized
+    /// states in `states`, with information that it is `turn`'s turn to move.
+    /// This is the inverse of `unpack`.
+    fn pack(&self, states: &[State], turn: Turn) -> State {
+        let state = states
+            .iter()
+            .zip(&self.offsets)
+            .fold(0, |acc, (&s, &offset)| acc | (s << offset));
+
+        util::pack_turn(state, turn, 2)
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::testing;
+    use crate::model::StateCount;
+
+    use std::collections::HashSet;
+
+    #[test]
+    fn nim_values_agree_with_strong_solution() {
+        let variants = [
+            "zero-by.2-7-1-3+octal.0.77-4",
+            "octal.0.07-5+octal.0.77-3-2+zero-by.2-4-2",
+            "zero-by.2-6-1-2-4+zero-by.2-5-2-3",
+        ];
+
+        for variant in variants {
+            let session =
+                Session::initialize(Some(variant.to_owned())).unwrap();
+            let db = strong::acyclic::dynamic_solver::<2, Session>(
+                &session,
+                IOMode::Write,
+                Policy::default(),
+                &Resources::default(),
+            )
+            .unwrap();
+
+            let mut seen = HashSet::new();
+            let mut stack = vec![session.start()];
+            while let Some(state) = stack.pop() {
+                if !seen.insert(state) {
+                    continue;
+                }
+
+                let entry = util::query_entry(
+                    &session,
+                    &db,
+                    Some(session.encode(state)),
+                )
+                .unwrap();
+
+                let winning = entry.utility[session.turn(state)] > 0;
+                assert_eq!(winning, session.nim_value(state) != 0);
+                if !session.end(state) {
+                    stack.extend(session.prograde(state));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn queries_report_nim_values_of_sums() {
+        let session = Session::initialize(None).unwrap();
+        for state in [
+            session.start(),
+            session.prograde(session.start())[0],
+        ] {
+            let entry = session
+                .query(IOMode::Write, Some(session.encode(state)))
+                .unwrap();
+
+            let nim_value = entry.nim_value.unwrap();
+            assert_eq!(nim_value, session.nim_value(state));
+            assert_eq!(
+                entry.utility[session.turn(state)] > 0,
+                nim_value != 0
+            );
+        }
+    }
+
+    #[test]
+    fn sum_conforms_to_game_interfaces() {
+        for variant in [VARIANT_DEFAULT, "octal.0.07-5+zero-by.3-6-1-4"] {
+            let session =
+                Session::initialize(Some(variant.to_owned())).unwrap();
+            testing::verify_acyclic::<2, _>(&session, 1_000).unwrap();
+        }
+    }
+
+    #[test]
+    fn symmetric_solution_agrees_with_full_solution() {
+        let variant = "zero-by.2-6-1-2+octal.0.77-3+zero-by.2-6-1-2";
+        let session = Session::initialize(Some(variant.to_owned())).unwrap();
+        let full = strong::acyclic::dynamic_solver::<2, Session>(
+            &session,
+            IOMode::Write,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .unwrap();
+
+        let reduced = strong::acyclic::symmetric_dynamic_solver::<2, Session>(
+            &session,
+            IOMode::Write,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .unwrap();
+
+        let mut seen = HashSet::new();
+        let mut stack = vec![session.start()];
+        while let Some(state) = stack.pop() {
+            if !seen.insert(state) {
+                continue;
+            }
+
+            let encoding = Some(session.encode(state));
+            let expected =
+                util::query_entry(&session, &full, encoding.clone()).unwrap();
+            let actual =
+                util::query_canonical_entry(&session, &reduced, encoding)
+                    .unwrap();
+
+            assert_eq!(expected.utility, actual.utility);
+            assert_eq!(expected.remoteness, actual.remoteness);
+            stack.extend(session.prograde(state));
+        }
+
+        let canonical = seen
+            .iter()
+            .map(|&s| session.canonical(s))
+            .collect::<HashSet<State>>();
+
+        assert!(canonical.len() < seen.len());
+        assert!(canonical
+            .iter()
+            .all(|&s| session.canonical(s) == s));
+
+        let analysis = session
+            .analyze(Analyzer::Symmetry)
+            .unwrap();
+        assert_eq!(analysis.states, seen.len() as StateCount);
+        assert_eq!(analysis.stored, canonical.len() as StateCount);
+    }
+
+    #[test]
+    fn moves_are_made_in_exactly_one_component() {
+        let session = Session::initialize(None).unwrap();
+        let start = session.unpack(session.start()).0;
+        for child in session.prograde(session.start()) {
+            let (states, turn) = session.unpack(child);
+            let changed = states
+                .iter()
+                .zip(&start)
+                .filter(|(a, b)| a != b)
+                .count();
+
+            assert_eq!(changed, 1);
+            assert_eq!(turn, 1);
+        }
+    }
+
+    #[test]
+    fn sums_of_sums_are_sums() {
+        let inner = Session::initialize(Some(
+            "zero-by.2-5-1-2+octal.0.77-3".to_owned(),
+        ))
+        .unwrap();
+
+        let outer = Session::new(vec![
+            Box::new(inner),
+            Box::new(crate::game::octal::Session::initialize(None).unwrap()),
+        ])
+        .unwrap();
+
+        let entry = outer
+            .query(IOMode::Write, None)
+            .unwrap();
+
+        let winning = outer.nim_value(outer.start()) != 0;
+        assert_eq!(entry.utility[0] > 0, winning);
+    }
+}
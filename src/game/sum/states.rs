@@ -0,0 +1,170 @@
+//! # Disjunctive Sum State Handling Module
+//!
+//! This module helps parse the string encoding of a disjunctive sum state into
+//! a more efficient binary representation, delegating the parsing of the state
+//! of each component to the component itself.
+
+use regex::Regex;
+
+use crate::game::error::GameError;
+use crate::game::sum::{Session, NAME};
+use crate::model::{State, Turn};
+
+/* DISJUNCTIVE SUM STATE ENCODING */
+
+pub const STATE_DEFAULT: &str = "10-0+5,7-0/0";
+pub const STATE_PATTERN: &str = r"^[^+/]+(?:\+[^+/]+)+/\d+$";
+pub const STATE_PROTOCOL: &str =
+"The state string should be the plus-separated states of each component of the \
+sum (in the same order as in the game variant), each of which conforms to the \
+state protocol of its own game, followed by a slash and the turn of the player \
+who is next to move in the sum (either 0 or 1). The turn information contained \
+within the states of each component is ignored, as it is not relevant to their \
+positions. For example, '10-0+5,7-0/1' is the start of the default variant, \
+but where it is player 1's turn.";
+
+/* API */
+
+/// Returns a disjunctive sum state encoding using the components of `session`
+/// to parse each of the component states provided in `from`. This does not
+/// verify that the provided `from` is reachable in `session`'s game variant.
+pub fn parse_state(
+    session: &Session,
+    from: String,
+) -> Result<State, GameError> {
+    check_state_pattern(&from)?;
+    let (states, turn) = from.rsplit_once('/').unwrap();
+    let turn = parse_turn(turn)?;
+    let states = states
+        .split('+')
+        .collect::<Vec<&str>>();
+    check_component_count(&states, session)?;
+
+    let states = session
+        .components
+        .iter()
+        .zip(states)
+        .map(|(c, s)| {
+            c.decode(s.to_owned())
+                .map(|state| c.normalize(state))
+                .map_err(|e| GameError::StateMalformed {
+                    game_name: NAME,
+                    hint: format!(
+                        "The component state '{}' is malformed: {}",
+                        s,
+                        e.root_cause()
+                    ),
+                })
+        })
+        .collect::<Result<Vec<State>, GameError>>()?;
+
+    Ok(session.pack(&states, turn))
+}
+
+/* STATE STRING VERIFICATION */
+
+fn check_state_pattern(from: &str) -> Result<(), GameError> {
+    let re = Regex::new(STATE_PATTERN).unwrap();
+    if !re.is_match(from) {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String does not match the pattern '{}'.",
+                STATE_PATTERN
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_turn(turn: &str) -> Result<Turn, GameError> {
+    let turn = turn
+        .parse::<Turn>()
+        .map_err(|e| GameError::StateMalformed {
+            game_name: NAME,
+            hint: e.to_string(),
+        })?;
+
+    if turn >= 2 {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "Specified a turn ({}) too high for this (2-player) game.",
+                turn,
+            ),
+        })
+    } else {
+        Ok(turn)
+    }
+}
+
+fn check_component_count(
+    states: &[&str],
+    session: &Session,
+) -> Result<(), GameError> {
+    if states.len() != session.components.len() {
+        Err(GameError::StateMalformed {
+            game_name: NAME,
+            hint: format!(
+                "Specified {} component states, but this variant has {} \
+                components.",
+                states.len(),
+                session.components.len(),
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::{Bounded, Game, Legible};
+
+    #[test]
+    fn state_pattern_is_valid_regex() {
+        assert!(Regex::new(STATE_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_state_matches_state_pattern() {
+        let re = Regex::new(STATE_PATTERN).unwrap();
+        assert!(re.is_match(STATE_DEFAULT));
+    }
+
+    #[test]
+    fn default_state_is_start() {
+        let session = Session::initialize(None).unwrap();
+        let state = session
+            .decode(STATE_DEFAULT.to_owned())
+            .unwrap();
+
+        assert_eq!(state, session.start());
+        assert_eq!(session.encode(state), STATE_DEFAULT);
+    }
+
+    #[test]
+    fn component_turns_are_ignored() {
+        let session = Session::initialize(None).unwrap();
+        let a = session.decode("4-1+3,2-0/1".to_owned());
+        let b = session.decode("4-0+2,3-1/1".to_owned());
+        assert_eq!(a.unwrap(), b.unwrap());
+    }
+
+    #[test]
+    fn invalid_states_fail_checks() {
+        let session = Session::initialize(None).unwrap();
+        let s = |v: &str| parse_state(&session, v.to_owned());
+        assert!(s("10-0/0").is_err());
+        assert!(s("10-0+5,7-0").is_err());
+        assert!(s("10-0+5,7-0/2").is_err());
+        assert!(s("11-0+5,7-0/0").is_err());
+        assert!(s("10-0+5,8-0/0").is_err());
+        assert!(s("10-0+5,7-0+1-0/0").is_err());
+    }
+}
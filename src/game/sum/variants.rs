@@ -0,0 +1,131 @@
+//! # Disjunctive Sum Variant Handling Module
+//!
+//! This module helps parse the variant string provided to the disjunctive sum
+//! combinator into the sessions of the games which are its components.
+
+use regex::Regex;
+
+use crate::game::error::GameError;
+use crate::game::sum::{Session, NAME};
+use crate::game::{octal, zero_by, Game, Impartial};
+
+/* DISJUNCTIVE SUM VARIANT ENCODING */
+
+pub const VARIANT_DEFAULT: &str = "zero-by.2-10-1-2+octal.0.77-5-7";
+pub const VARIANT_PATTERN: &str = r"^[a-z-]+\.[^+]+(?:\+[a-z-]+\.[^+]+)+$";
+pub const VARIANT_PROTOCOL: &str =
+"The variant string should be two or more plus-separated game IDs, each of \
+which is the name of an impartial game followed by a dot and a variant of that \
+game. The games that can be added are zero-by (in any variant) and octal. For \
+example, 'zero-by.2-10-1-2+octal.0.77-5-7' is the sum of the default variants \
+of both games. Note that the states of all components must fit in 64 bits.";
+
+/* API */
+
+/// Returns a disjunctive sum session whose components are initialized from the
+/// game IDs in `variant`. Returns a `GameError::VariantMalformed` if the string
+/// does not conform to the variant protocol, if any of the component variants
+/// are malformed, or if the states of the sum could not be encoded in 64 bits.
+pub fn parse_variant(variant: String) -> Result<Session, GameError> {
+    check_variant_pattern(&variant)?;
+    let components = variant
+        .split('+')
+        .map(parse_component)
+        .collect::<Result<Vec<Box<dyn Impartial>>, GameError>>()?;
+
+    Session::new(components)
+}
+
+/* VARIANT STRING VERIFICATION */
+
+fn check_variant_pattern(variant: &str) -> Result<(), GameError> {
+    let re = Regex::new(VARIANT_PATTERN).unwrap();
+    if !re.is_match(variant) {
+        Err(GameError::VariantMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String does not match the pattern '{}'.",
+                VARIANT_PATTERN
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_component(id: &str) -> Result<Box<dyn Impartial>, GameError> {
+    let (name, variant) = id.split_once('.').unwrap();
+    let variant = Some(variant.to_owned());
+    let session: anyhow::Result<Box<dyn Impartial>> = match name {
+        "zero-by" => zero_by::Session::initialize(variant)
+            .map(|s| Box::new(s) as Box<dyn Impartial>),
+        "octal" => octal::Session::initialize(variant)
+            .map(|s| Box::new(s) as Box<dyn Impartial>),
+        _ => {
+            return Err(GameError::VariantMalformed {
+                game_name: NAME,
+                hint: format!(
+                    "The game '{}' is not an impartial game that can be added.",
+                    name
+                ),
+            })
+        },
+    };
+
+    session.map_err(|e| GameError::VariantMalformed {
+        game_name: NAME,
+        hint: format!(
+            "The component '{}' could not be initialized: {}",
+            id,
+            e.root_cause()
+        ),
+    })
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn variant_pattern_is_valid_regex() {
+        assert!(Regex::new(VARIANT_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn default_variant_matches_variant_pattern() {
+        let re = Regex::new(VARIANT_PATTERN).unwrap();
+        assert!(re.is_match(VARIANT_DEFAULT));
+    }
+
+    #[test]
+    fn variant_is_concatenation_of_component_ids() {
+        let session = parse_variant(VARIANT_DEFAULT.to_owned()).unwrap();
+        assert_eq!(
+            session.id(),
+            format!("{}.{}", NAME, VARIANT_DEFAULT)
+        );
+    }
+
+    #[test]
+    fn invalid_variants_fail_checks() {
+        let v = |s: &str| parse_variant(s.to_owned());
+        assert!(v("").is_err());
+        assert!(v("zero-by.2-10-1-2").is_err());
+        assert!(v("zero-by.2-10-1-2+").is_err());
+        assert!(v("zero-by.2-10-1-2+sliding.3x3").is_err());
+        assert!(v("zero-by.2-10-1-2+octal.0.77").is_err());
+        assert!(v("zero-by.2-10-1-2+zero-by.1").is_err());
+        assert!(v("octal.0.77-20-20+octal.0.77-20-20").is_err());
+    }
+
+    #[test]
+    fn valid_variants_pass_checks() {
+        let v = |s: &str| parse_variant(s.to_owned());
+        assert!(v("zero-by.2-10-1-2+zero-by.2-3-1").is_ok());
+        assert!(v("octal.0.07-5+zero-by.3-8-1-2").is_ok());
+        assert!(v("octal.0.77-1+octal.0.77-2+octal.0.137-4-4").is_ok());
+    }
+}
@@ -0,0 +1,525 @@
+//! # Game Conformance Testing Module
+//!
+//! This module provides a harness which verifies that a game implementation
+//! conforms to the expectations that the rest of the project has of it, which
+//! are otherwise only stated in the documentation of the game interfaces. It is
+//! meant to be called from the tests of each game implementation.
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::database::{KVStore, Tabular};
+use crate::game::util::query_entry;
+use crate::game::{Acyclic, Bounded, DTransition, Legible, Moves, Ranked};
+use crate::game::{Limited, Solvable};
+use crate::interface::IOMode;
+use crate::model::{PlayerCount, State};
+use crate::solver::record::mur::{Layout, RecordBuffer};
+use crate::solver::strong;
+use crate::solver::util::{Policy, Resources};
+
+/* CONFORMANCE CHECKS */
+
+/// Verifies that `game` conforms to the game interfaces by exploring at most
+/// `cap` of its states reachable from its starting state (in breadth-first
+/// order), checking the following properties:
+///
+/// - The default variant matches the variant pattern.
+/// - The default state matches the state pattern, and decodes to the starting
+///   state of a session initialized with the default variant.
+/// - Decoding the encoding of each explored state yields the same state, and
+///   each encoding matches the state pattern.
+/// - End states have no children.
+/// - Each explored state is contained by the retrograde of each of its children.
+/// - Each explored state is a child of each state in its retrograde.
+///
+/// Returns an error describing the first counterexample found, where all states
+/// involved are expressed through their string encodings.
+pub fn verify<G>(game: &G, cap: usize) -> Result<()>
+where
+    G: Bounded<State> + DTransition<State> + Legible<State>,
+{
+    verify_defaults::<G>()?;
+    verify_traversal(game, cap)
+}
+
+/// Verifies the same properties as `verify` except for the ones about default
+/// variants and states, which is useful for games that cannot be initialized
+/// without specifying a variant.
+pub fn verify_traversal<G>(game: &G, cap: usize) -> Result<()>
+where
+    G: Bounded<State> + DTransition<State> + Legible<State>,
+{
+    let states = explore(game, cap);
+    let pattern = Regex::new(game.info().state_pattern)?;
+    for &state in &states {
+        let encoding = game.encode(state);
+        if !pattern.is_match(&encoding) {
+            return Err(anyhow!(
+                "The encoding of the state '{}' does not match the state \
+                pattern '{}'.",
+                encoding,
+                game.info().state_pattern,
+            ));
+        }
+
+        let decoded = game.decode(encoding.clone())?;
+        if decoded != state {
+            return Err(anyhow!(
+                "Decoding the encoding '{}' yields the state '{}' instead.",
+                encoding,
+                game.encode(decoded),
+            ));
+        }
+
+        for parent in game.retrograde(state) {
+            if !game
+                .prograde(parent)
+                .contains(&state)
+            {
+                return Err(anyhow!(
+                    "The state '{}' has the parent '{}' in its retrograde, but \
+                    is not a child of that parent.",
+                    encoding,
+                    game.encode(parent),
+                ));
+            }
+        }
+
+        if game.end(state) {
+            if let Some(&child) = game.prograde(state).first() {
+                return Err(anyhow!(
+                    "The end state '{}' has the child '{}'.",
+                    encoding,
+                    game.encode(child),
+                ));
+            }
+            continue;
+        }
+
+        for child in game.prograde(state) {
+            if !game
+                .retrograde(child)
+                .contains(&state)
+            {
+                return Err(anyhow!(
+                    "The state '{}' has the child '{}', but is not contained \
+                    in the retrograde of its child.",
+                    encoding,
+                    game.encode(child),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that `game` conforms to the game interfaces in the same way as
+/// `verify`, and additionally that there are no cycles among the explored
+/// states, as is expected of all games marked as `Acyclic`.
+pub fn verify_acyclic<const N: PlayerCount, G>(
+    game: &G,
+    cap: usize,
+) -> Result<()>
+where
+    G: Acyclic<N> + Bounded<State> + DTransition<State> + Legible<State>,
+{
+    verify(game, cap)?;
+    verify_cycles(game, cap)
+}
+
+/// Verifies that there are no cycles among at most `cap` of the states of
+/// `game` reachable from its starting state, without any of the other checks
+/// made by `verify_acyclic`.
+pub fn verify_cycles<const N: PlayerCount, G>(
+    game: &G,
+    cap: usize,
+) -> Result<()>
+where
+    G: Acyclic<N> + Bounded<State> + DTransition<State> + Legible<State>,
+{
+    let states = explore(game, cap)
+        .into_iter()
+        .collect::<HashSet<State>>();
+
+    // A state is finished once all of its explored descendants are finished,
+    // so reaching a state which is in progress means we followed a cycle.
+    let mut finished = HashSet::new();
+    let mut progress = HashSet::new();
+    let mut stack = vec![game.start()];
+    while let Some(&curr) = stack.last() {
+        if finished.contains(&curr) {
+            stack.pop();
+        } else if progress.contains(&curr) {
+            finished.insert(curr);
+            progress.remove(&curr);
+            stack.pop();
+        } else {
+            progress.insert(curr);
+            if game.end(curr) {
+                continue;
+            }
+            for child in game.prograde(curr) {
+                if !states.contains(&child) || finished.contains(&child) {
+                    continue;
+                }
+                if progress.contains(&child) {
+                    return Err(anyhow!(
+                        "There is a cycle through the transition from '{}' \
+                        to '{}'.",
+                        game.encode(curr),
+                        game.encode(child),
+                    ));
+                }
+                stack.push(child);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that the ranking provided by `game` is a bijection between at most
+/// `cap` of its reachable states (explored in the same way as in `verify`) and
+/// a subset of `0..count()`, by checking that each state's rank is in range,
+/// that no two states share a rank, and that unranking the rank yields the
+/// same state.
+pub fn verify_ranking<G>(game: &G, cap: usize) -> Result<()>
+where
+    G: Bounded<State> + DTransition<State> + Legible<State> + Ranked<State>,
+{
+    let count = game.count();
+    let mut ranked = HashMap::new();
+    for state in explore(game, cap) {
+        let rank = game.rank(state);
+        if rank >= count {
+            return Err(anyhow!(
+                "The state '{}' has rank {}, but there are only {} ranks.",
+                game.encode(state),
+                rank,
+                count,
+            ));
+        }
+
+        if let Some(other) = ranked.insert(rank, state) {
+            return Err(anyhow!(
+                "The states '{}' and '{}' share the rank {}.",
+                game.encode(other),
+                game.encode(state),
+                rank,
+            ));
+        }
+
+        let unranked = game.unrank(rank);
+        if unranked != state {
+            return Err(anyhow!(
+                "The state '{}' has rank {}, which unranks to the state '{}'.",
+                game.encode(state),
+                rank,
+                game.encode(unranked),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that the moves provided by `game` agree with its transitions over
+/// at most `cap` of its reachable states (explored in the same way as in
+/// `verify`), by checking that applying each move from a state yields exactly
+/// the children of that state, and that the encoding of each move matches the
+/// move pattern and decodes back into the same move.
+pub fn verify_moves<G>(game: &G, cap: usize) -> Result<()>
+where
+    G: Bounded<State> + DTransition<State> + Legible<State> + Moves<State>,
+{
+    let pattern = match game.info().move_pattern {
+        Some(pattern) => Regex::new(pattern)?,
+        None => {
+            return Err(anyhow!(
+                "The game does not provide a move pattern."
+            ))
+        },
+    };
+
+    for state in explore(game, cap) {
+        let mut children = game.prograde(state);
+        let mut applied = Vec::new();
+        for choice in game.moves(state) {
+            let encoding = game.encode_move(choice);
+            if !pattern.is_match(&encoding) {
+                return Err(anyhow!(
+                    "The encoding of the move '{}' does not match the move \
+                    pattern '{}'.",
+                    encoding,
+                    pattern,
+                ));
+            }
+
+            let decoded = game.decode_move(encoding.clone())?;
+            if decoded != choice {
+                return Err(anyhow!(
+                    "Decoding the move encoding '{}' yields the move '{}' \
+                    instead.",
+                    encoding,
+                    game.encode_move(decoded),
+                ));
+            }
+            applied.push(game.apply(state, choice));
+        }
+
+        children.sort();
+        applied.sort();
+        if children != applied {
+            return Err(anyhow!(
+                "Applying the moves from the state '{}' does not yield exactly \
+                the children of that state.",
+                game.encode(state),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that the strong solution of `game` agrees with that of `reference`
+/// over at most `cap` of the reachable states of `game` (explored in the same
+/// way as in `verify`), by checking that the state with the same encoding in
+/// `reference` has the same utility and remoteness. This is useful for games
+/// which adapt other implementations of a game that is already available.
+pub fn verify_agreement<const N: PlayerCount, G, R>(
+    game: &G,
+    reference: &R,
+    cap: usize,
+) -> Result<()>
+where
+    G: Acyclic<N> + Bounded<State> + DTransition<State> + Legible<State>,
+    G: Solvable<N> + Limited,
+    R: Acyclic<N> + Bounded<State> + DTransition<State> + Legible<State>,
+    R: Solvable<N> + Limited,
+{
+    let (mode, policy) = (IOMode::Write, Policy::default());
+    let resources = Resources::default();
+    let db = strong::acyclic::dynamic_solver(game, mode, policy, &resources)?;
+    let expected =
+        strong::acyclic::dynamic_solver(reference, mode, policy, &resources)?;
+
+    for state in explore(game, cap) {
+        let encoding = game.encode(state);
+        let entry = query_entry(game, &db, Some(encoding.clone()))?;
+        let other = query_entry(reference, &expected, Some(encoding.clone()))?;
+        if entry.utility != other.utility
+            || entry.remoteness != other.remoteness
+        {
+            return Err(anyhow!(
+                "The state '{}' has utility {:?} and remoteness {}, but it has \
+                utility {:?} and remoteness {} in the reference game.",
+                encoding,
+                entry.utility,
+                entry.remoteness,
+                other.utility,
+                other.remoteness,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that the solution sets in `db` and `expected` share the same
+/// record layout and hold the same records for at most `cap` of the reachable
+/// states of `game` (explored in the same way as in `verify`). This is useful
+/// for comparing solvers which are meant to compute identical records.
+pub fn verify_same_records<G, D, E>(
+    game: &G,
+    db: &D,
+    expected: &E,
+    cap: usize,
+) -> Result<()>
+where
+    G: Bounded<State> + DTransition<State> + Legible<State>,
+    D: KVStore<RecordBuffer> + Tabular,
+    E: KVStore<RecordBuffer> + Tabular,
+{
+    let layout = Layout::from_schema(
+        db.schema()
+            .context("Solution set table has no schema.")?,
+    )?;
+    let other = Layout::from_schema(
+        expected
+            .schema()
+            .context("Expected solution set table has no schema.")?,
+    )?;
+    if layout != other {
+        return Err(anyhow!(
+            "The solution sets have different record layouts."
+        ));
+    }
+
+    for state in explore(game, cap) {
+        if KVStore::<RecordBuffer>::get(db, state)
+            != KVStore::<RecordBuffer>::get(expected, state)
+        {
+            return Err(anyhow!(
+                "The state '{}' has a different record than expected.",
+                game.encode(state),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/* HELPER FUNCTIONS */
+
+/// Verifies the default variant and default state of the game `G` against the
+/// patterns it provides, by initializing a session with the default variant.
+fn verify_defaults<G>() -> Result<()>
+where
+    G: Bounded<State> + Legible<State>,
+{
+    let game = G::initialize(None)?;
+    let info = game.info();
+    if !Regex::new(info.variant_pattern)?.is_match(info.variant_default) {
+        return Err(anyhow!(
+            "The default variant '{}' does not match the variant pattern '{}'.",
+            info.variant_default,
+            info.variant_pattern,
+        ));
+    }
+
+    if !Regex::new(info.state_pattern)?.is_match(info.state_default) {
+        return Err(anyhow!(
+            "The default state '{}' does not match the state pattern '{}'.",
+            info.state_default,
+            info.state_pattern,
+        ));
+    }
+
+    let state = game.decode(info.state_default.to_owned())?;
+    if state != game.start() {
+        return Err(anyhow!(
+            "The default state '{}' is not the starting state '{}' of the \
+            default variant.",
+            info.state_default,
+            game.encode(game.start()),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns at most `cap` states reachable from the starting state of `game`,
+/// in the order they are discovered by a breadth-first traversal.
+fn explore<G>(game: &G, cap: usize) -> Vec<State>
+where
+    G: Bounded<State> + DTransition<State>,
+{
+    let mut seen = HashSet::new();
+    let mut order = Vec::new();
+    let mut queue = VecDeque::new();
+    seen.insert(game.start());
+    queue.push_back(game.start());
+    while let Some(curr) = queue.pop_front() {
+        if order.len() == cap {
+            break;
+        }
+        order.push(curr);
+        if game.end(curr) {
+            continue;
+        }
+        for child in game.prograde(curr) {
+            if seen.insert(child) {
+                queue.push_back(child);
+            }
+        }
+    }
+    order
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::zero_by::Session;
+    use crate::game::{Entry, Game, GameData};
+    use crate::interface::SolutionMode;
+
+    /// A version of a zero-by game whose retrograde wrongly claims that each
+    /// state is also its own parent.
+    struct Looped(Session);
+
+    impl Bounded<State> for Looped {
+        fn start(&self) -> State {
+            self.0.start()
+        }
+
+        fn end(&self, state: State) -> bool {
+            self.0.end(state)
+        }
+    }
+
+    impl DTransition<State> for Looped {
+        fn prograde(&self, state: State) -> Vec<State> {
+            self.0.prograde(state)
+        }
+
+        fn retrograde(&self, state: State) -> Vec<State> {
+            let mut parents = self.0.retrograde(state);
+            parents.push(state);
+            parents
+        }
+    }
+
+    impl Legible<State> for Looped {
+        fn decode(&self, string: String) -> Result<State> {
+            self.0.decode(string)
+        }
+
+        fn encode(&self, state: State) -> String {
+            self.0.encode(state)
+        }
+    }
+
+    impl Game for Looped {
+        fn initialize(variant: Option<String>) -> Result<Self> {
+            Ok(Self(Session::initialize(variant)?))
+        }
+
+        fn id(&self) -> String {
+            self.0.id()
+        }
+
+        fn forward(&mut self, history: Vec<String>) -> Result<()> {
+            self.0.forward(history)
+        }
+
+        fn info(&self) -> GameData<'_> {
+            self.0.info()
+        }
+
+        fn solve(
+            &self,
+            mode: IOMode,
+            method: SolutionMode,
+            policy: Policy,
+            resources: &Resources,
+        ) -> Result<()> {
+            self.0
+                .solve(mode, method, policy, resources)
+        }
+
+        fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+            self.0.query(mode, state)
+        }
+    }
+
+    #[test]
+    fn parents_which_do_not_lead_to_a_state_are_reported() {
+        let game = Looped::initialize(None).unwrap();
+        assert!(verify_traversal(&game.0, 1_000).is_ok());
+
+        let error = verify_traversal(&game, 1_000)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("is not a child of that parent"));
+    }
+}
@@ -8,14 +8,51 @@
 //! - Max Fierro, 11/2/2023 (maxfierro@berkeley.edu)
 
 use anyhow::{Context, Result};
+use regex::Regex;
+
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
 
 use crate::{
+    database::{KVStore, Tabular},
     game::error::GameError,
-    game::{DTransition, Legible, STransition},
-    model::{PlayerCount, State, Turn},
+    game::{Analysis, Bounded, DTransition, Entry, Legible, Moves},
+    game::{STransition, Simultaneous, Symmetric},
+    interface::Analyzer,
+    model::{MinimumExcludedValue, Move, PlayerCount, State, StateCount, Turn},
+    solver::record::mur::{Layout, RecordBuffer},
+    solver::record::sur,
     solver::MAX_TRANSITIONS,
 };
 
+/* STATE GRAPH ANALYSIS */
+
+/// Returns the symmetry analysis of `game`, which counts the states reachable
+/// from its starting state along with the canonical states among them (as
+/// provided by `game`). Only the canonical states are stored by solvers which
+/// exploit the symmetries of `game`, so the difference between both counts is
+/// the number of states that such solvers save.
+pub fn analyze_symmetries<G>(game: &G) -> Analysis
+where
+    G: Bounded<State> + DTransition<State> + Symmetric<State>,
+{
+    let mut states = HashSet::new();
+    let mut canonical = HashSet::new();
+    let mut stack = vec![game.start()];
+    while let Some(state) = stack.pop() {
+        if states.insert(state) {
+            canonical.insert(game.canonical(state));
+            stack.extend(game.prograde(state));
+        }
+    }
+
+    Analysis {
+        analyzer: Analyzer::Symmetry,
+        states: states.len() as StateCount,
+        stored: canonical.len() as StateCount,
+    }
+}
+
 /* TURN ENCODING */
 
 /// Minimally encodes turn information into the 64-bit integer `state` by
@@ -55,6 +92,57 @@ pub fn unpack_turn(
     }
 }
 
+/* IMPARTIAL GAME THEORY */
+
+/// Returns the minimum excluded value of `values`, which is the smallest
+/// non-negative integer not contained in `values`. This is the nim-value of an
+/// impartial game position whose options have the nim-values in `values`.
+pub fn mex(values: &[MinimumExcludedValue]) -> MinimumExcludedValue {
+    let mut present = vec![false; values.len() + 1];
+    for &v in values {
+        if (v as usize) < present.len() {
+            present[v as usize] = true;
+        }
+    }
+    present
+        .iter()
+        .position(|&p| !p)
+        .unwrap() as MinimumExcludedValue
+}
+
+/* RUNTIME GAME DATA */
+
+/// Returns a reference to a copy of `string` which lives for the rest of the
+/// program, so that games described at runtime can provide `GameData` like all
+/// other games. Each distinct string is only copied once, no matter how many
+/// sessions of the same game are initialized.
+pub fn intern(string: String) -> &'static str {
+    static STRINGS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut strings = STRINGS
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    if let Some(&interned) = strings.get(string.as_str()) {
+        interned
+    } else {
+        let interned: &'static str = Box::leak(string.into_boxed_str());
+        strings.insert(interned);
+        interned
+    }
+}
+
+/// Returns a 64-bit FNV-1a hash of `text`, which is stable across builds and
+/// platforms. This allows games described at runtime to tell apart sessions
+/// whose descriptions are too long (or unsafe) to be part of their IDs, which
+/// are used to name the files of their solution sets.
+pub fn fingerprint(text: &str) -> u64 {
+    text.bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
 /* STATE HISTORY VERIFICATION */
 
 /// Returns the latest state in a sequential `history` of state string encodings
@@ -90,6 +178,45 @@ where
     }
 }
 
+/// Returns `history` where each entry after the first one which matches the
+/// move pattern of `game` is replaced by the encoding of the state that results
+/// from making that move at the state before it. This allows histories to name
+/// the moves made instead of the states they lead to, which is only possible
+/// for games whose state and move encodings cannot be mistaken for each other.
+/// Returns an error if one of these moves cannot be made at its state.
+pub fn resolve_history_moves<G>(
+    game: &G,
+    history: Vec<String>,
+) -> Result<Vec<String>>
+where
+    G: Legible<State> + Moves<State>,
+{
+    let pattern = match game.info().move_pattern {
+        Some(pattern) => Regex::new(pattern)?,
+        None => return Ok(history),
+    };
+
+    let mut resolved: Vec<String> = Vec::with_capacity(history.len());
+    for entry in history {
+        let prev = match resolved.last() {
+            Some(prev) if pattern.is_match(&entry) => {
+                game.decode(prev.clone())?
+            },
+            _ => {
+                resolved.push(entry);
+                continue;
+            },
+        };
+
+        let choice = game.decode_move(entry)?;
+        if !game.moves(prev).contains(&choice) {
+            return move_history_error(game, prev, choice);
+        }
+        resolved.push(game.encode(game.apply(prev, choice)));
+    }
+    Ok(resolved)
+}
+
 /// Returns the latest state in a sequential `history` of state string encodings
 /// by verifying that the first state in the history is the same as the `game`'s
 /// start and that each state can be reached from its predecessor through the
@@ -120,6 +247,62 @@ where
     }
 }
 
+/// Returns the latest state in a sequential `history` of state string encodings
+/// in the same way as `verify_history_dynamic`, but for a simultaneous-move
+/// `game`, where each state must be reachable from its predecessor through
+/// some joint action of all players.
+pub fn verify_history_simultaneous<const N: PlayerCount, G>(
+    game: &G,
+    history: Vec<String>,
+) -> Result<State>
+where
+    G: Legible<State> + Simultaneous<N>,
+{
+    if let Some(s) = history.first() {
+        let mut prev = game.decode(s.clone())?;
+        if prev == game.start() {
+            for s in &history[1..] {
+                let next = game.decode(s.clone())?;
+                let transitions = joint_actions(&game.actions(prev))
+                    .into_iter()
+                    .map(|joint| game.transition(prev, joint))
+                    .collect::<Vec<State>>();
+                if game.end(prev) || !transitions.contains(&next) {
+                    return transition_history_error(game, prev, next);
+                }
+                prev = next;
+            }
+            Ok(prev)
+        } else {
+            start_history_error(game, game.start())
+        }
+    } else {
+        empty_history_error(game)
+    }
+}
+
+/// Returns all of the joint actions that can be formed by choosing one of the
+/// `actions` available to each player, where `actions[i]` are those of player
+/// `i`. There are none if any player has no actions.
+pub fn joint_actions<const N: PlayerCount>(
+    actions: &[Vec<Move>; N],
+) -> Vec<[Move; N]> {
+    let mut joint = vec![[0; N]];
+    for (player, choices) in actions.iter().enumerate() {
+        joint = joint
+            .into_iter()
+            .flat_map(|partial| {
+                choices.iter().map(move |&choice| {
+                    let mut next = partial;
+                    next[player] = choice;
+                    next
+                })
+            })
+            .collect();
+    }
+    joint
+}
+
 fn empty_history_error<G: Legible<State>>(game: &G) -> Result<State> {
     Err(GameError::InvalidHistory {
         game_name: game.info().name,
@@ -162,6 +345,158 @@ fn transition_history_error<G: Legible<State>>(
     .context("Invalid game history.")
 }
 
+fn move_history_error<G: Legible<State> + Moves<State>>(
+    game: &G,
+    prev: State,
+    choice: Move,
+) -> Result<Vec<String>> {
+    Err(GameError::InvalidHistory {
+        game_name: game.info().name,
+        hint: format!(
+            "Making the move '{}' at the state '{}' is illegal in the current \
+            game variant ({}).",
+            game.encode_move(choice),
+            game.encode(prev),
+            game.info().variant,
+        ),
+    })
+    .context("Invalid game history.")
+}
+
+/* SOLUTION SET QUERIES */
+
+/// Returns the solution set entry of the state encoded by `state` (or of the
+/// `game`'s starting state if `state` is `None`) stored within `db`, which is
+/// expected to have a selected table of `mur` records. Fails if `state` cannot
+/// be decoded, or if there is no record for it within `db` (which happens when
+/// the state is not reachable from the starting state of the game variant).
+pub fn query_entry<G, D>(
+    game: &G,
+    db: &D,
+    state: Option<String>,
+) -> Result<Entry>
+where
+    G: Legible<State>,
+    D: KVStore<RecordBuffer> + Tabular,
+{
+    let state =
+        if let Some(s) = state { game.decode(s)? } else { game.start() };
+
+    entry(game, db, state, state)
+}
+
+/// Returns the solution set entry of the state encoded by `state` in the same
+/// way as `query_entry`, but looking up the record of its canonical state (as
+/// provided by `game`) within `db`, which is expected to only contain records
+/// of canonical states.
+pub fn query_canonical_entry<G, D>(
+    game: &G,
+    db: &D,
+    state: Option<String>,
+) -> Result<Entry>
+where
+    G: Legible<State> + Symmetric<State>,
+    D: KVStore<RecordBuffer> + Tabular,
+{
+    let state =
+        if let Some(s) = state { game.decode(s)? } else { game.start() };
+
+    entry(game, db, state, game.canonical(state))
+}
+
+/// Returns the entry of `state` built from the record stored under `key` in
+/// `db`, failing if there is no such record. Records are parsed according to
+/// the layout described by the schema of the selected table of `db`.
+fn entry<G, D>(game: &G, db: &D, state: State, key: State) -> Result<Entry>
+where
+    G: Legible<State>,
+    D: KVStore<RecordBuffer> + Tabular,
+{
+    if let Some(bits) = db.get(key) {
+        let layout = db
+            .schema()
+            .context("Failed to find schema of solution set table.")
+            .and_then(Layout::from_schema)
+            .context("Failed to infer layout of solution set records.")?;
+        let record = RecordBuffer::parse(bits, layout)
+            .context("Failed to parse solution set record.")?;
+        let utility = (0..record.players())
+            .map(|p| record.get_utility(p))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to get utility from record.")?;
+        Ok(Entry {
+            state: game.encode(state),
+            utility,
+            remoteness: record.get_remoteness(),
+            adjusted: Vec::new(),
+            moves: Vec::new(),
+            supports: Vec::new(),
+            nim_value: None,
+        })
+    } else {
+        unreachable_state_error(game, state)
+    }
+}
+
+/// Returns the solution set entry of the state encoded by `state` in the same
+/// way as `query_entry`, but from a `db` whose selected table contains `sur`
+/// records, such that the entry also includes adjusted utility values.
+pub fn query_external_entry<G, D>(
+    game: &G,
+    db: &D,
+    state: Option<String>,
+) -> Result<Entry>
+where
+    G: Legible<State>,
+    D: KVStore<sur::RecordBuffer>,
+{
+    let state =
+        if let Some(s) = state { game.decode(s)? } else { game.start() };
+
+    if let Some(bits) = db.get(state) {
+        let record = sur::RecordBuffer::from(bits)
+            .context("Failed to parse solution set record.")?;
+        let utility = (0..record.players())
+            .map(|p| record.get_utility(p))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to get utility from record.")?;
+        let adjusted = (0..record.players())
+            .map(|p| record.get_adjusted(p))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to get adjusted utility from record.")?;
+        Ok(Entry {
+            state: game.encode(state),
+            utility,
+            remoteness: record.get_remoteness(),
+            adjusted,
+            moves: Vec::new(),
+            supports: Vec::new(),
+            nim_value: None,
+        })
+    } else {
+        unreachable_state_error(game, state)
+    }
+}
+
+/// Returns an error indicating that `state` has no record in a solution set of
+/// `game` because it is not reachable from the starting state of `game`.
+fn unreachable_state_error<G>(game: &G, state: State) -> Result<Entry>
+where
+    G: Legible<State>,
+{
+    Err(GameError::StateMalformed {
+        game_name: game.info().name,
+        hint: format!(
+            "The state '{}' is not reachable from the starting state of \
+            the current game variant ({}), which is '{}'.",
+            game.encode(state),
+            game.info().variant,
+            game.encode(game.start()),
+        ),
+    })
+    .context("State not found in solution set.")
+}
+
 /* TESTS */
 
 #[cfg(test)]
@@ -221,4 +556,16 @@ mod test {
             }
         }
     }
+
+    /* RUNTIME GAME DATA TESTS */
+
+    #[test]
+    fn equal_strings_are_interned_once() {
+        let a = intern("interned".to_owned());
+        let b = intern("interned".to_owned());
+        let c = intern("different".to_owned());
+        assert!(std::ptr::eq(a, b));
+        assert!(!std::ptr::eq(a, c));
+        assert_eq!(a, "interned");
+    }
 }
@@ -14,23 +14,33 @@
 //! - Max Fierro, 4/6/2023 (maxfierro@berkeley.edu)
 
 use anyhow::{Context, Result};
+use moves::*;
+use nalgebra::SMatrix;
 use states::*;
 
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::database::{sharded, volatile};
 use crate::game::error::GameError;
+use crate::game::misere::Misere;
 use crate::game::zero_by::variants::*;
-use crate::game::{util, Acyclic, Bounded, Legible};
-use crate::game::{DTransition, Game, GameData, Solvable};
-use crate::implement;
+use crate::game::{util, Acyclic, Bounded, Legible, Limited, Moves, Ranked};
+use crate::game::{DTransition, Entry, Game, GameData, Impartial, Solvable};
+use crate::game::{External, TerminalEnumerable};
 use crate::interface::{IOMode, SolutionMode};
-use crate::model::PlayerCount;
 use crate::model::Utility;
-use crate::model::{State, Turn};
+use crate::model::{MinimumExcludedValue, PlayerCount, StateCount};
+use crate::model::{Move, Remoteness, State, Turn};
 use crate::solver::strong;
-
-use super::util::unpack_turn;
+use crate::solver::util::{min_ubits, Policy, RankedDatabase, Resources};
+use crate::solver::verification::{Audit, Report};
+use crate::{implement, register, solvable};
 
 /* SUBMODULES */
 
+mod moves;
 mod states;
 mod variants;
 
@@ -46,6 +56,46 @@ amount of elements to remove during their turn. The player who is left with 0 \
 elements in their turn loses. A player cannot remove more elements than \
 currently available in the set.";
 
+/* REGISTRATION */
+
+const DATA: GameData = GameData {
+    variant: VARIANT_DEFAULT,
+
+    name: NAME,
+    authors: AUTHORS,
+    about: ABOUT,
+
+    variant_protocol: VARIANT_PROTOCOL,
+    variant_pattern: VARIANT_PATTERN,
+    variant_default: VARIANT_DEFAULT,
+
+    state_default: STATE_DEFAULT,
+    state_pattern: STATE_PATTERN,
+    state_protocol: STATE_PROTOCOL,
+
+    move_protocol: Some(MOVE_PROTOCOL),
+    move_pattern: Some(MOVE_PATTERN),
+};
+
+register! { DATA => session }
+
+/// Initializes a session of this game with `variant` for the game registry,
+/// which is of its misère version if `misere` is set.
+fn session(variant: Option<String>, misere: bool) -> Result<Box<dyn Game>> {
+    let session = Session::initialize(variant)
+        .context("Failed to initialize zero-by game session.")?;
+    if misere && session.externality.is_some() {
+        Err(GameError::MisereNotFound {
+            input_game_name: NAME,
+        })
+        .context("Misère version not found.")
+    } else if misere {
+        session.into_misere()
+    } else {
+        Ok(Box::new(session))
+    }
+}
+
 /* GAME IMPLEMENTATION */
 
 pub struct Session {
@@ -53,6 +103,8 @@ pub struct Session {
     players: PlayerCount,
     start: State,
     by: Vec<u64>,
+    externality: Option<Vec<Utility>>,
+    nimbers: OnceLock<Vec<MinimumExcludedValue>>,
 }
 
 impl Game for Session {
@@ -69,38 +121,43 @@ impl Game for Session {
     }
 
     fn forward(&mut self, history: Vec<String>) -> Result<()> {
+        let history = util::resolve_history_moves(self, history)
+            .context("Malformed game move encoding.")?;
         self.start = util::verify_history_dynamic(self, history)
             .context("Malformed game state encoding.")?;
         Ok(())
     }
 
-    fn info(&self) -> GameData {
+    fn variants(&self, max_states: StateCount) -> Vec<String> {
+        enumerate_variants(self, max_states)
+    }
+
+    fn info(&self) -> GameData<'_> {
         GameData {
             variant: &self.variant,
-
-            name: NAME,
-            authors: AUTHORS,
-            about: ABOUT,
-
-            variant_protocol: VARIANT_PROTOCOL,
-            variant_pattern: VARIANT_PATTERN,
-            variant_default: VARIANT_DEFAULT,
-
-            state_default: STATE_DEFAULT,
-            state_pattern: STATE_PATTERN,
-            state_protocol: STATE_PROTOCOL,
+            ..DATA
         }
     }
 
-    fn solve(&self, mode: IOMode, method: SolutionMode) -> Result<()> {
-        match (self.players, method) {
-            (2, SolutionMode::Strong) => {
-                strong::acyclic::dynamic_solver::<2, Self>(self, mode)
-                    .context("Failed solver run.")?
+    fn solve(
+        &self,
+        mode: IOMode,
+        method: SolutionMode,
+        policy: Policy,
+        resources: &Resources,
+    ) -> Result<()> {
+        match method {
+            SolutionMode::Strong if self.externality.is_some() => {
+                self.external_solver(mode, policy, resources)?;
             },
-            (10, SolutionMode::Strong) => {
-                strong::acyclic::dynamic_solver::<10, Self>(self, mode)
-                    .context("Failed solver run.")?
+            SolutionMode::Strong if resources.parallel() => {
+                self.parallel_solver(policy, resources)?;
+            },
+            SolutionMode::Strong => {
+                self.strong_solver(mode, policy, resources)?;
+            },
+            SolutionMode::Retrograde if self.externality.is_none() => {
+                self.retrograde_solver(mode, policy, resources)?;
             },
             _ => {
                 return Err(GameError::SolverNotFound {
@@ -111,6 +168,44 @@ impl Game for Session {
         }
         Ok(())
     }
+
+    fn query(&self, mode: IOMode, state: Option<String>) -> Result<Entry> {
+        let mut entry = if self.externality.is_some() {
+            let db = self.external_solver(
+                mode,
+                Policy::default(),
+                &Resources::default(),
+            )?;
+            util::query_external_entry(self, &db, state)?
+        } else {
+            let db = self.strong_solver(
+                mode,
+                Policy::default(),
+                &Resources::default(),
+            )?;
+            util::query_entry(self, &db, state)?
+        };
+        entry.moves = self
+            .moves(self.decode(entry.state.clone())?)
+            .into_iter()
+            .map(|choice| self.encode_move(choice))
+            .collect();
+
+        Ok(entry)
+    }
+
+    fn verify(&self, directory: &Path, audit: Audit) -> Result<Report> {
+        if self.externality.is_some() {
+            return Err(GameError::VerifierNotFound {
+                input_game_name: NAME,
+            })
+            .context(
+                "Verifier not found. Only variants without an externality \
+                matrix can be verified.",
+            );
+        }
+        self.verifier(directory, audit)
+    }
 }
 
 /* TRAVERSAL DECLARATIONS */
@@ -121,13 +216,17 @@ impl Bounded<State> for Session {
     }
 
     fn end(&self, state: State) -> bool {
-        state == 0
+        util::unpack_turn(state, self.players).0 == 0
     }
 }
 
 impl DTransition<State> for Session {
     fn prograde(&self, state: State) -> Vec<State> {
         let (state, turn) = util::unpack_turn(state, self.players);
+        if state == 0 {
+            return Vec::new();
+        }
+
         let mut next = self
             .by
             .iter()
@@ -147,30 +246,38 @@ impl DTransition<State> for Session {
 
     fn retrograde(&self, state: State) -> Vec<State> {
         let (state, turn) = util::unpack_turn(state, self.players);
-        let mut next =
-            self.by
-                .iter()
-                .map(|&choice| {
-                    if state + choice <= self.start {
-                        choice
-                    } else {
-                        self.start
-                    }
-                })
-                .map(|choice| {
-                    util::pack_turn(
-                        state + choice,
-                        (turn - 1) % self.players,
-                        self.players,
-                    )
-                })
-                .collect::<Vec<State>>();
+        let (start, _) = util::unpack_turn(self.start, self.players);
+        let turn = (turn + self.players - 1) % self.players;
+        let mut next = Vec::new();
+        for &choice in &self.by {
+            if state == 0 {
+                // Any choice at least as large as the set empties it.
+                next.extend(1..=choice.min(start));
+            } else if state + choice <= start {
+                next.push(state + choice);
+            }
+        }
+
+        let mut next = next
+            .into_iter()
+            .map(|elements| util::pack_turn(elements, turn, self.players))
+            .collect::<Vec<State>>();
         next.sort();
         next.dedup();
         next
     }
 }
 
+impl TerminalEnumerable<State> for Session {
+    /// The terminal states are those with no elements left, which hold the
+    /// lowest ranks (one for each player's turn).
+    fn terminals(&self) -> Vec<State> {
+        (0..self.players as StateCount)
+            .map(|rank| self.unrank(rank))
+            .collect()
+    }
+}
+
 /* SUPPLEMENTAL DECLARATIONS */
 
 impl Legible<State> for Session {
@@ -184,35 +291,270 @@ impl Legible<State> for Session {
     }
 }
 
+impl Moves<State> for Session {
+    fn moves(&self, state: State) -> Vec<Move> {
+        let (elements, _) = util::unpack_turn(state, self.players);
+        let mut moves = self
+            .by
+            .iter()
+            .map(|&choice| choice.min(elements))
+            .filter(|&choice| choice > 0)
+            .collect::<Vec<Move>>();
+        moves.sort();
+        moves.dedup();
+        moves
+    }
+
+    fn apply(&self, state: State, choice: Move) -> State {
+        let (elements, turn) = util::unpack_turn(state, self.players);
+        util::pack_turn(
+            elements - choice,
+            (turn + 1) % self.players,
+            self.players,
+        )
+    }
+
+    fn decode_move(&self, string: String) -> Result<Move> {
+        Ok(parse_move(&self, string)?)
+    }
+
+    fn encode_move(&self, choice: Move) -> String {
+        choice.to_string()
+    }
+}
+
+impl Ranked<State> for Session {
+    fn rank(&self, state: State) -> StateCount {
+        let (elements, turn) = util::unpack_turn(state, self.players);
+        elements * self.players as StateCount + turn as StateCount
+    }
+
+    fn unrank(&self, rank: StateCount) -> State {
+        let players = self.players as StateCount;
+        util::pack_turn(
+            rank / players,
+            (rank % players) as Turn,
+            self.players,
+        )
+    }
+
+    /// Ranks every pairing of a number of remaining elements (up to the number
+    /// at the start) with a player turn, so the rank space is larger than the
+    /// set of reachable states when some element counts cannot be reached
+    /// through the allowed moves, or are only reached on some players' turns.
+    fn count(&self) -> StateCount {
+        let (elements, _) = util::unpack_turn(self.start, self.players);
+        (elements + 1) * self.players as StateCount
+    }
+}
+
 /* SOLVING DECLARATIONS */
 
-implement! { for Session =>
-    Acyclic<2>,
-    Acyclic<10>
+/// Generates the solving declarations of sessions with `N` players for each
+/// `N` provided, along with the routines which dispatch to the solvers (and
+/// transformations) whose generic player count matches that of the session.
+macro_rules! players {
+    ($($n:literal),+) => {
+        $(
+            implement! { for Session =>
+                Acyclic<$n>
+            }
+
+            impl Solvable<$n> for Session {
+                fn utility(&self, state: State) -> [Utility; $n] {
+                    let (_, turn) = util::unpack_turn(state, $n);
+                    let mut payoffs = [1; $n];
+                    payoffs[turn] = 1 - $n as Utility;
+                    payoffs
+                }
+
+                fn turn(&self, state: State) -> Turn {
+                    util::unpack_turn(state, $n).1
+                }
+            }
+
+            impl External<$n> for Session {
+                fn externality(&self) -> SMatrix<Utility, $n, $n> {
+                    match &self.externality {
+                        Some(matrix) => SMatrix::from_row_slice(matrix),
+                        None => SMatrix::identity(),
+                    }
+                }
+            }
+        )+
+
+        impl Session {
+            /// Strongly solves this session with the solver whose generic
+            /// player count matches that of this session's variant, breaking
+            /// ties between moves according to `policy`.
+            fn strong_solver(
+                &self,
+                mode: IOMode,
+                policy: Policy,
+                resources: &Resources,
+            ) -> Result<RankedDatabase<'_, Self>> {
+                match self.players {
+                    $($n => strong::acyclic::ranked_dynamic_solver::<$n, Self>(
+                        self, mode, policy, resources,
+                    )
+                    .context("Failed solver run."),)+
+                    _ => Err(GameError::SolverNotFound {
+                        input_game_name: NAME,
+                    })
+                    .context("Solver not found."),
+                }
+            }
+
+            /// Strongly solves this session in the same way as
+            /// `strong_solver`, but across the threads given by `resources`.
+            fn parallel_solver(
+                &self,
+                policy: Policy,
+                resources: &Resources,
+            ) -> Result<sharded::Database> {
+                match self.players {
+                    $($n => strong::acyclic::parallel_dynamic_solver::<$n, Self>(
+                        self, policy, resources,
+                    )
+                    .context("Failed solver run."),)+
+                    _ => Err(GameError::SolverNotFound {
+                        input_game_name: NAME,
+                    })
+                    .context("Solver not found."),
+                }
+            }
+
+            /// Strongly solves this session in the same way as
+            /// `strong_solver`, but propagating records in retrograde from the
+            /// end states of the game instead of searching for them.
+            fn retrograde_solver(
+                &self,
+                mode: IOMode,
+                policy: Policy,
+                resources: &Resources,
+            ) -> Result<volatile::Database> {
+                match self.players {
+                    $($n => strong::acyclic::retrograde_solver::<$n, Self>(
+                        self, mode, policy, resources,
+                    )
+                    .context("Failed solver run."),)+
+                    _ => Err(GameError::SolverNotFound {
+                        input_game_name: NAME,
+                    })
+                    .context("Solver not found."),
+                }
+            }
+
+            /// Strongly solves this session in the same way as
+            /// `strong_solver`, but having players optimize for the utility
+            /// adjusted by the externality matrix of this session's variant.
+            fn external_solver(
+                &self,
+                mode: IOMode,
+                policy: Policy,
+                resources: &Resources,
+            ) -> Result<volatile::Database> {
+                match self.players {
+                    $($n => strong::acyclic::external_dynamic_solver::<$n, Self>(
+                        self, mode, policy, resources,
+                    )
+                    .context("Failed solver run."),)+
+                    _ => Err(GameError::SolverNotFound {
+                        input_game_name: NAME,
+                    })
+                    .context("Solver not found."),
+                }
+            }
+
+            /// Verifies the solution set of this session persisted in
+            /// `directory` by `strong_solver`, as specified by `audit`.
+            fn verifier(
+                &self,
+                directory: &Path,
+                audit: Audit,
+            ) -> Result<Report> {
+                match self.players {
+                    $($n => strong::acyclic::ranked_dynamic_verifier::<$n, Self>(
+                        self, directory, audit,
+                    )
+                    .context("Failed verifier run."),)+
+                    _ => Err(GameError::VerifierNotFound {
+                        input_game_name: NAME,
+                    })
+                    .context("Verifier not found."),
+                }
+            }
+
+            /// Returns the misère version of this session, where the player
+            /// who removes the last element from the set loses instead. Fails
+            /// if there is no solver available for the player count of this
+            /// session's variant.
+            pub fn into_misere(self) -> Result<Box<dyn Game>> {
+                match self.players {
+                    $($n => Ok(Box::new(Misere::<$n, _>::new(self))),)+
+                    _ => Err(GameError::MisereNotFound {
+                        input_game_name: NAME,
+                    })
+                    .context("Misère version not found."),
+                }
+            }
+        }
+    };
 }
 
-impl Solvable<2> for Session {
-    fn utility(&self, state: State) -> [Utility; 2] {
-        let (_, turn) = unpack_turn(state, 2);
-        let mut payoffs = [-1; 2];
-        payoffs[turn] = 1;
-        payoffs
+solvable! { players }
+
+impl Limited for Session {
+    fn utility_range(&self) -> RangeInclusive<Utility> {
+        (1 - self.players as Utility)..=1
     }
 
-    fn turn(&self, state: State) -> Turn {
-        util::unpack_turn(state, 2).1
+    fn max_remoteness(&self) -> Remoteness {
+        // Every move removes at least one element from the set.
+        util::unpack_turn(self.start, self.players).0
     }
 }
 
-impl Solvable<10> for Session {
-    fn utility(&self, state: State) -> [Utility; 10] {
-        let (_, turn) = unpack_turn(state, 10);
-        let mut payoffs = [-1; 10];
-        payoffs[turn] = 9;
-        payoffs
+/* IMPARTIAL GAME THEORY */
+
+impl Impartial for Session {
+    fn nim_value(&self, state: State) -> MinimumExcludedValue {
+        let (elements, _) = util::unpack_turn(state, self.players);
+        let nimbers = self.nimbers.get_or_init(|| {
+            let (start, _) = util::unpack_turn(self.start, self.players);
+            self.nimbers(start)
+        });
+
+        match nimbers.get(elements as usize) {
+            Some(&nimber) => nimber,
+            None => self.nimbers(elements)[elements as usize],
+        }
+    }
+
+    fn normalize(&self, state: State) -> State {
+        let (elements, _) = util::unpack_turn(state, self.players);
+        util::pack_turn(elements, 0, self.players)
     }
 
-    fn turn(&self, state: State) -> Turn {
-        util::unpack_turn(state, 10).1
+    fn width(&self) -> usize {
+        min_ubits(self.normalize(self.start))
+    }
+}
+
+impl Session {
+    /// Returns the nim-values of the positions with each number of elements up
+    /// to `elements`, which are cached in this session the first time they are
+    /// needed for the positions reachable from its starting state.
+    fn nimbers(&self, elements: u64) -> Vec<MinimumExcludedValue> {
+        let mut nimbers: Vec<MinimumExcludedValue> = vec![0];
+        for n in 1..=elements {
+            let options = self
+                .by
+                .iter()
+                .map(|&choice| nimbers[(n - choice.min(n)) as usize])
+                .collect::<Vec<MinimumExcludedValue>>();
+            nimbers.push(util::mex(&options));
+        }
+        nimbers
     }
 }
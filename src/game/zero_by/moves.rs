@@ -0,0 +1,176 @@
+//! # Zero-By Move Handling Module
+//!
+//! This module helps parse the string encoding of a zero-by move, which is the
+//! number of elements a player removes from the set, performing a series of
+//! checks which ensure that it is one of the choices allowed by a game variant.
+
+use regex::Regex;
+
+use crate::game::error::GameError;
+use crate::game::zero_by::Session;
+use crate::game::zero_by::NAME;
+use crate::model::Move;
+
+/* ZERO-BY MOVE ENCODING */
+
+pub const MOVE_PATTERN: &str = r"^[1-9]\d*$";
+pub const MOVE_PROTOCOL: &str =
+    "The move string should be a single positive integer without any decimal \
+points, indicating the amount of elements to remove from the set. It must be \
+one of the amounts specified by the game variant, except when there are fewer \
+elements left in the set than that amount, in which case removing all of the \
+remaining elements is encoded as the amount left in the set.";
+
+/* API */
+
+/// Returns a zero-by move encoding from the string `from`, verifying that it is
+/// one of the amounts that can be removed in `session`'s game variant. This
+/// does not verify that the move is legal from any particular state.
+pub fn parse_move(session: &Session, from: String) -> Result<Move, GameError> {
+    check_move_pattern(&from)?;
+    let amount = parse_amount(&from)?;
+    check_variant_coherence(amount, session)?;
+    Ok(amount)
+}
+
+/* MOVE STRING VERIFICATION */
+
+fn check_move_pattern(from: &str) -> Result<(), GameError> {
+    let re = Regex::new(MOVE_PATTERN).unwrap();
+    if !re.is_match(from) {
+        Err(GameError::MoveMalformed {
+            game_name: NAME,
+            hint: format!(
+                "String does not match the pattern '{}'.",
+                MOVE_PATTERN
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_amount(from: &str) -> Result<Move, GameError> {
+    from.parse::<Move>()
+        .map_err(|e| GameError::MoveMalformed {
+            game_name: NAME,
+            hint: e.to_string(),
+        })
+}
+
+fn check_variant_coherence(
+    amount: Move,
+    session: &Session,
+) -> Result<(), GameError> {
+    let largest = session
+        .by
+        .iter()
+        .max()
+        .copied()
+        .unwrap_or(0);
+
+    if amount > largest {
+        Err(GameError::MoveMalformed {
+            game_name: NAME,
+            hint: format!(
+                "Specified removing more elements ({}) than the variant allows \
+                in a single move ({}).",
+                amount, largest,
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::game::{testing, Bounded, DTransition, Game, Legible, Moves};
+
+    #[test]
+    fn move_pattern_is_valid_regex() {
+        assert!(Regex::new(MOVE_PATTERN).is_ok());
+    }
+
+    #[test]
+    fn malformed_moves_fail_checks() {
+        // 2-player 10-to-zero by 1 or 2
+        let session = Session::initialize(None).unwrap();
+        let m = |s: &str| parse_move(&session, s.to_owned());
+        assert!(m("").is_err());
+        assert!(m("0").is_err());
+        assert!(m("-1").is_err());
+        assert!(m("01").is_err());
+        assert!(m("1.0").is_err());
+        assert!(m("3").is_err());
+        assert!(m("99999999999999999999999").is_err());
+    }
+
+    #[test]
+    fn well_formed_moves_pass_checks() {
+        let session = Session::initialize(None).unwrap();
+        assert_eq!(parse_move(&session, "1".to_owned()).unwrap(), 1);
+        assert_eq!(parse_move(&session, "2".to_owned()).unwrap(), 2);
+    }
+
+    #[test]
+    fn moves_are_capped_by_remaining_elements() {
+        let session = Session::initialize(Some("3-4-3-5".into())).unwrap();
+        let state = session
+            .decode("4-1".to_owned())
+            .unwrap();
+
+        let moves = session
+            .moves(state)
+            .into_iter()
+            .map(|m| session.encode_move(m))
+            .collect::<Vec<String>>();
+
+        assert_eq!(moves, vec!["3", "4"]);
+        assert_eq!(session.encode(session.apply(state, 4)), "0-2");
+        assert!(session
+            .moves(
+                session
+                    .decode("0-0".to_owned())
+                    .unwrap()
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn histories_can_name_moves_instead_of_states() {
+        let history = |h: &[&str]| {
+            let mut session = Session::initialize(None).unwrap();
+            session
+                .forward(
+                    h.iter()
+                        .map(|&s| s.to_owned())
+                        .collect(),
+                )
+                .map(|_| session.encode(session.start()))
+        };
+
+        assert_eq!(history(&["10-0", "2", "1"]).unwrap(), "7-0");
+        assert_eq!(history(&["10-0", "8-1", "1"]).unwrap(), "7-0");
+        assert!(history(&["10-0", "3"]).is_err());
+        assert!(history(&["2", "1"]).is_err());
+    }
+
+    #[test]
+    fn moves_agree_with_transitions() {
+        let session = Session::initialize(Some("3-20-3-5-7".into())).unwrap();
+        testing::verify_moves(&session, 1_000).unwrap();
+        assert_eq!(
+            session
+                .moves(session.start())
+                .len(),
+            session
+                .prograde(session.start())
+                .len()
+        );
+    }
+}
@@ -127,7 +127,7 @@ fn check_variant_coherence(
 mod test {
 
     use super::*;
-    use crate::game::{util::verify_history_dynamic, Game};
+    use crate::game::{testing, util::verify_history_dynamic, Game};
 
     /* STATE STRING PARSING */
 
@@ -202,7 +202,7 @@ mod test {
 
     #[test]
     fn compatible_variants_and_states_pass_checks() {
-        let v1 = "50-10-12-1-4";
+        let v1 = "14-10-12-1-4";
         let v2 = "5-100-6-2-7";
         let v3 = "10-200-1-5";
 
@@ -296,6 +296,24 @@ mod test {
         assert!(verify_history_dynamic(&session(v()), owned(i4)).is_err());
     }
 
+    /* INTERFACE CONFORMANCE */
+
+    #[test]
+    fn zero_by_conforms_to_game_interfaces() {
+        for variant in ["2-10-1-2", "3-25-3-1-4", "1-8-5", "14-40-2-7-3"] {
+            let session = session(Some(variant.to_owned()));
+            testing::verify_acyclic::<2, _>(&session, 10_000).unwrap();
+        }
+    }
+
+    #[test]
+    fn zero_by_ranking_is_bijective() {
+        for variant in ["2-10-1-2", "3-25-3-1-4", "1-8-5", "14-40-2-7-3"] {
+            let session = session(Some(variant.to_owned()));
+            testing::verify_ranking(&session, 10_000).unwrap();
+        }
+    }
+
     /* UTILITIES */
 
     fn session(v: Option<String>) -> Session {
@@ -9,15 +9,19 @@
 
 use regex::Regex;
 
+use std::sync::OnceLock;
+
 use crate::game::error::GameError;
 use crate::game::util::pack_turn;
 use crate::game::zero_by::{Session, NAME};
-use crate::model::Turn;
+use crate::model::{PlayerCount, StateCount, Turn, Utility};
+use crate::solver::SOLVABLE_PLAYERS;
 
 /* ZERO-BY VARIANT ENCODING */
 
 pub const VARIANT_DEFAULT: &'static str = "2-10-1-2";
-pub const VARIANT_PATTERN: &'static str = r"^[1-9]\d*(?:-[1-9]\d*)+$";
+pub const VARIANT_PATTERN: &'static str =
+    r"^[1-9]\d*(?:-[1-9]\d*)+(?::-?\d+(?:,-?\d+)*)?$";
 pub const VARIANT_PROTOCOL: &'static str =
 "The variant string should be a dash-separated group of three or more positive \
 integers. For example, '4-232-23-6-3-6' is valid but '598', '-23-1-5', and \
@@ -27,7 +31,15 @@ rest are choices that the players have when they need to remove a number of \
 pieces on their turn. Note that the numbers can be repeated, but if you repeat \
 the first number it will be a win for the player with the first turn in 1 \
 move. If you repeat any of the rest of the numbers, the only consequence will \
-be a slight decrease in performance.";
+be a slight decrease in performance. There can be at most 14 players. \
+Optionally, the integers can be followed by a colon and a comma-separated list \
+of N * N integers (where N is the number of players), which is an externality \
+matrix in row-major order. The entry in row i and column j is the utility that \
+player i obtains for each unit of utility of player j. If it is provided, the \
+players choose their moves to maximize the utility adjusted by this matrix, \
+and both the raw and the adjusted utility are reported. For example, in \
+'2-10-1-2:1,0,-1,1' the second player is spiteful towards the first one. The \
+misère versions of variants with an externality matrix are not available.";
 
 /* API */
 
@@ -36,18 +48,59 @@ be a slight decrease in performance.";
 /// does not conform to the variant protocol.
 pub fn parse_variant(variant: String) -> Result<Session, GameError> {
     check_variant_pattern(&variant)?;
-    let params = parse_parameters(&variant)?;
+    let (rules, matrix) = match variant.split_once(':') {
+        Some((rules, matrix)) => (rules, Some(matrix)),
+        None => (&variant[..], None),
+    };
+
+    let params = parse_parameters(rules)?;
     check_param_count(&params)?;
     check_params_are_positive(&params)?;
     let players = parse_player_count(&params)?;
+    let externality = matrix
+        .map(|m| parse_externality(m, players))
+        .transpose()?;
+
     Ok(Session {
-        variant,
-        players,
         start: pack_turn(params[1], 0, players),
         by: Vec::from(&params[2..]),
+        externality,
+        variant,
+        players,
+        nimbers: OnceLock::new(),
     })
 }
 
+/// Returns the variant strings of the variants of `session`'s family with at
+/// most `max_states` states, in increasing order of size. The family of a
+/// zero-by variant is made up of the variants with the same number of players
+/// and choices, but with any positive number of starting elements.
+pub fn enumerate_variants(
+    session: &Session,
+    max_states: StateCount,
+) -> Vec<String> {
+    let choices = session
+        .by
+        .iter()
+        .map(|choice| choice.to_string())
+        .collect::<Vec<String>>()
+        .join("-");
+
+    let matrix = session
+        .variant
+        .split_once(':')
+        .map(|(_, matrix)| format!(":{}", matrix))
+        .unwrap_or_default();
+
+    let players = session.players as StateCount;
+    (1..)
+        .take_while(|elements| (elements + 1) * players <= max_states)
+        .map(|elements| {
+            format!("{}-{}-{}{}", players, elements, choices, matrix)
+        })
+        .collect()
+}
+
 /* VARIANT STRING VERIFICATION */
 
 fn parse_parameters(variant: &str) -> Result<Vec<u64>, GameError> {
@@ -104,13 +157,46 @@ fn check_params_are_positive(params: &Vec<u64>) -> Result<(), GameError> {
     }
 }
 
+fn parse_externality(
+    matrix: &str,
+    players: PlayerCount,
+) -> Result<Vec<Utility>, GameError> {
+    let entries = matrix
+        .split(',')
+        .map(|entry| {
+            entry
+                .parse::<Utility>()
+                .map_err(|e| GameError::VariantMalformed {
+                    game_name: NAME,
+                    hint: e.to_string(),
+                })
+        })
+        .collect::<Result<Vec<Utility>, GameError>>()?;
+
+    if entries.len() != players * players {
+        Err(GameError::VariantMalformed {
+            game_name: NAME,
+            hint: format!(
+                "The externality matrix of a {}-player game must have {} \
+                entries, but {} were provided.",
+                players,
+                players * players,
+                entries.len(),
+            ),
+        })
+    } else {
+        Ok(entries)
+    }
+}
+
 fn parse_player_count(params: &Vec<u64>) -> Result<Turn, GameError> {
-    if params[0] > (Turn::MAX as u64) {
+    let max = SOLVABLE_PLAYERS[SOLVABLE_PLAYERS.len() - 1];
+    if params[0] > (max as u64) {
         Err(GameError::VariantMalformed {
             game_name: NAME,
             hint: format!(
-                "The number of players in the game must be lower than {}.",
-                Turn::MAX
+                "The number of players in the game must be at most {}.",
+                max
             ),
         })
     } else {
@@ -123,8 +209,17 @@ fn parse_player_count(params: &Vec<u64>) -> Result<Turn, GameError> {
 #[cfg(test)]
 mod test {
 
+    use std::{env, fs, process};
+
     use super::*;
-    use crate::game::Game;
+    use crate::database::{vector, KVStore, Persistence, Persistent, Tabular};
+    use crate::game::{Bounded, DTransition, Game, Legible, Limited, Ranked};
+    use crate::interface::{IOMode, SolutionMode, TieBreak};
+    use crate::model::{State, Utility};
+    use crate::solver::checkpoint::Checkpoint;
+    use crate::solver::record::mur::{Layout, RecordBuffer};
+    use crate::solver::util::{Policy, RankedDatabase, Resources};
+    use crate::solver::verification::{Audit, Inconsistency};
 
     #[test]
     fn variant_pattern_is_valid_regex() {
@@ -156,6 +251,27 @@ mod test {
         assert_eq!(with_none.by, with_default.by);
     }
 
+    #[test]
+    fn enumerated_variants_are_bounded_family_members() {
+        let session = Session::initialize(None).unwrap();
+        assert!(enumerate_variants(&session, 3).is_empty());
+        assert_eq!(
+            enumerate_variants(&session, 8),
+            vec!["2-1-1-2", "2-2-1-2", "2-3-1-2"]
+        );
+
+        let session =
+            Session::initialize(Some("3-40-4-1:1,0,0,0,1,0,0,0,1".to_owned()))
+                .unwrap();
+        for variant in enumerate_variants(&session, 100) {
+            let member = Session::initialize(Some(variant)).unwrap();
+            assert_eq!(member.players, 3);
+            assert_eq!(member.by, vec![4, 1]);
+            assert_eq!(member.externality, session.externality);
+            assert!(member.count() <= 100);
+        }
+    }
+
     #[test]
     fn invalid_variants_fail_checks() {
         let v1 = "23-34-0-23";
@@ -164,6 +280,7 @@ mod test {
         let v4 = "34-236--8-6-3";
         let v5 = "0-12-234-364";
         let v6 = "-234-256";
+        let v7 = "34-23623-8-6-3";
 
         fn wrapper(v: &'static str) -> Result<Session, GameError> {
             parse_variant(v.to_owned())
@@ -175,13 +292,18 @@ mod test {
         assert!(wrapper(v4).is_err());
         assert!(wrapper(v5).is_err());
         assert!(wrapper(v6).is_err());
+        assert!(wrapper(v7).is_err());
+        assert!(wrapper("2-10-1-2:1,0,0").is_err());
+        assert!(wrapper("2-10-1-2:1,0,0,1,0").is_err());
+        assert!(wrapper("2-10-1-2:").is_err());
+        assert!(wrapper("2-10-1-2:1,,0,1").is_err());
     }
 
     #[test]
     fn valid_variants_pass_checks() {
         let v1 = "5-1000-8-23-63-7";
         let v2 = "1-1-1";
-        let v3 = "34-23623-8-6-3";
+        let v3 = "14-23623-8-6-3";
         let v4 = "5-2-8-23";
         let v5 = "1-619-496-1150";
 
@@ -194,5 +316,240 @@ mod test {
         assert!(wrapper(v3).is_ok());
         assert!(wrapper(v4).is_ok());
         assert!(wrapper(v5).is_ok());
+        assert!(wrapper("2-10-1-2:1,0,-1,1").is_ok());
+        assert!(wrapper("3-10-1-2:1,1,1,0,1,0,-2,0,1").is_ok());
+    }
+
+    #[test]
+    fn all_accepted_player_counts_are_solvable() {
+        for &players in SOLVABLE_PLAYERS {
+            let variant = format!("{}-12-1-2", players);
+            let session = parse_variant(variant).unwrap();
+            let entry = session
+                .query(IOMode::Write, None)
+                .unwrap();
+
+            assert_eq!(entry.utility.len(), players);
+            assert_eq!(
+                entry
+                    .utility
+                    .iter()
+                    .sum::<Utility>(),
+                0
+            );
+        }
+
+        let players = SOLVABLE_PLAYERS[SOLVABLE_PLAYERS.len() - 1] + 1;
+        let variant = format!("{}-12-1-2", players);
+        assert!(parse_variant(variant).is_err());
+    }
+
+    #[test]
+    fn misere_versions_exist_for_all_player_counts() {
+        for &players in SOLVABLE_PLAYERS {
+            let variant = format!("{}-5-1-2", players);
+            let session = parse_variant(variant).unwrap();
+            assert!(session
+                .into_misere()
+                .unwrap()
+                .query(IOMode::Write, None)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn queries_report_adjusted_utility_with_externalities() {
+        let plain = parse_variant("2-4-1-2".to_owned()).unwrap();
+        let entry = plain
+            .query(IOMode::Write, None)
+            .unwrap();
+        assert_eq!(entry.utility, vec![1, -1]);
+        assert!(entry.adjusted.is_empty());
+
+        let external = parse_variant("2-4-1-2:-1,0,0,1".to_owned()).unwrap();
+        let entry = external
+            .query(IOMode::Write, None)
+            .unwrap();
+        assert_eq!(entry.utility, vec![-1, 1]);
+        assert_eq!(entry.adjusted, vec![1, 1]);
+        assert!(super::super::session(
+            Some("2-4-1-2:-1,0,0,1".to_owned()),
+            true
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn records_fit_declared_bounds() {
+        // Utility values lie in [-2, 1], and remoteness is at most 9.
+        let session = parse_variant("3-9-1-2".to_owned()).unwrap();
+        assert_eq!(session.utility_range(), -2..=1);
+        assert_eq!(session.max_remoteness(), 9);
+
+        let db = session
+            .strong_solver(
+                IOMode::Write,
+                Policy::default(),
+                &Resources::default(),
+            )
+            .unwrap();
+        let layout = Layout::from_schema(db.schema().unwrap()).unwrap();
+        assert_eq!(layout.size(), 3 * 3 + 4);
+
+        let misere = session.into_misere().unwrap();
+        for elements in 0..=9 {
+            let state = Some(format!("{}-{}", elements, (9 - elements) % 3));
+            let entry = misere
+                .query(IOMode::Write, state)
+                .unwrap();
+            assert!(entry.remoteness <= 9);
+        }
+    }
+
+    /// Returns the states reachable from the start of `session`.
+    fn reachable(session: &Session) -> Vec<State> {
+        let mut reachable = vec![session.start()];
+        let mut frontier = reachable.clone();
+        while let Some(state) = frontier.pop() {
+            for child in session.prograde(state) {
+                if !reachable.contains(&child) {
+                    reachable.push(child);
+                    frontier.push(child);
+                }
+            }
+        }
+        reachable
+    }
+
+    #[test]
+    fn retrograde_solutions_match_depth_first_solutions() {
+        let policies = [
+            Policy::default(),
+            Policy {
+                win_by: true,
+                ..Policy::default()
+            },
+            Policy::new(TieBreak::Random, 3),
+        ];
+
+        for variant in ["2-25-1-3-4", "3-20-1-2-3", "4-300-7-2-5"] {
+            let session = parse_variant(variant.to_owned()).unwrap();
+            let reachable = reachable(&session);
+            for policy in policies {
+                let dfs = session
+                    .strong_solver(IOMode::Write, policy, &Resources::default())
+                    .unwrap();
+                let retrograde = session
+                    .retrograde_solver(
+                        IOMode::Write,
+                        policy,
+                        &Resources::default(),
+                    )
+                    .unwrap();
+
+                for &state in &reachable {
+                    assert_eq!(
+                        KVStore::<RecordBuffer>::get(&dfs, state),
+                        KVStore::<RecordBuffer>::get(&retrograde, state),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn retrograde_solutions_are_offered_without_externalities() {
+        let solve = |variant: &str| {
+            parse_variant(variant.to_owned())
+                .unwrap()
+                .solve(
+                    IOMode::Write,
+                    SolutionMode::Retrograde,
+                    Policy::default(),
+                    &Resources::default(),
+                )
+        };
+
+        assert!(solve("3-20-1-2-3").is_ok());
+        let error = solve("2-10-1-2:1,0,-1,1").unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<GameError>(),
+            Some(GameError::SolverNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn verification_reports_corrupted_records() {
+        let directory = env::temp_dir().join(format!(
+            "nova-verification-test-{}",
+            process::id()
+        ));
+
+        let session = parse_variant("3-20-1-2-3".to_owned()).unwrap();
+        let policy = Policy {
+            win_by: true,
+            ..Policy::new(TieBreak::Paranoid, 0)
+        };
+
+        let resources = Resources {
+            checkpoint: Some(Checkpoint {
+                directory: directory.clone(),
+                interval: u64::MAX,
+            }),
+            ..Resources::default()
+        };
+
+        session
+            .strong_solver(IOMode::Write, policy, &resources)
+            .unwrap();
+
+        let audit = Audit {
+            limit: 10,
+            samples: None,
+            seed: 0,
+        };
+
+        let report = session
+            .verify(&directory, audit)
+            .unwrap();
+        assert!(report.consistent());
+        assert_eq!(report.checked, reachable(&session).len() as u64);
+
+        let mut db = vector::Database::initialize(vector::Parameters {
+            persistence: Persistence::On(&directory),
+        })
+        .unwrap();
+        db.load_table(&session.id())
+            .unwrap();
+        db.select_table(&session.id())
+            .unwrap();
+
+        let mut db = RankedDatabase::new(&session, db).unwrap();
+        let start = session.start();
+        let layout = Layout::from_schema(db.schema().unwrap()).unwrap();
+        let mut record = RecordBuffer::parse(
+            KVStore::<RecordBuffer>::get(&db, start).unwrap(),
+            layout,
+        )
+        .unwrap();
+        record
+            .set_remoteness(record.get_remoteness() + 1)
+            .unwrap();
+        db.put(start, &record);
+        db.materialize().unwrap();
+
+        for samples in [None, Some(3)] {
+            let report = session
+                .verify(&directory, Audit { samples, ..audit })
+                .unwrap();
+            assert_eq!(report.inconsistencies.len(), 1);
+            assert!(matches!(
+                &report.inconsistencies[0],
+                Inconsistency::Mismatch { state, .. }
+                    if *state == session.encode(start)
+            ));
+        }
+
+        fs::remove_dir_all(&directory).unwrap();
     }
 }
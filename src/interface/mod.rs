@@ -46,6 +46,10 @@ pub enum OutputMode {
 /// guarantee that the solving algorithm will traverse less states than the
 /// strong alternative. The relative convenience of a weak solution relies on
 /// the structure of the underlying game.
+///
+/// Games which can list their end states can also be strongly solved in
+/// retrograde (see `crate::game::TerminalEnumerable`), which does not need to
+/// keep a search stack and suits games with long sessions.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum SolutionMode {
     /// Minimally prove an optimal strategy beginning from a starting state.
@@ -53,6 +57,10 @@ pub enum SolutionMode {
 
     /// Provide a strategy for all game states reachable from starting state.
     Strong,
+
+    /// Provide a strategy for all game states by working backward from the
+    /// end states of the game instead of searching for them.
+    Retrograde,
 }
 
 /// Specifies a mode of operation for solving algorithms in regard to database
@@ -93,6 +101,44 @@ pub enum IOMode {
     Write,
 }
 
+/// Specifies how a player chooses among the moves that are equally good for
+/// them (as in, those which lead to the same utility for them) when solving
+/// games with more than two players or which are not zero-sum. In these games,
+/// the choice affects the utility of the other players, so the solution set of
+/// a game depends on the policy used to compute it.
+///
+/// Regardless of policy, ties which remain after applying it are broken by
+/// remoteness, where players who are losing (with a negative utility) prefer
+/// the moves that lead to higher remoteness, and all others prefer the moves
+/// that lead to lower remoteness.
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum TieBreak {
+    /// Break ties by remoteness alone.
+    #[default]
+    Remoteness,
+
+    /// Prefer the moves that minimize the sum of the opponents' utility.
+    Paranoid,
+
+    /// Prefer the moves that maximize the sum of all players' utility.
+    Cooperative,
+
+    /// Prefer the moves that are best for player 0, then for player 1, etc.
+    Lexicographic,
+
+    /// Prefer moves pseudo-randomly, reproducibly for the same seed.
+    Random,
+}
+
+/// Specifies which analysis to perform on the state graph of a game variant.
+/// Different analyzers compute different statistics about the states of a game,
+/// and games only support the analyzers which make sense for their structure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Analyzer {
+    /// Count the states that are not stored when exploiting symmetries.
+    Symmetry,
+}
+
 /* AUXILIARY IMPLEMENTATIONS */
 
 impl fmt::Display for IOMode {
@@ -109,6 +155,7 @@ impl fmt::Display for SolutionMode {
         match self {
             SolutionMode::Weak => write!(f, "weak"),
             SolutionMode::Strong => write!(f, "strong"),
+            SolutionMode::Retrograde => write!(f, "retrograde"),
         }
     }
 }
@@ -122,3 +169,23 @@ impl fmt::Display for OutputMode {
         }
     }
 }
+
+impl fmt::Display for TieBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TieBreak::Remoteness => write!(f, "remoteness"),
+            TieBreak::Paranoid => write!(f, "paranoid"),
+            TieBreak::Cooperative => write!(f, "cooperative"),
+            TieBreak::Lexicographic => write!(f, "lexicographic"),
+            TieBreak::Random => write!(f, "random"),
+        }
+    }
+}
+
+impl fmt::Display for Analyzer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Analyzer::Symmetry => write!(f, "symmetry"),
+        }
+    }
+}
@@ -11,8 +11,18 @@
 
 use clap::{Args, Parser, Subcommand};
 
-use crate::interface::{IOMode, OutputMode, SolutionMode};
-use crate::util::GameModule;
+use std::path::PathBuf;
+
+use crate::interface::{Analyzer, IOMode, OutputMode, SolutionMode, TieBreak};
+use crate::model::StateCount;
+use crate::util::{game_parser, parse_memory};
+
+/* CONSTANTS */
+
+/// The directory (relative to the working directory) where solution sets are
+/// saved when solving with a checkpoint but without naming a directory, and
+/// where they are looked up when verifying without naming one.
+pub const DATA_DIRECTORY: &str = "data";
 
 /* COMMAND LINE INTERFACE */
 
@@ -43,6 +53,12 @@ pub enum Commands {
     /// Solve a game from the start position.
     Solve(SolveArgs),
 
+    /// Query the solution of a specific game state.
+    Query(QueryArgs),
+
+    /// Verify the consistency of a persisted solution set.
+    Verify(VerifyArgs),
+
     /// Analyze a game's state graph.
     Analyze(AnalyzeArgs),
 
@@ -61,8 +77,8 @@ pub enum Commands {
 pub struct TuiArgs {
     /* DEFAULTS PROVIDED */
     /// Game to display (optional).
-    #[arg(short, long)]
-    pub target: Option<GameModule>,
+    #[arg(short, long, value_parser = game_parser())]
+    pub target: Option<String>,
     /// Enter TUI in debug mode.
     #[arg(short, long)]
     pub debug: bool,
@@ -79,19 +95,38 @@ pub struct TuiArgs {
 /// - Formats output aesthetically (see `output` argument).
 /// - Uses the game's default solver to create state graph (see `solver`
 /// argument).
+/// - Solves only the specified variant (see `all_variants` flag).
+/// - Breaks ties between equally good moves by remoteness alone (see
+///   `tie_break` argument).
+/// - Solves on a single thread (see `threads` argument).
+/// - Does not save progress while solving (see `checkpoint` argument).
+/// - Does not limit the memory used while solving (see `memory_limit`
+///   argument).
 /// - Prompts the user before executing any potentially destructive operations
 /// such as overwriting a database file (see `yes` flag).
 #[derive(Args)]
 pub struct SolveArgs {
     /* REQUIRED ARGUMENTS */
     /// Target game name.
-    pub target: GameModule,
+    #[arg(
+        value_parser = game_parser(),
+        required_unless_present = "plugin",
+        conflicts_with = "plugin"
+    )]
+    pub target: Option<String>,
 
     /* DEFAULTS PROVIDED */
+    /// Load the target game from a shared library plugin instead.
+    #[arg(long, conflicts_with = "target")]
+    pub plugin: Option<PathBuf>,
     /// Solve a specific variant of target.
     #[arg(short, long)]
     pub variant: Option<String>,
-    /// Compute solution starting after a file-provided state history.
+    /// Read the variant of target from a file (such as a game description).
+    #[arg(long, conflicts_with_all = ["variant", "plugin"])]
+    pub rules: Option<PathBuf>,
+    /// Compute solution starting after a file-provided state history (with one
+    /// state per line, or a move after the first line if the game names moves).
     #[arg(short, long)]
     pub from: Option<String>,
     /// Specify what type of solution to compute.
@@ -100,31 +135,179 @@ pub struct SolveArgs {
     /// Specify whether the solution should be fetched or generated.
     #[arg(short, long, default_value_t = IOMode::Find)]
     pub mode: IOMode,
+    /// Solve the misère version of target (last player to move loses).
+    #[arg(long)]
+    pub misere: bool,
+    /// Solve all variants in the family of the variant and print a summary
+    /// (under the default solver, policy, and resources).
+    #[arg(
+        long,
+        requires = "max_states",
+        conflicts_with_all = [
+            "from",
+            "solver",
+            "seed",
+            "win_by",
+            "threads",
+            "checkpoint",
+            "memory_limit",
+        ],
+    )]
+    pub all_variants: bool,
+    /// Limit the number of states of the variants solved with all-variants.
+    #[arg(long, requires = "all_variants")]
+    pub max_states: Option<StateCount>,
+    /// Specify how players choose among equally good moves.
+    #[arg(long, default_value_t = TieBreak::Remoteness, conflicts_with = "all_variants")]
+    pub tie_break: TieBreak,
+    /// Seed the pseudo-random choices of the random tie-breaking policy.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Record the win-by margin of each state along with its solution.
+    #[arg(long)]
+    pub win_by: bool,
+    /// Number of threads to solve with, where the game's solver supports it.
+    #[arg(long, default_value_t = 1)]
+    pub threads: usize,
+    /// Periodically save progress to a directory (or to the data directory if
+    /// none is given), resuming from it in find mode.
+    #[arg(long, num_args = 0..=1, default_missing_value = DATA_DIRECTORY)]
+    pub checkpoint: Option<PathBuf>,
+    /// Number of states visited between consecutive checkpoints.
+    #[arg(long, default_value_t = 1 << 20, requires = "checkpoint")]
+    pub checkpoint_interval: u64,
+    /// Limit the memory used while solving, in bytes (or with a K, M, or G
+    /// suffix), spilling unused tables and records to the checkpoint directory.
+    #[arg(long, value_parser = parse_memory)]
+    pub memory_limit: Option<usize>,
     /// Skips prompts for confirming destructive operations.
     #[arg(short, long)]
     pub yes: bool,
 }
 
+/// Provides the solution set entry of a single game state, such as its utility
+/// and remoteness. This uses the same logic on finding or generating missing
+/// data as the solving routine; see `cli::IOMode` for specifics. Default
+/// behavior:
+///
+/// - Uses the target's default variant (see `variant` argument).
+/// - Queries the starting state of the variant (see `state` argument).
+/// - Formats output aesthetically (see `output` argument).
+#[derive(Args)]
+pub struct QueryArgs {
+    /* REQUIRED ARGUMENTS */
+    /// Target game name.
+    #[arg(
+        value_parser = game_parser(),
+        required_unless_present = "plugin",
+        conflicts_with = "plugin"
+    )]
+    pub target: Option<String>,
+
+    /* DEFAULTS PROVIDED */
+    /// Load the target game from a shared library plugin instead.
+    #[arg(long, conflicts_with = "target")]
+    pub plugin: Option<PathBuf>,
+    /// Query a specific variant of target.
+    #[arg(short, long)]
+    pub variant: Option<String>,
+    /// Read the variant of target from a file (such as a game description).
+    #[arg(long, conflicts_with_all = ["variant", "plugin"])]
+    pub rules: Option<PathBuf>,
+    /// Query a specific state of the variant (per its state protocol).
+    #[arg(short, long)]
+    pub state: Option<String>,
+    /// Specify whether the solution should be fetched or generated.
+    #[arg(short, long, default_value_t = IOMode::Find)]
+    pub mode: IOMode,
+    /// Set output in a specific format.
+    #[arg(short, long, default_value_t = OutputMode::Extra)]
+    pub output: OutputMode,
+    /// Query the misère version of target (last player to move loses).
+    #[arg(long)]
+    pub misere: bool,
+    /// Skips prompts for confirming destructive operations.
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+/// Checks that the solution set of a game variant persisted in a checkpoint
+/// directory (see `SolveArgs`) is consistent with the game, reporting the
+/// records which are not. Default behavior:
+///
+/// - Uses the target's default variant (see `variant` argument).
+/// - Checks every state reachable from the starting state of the variant (see
+///   `samples` argument).
+/// - Reports at most 10 inconsistent records (see `limit` argument).
+/// - Looks for the solution set in the data directory, where it is saved by
+///   solving with a checkpoint that names no directory (see `checkpoint`
+///   argument).
+#[derive(Args)]
+pub struct VerifyArgs {
+    /* REQUIRED ARGUMENTS */
+    /// Target game name.
+    #[arg(
+        value_parser = game_parser(),
+        required_unless_present = "plugin",
+        conflicts_with = "plugin"
+    )]
+    pub target: Option<String>,
+
+    /* DEFAULTS PROVIDED */
+    /// Directory where the solution set was saved by solving with checkpoints.
+    #[arg(long, default_value = DATA_DIRECTORY)]
+    pub checkpoint: PathBuf,
+    /// Load the target game from a shared library plugin instead.
+    #[arg(long, conflicts_with = "target")]
+    pub plugin: Option<PathBuf>,
+    /// Verify a specific variant of target.
+    #[arg(short, long)]
+    pub variant: Option<String>,
+    /// Read the variant of target from a file (such as a game description).
+    #[arg(long, conflicts_with_all = ["variant", "plugin"])]
+    pub rules: Option<PathBuf>,
+    /// Verify the misère version of target (last player to move loses).
+    #[arg(long)]
+    pub misere: bool,
+    /// Stop after finding this many inconsistent records.
+    #[arg(long, default_value_t = 10)]
+    pub limit: usize,
+    /// Only check the states visited by this many random walks from the start.
+    #[arg(long)]
+    pub samples: Option<usize>,
+    /// Seed the pseudo-random choices of the random walks.
+    #[arg(long, default_value_t = 0, requires = "samples")]
+    pub seed: u64,
+}
+
 /// Specifies the way in which a game's analysis happens. Uses the provided
-/// `analyzer` to analyze the `target` game. This uses the same logic on finding
-/// or generating missing data as the solving routine; see `cli::IOMode` for
-/// specifics.
+/// `analyzer` to analyze the `target` game, whose state graph is explored from
+/// its starting state. Default behavior:
+///
+/// - Uses the target's default variant (see `variant` argument).
+/// - Counts the states saved by exploiting symmetries (see `analyzer`
+///   argument).
+/// - Formats output aesthetically (see `output` argument).
 #[derive(Args)]
 pub struct AnalyzeArgs {
     /* REQUIRED ARGUMENTS */
     /// Target game name.
-    pub target: GameModule,
+    #[arg(value_parser = game_parser())]
+    pub target: String,
 
     /* DEFAULTS PROVIDED */
     /// Analyzer module to use.
-    #[arg(short, long)]
-    pub analyzer: Option<String>,
+    #[arg(short, long, default_value_t = Analyzer::Symmetry)]
+    pub analyzer: Analyzer,
     /// Analyze a specific variant of target.
     #[arg(short, long)]
     pub variant: Option<String>,
     /// Set output in a specific format.
     #[arg(short, long, default_value_t = OutputMode::Extra)]
     pub output: OutputMode,
+    /// Analyze the misère version of target (last player to move loses).
+    #[arg(long)]
+    pub misere: bool,
     /// Skips prompts for confirming destructive operations.
     #[arg(short, long)]
     pub yes: bool,
@@ -139,9 +322,13 @@ pub struct AnalyzeArgs {
 pub struct InfoArgs {
     /* REQUIRED ARGUMENTS */
     /// Specify game for which to provide information about.
-    pub target: GameModule,
+    #[arg(value_parser = game_parser())]
+    pub target: Option<String>,
 
     /* DEFAULTS PROVIDED */
+    /// Provide information about a shared library plugin game instead.
+    #[arg(long, conflicts_with = "target")]
+    pub plugin: Option<PathBuf>,
     /// Set output in a specific format.
     #[arg(short, long, default_value_t = OutputMode::Extra)]
     pub output: OutputMode,
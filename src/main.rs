@@ -37,11 +37,16 @@ fn main() {
         Commands::Tui(args) => tui(args),
         Commands::Info(args) => info(args),
         Commands::Solve(args) => solve(args),
+        Commands::Query(args) => query(args),
+        Commands::Verify(args) => verify(args),
         Commands::Analyze(args) => analyze(args),
     };
     if let Err(e) = ret {
         if !cli.quiet {
             eprintln!("{}", e);
+            for cause in e.chain().skip(1) {
+                eprintln!("- {}", cause);
+            }
         }
         process::exit(exitcode::USAGE)
     }
@@ -55,21 +60,126 @@ fn tui(args: &TuiArgs) -> Result<()> {
 }
 
 fn analyze(args: &AnalyzeArgs) -> Result<()> {
-    todo!()
+    let game = util::find_game(
+        &args.target,
+        args.variant.to_owned(),
+        None,
+        args.misere,
+    )?;
+    game.analyze(args.analyzer)?
+        .print(args.output);
+    Ok(())
 }
 
 fn solve(args: &SolveArgs) -> Result<()> {
     util::confirm_potential_overwrite(args.yes, args.mode);
-    let game = util::find_game(
-        args.target,
-        args.variant.to_owned(),
-        args.from.to_owned(),
-    )?;
-    game.solve(args.mode, args.solver)?;
+    let variant =
+        util::read_variant(args.variant.to_owned(), args.rules.as_deref())?;
+    let load = |variant: Option<String>| {
+        if let Some(path) = &args.plugin {
+            util::find_plugin(path, variant, args.from.to_owned(), args.misere)
+        } else {
+            util::find_game(
+                args.target
+                    .as_deref()
+                    .unwrap_or_default(),
+                variant,
+                args.from.to_owned(),
+                args.misere,
+            )
+        }
+    };
+
+    let game = load(variant)?;
+    if args.all_variants {
+        util::solve_variants(
+            game.as_ref(),
+            args.max_states.unwrap_or_default(),
+            args.mode,
+            |variant| load(Some(variant)),
+        )?;
+    } else {
+        let policy = solver::util::Policy {
+            win_by: args.win_by,
+            ..solver::util::Policy::new(args.tie_break, args.seed)
+        };
+
+        let resources = solver::util::Resources {
+            threads: args.threads,
+            checkpoint: args
+                .checkpoint
+                .clone()
+                .map(|directory| solver::checkpoint::Checkpoint {
+                    directory,
+                    interval: args.checkpoint_interval,
+                }),
+            memory_limit: args.memory_limit,
+        };
+
+        game.solve(args.mode, args.solver, policy, &resources)?;
+    }
+    Ok(())
+}
+
+fn query(args: &QueryArgs) -> Result<()> {
+    util::confirm_potential_overwrite(args.yes, args.mode);
+    let variant =
+        util::read_variant(args.variant.to_owned(), args.rules.as_deref())?;
+    let game = if let Some(path) = &args.plugin {
+        util::find_plugin(path, variant, None, args.misere)?
+    } else {
+        util::find_game(
+            args.target
+                .as_deref()
+                .unwrap_or_default(),
+            variant,
+            None,
+            args.misere,
+        )?
+    };
+    game.query(args.mode, args.state.to_owned())?
+        .print(args.output);
+    Ok(())
+}
+
+fn verify(args: &VerifyArgs) -> Result<()> {
+    let variant =
+        util::read_variant(args.variant.to_owned(), args.rules.as_deref())?;
+    let game = if let Some(path) = &args.plugin {
+        util::find_plugin(path, variant, None, args.misere)?
+    } else {
+        util::find_game(
+            args.target
+                .as_deref()
+                .unwrap_or_default(),
+            variant,
+            None,
+            args.misere,
+        )?
+    };
+
+    let audit = solver::verification::Audit {
+        limit: args.limit,
+        samples: args.samples,
+        seed: args.seed,
+    };
+
+    let report = game.verify(&args.checkpoint, audit)?;
+    println!("{}", report);
+    if !report.consistent() {
+        Err(solver::error::SolverError::InconsistentSolution {
+            game_id: game.id(),
+            inconsistencies: report.inconsistencies.len(),
+        })?
+    }
     Ok(())
 }
 
 fn info(args: &InfoArgs) -> Result<()> {
-    util::print_game_info(args.target, args.output)?;
+    util::print_game_info(
+        args.target.as_deref(),
+        args.plugin.as_deref(),
+        args.output,
+    )?;
     Ok(())
 }
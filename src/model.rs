@@ -20,6 +20,12 @@ pub type State = u64;
 /// limitation to player count that is dependent on the target architecture.
 pub type Turn = usize;
 
+/// Encodes a move that can be made from a state of a game in a 64-bit unsigned
+/// integer. The meaning of this integer is up to each game implementation, but
+/// it should be enough to determine the resulting state when paired with the
+/// state the move is made from.
+pub type Move = u64;
+
 /* ATTRIBUTE TYPES */
 
 /// A measure of how "good" an outcome is for a given player in a game. Positive
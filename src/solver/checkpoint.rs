@@ -0,0 +1,227 @@
+//! # Solver Checkpoint Module
+//!
+//! This module provides the means for solvers to periodically save their
+//! progress to persistent media, so that solving can be resumed after being
+//! interrupted. A checkpoint consists of the persisted solution set table of
+//! a game (whose metadata includes the states that the solver had yet to
+//! traverse, see `FRONTIER_KEY`), and a manifest file keyed by the game's ID.
+//!
+//! The manifest is only written after the table is persisted, and the table is
+//! persisted atomically (see `Persistent`). Hence, the records and the frontier
+//! in a persisted table always agree with each other, and the presence of a
+//! manifest means that its table can be resumed from.
+
+use anyhow::{Context, Result};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::database::{Persistent, Tabular};
+use crate::game::Game;
+use crate::interface::IOMode;
+use crate::model::State;
+use crate::solver::error::SolverError;
+use crate::solver::util::{Policy, Resources};
+use crate::solver::FRONTIER_KEY;
+
+/* DEFINITIONS */
+
+/// Specifies that solvers should save their progress to `directory` every
+/// time they visit `interval` more states, such that solving can be resumed
+/// from the last checkpoint by solving under `IOMode::Find`.
+#[derive(Clone)]
+pub struct Checkpoint {
+    pub directory: PathBuf,
+    pub interval: u64,
+}
+
+/// The contents of the manifest of a checkpoint, which describe the solution
+/// set of `game` being computed under `policy` (as described by `describe`),
+/// and whether it is `complete`.
+struct Manifest {
+    game: String,
+    policy: String,
+    complete: bool,
+}
+
+/* CHECKPOINTING */
+
+/// Prepares `db` for solving `game` under `policy`, returning the frontier of
+/// states to continue traversing from if there is a checkpoint to resume. This
+/// is only the case when solving under `IOMode::Find` with a checkpoint whose
+/// manifest is found in the directory of `resources.checkpoint`, in which case
+/// the table of the checkpoint is loaded and selected in `db` (and the frontier
+/// is empty if the checkpoint is complete). Fails if the checkpoint was
+/// computed under a different policy.
+pub fn resume<D, G>(
+    db: &mut D,
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<Option<Vec<State>>>
+where
+    D: Tabular + Persistent,
+    G: Game,
+{
+    let checkpoint = match &resources.checkpoint {
+        Some(checkpoint) => checkpoint,
+        None => return Ok(None),
+    };
+
+    fs::create_dir_all(&checkpoint.directory)
+        .context("Failed to create checkpoint directory.")?;
+    db.bind_path(&checkpoint.directory)
+        .context("Failed to bind database to checkpoint directory.")?;
+
+    let id = game.id();
+    let path = manifest_path(&checkpoint.directory, &id);
+    if mode == IOMode::Write || !path.is_file() {
+        return Ok(None);
+    }
+
+    let manifest = Manifest::read(&path, &id)?;
+    if manifest.game != id {
+        Err(SolverError::CorruptedCheckpoint {
+            game_id: id.clone(),
+        })?
+    } else if manifest.policy != describe(policy) {
+        Err(SolverError::CheckpointMismatch {
+            game_id: id.clone(),
+            found: manifest.policy,
+            expected: describe(policy),
+        })?
+    }
+
+    db.load_table(&id)
+        .context("Failed to load checkpoint table.")?;
+    db.select_table(&id)
+        .context("Failed to select checkpoint table.")?;
+    if manifest.complete {
+        return Ok(Some(Vec::new()));
+    }
+
+    db.get_metadata(FRONTIER_KEY)
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|state| state.parse::<State>())
+        .collect::<Result<Vec<State>, _>>()
+        .map(Some)
+        .map_err(|_| SolverError::CorruptedCheckpoint { game_id: id }.into())
+}
+
+/// Returns true if a checkpoint is due with `resources` after visiting
+/// `visited` states since the start of a solver's execution.
+pub fn due(resources: &Resources, visited: u64) -> bool {
+    resources
+        .checkpoint
+        .as_ref()
+        .is_some_and(|c| visited.is_multiple_of(c.interval.max(1)))
+}
+
+/// Saves a checkpoint of solving `game` under `policy` into the directory of
+/// `resources.checkpoint`, with the currently selected table of `db` and the
+/// `frontier` of states which remain to be traversed. A checkpoint without a
+/// frontier is saved as complete. Does nothing if there is no checkpoint.
+pub fn save<D, G>(
+    db: &mut D,
+    game: &G,
+    policy: Policy,
+    resources: &Resources,
+    frontier: Option<&[State]>,
+) -> Result<()>
+where
+    D: Tabular + Persistent,
+    G: Game,
+{
+    let checkpoint = match &resources.checkpoint {
+        Some(checkpoint) => checkpoint,
+        None => return Ok(()),
+    };
+
+    let states = frontier
+        .unwrap_or_default()
+        .iter()
+        .map(|state| state.to_string())
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    db.set_metadata(FRONTIER_KEY, &states)
+        .context("Failed to record frontier in table metadata.")?;
+    db.materialize()
+        .context("Failed to persist checkpoint table.")?;
+
+    let id = game.id();
+    let manifest = Manifest {
+        game: id.clone(),
+        policy: describe(policy),
+        complete: frontier.is_none(),
+    };
+
+    manifest
+        .write(&manifest_path(&checkpoint.directory, &id))
+        .context("Failed to write checkpoint manifest.")?;
+    Ok(())
+}
+
+/* HELPER FUNCTIONS */
+
+/// Returns the path of the manifest of the checkpoint of the game with `id`
+/// within `directory`.
+fn manifest_path(directory: &Path, id: &str) -> PathBuf {
+    directory.join(format!("{}.checkpoint", id))
+}
+
+/// Returns a description of the parts of `policy` which affect the solution
+/// set computed under it.
+fn describe(policy: Policy) -> String {
+    if policy.win_by {
+        format!("{} with win-by", policy)
+    } else {
+        policy.to_string()
+    }
+}
+
+impl Manifest {
+    /// Returns the manifest of the game with `id` persisted at `path`.
+    fn read(path: &Path, id: &str) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .context("Failed to read checkpoint manifest.")?;
+
+        let corrupted = || SolverError::CorruptedCheckpoint {
+            game_id: id.to_owned(),
+        };
+
+        let mut lines = contents.lines();
+        let mut field = |key: &str| {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(key))
+                .and_then(|line| line.strip_prefix(' '))
+                .map(|value| value.to_owned())
+                .ok_or_else(corrupted)
+        };
+
+        Ok(Self {
+            game: field("game")?,
+            policy: field("policy")?,
+            complete: field("complete")?
+                .parse()
+                .map_err(|_| corrupted())?,
+        })
+    }
+
+    /// Persists this manifest at `path`, replacing any previous manifest.
+    fn write(&self, path: &Path) -> Result<()> {
+        let contents = format!(
+            "game {}\npolicy {}\ncomplete {}\n",
+            self.game, self.policy, self.complete
+        );
+
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, contents)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
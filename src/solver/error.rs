@@ -9,21 +9,447 @@
 //!
 //! - Max Fierro, 2/24/2024 (maxfierro@berkeley.edu)
 
-use std::{error::Error, fmt};
+use std::{error::Error, fmt, ops::RangeInclusive};
+
+use crate::model::{PlayerCount, Remoteness, State, Turn, Utility};
 
 /* ERROR WRAPPER */
 
 /// Wrapper for all solver-related errors that could happen during runtime. This
 /// pertains specifically to the elements of the `crate::solver` module.
 #[derive(Debug)]
-pub enum SolverError {}
+pub enum SolverError {
+    /// An error to indicate that a player obtains a `utility` at `state` of the
+    /// game with `game_id` which is outside of the `bounds` that the game
+    /// declared on its utility values (see `crate::game::Limited`).
+    UtilityOutOfBounds {
+        game_id: String,
+        state: State,
+        utility: Utility,
+        bounds: RangeInclusive<Utility>,
+    },
+
+    /// An error to indicate that the `remoteness` of `state` in the game with
+    /// `game_id` exceeds the maximum remoteness `bound` that the game declared
+    /// (see `crate::game::Limited`).
+    RemotenessOutOfBounds {
+        game_id: String,
+        state: State,
+        remoteness: Remoteness,
+        bound: Remoteness,
+    },
+
+    /// An error to indicate that the `utility` obtained by a player at `state`
+    /// of the game with `game_id` does not fit in the `size` bits dedicated to
+    /// each utility value in the records of the game.
+    RecordOverflow {
+        game_id: String,
+        state: State,
+        utility: Utility,
+        size: usize,
+    },
+
+    /// An error to indicate that the `remoteness` of `state` in the game with
+    /// `game_id` does not fit in the `size` bits dedicated to remoteness in the
+    /// records of the game.
+    RemotenessOverflow {
+        game_id: String,
+        state: State,
+        remoteness: Remoteness,
+        size: usize,
+    },
+
+    /// An error to indicate that a solver attempted to store or read the utility
+    /// of `found` players at `state` of the game with `game_id`, whose records
+    /// hold the utility values of `expected` players.
+    PlayerCountMismatch {
+        game_id: String,
+        state: State,
+        expected: PlayerCount,
+        found: PlayerCount,
+    },
+
+    /// An error to indicate that the utility of `player` was accessed in a
+    /// record which only holds the utility values of `players` players.
+    RecordPlayerOutOfRange { player: Turn, players: PlayerCount },
+
+    /// An error to indicate that `found` utility values were stored in a record
+    /// which holds the utility values of `expected` players.
+    RecordPlayerMismatch {
+        expected: PlayerCount,
+        found: PlayerCount,
+    },
+
+    /// An error to indicate that the `value` of the record `attribute` does not
+    /// fit in the `size` bits dedicated to it, or is negative while the
+    /// attribute is unsigned.
+    RecordAttributeOverflow {
+        attribute: &'static str,
+        value: i128,
+        size: usize,
+    },
+
+    /// An error to indicate that the win-by margin of a record which does not
+    /// store one was accessed.
+    RecordWithoutWinBy,
+
+    /// An error to indicate that a record of `length` bits was parsed under a
+    /// layout of records which have `expected` bits.
+    RecordLengthMismatch { length: usize, expected: usize },
+
+    /// An error to indicate that the record of `state` in the solution set of
+    /// the game with `game_id` has `length` bits, while the records of the game
+    /// have `expected` bits.
+    CorruptedRecord {
+        game_id: String,
+        state: State,
+        length: usize,
+        expected: usize,
+    },
+
+    /// An error to indicate that there are `transitions` states one move away
+    /// from `state` in the game with `game_id`, which is more than the `limit`
+    /// supported by solvers (see `crate::solver::MAX_TRANSITIONS`).
+    TransitionLimitExceeded {
+        game_id: String,
+        state: State,
+        transitions: usize,
+        limit: usize,
+    },
+
+    /// An error to indicate that `state` in the game with `game_id` could not
+    /// be solved by propagating outcomes in retrograde from the end states of
+    /// the game, because not all of its children were reached in this way.
+    IncompleteRetrograde { game_id: String, state: State },
+
+    /// An error to indicate that the game with `game_id` was provided to a
+    /// solver which requires an acyclic state graph, but the game reported at
+    /// runtime that its state graph may contain cycles.
+    CyclicGame { game_id: String },
+
+    /// An error to indicate that the `utility` values of the players at the
+    /// end `state` of the game with `game_id` do not add up to zero, while it
+    /// was provided to a solver for zero-sum games.
+    NonZeroSum {
+        game_id: String,
+        state: State,
+        utility: Vec<Utility>,
+    },
+
+    /// An error to indicate that `player` has `actions` available at the
+    /// non-terminal `state` of the simultaneous-move game with `game_id`, while
+    /// solvers require that players have between 1 and `limit` actions there
+    /// (see `crate::solver::record::mes::MAX_ACTIONS`).
+    ActionCountOutOfRange {
+        game_id: String,
+        state: State,
+        player: Turn,
+        actions: usize,
+        limit: usize,
+    },
+
+    /// An error to indicate that the game with `game_id` was provided to a
+    /// solver running on multiple threads along with a `resource` (such as a
+    /// checkpoint or a memory limit) which that solver does not support.
+    UnsupportedResource { game_id: String, resource: String },
+
+    /// An error to indicate that the game with `game_id` was provided to a
+    /// solver whose records have no room for win-by margins, under a policy
+    /// which requests that they be recorded.
+    UnsupportedWinBy { game_id: String },
+
+    /// An error to indicate that a checkpoint of the solution set of the game
+    /// with `game_id` was found to resume from, but that it was computed under
+    /// the policy described by `found` instead of the `expected` one.
+    CheckpointMismatch {
+        game_id: String,
+        found: String,
+        expected: String,
+    },
+
+    /// An error to indicate that the checkpoint of the solution set of the game
+    /// with `game_id` is malformed, and cannot be resumed from.
+    CorruptedCheckpoint { game_id: String },
+
+    /// An error to indicate that the persisted solution set of the game with
+    /// `game_id` records that it was computed under the tie-breaking `policy`,
+    /// which is not one that solvers use.
+    UnrecognizedPolicy { game_id: String, policy: String },
+
+    /// An error to indicate that verifying the persisted solution set of the
+    /// game with `game_id` found `inconsistencies` among its records.
+    InconsistentSolution {
+        game_id: String,
+        inconsistencies: usize,
+    },
+}
 
 impl Error for SolverError {}
 
 impl fmt::Display for SolverError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            _ => todo!(),
+            Self::UtilityOutOfBounds {
+                game_id,
+                state,
+                utility,
+                bounds,
+            } => {
+                write!(
+                    f,
+                    "A utility of {} was found at state {} of the game '{}', \
+                    which is outside of the declared range [{}, {}]. The game \
+                    must declare wider bounds through 'Limited' to be solved.",
+                    utility,
+                    state,
+                    game_id,
+                    bounds.start(),
+                    bounds.end(),
+                )
+            },
+            Self::RemotenessOutOfBounds {
+                game_id,
+                state,
+                remoteness,
+                bound,
+            } => {
+                write!(
+                    f,
+                    "A remoteness of {} was found at state {} of the game \
+                    '{}', which exceeds the declared maximum of {}. The game \
+                    must declare a higher bound through 'Limited' to be \
+                    solved.",
+                    remoteness, state, game_id, bound,
+                )
+            },
+            Self::RecordOverflow {
+                game_id,
+                state,
+                utility,
+                size,
+            } => {
+                write!(
+                    f,
+                    "A utility of {} was found at state {} of the game '{}', \
+                    which does not fit in the {} bits dedicated to each \
+                    utility value in its records. The game must declare its \
+                    utility bounds through 'Limited' to be solved.",
+                    utility, state, game_id, size,
+                )
+            },
+            Self::RemotenessOverflow {
+                game_id,
+                state,
+                remoteness,
+                size,
+            } => {
+                write!(
+                    f,
+                    "A remoteness of {} was found at state {} of the game \
+                    '{}', which does not fit in the {} bits dedicated to \
+                    remoteness in its records. The game must declare its \
+                    maximum remoteness through 'Limited' to be solved.",
+                    remoteness, state, game_id, size,
+                )
+            },
+            Self::PlayerCountMismatch {
+                game_id,
+                state,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "Attempted to access the utility of {} players at state {} \
+                    of the game '{}', but its records are for {} players. The \
+                    game's player count must agree with the utility vectors it \
+                    provides.",
+                    found, state, game_id, expected,
+                )
+            },
+            Self::RecordPlayerOutOfRange { player, players } => {
+                write!(
+                    f,
+                    "Attempted to access the utility of player {} in a record \
+                    which holds the utility values of {} players.",
+                    player, players,
+                )
+            },
+            Self::RecordPlayerMismatch { expected, found } => {
+                write!(
+                    f,
+                    "Attempted to store {} utility values in a record which \
+                    holds the utility values of {} players.",
+                    found, expected,
+                )
+            },
+            Self::RecordAttributeOverflow {
+                attribute,
+                value,
+                size,
+            } => {
+                write!(
+                    f,
+                    "The value {} cannot be stored as the {} of a record, \
+                    which is encoded in {} bits.",
+                    value, attribute, size,
+                )
+            },
+            Self::RecordWithoutWinBy => {
+                write!(
+                    f,
+                    "Attempted to access the win-by margin of a record which \
+                    does not store one.",
+                )
+            },
+            Self::RecordLengthMismatch { length, expected } => {
+                write!(
+                    f,
+                    "A record of {} bits cannot be parsed, as records with its \
+                    layout have exactly {} bits.",
+                    length, expected,
+                )
+            },
+            Self::CorruptedRecord {
+                game_id,
+                state,
+                length,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "The record of state {} of the game '{}' has {} bits, but \
+                    its records should have {} bits. The solution set may have \
+                    been computed with a different record layout, and should \
+                    be computed again.",
+                    state, game_id, length, expected,
+                )
+            },
+            Self::TransitionLimitExceeded {
+                game_id,
+                state,
+                transitions,
+                limit,
+            } => {
+                write!(
+                    f,
+                    "The state {} of the game '{}' has {} possible transitions, \
+                    but solvers support at most {}. The game must have fewer \
+                    moves available from each of its states to be solved.",
+                    state, game_id, transitions, limit,
+                )
+            },
+            Self::IncompleteRetrograde { game_id, state } => {
+                write!(
+                    f,
+                    "The state {} of the game '{}' could not be solved in \
+                    retrograde, as not all of its children were reached from \
+                    the end states of the game. The game's terminal states and \
+                    retrograde transitions must agree with its prograde \
+                    transitions.",
+                    state, game_id,
+                )
+            },
+            Self::CyclicGame { game_id } => {
+                write!(
+                    f,
+                    "The state graph of the game '{}' may contain cycles, so \
+                    it cannot be solved by backward induction. Only solvers \
+                    which support cyclic games can be used with it.",
+                    game_id,
+                )
+            },
+            Self::NonZeroSum {
+                game_id,
+                state,
+                utility,
+            } => {
+                write!(
+                    f,
+                    "The utility values {:?} of the players at the end state \
+                    {} of the game '{}' do not add up to zero, so it cannot be \
+                    solved as a zero-sum game.",
+                    utility, state, game_id,
+                )
+            },
+            Self::ActionCountOutOfRange {
+                game_id,
+                state,
+                player,
+                actions,
+                limit,
+            } => {
+                write!(
+                    f,
+                    "Player {} has {} actions available at the non-terminal \
+                    state {} of the game '{}', but solvers require that \
+                    players have between 1 and {} actions at such states.",
+                    player, actions, state, game_id, limit,
+                )
+            },
+            Self::UnsupportedResource { game_id, resource } => {
+                write!(
+                    f,
+                    "The game '{}' was requested to be solved on multiple \
+                    threads with {}, which is not supported by its parallel \
+                    solver. The game must be solved on a single thread to use \
+                    it.",
+                    game_id, resource,
+                )
+            },
+            Self::UnsupportedWinBy { game_id } => {
+                write!(
+                    f,
+                    "The game '{}' was requested to be solved with win-by \
+                    margins, which are not recorded by its solver. The game \
+                    must be solved without them.",
+                    game_id,
+                )
+            },
+            Self::CheckpointMismatch {
+                game_id,
+                found,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "Found a checkpoint of the game '{}' to resume from, but \
+                    it was computed under the policy '{}' instead of '{}'. \
+                    Solve in write mode to discard it and start over.",
+                    game_id, found, expected,
+                )
+            },
+            Self::CorruptedCheckpoint { game_id } => {
+                write!(
+                    f,
+                    "The checkpoint of the game '{}' is malformed, so it \
+                    cannot be resumed from. Solve in write mode to discard it \
+                    and start over.",
+                    game_id,
+                )
+            },
+            Self::UnrecognizedPolicy { game_id, policy } => {
+                write!(
+                    f,
+                    "The solution set of the game '{}' was computed under the \
+                    tie-breaking policy '{}', which is not recognized. The \
+                    solution set may be corrupted, and should be computed \
+                    again.",
+                    game_id, policy,
+                )
+            },
+            Self::InconsistentSolution {
+                game_id,
+                inconsistencies,
+            } => {
+                write!(
+                    f,
+                    "Found {} inconsistent records in the solution set of the \
+                    game '{}'. The solution set should not be trusted, and \
+                    should be computed again.",
+                    inconsistencies, game_id,
+                )
+            },
         }
     }
 }
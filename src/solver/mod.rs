@@ -19,14 +19,36 @@
 //!
 //! - Max Fierro, 4/6/2023 (maxfierro@berkeley.edu)
 
+use crate::model::PlayerCount;
+use crate::solvable;
+
 /* CONSTANTS */
 
 /// Describes the maximum number of states that are one move away from any state
 /// within a game. Used to allocate statically-sized arrays on the stack for
 /// faster execution of solving algorithms. If this limit is violated by a game
-/// implementation, this program should panic.
+/// implementation, solvers fail with `SolverError::TransitionLimitExceeded`.
 pub const MAX_TRANSITIONS: usize = 50;
 
+/// The key of the table metadata entry where solvers that are sensitive to
+/// tie-breaking store the name of the policy used to compute a solution set.
+pub const TIE_BREAK_KEY: &str = "tie-break";
+
+/// The key of the table metadata entry where solvers that save checkpoints
+/// store the states which they had yet to traverse at the last checkpoint.
+pub const FRONTIER_KEY: &str = "frontier";
+
+/// The player counts that solvers are generated for in games whose player count
+/// is only known when they are initialized (see `solvable!`), in increasing
+/// order. Sessions of these games with other player counts are rejected.
+pub const SOLVABLE_PLAYERS: &[PlayerCount] = {
+    macro_rules! counts {
+        ($($n:literal),+) => { &[$($n),+] };
+    }
+
+    solvable! { counts }
+};
+
 /* SOLVER MODULES */
 
 /// Solving algorithms for games that are either of incomplete information or
@@ -43,6 +65,7 @@ pub mod stochastic {
 pub mod strong {
     pub mod acyclic;
     pub mod cyclic;
+    pub mod simultaneous;
 }
 
 /// Solving algorithms for deterministic complete-information games that only
@@ -53,7 +76,20 @@ pub mod weak {
     pub mod cyclic;
 }
 
+/* RECORD MODULES */
+
+/// Implementations of records that can be used by solving algorithms to store
+/// or persist the information they compute about a game, and communicate it to
+/// a database system.
+pub mod record {
+    pub mod mes;
+    pub mod mur;
+    pub mod sur;
+}
+
 /* UTILITY MODULES */
 
+pub mod checkpoint;
 pub mod error;
 pub mod util;
+pub mod verification;
@@ -0,0 +1,214 @@
+//! # Mixed Equilibrium Strategy (MES) Record Module
+//!
+//! Implementation of a database record buffer for storing the value of a game
+//! state under a mixed-strategy equilibrium of a two-player zero-sum game, in
+//! addition to the support of each player's equilibrium strategy (the actions
+//! which they choose with non-zero probability) at that state.
+
+use anyhow::{anyhow, Context, Result};
+use bitvec::prelude::*;
+
+use crate::database::{Attribute, Datatype, Record, Schema, SchemaBuilder};
+use crate::model::Turn;
+
+/* CONSTANTS */
+
+/// The exact number of bits that are used to encode the equilibrium value.
+pub const VALUE_SIZE: usize = 64;
+
+/// The exact number of bits that are used to encode one player's support.
+pub const SUPPORT_SIZE: usize = 32;
+
+/// The number of players whose strategies are described by a record.
+pub const PLAYERS: usize = 2;
+
+/// The exact number of bits that are used to encode a record.
+pub const BUFFER_SIZE: usize = VALUE_SIZE + PLAYERS * SUPPORT_SIZE;
+
+/// The maximum number of actions any player can have at a state, since each
+/// action is represented by one bit in the player's support.
+pub const MAX_ACTIONS: usize = SUPPORT_SIZE;
+
+/* SCHEMA GENERATOR */
+
+/// Return the database table schema associated with this record layout.
+pub fn schema() -> Result<Schema> {
+    let name = "P0 equilibrium value";
+    let data = Datatype::DPFP;
+    let size = VALUE_SIZE;
+    let mut schema = SchemaBuilder::new()
+        .add(Attribute::new(name, data, size))
+        .context("Failed to add value attribute to database schema.")?;
+
+    for i in 0..PLAYERS {
+        let name = &format!("P{} strategy support", i);
+        let data = Datatype::UINT;
+        let size = SUPPORT_SIZE;
+        schema = schema
+            .add(Attribute::new(name, data, size))
+            .context("Failed to add support attribute to database schema.")?;
+    }
+
+    Ok(schema.build())
+}
+
+/* RECORD IMPLEMENTATION */
+
+/// Solver-specific record entry, meant to communicate the equilibrium value
+/// and the strategy supports at a corresponding game state. The layout is as
+/// follows:
+///
+/// ```none
+/// [VALUE_SIZE bits: P0 equilibrium value]
+/// [SUPPORT_SIZE bits: P0 strategy support]
+/// [SUPPORT_SIZE bits: P1 strategy support]
+/// ```
+///
+/// The value is the expected utility of player 0 (which is the negative of the
+/// expected utility of player 1), encoded as a big-endian IEEE 754 double. The
+/// support of each player is a bit set where bit `i` (counting from the least
+/// significant bit) is set if their `i`th action is in the support.
+pub struct RecordBuffer {
+    buf: BitArr!(for BUFFER_SIZE, in u8, Msb0),
+}
+
+impl Record for RecordBuffer {
+    #[inline(always)]
+    fn raw(&self) -> &BitSlice<u8, Msb0> {
+        &self.buf[..BUFFER_SIZE]
+    }
+}
+
+impl RecordBuffer {
+    /// Returns a new instance of a bit-packed record buffer.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self {
+            buf: bitarr!(u8, Msb0; 0; BUFFER_SIZE),
+        }
+    }
+
+    /// Return a new instance with `bits` as the underlying buffer. Fails in the
+    /// event that the size of `bits` is incoherent with the record.
+    #[inline(always)]
+    pub fn from(bits: &BitSlice<u8, Msb0>) -> Result<Self> {
+        if bits.len() != BUFFER_SIZE {
+            Err(anyhow!(
+                "A record of {} bits cannot be parsed, as records must have \
+                exactly {} bits.",
+                bits.len(),
+                BUFFER_SIZE,
+            ))
+        } else {
+            let mut buf = bitarr!(u8, Msb0; 0; BUFFER_SIZE);
+            buf.copy_from_bitslice(bits);
+            Ok(Self { buf })
+        }
+    }
+
+    /* GET METHODS */
+
+    /// Parse and return the equilibrium value of player 0.
+    #[inline(always)]
+    pub fn get_value(&self) -> f64 {
+        f64::from_bits(self.buf[..VALUE_SIZE].load_be::<u64>())
+    }
+
+    /// Parse and return the indices of the actions in the support of the
+    /// strategy of `player`. Fails if there is no such player.
+    #[inline(always)]
+    pub fn get_support(&self, player: Turn) -> Result<Vec<usize>> {
+        let start = Self::support_index(player)?;
+        let set = self.buf[start..start + SUPPORT_SIZE].load_be::<u32>();
+        Ok((0..MAX_ACTIONS)
+            .filter(|i| set & (1 << i) != 0)
+            .collect())
+    }
+
+    /* SET METHODS */
+
+    /// Set this entry to have `value` as the equilibrium value of player 0.
+    #[inline(always)]
+    pub fn set_value(&mut self, value: f64) {
+        self.buf[..VALUE_SIZE].store_be(value.to_bits());
+    }
+
+    /// Set this entry to have the actions with the indices in `support` as the
+    /// support of the strategy of `player`. Fails if there is no such player,
+    /// or if any of the indices is not lower than `MAX_ACTIONS`.
+    #[inline(always)]
+    pub fn set_support(
+        &mut self,
+        player: Turn,
+        support: &[usize],
+    ) -> Result<()> {
+        let start = Self::support_index(player)?;
+        let mut set: u32 = 0;
+        for &i in support {
+            if i >= MAX_ACTIONS {
+                return Err(anyhow!(
+                    "Supports can only contain the first {} actions of a \
+                    player, but action {} was provided.",
+                    MAX_ACTIONS,
+                    i,
+                ));
+            }
+            set |= 1 << i;
+        }
+
+        self.buf[start..start + SUPPORT_SIZE].store_be(set);
+        Ok(())
+    }
+
+    /* LAYOUT HELPER METHODS */
+
+    /// Return the bit index of the start of `player`'s support, failing if
+    /// there is no such player.
+    #[inline(always)]
+    fn support_index(player: Turn) -> Result<usize> {
+        if player >= PLAYERS {
+            Err(anyhow!(
+                "There is no player {} in a record for {} players.",
+                player,
+                PLAYERS,
+            ))
+        } else {
+            Ok(VALUE_SIZE + player * SUPPORT_SIZE)
+        }
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn schema_size_matches_record_size() {
+        let record = RecordBuffer::new();
+        assert_eq!(schema().unwrap().size(), record.raw().len());
+    }
+
+    #[test]
+    fn value_and_supports_survive_round_trip() {
+        let mut record = RecordBuffer::new();
+        record.set_value(-0.2);
+        record
+            .set_support(0, &[0, 2, 31])
+            .unwrap();
+        record
+            .set_support(1, &[5])
+            .unwrap();
+
+        let copy = RecordBuffer::from(record.raw()).unwrap();
+        assert_eq!(copy.get_value(), -0.2);
+        assert_eq!(copy.get_support(0).unwrap(), vec![0, 2, 31]);
+        assert_eq!(copy.get_support(1).unwrap(), vec![5]);
+        assert!(copy.get_support(2).is_err());
+        assert!(record
+            .set_support(0, &[32])
+            .is_err());
+    }
+}
@@ -0,0 +1,598 @@
+//! # Multi-Utility Remoteness (MUR) Record Module
+//!
+//! Implementation of a database record buffer for storing the utility values
+//! of an arbitrary number of players, in addition to the remoteness of a game
+//! state. This record is shared by all solvers which compute these attributes.
+//! Records can optionally also store the win-by margin of a state, which is the
+//! amount by which the utility of the best-off player exceeds that of the
+//! runner-up under optimal play (see `margin`).
+//!
+//! The number of bits used for each attribute is described by a `Layout`,
+//! which can be derived from the bounds that a game declares on its utility
+//! values and remoteness. Records whose layout does not fit in `BUFFER_SIZE`
+//! bits are stored in heap-allocated memory instead.
+
+use anyhow::{anyhow, Context, Result};
+use bitvec::prelude::*;
+
+use std::ops::RangeInclusive;
+
+use crate::database::{Attribute, Datatype, Record, Schema, SchemaBuilder};
+use crate::model::{PlayerCount, Remoteness, Turn, Utility};
+use crate::solver::error::SolverError;
+use crate::solver::util;
+
+/* CONSTANTS */
+
+/// The default number of bits that are used to encode remoteness.
+pub const REMOTENESS_SIZE: usize = 16;
+
+/// The maximum number of bits of a record that is stored without allocating
+/// memory on the heap.
+pub const BUFFER_SIZE: usize = 128;
+
+/// The default number of bits that are used to encode utility for one player.
+pub const UTILITY_SIZE: usize = 8;
+
+/// The names of the attributes of the schemas generated by this module.
+const REMOTENESS_NAME: &str = "State remoteness";
+const WIN_BY_NAME: &str = "Win-by margin";
+
+/* SCHEMA GENERATOR */
+
+/// Return the database table schema associated with a record instance with
+/// a specific number of `players` under the default layout of this record.
+pub fn schema(players: PlayerCount) -> Result<Schema> {
+    Layout::new(players).schema()
+}
+
+/* WIN-BY MARGIN */
+
+/// Returns the amount by which the highest utility value in `utility` exceeds
+/// the second highest one, which is zero if several players share the highest
+/// utility (or if there is only one player).
+pub fn margin(utility: &[Utility]) -> Utility {
+    let mut sorted = utility.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    match sorted[..] {
+        [first, second, ..] => first - second,
+        _ => 0,
+    }
+}
+
+/* RECORD LAYOUT */
+
+/// Describes the number of players whose utility values are stored in a record
+/// and the number of bits used for each of its attributes. A win-by margin is
+/// only stored if `win_by_size` is not zero.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub players: PlayerCount,
+    pub utility_size: usize,
+    pub remoteness_size: usize,
+    pub win_by_size: usize,
+}
+
+impl Layout {
+    /// Returns the default layout of records for `players`, which does not
+    /// include a win-by margin.
+    pub const fn new(players: PlayerCount) -> Self {
+        Self {
+            players,
+            utility_size: UTILITY_SIZE,
+            remoteness_size: REMOTENESS_SIZE,
+            win_by_size: 0,
+        }
+    }
+
+    /// Returns the smallest layout of records for `players` which can encode
+    /// any utility value within `utility` and any remoteness up to (and
+    /// including) `remoteness`.
+    pub fn fitting(
+        players: PlayerCount,
+        utility: RangeInclusive<Utility>,
+        remoteness: Remoteness,
+    ) -> Self {
+        let utility_size = util::min_sbits(*utility.start())
+            .max(util::min_sbits(*utility.end()))
+            .max(2);
+
+        Self {
+            players,
+            utility_size,
+            remoteness_size: util::min_ubits(remoteness).max(1),
+            win_by_size: 0,
+        }
+    }
+
+    /// Returns this layout with enough room for the margin between any two
+    /// utility values that fit in it.
+    pub const fn with_win_by(self) -> Self {
+        Self {
+            win_by_size: self.utility_size,
+            ..self
+        }
+    }
+
+    /// Returns true if records with this layout store a win-by margin.
+    pub const fn win_by(&self) -> bool {
+        self.win_by_size != 0
+    }
+
+    /// Returns the number of bits in a record with this layout.
+    pub const fn size(&self) -> usize {
+        self.players * self.utility_size
+            + self.remoteness_size
+            + self.win_by_size
+    }
+
+    /// Returns the database table schema of records with this layout.
+    pub fn schema(&self) -> Result<Schema> {
+        let mut schema = SchemaBuilder::new();
+        for i in 0..self.players {
+            let name = &format!("P{} utility", i);
+            let data = Datatype::SINT;
+            let size = self.utility_size;
+            schema = schema
+                .add(Attribute::new(name, data, size))
+                .context(
+                    "Failed to add utility attribute to database schema.",
+                )?;
+        }
+
+        let name = REMOTENESS_NAME;
+        let data = Datatype::UINT;
+        let size = self.remoteness_size;
+        schema = schema
+            .add(Attribute::new(name, data, size))
+            .context(
+                "Failed to add remoteness attribute to database schema.",
+            )?;
+
+        if self.win_by() {
+            let name = WIN_BY_NAME;
+            let data = Datatype::UINT;
+            let size = self.win_by_size;
+            schema = schema
+                .add(Attribute::new(name, data, size))
+                .context(
+                    "Failed to add win-by attribute to database schema.",
+                )?;
+        }
+
+        Ok(schema.build())
+    }
+
+    /// Returns the layout of the records described by `schema`, which should
+    /// have been generated by `Layout::schema`. Fails otherwise.
+    pub fn from_schema(schema: &Schema) -> Result<Self> {
+        let mut layout = Self {
+            players: 0,
+            utility_size: UTILITY_SIZE,
+            remoteness_size: 0,
+            win_by_size: 0,
+        };
+
+        for attr in schema.iter() {
+            match attr.name() {
+                REMOTENESS_NAME => layout.remoteness_size = attr.size(),
+                WIN_BY_NAME => layout.win_by_size = attr.size(),
+                name if name == format!("P{} utility", layout.players) => {
+                    layout.utility_size = attr.size();
+                    layout.players += 1;
+                },
+                name => {
+                    return Err(anyhow!(
+                        "The attribute '{}' is not part of any record layout.",
+                        name,
+                    ))
+                },
+            }
+        }
+
+        if layout.remoteness_size == 0 {
+            Err(anyhow!("The schema has no remoteness attribute."))
+        } else {
+            Ok(layout)
+        }
+    }
+
+    /* LAYOUT HELPER METHODS */
+
+    /// Return the bit index of the 'i'th player's utility entry start.
+    #[inline(always)]
+    const fn utility_index(&self, player: Turn) -> usize {
+        player * self.utility_size
+    }
+
+    /// Return the bit index of the remoteness entry start in the record buffer.
+    #[inline(always)]
+    const fn remoteness_index(&self) -> usize {
+        self.players * self.utility_size
+    }
+
+    /// Return the bit index of the win-by margin entry start in the buffer.
+    #[inline(always)]
+    const fn win_by_index(&self) -> usize {
+        self.remoteness_index() + self.remoteness_size
+    }
+}
+
+/* RECORD IMPLEMENTATION */
+
+/// Solver-specific record entry, meant to communicate the remoteness and each
+/// player's utility at a corresponding game state. The layout is as follows:
+///
+/// ```none
+/// [utility_size bits: P0 utility]
+/// ...
+/// [utility_size bits: P(N-1) utility]
+/// [remoteness_size bits: Remoteness]
+/// [win_by_size bits: Win-by margin (optional)]
+/// ```
+///
+/// The sizes are given by the record's `Layout`. Records of up to `BUFFER_SIZE`
+/// bits are kept in a statically sized buffer for intermediary storage, while
+/// larger ones are allocated on the heap. The utility and remoteness values
+/// are encoded in big-endian, with utility being a signed two's complement
+/// integer and remoteness (as well as the win-by margin) an unsigned integer.
+/// Because different layouts can have the same length, the layout of a record
+/// must be known to parse it.
+pub struct RecordBuffer {
+    buf: Buffer,
+    layout: Layout,
+}
+
+/// Backing memory of a record buffer.
+enum Buffer {
+    Static(BitArr!(for BUFFER_SIZE, in u8, Msb0)),
+    Heap(BitVec<u8, Msb0>),
+}
+
+impl Record for RecordBuffer {
+    #[inline(always)]
+    fn raw(&self) -> &BitSlice<u8, Msb0> {
+        &self.bits()[..self.layout.size()]
+    }
+}
+
+impl RecordBuffer {
+    /// Returns a new instance of a bit-packed record buffer that is able to
+    /// store utility values for `players` under the default layout.
+    #[inline(always)]
+    pub fn new(players: PlayerCount) -> Result<Self> {
+        Ok(Self::with_layout(Layout::new(players)))
+    }
+
+    /// Returns a new instance of a bit-packed record buffer with `layout`,
+    /// which is allocated on the heap if it does not fit in `BUFFER_SIZE` bits.
+    #[inline(always)]
+    pub fn with_layout(layout: Layout) -> Self {
+        let buf = if layout.size() > BUFFER_SIZE {
+            Buffer::Heap(bitvec![u8, Msb0; 0; layout.size()])
+        } else {
+            Buffer::Static(bitarr!(u8, Msb0; 0; BUFFER_SIZE))
+        };
+
+        Self { buf, layout }
+    }
+
+    /// Return a new instance with `bits` as the underlying buffer, which are
+    /// interpreted according to `layout`. Fails if the size of `bits` does not
+    /// match that of `layout`.
+    #[inline(always)]
+    pub fn parse(bits: &BitSlice<u8, Msb0>, layout: Layout) -> Result<Self> {
+        if bits.len() != layout.size() {
+            Err(SolverError::RecordLengthMismatch {
+                length: bits.len(),
+                expected: layout.size(),
+            })?
+        } else {
+            let mut record = Self::with_layout(layout);
+            record.bits_mut()[..bits.len()].copy_from_bitslice(bits);
+            Ok(record)
+        }
+    }
+
+    /* GET METHODS */
+
+    /// Parse and return the utility value corresponding to `player`. Fails if
+    /// the `player` index passed in is incoherent with player count.
+    #[inline(always)]
+    pub fn get_utility(&self, player: Turn) -> Result<Utility> {
+        if player >= self.layout.players {
+            Err(SolverError::RecordPlayerOutOfRange {
+                player,
+                players: self.layout.players,
+            })?
+        } else {
+            let start = self.layout.utility_index(player);
+            let end = start + self.layout.utility_size;
+            Ok(self.bits()[start..end].load_be::<Utility>())
+        }
+    }
+
+    /// Parse and return the remoteness value in the record encoding. Failure
+    /// here indicates corrupted state.
+    #[inline(always)]
+    pub fn get_remoteness(&self) -> Remoteness {
+        let start = self.layout.remoteness_index();
+        let end = start + self.layout.remoteness_size;
+        self.bits()[start..end].load_be::<Remoteness>()
+    }
+
+    /// Parse and return the win-by margin in the record encoding. Fails if the
+    /// record does not store a win-by margin.
+    #[inline(always)]
+    pub fn get_win_by(&self) -> Result<Utility> {
+        if !self.layout.win_by() {
+            Err(SolverError::RecordWithoutWinBy)?
+        } else {
+            let start = self.layout.win_by_index();
+            let end = start + self.layout.win_by_size;
+            Ok(self.bits()[start..end].load_be::<u64>() as Utility)
+        }
+    }
+
+    /// Return true if this record stores a win-by margin.
+    #[inline(always)]
+    pub fn has_win_by(&self) -> bool {
+        self.layout.win_by()
+    }
+
+    /// Return the number of players whose utility values are in the record.
+    #[inline(always)]
+    pub fn players(&self) -> PlayerCount {
+        self.layout.players
+    }
+
+    /// Return the layout of this record.
+    #[inline(always)]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /* SET METHODS */
+
+    /// Set this entry to have the utility values in `v` for each player. Fails
+    /// if any of the utility values are too high to fit in the space dedicated
+    /// for each player's utility, or if there is a mismatch between player
+    /// count and the number of utility values passed in.
+    #[inline(always)]
+    pub fn set_utility<const N: usize>(
+        &mut self,
+        v: [Utility; N],
+    ) -> Result<()> {
+        if N != self.layout.players {
+            Err(SolverError::RecordPlayerMismatch {
+                expected: self.layout.players,
+                found: N,
+            })?
+        } else {
+            let size = self.layout.utility_size;
+            for (player, &utility) in v.iter().enumerate() {
+                if util::min_sbits(utility) > size {
+                    Err(SolverError::RecordAttributeOverflow {
+                        attribute: "utility",
+                        value: utility as i128,
+                        size,
+                    })?
+                }
+
+                let start = self.layout.utility_index(player);
+                self.bits_mut()[start..start + size].store_be(utility);
+            }
+            Ok(())
+        }
+    }
+
+    /// Set this entry to have `value` remoteness. Fails if `value` is too high
+    /// to fit in the space dedicated for remoteness within the record.
+    #[inline(always)]
+    pub fn set_remoteness(&mut self, value: Remoteness) -> Result<()> {
+        if util::min_ubits(value) > self.layout.remoteness_size {
+            Err(SolverError::RecordAttributeOverflow {
+                attribute: "remoteness",
+                value: value as i128,
+                size: self.layout.remoteness_size,
+            })?
+        } else {
+            let start = self.layout.remoteness_index();
+            let end = start + self.layout.remoteness_size;
+            self.bits_mut()[start..end].store_be(value);
+            Ok(())
+        }
+    }
+
+    /// Set this entry to have a win-by margin of `value`. Fails if the record
+    /// does not store a win-by margin, or if `value` is negative or too high to
+    /// fit in the space dedicated for it within the record.
+    #[inline(always)]
+    pub fn set_win_by(&mut self, value: Utility) -> Result<()> {
+        let size = self.layout.win_by_size;
+        if !self.layout.win_by() {
+            Err(SolverError::RecordWithoutWinBy)?
+        } else if value < 0 || util::min_ubits(value as u64) > size {
+            Err(SolverError::RecordAttributeOverflow {
+                attribute: "win-by margin",
+                value: value as i128,
+                size,
+            })?
+        } else {
+            let start = self.layout.win_by_index();
+            self.bits_mut()[start..start + size].store_be(value as u64);
+            Ok(())
+        }
+    }
+
+    /* BUFFER HELPER METHODS */
+
+    /// Return the bits of the underlying buffer, including unused ones.
+    #[inline(always)]
+    fn bits(&self) -> &BitSlice<u8, Msb0> {
+        match &self.buf {
+            Buffer::Static(buf) => buf.as_bitslice(),
+            Buffer::Heap(buf) => buf.as_bitslice(),
+        }
+    }
+
+    /// Return the bits of the underlying buffer mutably.
+    #[inline(always)]
+    fn bits_mut(&mut self) -> &mut BitSlice<u8, Msb0> {
+        match &mut self.buf {
+            Buffer::Static(buf) => buf.as_mut_bitslice(),
+            Buffer::Heap(buf) => buf.as_mut_bitslice(),
+        }
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn schema_size_matches_record_size() {
+        for players in 1..=10 {
+            let record = RecordBuffer::new(players).unwrap();
+            let schema = schema(players).unwrap();
+            assert_eq!(schema.size(), record.raw().len());
+        }
+    }
+
+    #[test]
+    fn utility_and_remoteness_survive_round_trip() {
+        let mut record = RecordBuffer::new(3).unwrap();
+        record
+            .set_utility([-1, 0, 127])
+            .unwrap();
+        record.set_remoteness(513).unwrap();
+
+        let copy = RecordBuffer::parse(record.raw(), Layout::new(3)).unwrap();
+        assert_eq!(copy.players(), 3);
+        assert_eq!(copy.get_utility(0).unwrap(), -1);
+        assert_eq!(copy.get_utility(1).unwrap(), 0);
+        assert_eq!(copy.get_utility(2).unwrap(), 127);
+        assert_eq!(copy.get_remoteness(), 513);
+    }
+
+    #[test]
+    fn win_by_margin_survives_round_trip() {
+        // Records of more than 13 players are allocated on the heap.
+        for players in 1..20 {
+            let layout = Layout::new(players).with_win_by();
+            let record = RecordBuffer::with_layout(layout);
+            let schema = layout.schema().unwrap();
+            assert_eq!(schema.size(), record.raw().len());
+        }
+
+        let layout = Layout::new(2).with_win_by();
+        let mut record = RecordBuffer::with_layout(layout);
+        record
+            .set_utility([127, -127])
+            .unwrap();
+        record.set_remoteness(9).unwrap();
+        record.set_win_by(255).unwrap();
+        assert!(record.set_win_by(256).is_err());
+        assert!(record.set_win_by(-1).is_err());
+
+        let copy = RecordBuffer::parse(record.raw(), layout).unwrap();
+        assert_eq!(copy.players(), 2);
+        assert_eq!(copy.get_utility(1).unwrap(), -127);
+        assert_eq!(copy.get_remoteness(), 9);
+        assert_eq!(copy.get_win_by().unwrap(), 255);
+
+        let plain = RecordBuffer::new(2).unwrap();
+        assert!(!plain.has_win_by());
+        assert!(plain.get_win_by().is_err());
+    }
+
+    #[test]
+    fn margin_is_difference_between_two_best_players() {
+        assert_eq!(margin(&[1, -1]), 2);
+        assert_eq!(margin(&[-2, 1, 1]), 0);
+        assert_eq!(margin(&[0, 5, -3, 2]), 3);
+        assert_eq!(margin(&[4]), 0);
+    }
+
+    #[test]
+    fn layouts_fit_declared_bounds() {
+        let layout = Layout::fitting(3, -2..=1, 1000);
+        assert_eq!(layout.utility_size, 3);
+        assert_eq!(layout.remoteness_size, 10);
+        assert_eq!(layout.size(), 19);
+        assert_eq!(layout.with_win_by().size(), 22);
+
+        let layout = Layout::fitting(1, 0..=0, 0);
+        assert_eq!(layout.utility_size, 2);
+        assert_eq!(layout.remoteness_size, 1);
+
+        let layout = Layout::fitting(2, -1_000_000..=5, 1 << 40).with_win_by();
+        let table = layout.schema().unwrap();
+        assert_eq!(Layout::from_schema(&table).unwrap(), layout);
+        assert_eq!(
+            Layout::from_schema(&schema(4).unwrap()).unwrap(),
+            Layout::new(4)
+        );
+    }
+
+    #[test]
+    fn large_layouts_are_allocated_on_the_heap() {
+        let layout = Layout::fitting(20, -1000..=1000, Remoteness::MAX);
+        assert!(layout.size() > BUFFER_SIZE);
+
+        let mut utility = [0; 20];
+        for (i, u) in utility.iter_mut().enumerate() {
+            *u = 50 * i as Utility - 500;
+        }
+
+        let mut record = RecordBuffer::with_layout(layout);
+        record
+            .set_utility(utility)
+            .unwrap();
+        record
+            .set_remoteness(Remoteness::MAX)
+            .unwrap();
+        assert_eq!(record.raw().len(), layout.size());
+
+        let copy = RecordBuffer::parse(record.raw(), layout).unwrap();
+        for (i, &u) in utility.iter().enumerate() {
+            assert_eq!(copy.get_utility(i).unwrap(), u);
+        }
+        assert_eq!(copy.get_remoteness(), Remoteness::MAX);
+        assert!(RecordBuffer::parse(&record.raw()[1..], layout).is_err());
+    }
+
+    #[test]
+    fn incoherent_accesses_are_errors() {
+        let mut record = RecordBuffer::new(2).unwrap();
+        assert!(record.get_utility(2).is_err());
+        assert!(record.set_utility([0; 3]).is_err());
+        assert!(record
+            .set_utility([0, 128])
+            .is_err());
+        assert!(record
+            .set_remoteness(1 << 16)
+            .is_err());
+        assert!(matches!(
+            record
+                .get_win_by()
+                .unwrap_err()
+                .downcast_ref::<SolverError>(),
+            Some(SolverError::RecordWithoutWinBy)
+        ));
+
+        let error = RecordBuffer::parse(&record.raw()[..15], Layout::new(2))
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::RecordLengthMismatch {
+                length: 15,
+                expected: 32
+            })
+        ));
+    }
+}
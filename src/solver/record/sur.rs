@@ -0,0 +1,353 @@
+//! # Social Utility Remoteness (SUR) Record Module
+//!
+//! Implementation of a database record buffer for storing two utility vectors
+//! of an arbitrary number of players, in addition to the remoteness of a game
+//! state. The first vector holds the raw utility of each player as provided by
+//! the game, and the second one the effective utility of each player after
+//! accounting for the externalities between players (see `game::External`).
+
+use anyhow::{anyhow, Context, Result};
+use bitvec::prelude::*;
+
+use crate::database::{Attribute, Datatype, Record, Schema, SchemaBuilder};
+use crate::model::{PlayerCount, Remoteness, Turn, Utility};
+use crate::solver::util;
+
+/* CONSTANTS */
+
+/// The exact number of bits that are used to encode remoteness.
+pub const REMOTENESS_SIZE: usize = 16;
+
+/// The maximum number of bits that can be used to encode a record.
+pub const BUFFER_SIZE: usize = 256;
+
+/// The exact number of bits that are used to encode one utility value.
+pub const UTILITY_SIZE: usize = 8;
+
+/// The maximum number of players whose utility values fit in a record.
+pub const MAX_PLAYERS: PlayerCount =
+    (BUFFER_SIZE - REMOTENESS_SIZE) / (2 * UTILITY_SIZE);
+
+/* SCHEMA GENERATOR */
+
+/// Return the database table schema associated with a record instance with
+/// a specific number of `players` under this record implementation.
+pub fn schema(players: PlayerCount) -> Result<Schema> {
+    let mut schema = SchemaBuilder::new();
+    for kind in ["raw", "adjusted"] {
+        for i in 0..players {
+            let name = &format!("P{} {} utility", i, kind);
+            let data = Datatype::SINT;
+            let size = UTILITY_SIZE;
+            schema = schema
+                .add(Attribute::new(name, data, size))
+                .context(
+                    "Failed to add utility attribute to database schema.",
+                )?;
+        }
+    }
+
+    let name = "State remoteness";
+    let data = Datatype::UINT;
+    let size = REMOTENESS_SIZE;
+    schema = schema
+        .add(Attribute::new(name, data, size))
+        .context("Failed to add remoteness attribute to database schema.")?;
+
+    Ok(schema.build())
+}
+
+/* RECORD IMPLEMENTATION */
+
+/// Solver-specific record entry, meant to communicate the remoteness and both
+/// the raw and adjusted utility of each player at a corresponding game state.
+/// The layout is as follows:
+///
+/// ```none
+/// [UTILITY_SIZE bits: P0 raw utility]
+/// ...
+/// [UTILITY_SIZE bits: P(N-1) raw utility]
+/// [UTILITY_SIZE bits: P0 adjusted utility]
+/// ...
+/// [UTILITY_SIZE bits: P(N-1) adjusted utility]
+/// [REMOTENESS_SIZE bits: Remoteness]
+/// [0b0 until BUFFER_SIZE]
+/// ```
+///
+/// The encoding of each value is the same as in `mur::RecordBuffer`, and the
+/// number of players `N` is similarly limited by `BUFFER_SIZE`.
+pub struct RecordBuffer {
+    buf: BitArr!(for BUFFER_SIZE, in u8, Msb0),
+    players: PlayerCount,
+}
+
+impl Record for RecordBuffer {
+    #[inline(always)]
+    fn raw(&self) -> &BitSlice<u8, Msb0> {
+        &self.buf[..Self::bit_size(self.players)]
+    }
+}
+
+impl RecordBuffer {
+    /// Returns a new instance of a bit-packed record buffer that is able to
+    /// store utility values for `players`. Fails if `players` is too high for
+    /// the underlying buffer's capacity.
+    #[inline(always)]
+    pub fn new(players: PlayerCount) -> Result<Self> {
+        if Self::bit_size(players) > BUFFER_SIZE {
+            Err(anyhow!(
+                "Records can hold the utility of at most {} players, but {} \
+                were requested.",
+                MAX_PLAYERS,
+                players,
+            ))
+        } else {
+            Ok(Self {
+                buf: bitarr!(u8, Msb0; 0; BUFFER_SIZE),
+                players,
+            })
+        }
+    }
+
+    /// Return a new instance with `bits` as the underlying buffer. Fails in the
+    /// event that the size of `bits` is incoherent with the record.
+    #[inline(always)]
+    pub fn from(bits: &BitSlice<u8, Msb0>) -> Result<Self> {
+        let len = bits.len();
+        if !(REMOTENESS_SIZE..=BUFFER_SIZE).contains(&len) {
+            Err(anyhow!(
+                "A record of {} bits cannot be parsed, as records must have \
+                between {} and {} bits.",
+                len,
+                REMOTENESS_SIZE,
+                BUFFER_SIZE,
+            ))
+        } else {
+            let players = Self::player_count(len);
+            let mut buf = bitarr!(u8, Msb0; 0; BUFFER_SIZE);
+            buf[..len].copy_from_bitslice(bits);
+            Ok(Self { players, buf })
+        }
+    }
+
+    /* GET METHODS */
+
+    /// Parse and return the raw utility value corresponding to `player`. Fails
+    /// if the `player` index passed in is incoherent with player count.
+    #[inline(always)]
+    pub fn get_utility(&self, player: Turn) -> Result<Utility> {
+        self.check_player(player)?;
+        let start = Self::utility_index(player);
+        Ok(self.buf[start..start + UTILITY_SIZE].load_be::<Utility>())
+    }
+
+    /// Parse and return the adjusted utility value corresponding to `player`.
+    /// Fails if the `player` index passed in is incoherent with player count.
+    #[inline(always)]
+    pub fn get_adjusted(&self, player: Turn) -> Result<Utility> {
+        self.check_player(player)?;
+        let start = Self::adjusted_index(self.players, player);
+        Ok(self.buf[start..start + UTILITY_SIZE].load_be::<Utility>())
+    }
+
+    /// Parse and return the remoteness value in the record encoding. Failure
+    /// here indicates corrupted state.
+    #[inline(always)]
+    pub fn get_remoteness(&self) -> Remoteness {
+        let start = Self::remoteness_index(self.players);
+        let end = start + REMOTENESS_SIZE;
+        self.buf[start..end].load_be::<Remoteness>()
+    }
+
+    /// Return the number of players whose utility values are in the record.
+    #[inline(always)]
+    pub fn players(&self) -> PlayerCount {
+        self.players
+    }
+
+    /* SET METHODS */
+
+    /// Set this entry to have the raw utility values in `v` for each player.
+    /// Fails under the same conditions as `mur::RecordBuffer::set_utility`.
+    #[inline(always)]
+    pub fn set_utility<const N: usize>(
+        &mut self,
+        v: [Utility; N],
+    ) -> Result<()> {
+        self.check_length(N)?;
+        for (player, &utility) in v.iter().enumerate() {
+            self.store(Self::utility_index(player), utility)?;
+        }
+        Ok(())
+    }
+
+    /// Set this entry to have the adjusted utility values in `v` for each
+    /// player. Fails under the same conditions as `set_utility`.
+    #[inline(always)]
+    pub fn set_adjusted<const N: usize>(
+        &mut self,
+        v: [Utility; N],
+    ) -> Result<()> {
+        self.check_length(N)?;
+        for (player, &utility) in v.iter().enumerate() {
+            self.store(
+                Self::adjusted_index(self.players, player),
+                utility,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Set this entry to have `value` remoteness. Fails if `value` is too high
+    /// to fit in the space dedicated for remoteness within the record.
+    #[inline(always)]
+    pub fn set_remoteness(&mut self, value: Remoteness) -> Result<()> {
+        if util::min_ubits(value) > REMOTENESS_SIZE {
+            Err(anyhow!(
+                "The remoteness {} does not fit in {} bits.",
+                value,
+                REMOTENESS_SIZE,
+            ))
+        } else {
+            let start = Self::remoteness_index(self.players);
+            let end = start + REMOTENESS_SIZE;
+            self.buf[start..end].store_be(value);
+            Ok(())
+        }
+    }
+
+    /* VALIDATION HELPER METHODS */
+
+    /// Fails if `player` has no utility values in this record.
+    #[inline(always)]
+    fn check_player(&self, player: Turn) -> Result<()> {
+        if player >= self.players {
+            Err(anyhow!(
+                "There is no player {} in a record for {} players.",
+                player,
+                self.players,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Fails if a utility vector of `length` is incoherent with player count.
+    #[inline(always)]
+    fn check_length(&self, length: usize) -> Result<()> {
+        if length != self.players {
+            Err(anyhow!(
+                "Attempted to store {} utility values in a record for {} \
+                players.",
+                length,
+                self.players,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Stores `utility` at the bit index `start`, failing if it does not fit.
+    #[inline(always)]
+    fn store(&mut self, start: usize, utility: Utility) -> Result<()> {
+        if util::min_sbits(utility) > UTILITY_SIZE {
+            Err(anyhow!(
+                "The utility {} does not fit in {} bits.",
+                utility,
+                UTILITY_SIZE,
+            ))
+        } else {
+            self.buf[start..start + UTILITY_SIZE].store_be(utility);
+            Ok(())
+        }
+    }
+
+    /* LAYOUT HELPER METHODS */
+
+    /// Return the number of bits that would be needed to store a record
+    /// containing both utility vectors for `players` as well as remoteness.
+    #[inline(always)]
+    const fn bit_size(players: usize) -> usize {
+        2 * players * UTILITY_SIZE + REMOTENESS_SIZE
+    }
+
+    /// Return the bit index of the remoteness entry start in the record buffer.
+    #[inline(always)]
+    const fn remoteness_index(players: usize) -> usize {
+        2 * players * UTILITY_SIZE
+    }
+
+    /// Return the bit index of the 'i'th player's raw utility entry start.
+    #[inline(always)]
+    const fn utility_index(player: Turn) -> usize {
+        player * UTILITY_SIZE
+    }
+
+    /// Return the bit index of the 'i'th player's adjusted utility entry start.
+    #[inline(always)]
+    const fn adjusted_index(players: usize, player: Turn) -> usize {
+        (players + player) * UTILITY_SIZE
+    }
+
+    /// Return the maximum number of players whose utility vectors fit in a
+    /// dense record with `length`. Ignores unused bits.
+    #[inline(always)]
+    const fn player_count(length: usize) -> usize {
+        (length - REMOTENESS_SIZE) / (2 * UTILITY_SIZE)
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn schema_size_matches_record_size() {
+        for players in 1..=MAX_PLAYERS {
+            let record = RecordBuffer::new(players).unwrap();
+            let schema = schema(players).unwrap();
+            assert_eq!(schema.size(), record.raw().len());
+        }
+        assert!(RecordBuffer::new(MAX_PLAYERS + 1).is_err());
+    }
+
+    #[test]
+    fn both_utility_vectors_survive_round_trip() {
+        let mut record = RecordBuffer::new(3).unwrap();
+        record
+            .set_utility([-1, 0, 127])
+            .unwrap();
+        record
+            .set_adjusted([5, -127, 2])
+            .unwrap();
+        record.set_remoteness(513).unwrap();
+
+        let copy = RecordBuffer::from(record.raw()).unwrap();
+        assert_eq!(copy.players(), 3);
+        assert_eq!(copy.get_utility(0).unwrap(), -1);
+        assert_eq!(copy.get_utility(1).unwrap(), 0);
+        assert_eq!(copy.get_utility(2).unwrap(), 127);
+        assert_eq!(copy.get_adjusted(0).unwrap(), 5);
+        assert_eq!(copy.get_adjusted(1).unwrap(), -127);
+        assert_eq!(copy.get_adjusted(2).unwrap(), 2);
+        assert_eq!(copy.get_remoteness(), 513);
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        let mut record = RecordBuffer::new(2).unwrap();
+        assert!(record
+            .set_utility([128, 0])
+            .is_err());
+        assert!(record
+            .set_adjusted([0, 0, 0])
+            .is_err());
+        assert!(record
+            .set_remoteness(1 << 16)
+            .is_err());
+        assert!(record.get_adjusted(2).is_err());
+    }
+}
@@ -7,88 +7,410 @@
 //! - Max Fierro, 12/3/2023 (maxfierro@berkeley.edu)
 
 use anyhow::{Context, Result};
-use bitvec::prelude::*;
+use nalgebra::SVector;
 
-use crate::database::{volatile, Record};
-use crate::database::{Attribute, Datatype, SchemaBuilder};
-use crate::database::{KVStore, Tabular};
-use crate::game::{Acyclic, Bounded, DTransition, STransition, Solvable};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::database::{sharded, vector, volatile, Persistence};
+use crate::database::{Budgeted, KVStore, Schema, Tabular};
+use crate::game::External;
+use crate::game::Solvable;
+use crate::game::{Acyclic, Bounded, DTransition, Limited, STransition};
+use crate::game::{Legible, Ranked, Symmetric, TerminalEnumerable};
 use crate::interface::IOMode;
-use crate::model::{PlayerCount, Remoteness, State, Turn, Utility};
-use crate::solver::{util, MAX_TRANSITIONS};
+use crate::model::{PlayerCount, Remoteness, State, Utility};
+use crate::solver::checkpoint;
+use crate::solver::error::SolverError;
+use crate::solver::record::mur::{self, Layout, RecordBuffer};
+use crate::solver::record::sur;
+use crate::solver::util::{self, Choice, Policy, RankedDatabase, Resources};
+use crate::solver::verification::{self, Audit, Frontier, Inconsistency};
+use crate::solver::verification::{Outcome, Report};
+use crate::solver::{MAX_TRANSITIONS, TIE_BREAK_KEY};
 
-/* CONSTANTS */
+/* SOLVERS */
 
-/// The exact number of bits that are used to encode remoteness.
-const REMOTENESS_SIZE: usize = 16;
+pub fn dynamic_solver<const N: usize, G>(
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<volatile::Database>
+where
+    G: Acyclic<N> + DTransition<State> + Bounded<State> + Solvable<N> + Limited,
+{
+    util::check_acyclic(game)?;
+    let mut db = volatile_database(game, policy)
+        .context("Failed to initialize volatile database.")?;
+    dynamic_backward_induction(
+        &mut db,
+        game,
+        mode,
+        policy,
+        resources,
+        |state| state,
+    )
+    .context("Failed solving algorithm execution.")?;
+    Ok(db)
+}
 
-/// The maximum number of bits that can be used to encode a record.
-const BUFFER_SIZE: usize = 128;
+/// Solves `game` in the same way as `dynamic_solver`, but only traversing and
+/// storing the canonical states of `game`. The records of other states must be
+/// looked up through their canonical states.
+pub fn symmetric_dynamic_solver<const N: usize, G>(
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<volatile::Database>
+where
+    G: Acyclic<N>
+        + DTransition<State>
+        + Bounded<State>
+        + Solvable<N>
+        + Limited
+        + Symmetric<State>,
+{
+    util::check_acyclic(game)?;
+    let mut db = volatile_database(game, policy)
+        .context("Failed to initialize volatile database.")?;
+    dynamic_backward_induction(
+        &mut db,
+        game,
+        mode,
+        policy,
+        resources,
+        |state| game.canonical(state),
+    )
+    .context("Failed solving algorithm execution.")?;
+    Ok(db)
+}
 
-/// The exact number of bits that are used to encode utility for one player.
-const UTILITY_SIZE: usize = 8;
+/// Solves `game` in the same way as `dynamic_solver`, but storing records in a
+/// vector database under the ranks of their states, which uses less memory for
+/// games whose state encodings are sparse.
+pub fn ranked_dynamic_solver<'a, const N: usize, G>(
+    game: &'a G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<RankedDatabase<'a, G>>
+where
+    G: Acyclic<N>
+        + DTransition<State>
+        + Bounded<State>
+        + Solvable<N>
+        + Limited
+        + Ranked<State>,
+{
+    util::check_acyclic(game)?;
+    let db = vector_database(game, policy)
+        .context("Failed to initialize vector database.")?;
+    let mut db = RankedDatabase::new(game, db)?;
+    dynamic_backward_induction(
+        &mut db,
+        game,
+        mode,
+        policy,
+        resources,
+        |state| state,
+    )
+    .context("Failed solving algorithm execution.")?;
+    Ok(db)
+}
 
-/* SOLVERS */
+/// Solves `game` in the same way as `dynamic_solver`, but spreading the work
+/// across `resources.threads` threads and storing records in a sharded database
+/// (see `parallel_backward_induction`). The records computed are identical to
+/// those computed by `dynamic_solver`. Since the sharded database is neither
+/// persisted nor evicted, this fails with `SolverError::UnsupportedResource`
+/// if `resources` specify a checkpoint or a memory limit.
+pub fn parallel_dynamic_solver<const N: usize, G>(
+    game: &G,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<sharded::Database>
+where
+    G: Acyclic<N>
+        + DTransition<State>
+        + Bounded<State>
+        + Solvable<N>
+        + Limited
+        + Sync,
+{
+    util::check_acyclic(game)?;
+    if resources.checkpoint.is_some() {
+        Err(SolverError::UnsupportedResource {
+            game_id: game.id(),
+            resource: "a checkpoint".into(),
+        })?
+    } else if resources.memory_limit.is_some() {
+        Err(SolverError::UnsupportedResource {
+            game_id: game.id(),
+            resource: "a memory limit".into(),
+        })?
+    }
 
-pub fn dynamic_solver<const N: usize, G>(game: &G, mode: IOMode) -> Result<()>
+    let mut db = sharded_database(game, policy, resources)
+        .context("Failed to initialize sharded database.")?;
+    parallel_backward_induction(&mut db, game, policy, resources)
+        .context("Failed solving algorithm execution.")?;
+    Ok(db)
+}
+
+/// Solves `game` in the same way as `dynamic_solver`, but propagating records
+/// in retrograde from the end states of `game` instead of searching for them
+/// (see `retrograde_backward_induction`). This does not keep a search stack,
+/// so it is better suited for games with long sequences of moves. Besides the
+/// states reachable from the start of `game`, this also solves all other
+/// states which can be reached in retrograde from its end states.
+pub fn retrograde_solver<const N: usize, G>(
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<volatile::Database>
 where
-    G: Acyclic<N> + DTransition<State> + Bounded<State> + Solvable<N>,
+    G: Acyclic<N>
+        + DTransition<State>
+        + TerminalEnumerable<State>
+        + Solvable<N>
+        + Limited,
 {
-    let mut db = volatile_database(game)
+    util::check_acyclic(game)?;
+    let mut db = volatile_database(game, policy)
         .context("Failed to initialize volatile database.")?;
-    dynamic_backward_induction(&mut db, game)
+    retrograde_backward_induction(&mut db, game, mode, policy, resources)
         .context("Failed solving algorithm execution.")?;
-    Ok(())
+    Ok(db)
 }
 
-pub fn static_solver<const N: usize, G>(game: &G, mode: IOMode) -> Result<()>
+/// Solves `game` in the same way as `dynamic_solver`, but having each player
+/// choose moves by their effective utility instead of their raw utility. The
+/// effective utility of player `i` is the `i`th row of the externality matrix
+/// of `game` times the vector of raw utilities. Both the raw and the effective
+/// (adjusted) utility vectors are stored in each record (see `record::sur`).
+/// Since these records have no room for win-by margins, this fails with
+/// `SolverError::UnsupportedWinBy` if `policy` requests them.
+pub fn external_dynamic_solver<const N: usize, G>(
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<volatile::Database>
+where
+    G: Acyclic<N> + DTransition<State> + Bounded<State> + External<N> + Limited,
+{
+    util::check_acyclic(game)?;
+    if policy.win_by {
+        Err(SolverError::UnsupportedWinBy { game_id: game.id() })?
+    }
+
+    let schema = sur::schema(game.players())
+        .context("Failed to create table schema for solver records.")?;
+    let mut db = volatile_database_with(game, schema, policy)
+        .context("Failed to initialize volatile database.")?;
+    external_backward_induction(&mut db, game, mode, policy, resources)
+        .context("Failed solving algorithm execution.")?;
+    Ok(db)
+}
+
+pub fn static_solver<const N: usize, G>(
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<volatile::Database>
 where
     G: Acyclic<N>
         + STransition<State, MAX_TRANSITIONS>
         + Bounded<State>
-        + Solvable<N>,
+        + Solvable<N>
+        + Limited,
 {
-    let mut db = volatile_database(game)
+    util::check_acyclic(game)?;
+    let mut db = volatile_database(game, policy)
         .context("Failed to initialize volatile database.")?;
-    static_backward_induction(&mut db, game)
+    static_backward_induction(&mut db, game, mode, policy, resources)
         .context("Failed solving algorithm execution.")?;
-    Ok(())
+    Ok(db)
+}
+
+/* VERIFIERS */
+
+/// Verifies the solution set of `game` persisted in `directory` by solving it
+/// with `dynamic_solver` under a checkpoint, checking the records of the states
+/// reachable from the start of `game` as specified by `audit` (see `verify`).
+pub fn dynamic_verifier<const N: usize, G>(
+    game: &G,
+    directory: &Path,
+    audit: Audit,
+) -> Result<Report>
+where
+    G: Acyclic<N>
+        + DTransition<State>
+        + Bounded<State>
+        + Solvable<N>
+        + Legible<State>,
+{
+    util::check_acyclic(game)?;
+    let mut db = volatile::Database::initialize();
+    let policy = verification::load(&mut db, game, directory)
+        .context("Failed to load persisted solution set.")?;
+    verify(&db, game, policy, audit, |state| state)
+        .context("Failed verification algorithm execution.")
+}
+
+/// Verifies the solution set of `game` persisted by `symmetric_dynamic_solver`
+/// in the same way as `dynamic_verifier`, where only canonical states have
+/// records.
+pub fn symmetric_dynamic_verifier<const N: usize, G>(
+    game: &G,
+    directory: &Path,
+    audit: Audit,
+) -> Result<Report>
+where
+    G: Acyclic<N>
+        + DTransition<State>
+        + Bounded<State>
+        + Solvable<N>
+        + Legible<State>
+        + Symmetric<State>,
+{
+    util::check_acyclic(game)?;
+    let mut db = volatile::Database::initialize();
+    let policy = verification::load(&mut db, game, directory)
+        .context("Failed to load persisted solution set.")?;
+    verify(&db, game, policy, audit, |state| {
+        game.canonical(state)
+    })
+    .context("Failed verification algorithm execution.")
+}
+
+/// Verifies the solution set of `game` persisted by `ranked_dynamic_solver` in
+/// the same way as `dynamic_verifier`, where records are stored under the ranks
+/// of their states.
+pub fn ranked_dynamic_verifier<const N: usize, G>(
+    game: &G,
+    directory: &Path,
+    audit: Audit,
+) -> Result<Report>
+where
+    G: Acyclic<N>
+        + DTransition<State>
+        + Bounded<State>
+        + Solvable<N>
+        + Legible<State>
+        + Ranked<State>,
+{
+    util::check_acyclic(game)?;
+    let mut db = vector::Database::initialize(vector::Parameters {
+        persistence: Persistence::Off,
+    })?;
+    let policy = verification::load(&mut db, game, directory)
+        .context("Failed to load persisted solution set.")?;
+    let db = RankedDatabase::new(game, db)?;
+    verify(&db, game, policy, audit, |state| state)
+        .context("Failed verification algorithm execution.")
 }
 
 /* DATABASE INITIALIZATION */
 
 /// Initializes a volatile database, creating a table schema according to the
 /// solver record layout, initializing a table with that schema, and switching
-/// to that table before returning the database handle.
-fn volatile_database<const N: usize, G>(game: &G) -> Result<volatile::Database>
+/// to that table before returning the database handle. The tie-breaking
+/// `policy` is recorded in the metadata of the table, and determines whether
+/// records include a win-by margin.
+fn volatile_database<const N: usize, G>(
+    game: &G,
+    policy: Policy,
+) -> Result<volatile::Database>
+where
+    G: Solvable<N> + Limited,
+{
+    let schema = layout(game, policy)
+        .schema()
+        .context("Failed to create table schema for solver records.")?;
+    volatile_database_with(game, schema, policy)
+}
+
+/// Initializes a volatile database in the same way as `volatile_database`, but
+/// using the provided table `schema` instead of the `mur` record layout.
+fn volatile_database_with<const N: usize, G>(
+    game: &G,
+    schema: Schema,
+    policy: Policy,
+) -> Result<volatile::Database>
 where
     G: Solvable<N>,
 {
-    let db = volatile::Database::initialize();
-    let mut schema = SchemaBuilder::new();
+    let mut db = volatile::Database::initialize();
+    let id = game.id();
+    db.create_table(&id, schema)
+        .context("Failed to create database table for solution set.")?;
+    db.select_table(&id)
+        .context("Failed to select solution set database table.")?;
+    db.set_metadata(TIE_BREAK_KEY, &policy.to_string())
+        .context("Failed to record tie-breaking policy in table metadata.")?;
+
+    Ok(db)
+}
+
+/// Initializes an in-memory vector database in the same way as
+/// `volatile_database`, for storing records under dense keys.
+fn vector_database<const N: usize, G>(
+    game: &G,
+    policy: Policy,
+) -> Result<vector::Database>
+where
+    G: Solvable<N> + Limited,
+{
+    let mut db = vector::Database::initialize(vector::Parameters {
+        persistence: Persistence::Off,
+    })?;
+    let schema = layout(game, policy)
+        .schema()
+        .context("Failed to create table schema for solver records.")?;
 
-    for i in 0..game.players() {
-        let name = &format!("P{} utility", i);
-        let data = Datatype::SINT;
-        let size = UTILITY_SIZE;
-        schema = schema
-            .add(Attribute::new(name, data, size))
-            .context("Failed to add utility attribute to database schema.")?;
-    }
+    let id = game.id();
+    db.create_table(&id, schema)
+        .context("Failed to create database table for solution set.")?;
+    db.select_table(&id)
+        .context("Failed to select solution set database table.")?;
+    db.set_metadata(TIE_BREAK_KEY, &policy.to_string())
+        .context("Failed to record tie-breaking policy in table metadata.")?;
+
+    Ok(db)
+}
 
-    let name = "State remoteness";
-    let data = Datatype::UINT;
-    let size = REMOTENESS_SIZE;
-    schema = schema
-        .add(Attribute::new(name, data, size))
-        .context("Failed to add remoteness attribute to database schema.")?;
+/// Initializes a sharded database in the same way as `volatile_database`, with
+/// as many shards as the threads available in `resources`.
+fn sharded_database<const N: usize, G>(
+    game: &G,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<sharded::Database>
+where
+    G: Solvable<N> + Limited,
+{
+    let mut db = sharded::Database::initialize(sharded::Parameters {
+        shards: resources.threads,
+    });
+    let schema = layout(game, policy)
+        .schema()
+        .context("Failed to create table schema for solver records.")?;
 
     let id = game.id();
-    let schema = schema.build();
     db.create_table(&id, schema)
         .context("Failed to create database table for solution set.")?;
     db.select_table(&id)
         .context("Failed to select solution set database table.")?;
+    db.set_metadata(TIE_BREAK_KEY, &policy.to_string())
+        .context("Failed to record tie-breaking policy in table metadata.")?;
 
     Ok(db)
 }
@@ -98,30 +420,49 @@ where
 /// Performs an iterative depth-first traversal of the game tree, assigning to
 /// each game `state` a remoteness and utility values for each player within
 /// `db`. This uses heap-allocated memory for keeping a stack of positions to
-/// facilitate DFS, as well as for communicating state transitions.
-fn dynamic_backward_induction<const N: PlayerCount, D, G>(
+/// facilitate DFS, as well as for communicating state transitions. All states
+/// are mapped through `canonical` before being traversed or stored. Players
+/// choose between children according to `policy`.
+///
+/// If `resources` specify a checkpoint, the records in `db` and the stack are
+/// saved periodically (see `solver::checkpoint`), and the traversal resumes
+/// from the last saved stack when solving under `IOMode::Find`. Since both are
+/// saved between the same two iterations, resuming continues the traversal in
+/// exactly the same way as if it had not been interrupted. If `resources` have
+/// a memory limit, the memory used by `db` and the stack is kept under it.
+fn dynamic_backward_induction<const N: PlayerCount, D, G, F>(
     db: &mut D,
     game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+    canonical: F,
 ) -> Result<()>
 where
-    D: KVStore<RecordBuffer>,
-    G: Acyclic<N> + DTransition<State> + Bounded<State> + Solvable<N>,
+    D: KVStore<RecordBuffer> + Tabular + Budgeted,
+    G: Acyclic<N> + DTransition<State> + Bounded<State> + Solvable<N> + Limited,
+    F: Fn(State) -> State,
 {
-    let mut stack = Vec::new();
-    stack.push(game.start());
+    let layout = layout(game, policy);
+    let mut stack = checkpoint::resume(db, game, mode, policy, resources)
+        .context("Failed to resume from checkpoint.")?
+        .unwrap_or_else(|| vec![canonical(game.start())]);
+
+    let mut visited = 0;
     while let Some(curr) = stack.pop() {
-        let children = game.prograde(curr);
-        let mut buf = RecordBuffer::new(game.players())
-            .context("Failed to create placeholder record.")?;
+        let children = game
+            .prograde(curr)
+            .into_iter()
+            .map(&canonical)
+            .collect::<Vec<State>>();
+        util::check_transitions(game, curr, children.len())?;
+        let mut buf = RecordBuffer::with_layout(layout);
         if db.get(curr).is_none() {
             db.put(curr, &buf);
             if game.end(curr) {
-                buf = RecordBuffer::new(game.players())
-                    .context("Failed to create record for end state.")?;
-                buf.set_utility(game.utility(curr))
-                    .context("Failed to copy utility values to record.")?;
-                buf.set_remoteness(0)
-                    .context("Failed to set remoteness for end state.")?;
+                buf = RecordBuffer::with_layout(layout);
+                store(&mut buf, game, curr, game.utility(curr), 0)
+                    .context("Failed to store outcome of end state.")?;
                 db.put(curr, &buf);
             } else {
                 stack.push(curr);
@@ -131,29 +472,42 @@ where
                         .filter(|&x| db.get(*x).is_none()),
                 );
             }
-        } else {
-            let mut optimal = buf;
-            let mut max_val = Utility::MIN;
-            let mut min_rem = Remoteness::MAX;
+        } else if !game.end(curr) {
+            let mut optimal: Option<Choice<N>> = None;
             for state in children {
-                let buf = RecordBuffer::from(db.get(state).unwrap())
-                    .context("Failed to create record for middle state.")?;
-                let val = buf
-                    .get_utility(game.turn(state))
-                    .context("Failed to get utility from record.")?;
-                let rem = buf.get_remoteness();
-                if val > max_val || (val == max_val && rem < min_rem) {
-                    max_val = val;
-                    min_rem = rem;
-                    optimal = buf;
+                let buf =
+                    util::parse(game, state, db.get(state).unwrap(), layout)
+                        .context("Failed to create record for middle state.")?;
+                let choice = choice(game, state, &buf)?;
+                if optimal.as_ref().is_none_or(|o| {
+                    policy.prefers(game.turn(curr), curr, &choice, o)
+                }) {
+                    optimal = Some(choice);
                 }
             }
-            optimal
-                .set_remoteness(min_rem + 1)
-                .context("Failed to set remoteness for solved record.")?;
-            db.put(curr, &optimal);
+            if let Some(optimal) = optimal {
+                store(
+                    &mut buf,
+                    game,
+                    curr,
+                    optimal.utility,
+                    optimal.remoteness + 1,
+                )
+                .context("Failed to store outcome of solved record.")?;
+            }
+            db.put(curr, &buf);
+        }
+
+        resources.enforce(db, stack.capacity() * mem::size_of::<State>())?;
+        visited += 1;
+        if checkpoint::due(resources, visited) {
+            checkpoint::save(db, game, policy, resources, Some(&stack))
+                .context("Failed to save checkpoint.")?;
         }
     }
+
+    checkpoint::save(db, game, policy, resources, None)
+        .context("Failed to save complete checkpoint.")?;
     Ok(())
 }
 
@@ -161,32 +515,38 @@ where
 /// each `game` state a remoteness and utility values for each player within
 /// `db`. This uses heap-allocated memory for keeping a stack of positions to
 /// facilitate DFS, and stack memory for communicating state transitions.
+/// Players choose between children according to `policy`. Checkpoints and the
+/// memory limit in `resources` are handled as in `dynamic_backward_induction`.
 fn static_backward_induction<const N: PlayerCount, D, G>(
     db: &mut D,
     game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
 ) -> Result<()>
 where
-    D: KVStore<RecordBuffer>,
+    D: KVStore<RecordBuffer> + Tabular + Budgeted,
     G: Acyclic<N>
         + STransition<State, MAX_TRANSITIONS>
         + Bounded<State>
-        + Solvable<N>,
+        + Solvable<N>
+        + Limited,
 {
-    let mut stack = Vec::new();
-    stack.push(game.start());
+    let layout = layout(game, policy);
+    let mut stack = checkpoint::resume(db, game, mode, policy, resources)
+        .context("Failed to resume from checkpoint.")?
+        .unwrap_or_else(|| vec![game.start()]);
+
+    let mut visited = 0;
     while let Some(curr) = stack.pop() {
         let children = game.prograde(curr);
-        let mut buf = RecordBuffer::new(game.players())
-            .context("Failed to create placeholder record.")?;
+        let mut buf = RecordBuffer::with_layout(layout);
         if db.get(curr).is_none() {
             db.put(curr, &buf);
             if game.end(curr) {
-                buf = RecordBuffer::new(game.players())
-                    .context("Failed to create record for end state.")?;
-                buf.set_utility(game.utility(curr))
-                    .context("Failed to copy utility values to record.")?;
-                buf.set_remoteness(0)
-                    .context("Failed to set remoteness for end state.")?;
+                buf = RecordBuffer::with_layout(layout);
+                store(&mut buf, game, curr, game.utility(curr), 0)
+                    .context("Failed to store outcome of end state.")?;
                 db.put(curr, &buf);
             } else {
                 stack.push(curr);
@@ -197,193 +557,1475 @@ where
                         .filter(|&x| db.get(x).is_none()),
                 );
             }
-        } else {
+        } else if !game.end(curr) {
             let mut cur = 0;
-            let mut optimal = buf;
-            let mut max_val = Utility::MIN;
-            let mut min_rem = Remoteness::MAX;
+            let mut optimal: Option<Choice<N>> = None;
             while cur < MAX_TRANSITIONS {
-                cur += 1;
                 if let Some(state) = children[cur] {
-                    let buf = RecordBuffer::from(db.get(state).unwrap())
-                        .context("Failed to create record for middle state.")?;
-                    let val = buf
-                        .get_utility(game.turn(state))
-                        .context("Failed to get utility from record.")?;
-                    let rem = buf.get_remoteness();
-                    if val > max_val || (val == max_val && rem < min_rem) {
-                        max_val = val;
-                        min_rem = rem;
-                        optimal = buf;
+                    let buf = util::parse(
+                        game,
+                        state,
+                        db.get(state).unwrap(),
+                        layout,
+                    )
+                    .context("Failed to create record for middle state.")?;
+                    let choice = choice(game, state, &buf)?;
+                    if optimal.as_ref().is_none_or(|o| {
+                        policy.prefers(game.turn(curr), curr, &choice, o)
+                    }) {
+                        optimal = Some(choice);
                     }
                 }
+                cur += 1;
             }
-            optimal
-                .set_remoteness(min_rem + 1)
-                .context("Failed to set remoteness for solved record.")?;
-            db.put(curr, &optimal);
+            if let Some(optimal) = optimal {
+                store(
+                    &mut buf,
+                    game,
+                    curr,
+                    optimal.utility,
+                    optimal.remoteness + 1,
+                )
+                .context("Failed to store outcome of solved record.")?;
+            }
+            db.put(curr, &buf);
+        }
+
+        resources.enforce(db, stack.capacity() * mem::size_of::<State>())?;
+        visited += 1;
+        if checkpoint::due(resources, visited) {
+            checkpoint::save(db, game, policy, resources, Some(&stack))
+                .context("Failed to save checkpoint.")?;
         }
     }
+
+    checkpoint::save(db, game, policy, resources, None)
+        .context("Failed to save complete checkpoint.")?;
     Ok(())
 }
 
-/* RECORD IMPLEMENTATION */
+/// Computes the same records as `dynamic_backward_induction` (without mapping
+/// states to canonical ones) using `resources.threads` threads, in two phases:
+///
+/// 1. A forward breadth-first discovery of the states reachable from the start
+///    of `game`, where the children of each level of states are generated in
+///    parallel. This yields the number of children of each state, and the
+///    parents of each state.
+/// 2. A retrograde propagation from the states without children, where each
+///    state is solved in parallel with the others that became ready at the
+///    same time. Solving a state atomically decrements the counters of its
+///    parents, and the parents whose counters reach zero become ready.
+///
+/// Since the children of each state are considered in the same order as in
+/// `dynamic_backward_induction`, the same choices are made under `policy`.
+fn parallel_backward_induction<const N: PlayerCount, G>(
+    db: &mut sharded::Database,
+    game: &G,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<()>
+where
+    G: Acyclic<N>
+        + DTransition<State>
+        + Bounded<State>
+        + Solvable<N>
+        + Limited
+        + Sync,
+{
+    let threads = resources.threads.max(1);
+    let layout = layout(game, policy);
+
+    let mut graph = HashMap::new();
+    let mut seen = HashSet::from([game.start()]);
+    let mut frontier = vec![game.start()];
+    while !frontier.is_empty() {
+        let expanded = parallel(&frontier, threads, |state| {
+            if game.end(state) {
+                return Ok(Vec::new());
+            }
+            let children = game.prograde(state);
+            util::check_transitions(game, state, children.len())?;
+            Ok(children)
+        })?;
+
+        let mut next = Vec::new();
+        for (state, children) in frontier.into_iter().zip(expanded) {
+            next.extend(
+                children
+                    .iter()
+                    .filter(|&&child| seen.insert(child)),
+            );
+            graph.insert(state, children);
+        }
+        frontier = next;
+    }
+
+    let mut parents: HashMap<State, Vec<State>> = HashMap::new();
+    let mut pending = HashMap::new();
+    for (&state, children) in &graph {
+        pending.insert(state, AtomicUsize::new(children.len()));
+        for &child in children {
+            parents
+                .entry(child)
+                .or_default()
+                .push(state);
+        }
+    }
+
+    let states = graph
+        .keys()
+        .copied()
+        .collect::<Vec<State>>();
+    db.reserve(&states);
+
+    let db = &*db;
+    let mut ready = states
+        .into_iter()
+        .filter(|state| graph[state].is_empty())
+        .collect::<Vec<State>>();
+
+    while !ready.is_empty() {
+        let unlocked = parallel(&ready, threads, |curr| {
+            let mut buf = RecordBuffer::with_layout(layout);
+            if game.end(curr) {
+                store(&mut buf, game, curr, game.utility(curr), 0)
+                    .context("Failed to store outcome of end state.")?;
+            } else {
+                let mut optimal: Option<Choice<N>> = None;
+                for &state in &graph[&curr] {
+                    let bits = KVStore::<RecordBuffer>::get(db, state).unwrap();
+                    let buf = util::parse(game, state, bits, layout)
+                        .context("Failed to create record for middle state.")?;
+                    let choice = choice(game, state, &buf)?;
+                    if optimal.as_ref().is_none_or(|o| {
+                        policy.prefers(game.turn(curr), curr, &choice, o)
+                    }) {
+                        optimal = Some(choice);
+                    }
+                }
+                if let Some(optimal) = optimal {
+                    store(
+                        &mut buf,
+                        game,
+                        curr,
+                        optimal.utility,
+                        optimal.remoteness + 1,
+                    )
+                    .context("Failed to store outcome of solved record.")?;
+                }
+            }
+
+            db.insert(curr, &buf)
+                .context("Failed to insert solved record.")?;
+            Ok(parents
+                .get(&curr)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|parent| {
+                    pending[parent].fetch_sub(1, Ordering::AcqRel) == 1
+                })
+                .collect::<Vec<State>>())
+        })?;
+
+        ready = unlocked.concat();
+    }
+    Ok(())
+}
 
-/// Solver-specific record entry, meant to communicate the remoteness and each
-/// player's utility at a corresponding game state. The layout is as follows:
+/// Computes the same records as `dynamic_backward_induction` (without mapping
+/// states to canonical ones) by a breadth-first traversal of `game` in
+/// retrograde, starting from its terminal states. Each state reached in this
+/// way is assigned a counter of its distinct children which remain unsolved or
+/// untraversed, and is solved (and traversed from) as soon as its counter
+/// reaches zero, after which the counter is discarded. Hence, besides the
+/// database, this only keeps the states which are solved but not yet traversed,
+/// and the counters of the states which are only partially solved.
 ///
-/// ```none
-/// [UTILITY_SIZE bits: P0 utility]
-/// ...
-/// [UTILITY_SIZE bits: P(N-1) utility]
-/// [REMOTENESS_SIZE bits: Remoteness]
-/// [0b0 until BUFFER_SIZE]
-/// ```
+/// Since the children of each state are considered in the same order as in
+/// `dynamic_backward_induction`, the same choices are made under `policy`.
+/// Fails if the start of `game` is not solved by the end of the traversal. If
+/// `resources` have a memory limit, the memory used by `db`, the queue, and the
+/// counters is kept under it.
 ///
-/// The number of players `N` is limited by `BUFFER_SIZE`, because a statically
-/// sized buffer is used for intermediary storage. The utility and remoteness
-/// values are encoded in big-endian, with utility being a signed two's
-/// complement integer and remoteness an unsigned integer.
-struct RecordBuffer {
-    buf: BitArr!(for BUFFER_SIZE, in u8, Msb0),
-    players: PlayerCount,
+/// If `resources` specify a checkpoint, the records in `db` and the queue are
+/// saved periodically (see `solver::checkpoint`), and the traversal resumes
+/// from the last saved queue when solving under `IOMode::Find`. The counters
+/// are not saved, as they can be recovered from the records and the queue.
+fn retrograde_backward_induction<const N: PlayerCount, D, G>(
+    db: &mut D,
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<()>
+where
+    D: KVStore<RecordBuffer> + Tabular + Budgeted,
+    G: Acyclic<N>
+        + DTransition<State>
+        + TerminalEnumerable<State>
+        + Solvable<N>
+        + Limited,
+{
+    let layout = layout(game, policy);
+    let resumed = checkpoint::resume(db, game, mode, policy, resources)
+        .context("Failed to resume from checkpoint.")?;
+
+    let mut queue = VecDeque::new();
+    if let Some(frontier) = resumed {
+        queue.extend(frontier);
+    } else {
+        for state in game.terminals() {
+            if db.get(state).is_none() {
+                let mut buf = RecordBuffer::with_layout(layout);
+                store(&mut buf, game, state, game.utility(state), 0)
+                    .context("Failed to store outcome of end state.")?;
+                db.put(state, &buf);
+                queue.push_back(state);
+            }
+        }
+    }
+
+    let mut queued: HashSet<State> = queue.iter().copied().collect();
+    let mut pending: HashMap<State, usize> = HashMap::new();
+    let mut visited = 0;
+    while let Some(curr) = queue.pop_front() {
+        let mut parents = game.retrograde(curr);
+        parents.sort_unstable();
+        parents.dedup();
+        for parent in parents {
+            if game.end(parent) || db.get(parent).is_some() {
+                continue;
+            }
+
+            let remaining = match pending.get_mut(&parent) {
+                Some(remaining) => remaining,
+                None => {
+                    let mut children = game.prograde(parent);
+                    util::check_transitions(game, parent, children.len())?;
+                    children.sort_unstable();
+                    children.dedup();
+                    let untraversed = children
+                        .into_iter()
+                        .filter(|&child| {
+                            db.get(child).is_none() || queued.contains(&child)
+                        })
+                        .count();
+                    pending
+                        .entry(parent)
+                        .or_insert(untraversed)
+                },
+            };
+
+            *remaining -= 1;
+            if *remaining > 0 {
+                continue;
+            }
+
+            pending.remove(&parent);
+            let mut optimal: Option<Choice<N>> = None;
+            for state in game.prograde(parent) {
+                let bits = db.get(state).ok_or_else(|| {
+                    SolverError::IncompleteRetrograde {
+                        game_id: game.id(),
+                        state: parent,
+                    }
+                })?;
+                let buf = util::parse(game, state, bits, layout)
+                    .context("Failed to create record for middle state.")?;
+                let choice = choice(game, state, &buf)?;
+                if optimal.as_ref().is_none_or(|o| {
+                    policy.prefers(game.turn(parent), parent, &choice, o)
+                }) {
+                    optimal = Some(choice);
+                }
+            }
+
+            let mut buf = RecordBuffer::with_layout(layout);
+            if let Some(optimal) = optimal {
+                store(
+                    &mut buf,
+                    game,
+                    parent,
+                    optimal.utility,
+                    optimal.remoteness + 1,
+                )
+                .context("Failed to store outcome of solved record.")?;
+            }
+            db.put(parent, &buf);
+            queue.push_back(parent);
+            queued.insert(parent);
+        }
+
+        queued.remove(&curr);
+        let counter = mem::size_of::<(State, usize)>() + 1;
+        let member = mem::size_of::<State>() + 1;
+        resources.enforce(
+            db,
+            queue.capacity() * mem::size_of::<State>()
+                + pending.capacity() * counter
+                + queued.capacity() * member,
+        )?;
+
+        visited += 1;
+        if checkpoint::due(resources, visited) {
+            let frontier = queue.make_contiguous();
+            checkpoint::save(db, game, policy, resources, Some(frontier))
+                .context("Failed to save checkpoint.")?;
+        }
+    }
+
+    if db.get(game.start()).is_none() {
+        Err(SolverError::IncompleteRetrograde {
+            game_id: game.id(),
+            state: game.start(),
+        })?
+    }
+
+    checkpoint::save(db, game, policy, resources, None)
+        .context("Failed to save complete checkpoint.")?;
+    Ok(())
 }
 
-impl Record for RecordBuffer {
-    #[inline(always)]
-    fn raw(&self) -> &BitSlice<u8, Msb0> {
-        &self.buf[..Self::bit_size(self.players)]
+/// Performs an iterative depth-first traversal of the `game` tree in the same
+/// way as `dynamic_backward_induction`, but storing `sur` records. The adjusted
+/// utility vector of each end state is obtained by multiplying the externality
+/// matrix of `game` by its raw utility vector, and players choose the child
+/// which maximizes their adjusted utility (breaking ties according to `policy`
+/// as applied to adjusted utilities). Checkpoints and the memory limit in
+/// `resources` are handled as in `dynamic_backward_induction`, and both utility
+/// vectors and remoteness are checked against the bounds declared by `game`.
+fn external_backward_induction<const N: PlayerCount, D, G>(
+    db: &mut D,
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<()>
+where
+    D: KVStore<sur::RecordBuffer> + Tabular + Budgeted,
+    G: Acyclic<N> + DTransition<State> + Bounded<State> + External<N> + Limited,
+{
+    let externality = game.externality();
+    let mut stack = checkpoint::resume(db, game, mode, policy, resources)
+        .context("Failed to resume from checkpoint.")?
+        .unwrap_or_else(|| vec![game.start()]);
+
+    let mut visited = 0;
+    while let Some(curr) = stack.pop() {
+        let children = game.prograde(curr);
+        util::check_transitions(game, curr, children.len())?;
+        let buf = sur::RecordBuffer::new(game.players())
+            .context("Failed to create placeholder record.")?;
+        if db.get(curr).is_none() {
+            db.put(curr, &buf);
+            if game.end(curr) {
+                let raw = game.utility(curr);
+                let adjusted = (externality * SVector::from(raw)).into();
+                check_bounds(game, curr, raw, 0)?;
+                check_bounds(game, curr, adjusted, 0)?;
+                let mut buf = sur::RecordBuffer::new(game.players())
+                    .context("Failed to create record for end state.")?;
+                buf.set_utility(raw)
+                    .context("Failed to copy utility values to record.")?;
+                buf.set_adjusted(adjusted)
+                    .context(
+                        "Failed to copy adjusted utility values to record.",
+                    )?;
+                buf.set_remoteness(0)
+                    .context("Failed to set remoteness for end state.")?;
+                db.put(curr, &buf);
+            } else {
+                stack.push(curr);
+                stack.extend(
+                    children
+                        .iter()
+                        .filter(|&x| db.get(*x).is_none()),
+                );
+            }
+        } else if !game.end(curr) {
+            let mut optimal: Option<(Choice<N>, sur::RecordBuffer)> = None;
+            for state in children {
+                let buf = sur::RecordBuffer::from(db.get(state).unwrap())
+                    .context("Failed to create record for middle state.")?;
+                let mut adjusted = [0; N];
+                for (player, utility) in adjusted.iter_mut().enumerate() {
+                    *utility = buf.get_adjusted(player).context(
+                        "Failed to get adjusted utility from record.",
+                    )?;
+                }
+                let choice = Choice {
+                    state,
+                    utility: adjusted,
+                    remoteness: buf.get_remoteness(),
+                };
+                if optimal
+                    .as_ref()
+                    .is_none_or(|(o, _)| {
+                        policy.prefers(game.turn(curr), curr, &choice, o)
+                    })
+                {
+                    optimal = Some((choice, buf));
+                }
+            }
+            if let Some((choice, mut optimal)) = optimal {
+                let remoteness = choice.remoteness + 1;
+                check_bounds(game, curr, choice.utility, remoteness)?;
+                optimal
+                    .set_remoteness(remoteness)
+                    .context("Failed to set remoteness for solved record.")?;
+                db.put(curr, &optimal);
+            } else {
+                db.put(curr, &buf);
+            }
+        }
+
+        resources.enforce(db, stack.capacity() * mem::size_of::<State>())?;
+        visited += 1;
+        if checkpoint::due(resources, visited) {
+            checkpoint::save(db, game, policy, resources, Some(&stack))
+                .context("Failed to save checkpoint.")?;
+        }
     }
+
+    checkpoint::save(db, game, policy, resources, None)
+        .context("Failed to save complete checkpoint.")?;
+    Ok(())
 }
 
-impl RecordBuffer {
-    /// Returns a new instance of a bit-packed record buffer that is able to
-    /// store utility values for `players`. Fails if `players` is too high for
-    /// the underlying buffer's capacity.
-    #[inline(always)]
-    fn new(players: PlayerCount) -> Result<Self> {
-        if Self::bit_size(players) > BUFFER_SIZE {
-            todo!()
+/* VERIFICATION ALGORITHMS */
+
+/// Checks the records in `db` of the states of `game` visited as specified by
+/// `audit`, which are expected to have been computed under `policy` in the
+/// same way as `dynamic_backward_induction` does. That is, the records of end
+/// states must hold their utility with zero remoteness, and the records of all
+/// other states must hold the outcome of the child chosen under `policy` with
+/// one more remoteness. All states are mapped through `canonical` before being
+/// looked up. Stops after finding `audit.limit` inconsistencies.
+///
+/// Records are only compared to the records of their children, so a record
+/// which disagrees with its children is reported rather than its parent (whose
+/// record is consistent with it, even if it is wrong).
+fn verify<const N: PlayerCount, D, G, F>(
+    db: &D,
+    game: &G,
+    policy: Policy,
+    audit: Audit,
+    canonical: F,
+) -> Result<Report>
+where
+    D: KVStore<RecordBuffer> + Tabular,
+    G: Acyclic<N>
+        + DTransition<State>
+        + Bounded<State>
+        + Solvable<N>
+        + Legible<State>,
+    F: Fn(State) -> State,
+{
+    let schema = db
+        .schema()
+        .context("Solution set table has no schema.")?;
+    let layout = Layout::from_schema(schema)
+        .context("Failed to read record layout from table schema.")?;
+
+    let mut report = Report::default();
+    let mut frontier = Frontier::new(canonical(game.start()), audit);
+    while report.inconsistencies.len() < audit.limit {
+        let Some((curr, first)) = frontier.next() else {
+            break;
+        };
+
+        let children = game
+            .prograde(curr)
+            .into_iter()
+            .map(&canonical)
+            .collect::<Vec<State>>();
+        frontier.extend(&children);
+        if !first {
+            continue;
+        }
+
+        report.checked += 1;
+        let found = match db.get(curr) {
+            Some(bits) => util::parse(game, curr, bits, layout)
+                .context("Failed to parse record of visited state.")?,
+            None => {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::Missing {
+                        state: game.encode(curr),
+                    });
+                continue;
+            },
+        };
+
+        let expected = if game.end(curr) {
+            Outcome {
+                utility: game.utility(curr).to_vec(),
+                remoteness: 0,
+                win_by: layout
+                    .win_by()
+                    .then(|| mur::margin(&game.utility(curr))),
+            }
         } else {
-            Ok(Self {
-                buf: bitarr!(u8, Msb0; 0; BUFFER_SIZE),
-                players,
+            let mut optimal: Option<Choice<N>> = None;
+            let mut complete = true;
+            for &state in &children {
+                let Some(bits) = db.get(state) else {
+                    complete = false;
+                    break;
+                };
+                let buf = util::parse(game, state, bits, layout)
+                    .context("Failed to parse record of child state.")?;
+                let choice = choice(game, state, &buf)?;
+                if optimal.as_ref().is_none_or(|o| {
+                    policy.prefers(game.turn(curr), curr, &choice, o)
+                }) {
+                    optimal = Some(choice);
+                }
+            }
+
+            // Children without records are reported when they are visited.
+            if !complete {
+                continue;
+            }
+
+            match optimal {
+                Some(optimal) => Outcome {
+                    utility: optimal.utility.to_vec(),
+                    remoteness: optimal.remoteness + 1,
+                    win_by: layout
+                        .win_by()
+                        .then(|| mur::margin(&optimal.utility)),
+                },
+                None => Outcome::of(&RecordBuffer::with_layout(layout))?,
+            }
+        };
+
+        let found = Outcome::of(&found)?;
+        if found != expected {
+            report
+                .inconsistencies
+                .push(Inconsistency::Mismatch {
+                    state: game.encode(curr),
+                    found,
+                    expected,
+                });
+        }
+    }
+
+    Ok(report)
+}
+
+/* HELPER FUNCTIONS */
+
+/// Applies `function` to each of `states` across at most `threads` threads,
+/// returning the results in the same order as `states`. Fails if `function`
+/// fails for any of them.
+fn parallel<T, F>(
+    states: &[State],
+    threads: usize,
+    function: F,
+) -> Result<Vec<T>>
+where
+    T: Send,
+    F: Fn(State) -> Result<T> + Sync,
+{
+    let size = states
+        .len()
+        .div_ceil(threads)
+        .max(1);
+    let function = &function;
+    thread::scope(|s| {
+        let handles = states
+            .chunks(size)
+            .map(|chunk| {
+                s.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&state| function(state))
+                        .collect::<Result<Vec<T>>>()
+                })
             })
+            .collect::<Vec<_>>();
+
+        let mut results = Vec::with_capacity(states.len());
+        for handle in handles {
+            results.extend(
+                handle
+                    .join()
+                    .expect("Solver thread panicked.")?,
+            );
+        }
+        Ok(results)
+    })
+}
+
+/// Returns the layout of the records of `game` created under `policy`, which is
+/// derived from the bounds that `game` declares on its utility and remoteness.
+fn layout<const N: usize, G>(game: &G, policy: Policy) -> Layout
+where
+    G: Solvable<N> + Limited,
+{
+    let layout = Layout::fitting(
+        game.players(),
+        game.utility_range(),
+        game.max_remoteness(),
+    );
+
+    if policy.win_by {
+        layout.with_win_by()
+    } else {
+        layout
+    }
+}
+
+/// Stores `utility` and `remoteness` in `buf` as the outcome of `state` (see
+/// `util::store`). Fails if any of these values exceed the bounds declared by
+/// `game`.
+fn store<const N: usize, G>(
+    buf: &mut RecordBuffer,
+    game: &G,
+    state: State,
+    utility: [Utility; N],
+    remoteness: Remoteness,
+) -> Result<()>
+where
+    G: Solvable<N> + Limited,
+{
+    check_bounds(game, state, utility, remoteness)?;
+    util::store(buf, game, state, utility, remoteness)
+}
+
+/// Fails if `utility` or `remoteness` exceed the bounds declared by `game` for
+/// the outcome of `state`.
+fn check_bounds<const N: usize, G>(
+    game: &G,
+    state: State,
+    utility: [Utility; N],
+    remoteness: Remoteness,
+) -> Result<()>
+where
+    G: Solvable<N> + Limited,
+{
+    let bounds = game.utility_range();
+    if let Some(&u) = utility
+        .iter()
+        .find(|u| !bounds.contains(u))
+    {
+        Err(SolverError::UtilityOutOfBounds {
+            game_id: game.id(),
+            state,
+            utility: u,
+            bounds,
+        })?
+    }
+
+    let bound = game.max_remoteness();
+    if remoteness > bound {
+        Err(SolverError::RemotenessOutOfBounds {
+            game_id: game.id(),
+            state,
+            remoteness,
+            bound,
+        })?
+    }
+
+    Ok(())
+}
+
+/// Returns the choice of moving to `state` in `game`, whose record is `buf`.
+/// Fails if `buf` does not hold the utility values of `N` players.
+fn choice<const N: usize, G>(
+    game: &G,
+    state: State,
+    buf: &RecordBuffer,
+) -> Result<Choice<N>>
+where
+    G: Solvable<N>,
+{
+    if buf.players() != N {
+        Err(SolverError::PlayerCountMismatch {
+            game_id: game.id(),
+            state,
+            expected: buf.players(),
+            found: N,
+        })?
+    }
+
+    let mut utility = [0; N];
+    for (player, value) in utility.iter_mut().enumerate() {
+        *value = buf
+            .get_utility(player)
+            .context("Failed to get utility from record.")?;
+    }
+
+    Ok(Choice {
+        state,
+        utility,
+        remoteness: buf.get_remoteness(),
+    })
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::Record;
+    use crate::game::{testing, zero_by, Entry, Game, GameData};
+    use crate::interface::{SolutionMode, TieBreak};
+    use crate::solver::checkpoint::Checkpoint;
+
+    use std::cell::Cell;
+    use std::{env, fs, process};
+
+    /// A one-player game where the player moves from state `i` to state `i + 1`
+    /// (through any of `width` identical moves) until reaching state `length`,
+    /// where they obtain `payoff`. It declares `bounds` on its utility and `max`
+    /// on its remoteness, which are not necessarily correct. The moves out of
+    /// the `fault` state exceed the transition limit, and `calls` counts the
+    /// times that moves were generated.
+    struct Chain {
+        length: State,
+        width: usize,
+        payoff: Utility,
+        bounds: (Utility, Utility),
+        max: Remoteness,
+        fault: Cell<Option<State>>,
+        calls: Cell<usize>,
+    }
+
+    impl Game for Chain {
+        fn initialize(_: Option<String>) -> Result<Self> {
+            unimplemented!()
+        }
+
+        fn id(&self) -> String {
+            "chain".into()
+        }
+
+        fn forward(&mut self, _: Vec<String>) -> Result<()> {
+            unimplemented!()
+        }
+
+        fn info(&self) -> GameData<'_> {
+            unimplemented!()
+        }
+
+        fn solve(
+            &self,
+            _: IOMode,
+            _: SolutionMode,
+            _: Policy,
+            _: &Resources,
+        ) -> Result<()> {
+            unimplemented!()
+        }
+
+        fn query(&self, _: IOMode, _: Option<String>) -> Result<Entry> {
+            unimplemented!()
         }
     }
 
-    /// Return a new instance with `bits` as the underlying buffer. Fails in the
-    /// event that the size of `bits` is incoherent with the record.
-    #[inline(always)]
-    fn from(bits: &BitSlice<u8, Msb0>) -> Result<Self> {
-        let len = bits.len();
-        if len > BUFFER_SIZE {
-            todo!()
-        } else if len < Self::minimum_bit_size() {
-            todo!()
-        } else {
-            let players = Self::player_count(len);
-            let mut buf = bitarr!(u8, Msb0; 0; BUFFER_SIZE);
-            buf[..len].copy_from_bitslice(bits);
-            Ok(Self { players, buf })
+    impl Bounded<State> for Chain {
+        fn start(&self) -> State {
+            0
+        }
+
+        fn end(&self, state: State) -> bool {
+            state == self.length
         }
     }
 
-    /* GET METHODS */
+    impl DTransition<State> for Chain {
+        fn prograde(&self, state: State) -> Vec<State> {
+            self.calls
+                .set(self.calls.get() + 1);
+            if self.fault.get() == Some(state) {
+                vec![state + 1; MAX_TRANSITIONS + 1]
+            } else if state < self.length {
+                vec![state + 1; self.width]
+            } else {
+                vec![]
+            }
+        }
 
-    /// Parse and return the utility value corresponding to `player`. Fails if
-    /// the `player` index passed in is incoherent with player count.
-    #[inline(always)]
-    fn get_utility(&self, player: Turn) -> Result<Utility> {
-        if player >= self.players {
-            todo!()
-        } else {
-            let start = Self::utility_index(self.players);
-            let end = start + UTILITY_SIZE;
-            Ok(self.buf[start..end].load_be::<Utility>())
+        fn retrograde(&self, state: State) -> Vec<State> {
+            if state > 0 {
+                vec![state - 1]
+            } else {
+                vec![]
+            }
         }
     }
 
-    /// Parse and return the remoteness value in the record encoding. Failure
-    /// here indicates corrupted state.
-    #[inline(always)]
-    fn get_remoteness(&self) -> Remoteness {
-        let start = Self::remoteness_index(self.players);
-        let end = start + REMOTENESS_SIZE;
-        self.buf[start..end].load_be::<Remoteness>()
+    impl Solvable<1> for Chain {
+        fn utility(&self, _: State) -> [Utility; 1] {
+            [self.payoff]
+        }
+
+        fn turn(&self, _: State) -> usize {
+            0
+        }
     }
 
-    /* SET METHODS */
+    impl Limited for Chain {
+        fn utility_range(&self) -> std::ops::RangeInclusive<Utility> {
+            self.bounds.0..=self.bounds.1
+        }
 
-    /// Set this entry to have the utility values in `v` for each player. Fails
-    /// if any of the utility values are too high to fit in the space dedicated
-    /// for each player's utility, or if there is a mismatch between player
-    /// count and the number of utility values passed in.
-    #[inline(always)]
-    fn set_utility<const N: usize>(&mut self, v: [Utility; N]) -> Result<()> {
-        if N >= self.players {
-            todo!()
-        } else {
-            let player = 0;
-            while player < self.players {
-                let utility = v[player];
-                if util::min_sbits(utility) > UTILITY_SIZE {
-                    todo!()
-                }
+        fn max_remoteness(&self) -> Remoteness {
+            self.max
+        }
+    }
 
-                let start = Self::utility_index(player);
-                let end = start + UTILITY_SIZE;
-                self.buf[start..end].store_be(utility);
-            }
-            Ok(())
+    impl TerminalEnumerable<State> for Chain {
+        fn terminals(&self) -> Vec<State> {
+            vec![self.length]
         }
     }
 
-    /// Set this entry to have `value` remoteness. Fails if `value` is too high
-    /// to fit in the space dedicated for remoteness within the record.
-    #[inline(always)]
-    fn set_remoteness(&mut self, value: Remoteness) -> Result<()> {
-        if util::min_ubits(value) > REMOTENESS_SIZE {
-            todo!()
-        } else {
-            let start = Self::remoteness_index(self.players);
-            let end = start + REMOTENESS_SIZE;
-            self.buf[start..end].store_be(value);
-            Ok(())
+    impl Acyclic<1> for Chain {}
+
+    fn chain(payoff: Utility, max: Remoteness) -> Chain {
+        Chain {
+            length: 5,
+            width: 1,
+            payoff,
+            bounds: (-1000, 1000),
+            max,
+            fault: Cell::new(None),
+            calls: Cell::new(0),
+        }
+    }
+
+    /// Returns a session of the zero-by `variant`, along with its solution under
+    /// `policy` as computed by `dynamic_solver`, which serves as the reference
+    /// that the solutions computed by other solvers are compared against.
+    fn reference<const N: usize>(
+        variant: &str,
+        policy: Policy,
+    ) -> (zero_by::Session, volatile::Database)
+    where
+        zero_by::Session: Acyclic<N> + Solvable<N>,
+    {
+        let session =
+            zero_by::Session::initialize(Some(variant.into())).unwrap();
+        let db = dynamic_solver::<N, _>(
+            &session,
+            IOMode::Write,
+            policy,
+            &Resources::default(),
+        )
+        .unwrap();
+        (session, db)
+    }
+
+    /// Returns the record of `state` in the solution set `db`, which must have
+    /// one under the layout of its selected table.
+    fn record<D>(db: &D, state: State) -> RecordBuffer
+    where
+        D: KVStore<RecordBuffer> + Tabular,
+    {
+        let layout = Layout::from_schema(db.schema().unwrap()).unwrap();
+        let bits = KVStore::<RecordBuffer>::get(db, state).unwrap();
+        RecordBuffer::parse(bits, layout).unwrap()
+    }
+
+    fn failure(game: &Chain) -> anyhow::Error {
+        failure_with(game, &Resources::default())
+    }
+
+    fn failure_with(game: &Chain, resources: &Resources) -> anyhow::Error {
+        match dynamic_solver(game, IOMode::Write, Policy::default(), resources)
+        {
+            Ok(_) => panic!("Solved a game which should not be solvable."),
+            Err(error) => error,
+        }
+    }
+
+    fn solution(game: &Chain) -> volatile::Database {
+        dynamic_solver(
+            game,
+            IOMode::Write,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn records_are_as_wide_as_declared_bounds() {
+        let game = chain(1000, 5);
+        let db = solution(&game);
+        let layout = Layout::from_schema(db.schema().unwrap()).unwrap();
+        assert_eq!(layout, Layout::fitting(1, -1000..=1000, 5));
+        assert_eq!(layout.size(), 11 + 3);
+
+        let bits = KVStore::<RecordBuffer>::get(&db, 0).unwrap();
+        let record = RecordBuffer::parse(bits, layout).unwrap();
+        assert_eq!(record.get_utility(0).unwrap(), 1000);
+        assert_eq!(record.get_remoteness(), 5);
+    }
+
+    #[test]
+    fn exceeding_declared_bounds_is_an_error() {
+        let error = failure(&chain(1001, 5));
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::UtilityOutOfBounds {
+                state: 5,
+                utility: 1001,
+                ..
+            })
+        ));
+
+        let error = failure(&chain(0, 4));
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::RemotenessOutOfBounds {
+                state: 0,
+                remoteness: 5,
+                bound: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn malformed_games_and_records_are_errors() {
+        let game = Chain {
+            width: MAX_TRANSITIONS + 1,
+            ..chain(0, 5)
+        };
+
+        let error = failure(&game);
+        assert!(error
+            .root_cause()
+            .to_string()
+            .contains("state 0 of the game 'chain'"));
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::TransitionLimitExceeded {
+                state: 0,
+                transitions: 51,
+                limit: MAX_TRANSITIONS,
+                ..
+            })
+        ));
+
+        let game = chain(0, 5);
+        let layout = Layout::new(1);
+        let mut buf = RecordBuffer::with_layout(layout);
+        let error = util::store(&mut buf, &game, 3, [0, 0], 0).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::PlayerCountMismatch {
+                state: 3,
+                expected: 1,
+                found: 2,
+                ..
+            })
+        ));
+
+        let error = util::store(&mut buf, &game, 3, [128], 0).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::RecordOverflow {
+                utility: 128,
+                size: 8,
+                ..
+            })
+        ));
+
+        let error = util::store(&mut buf, &game, 3, [0], 1 << 16).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::RemotenessOverflow { size: 16, .. })
+        ));
+
+        let bits = &buf.raw()[1..];
+        let error = util::parse(&game, 3, bits, layout)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::CorruptedRecord {
+                state: 3,
+                length: 23,
+                expected: 24,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn interrupted_solves_resume_from_checkpoints() {
+        let directory = env::temp_dir()
+            .join(format!("nova-checkpoint-test-{}", process::id()));
+
+        let policy = Policy::default();
+        let resources = Resources {
+            checkpoint: Some(Checkpoint {
+                directory: directory.clone(),
+                interval: 3,
+            }),
+            ..Resources::default()
+        };
+
+        let game = Chain {
+            length: 40,
+            width: 2,
+            ..chain(1, 40)
+        };
+
+        let expected = solution(&game);
+        let uninterrupted = game.calls.replace(0);
+
+        game.fault.set(Some(25));
+        assert!(
+            dynamic_solver(&game, IOMode::Write, policy, &resources).is_err()
+        );
+        game.fault.set(None);
+        game.calls.set(0);
+
+        let resumed =
+            dynamic_solver(&game, IOMode::Find, policy, &resources).unwrap();
+        assert!(game.calls.replace(0) < uninterrupted);
+        for state in 0..=40 {
+            assert_eq!(
+                KVStore::<RecordBuffer>::get(&expected, state),
+                KVStore::<RecordBuffer>::get(&resumed, state),
+            );
+        }
+
+        let found =
+            dynamic_solver(&game, IOMode::Find, policy, &resources).unwrap();
+        assert_eq!(game.calls.get(), 0);
+        for state in 0..=40 {
+            assert_eq!(
+                KVStore::<RecordBuffer>::get(&expected, state),
+                KVStore::<RecordBuffer>::get(&found, state),
+            );
+        }
+
+        let other = Policy {
+            win_by: true,
+            ..policy
+        };
+
+        let error = match dynamic_solver(&game, IOMode::Find, other, &resources)
+        {
+            Ok(_) => panic!("Resumed a checkpoint of a different policy."),
+            Err(error) => error,
+        };
+
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::CheckpointMismatch { .. })
+        ));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn interrupted_retrograde_solves_resume_from_checkpoints() {
+        let directory = env::temp_dir()
+            .join(format!("nova-retrograde-test-{}", process::id()));
+
+        let policy = Policy::default();
+        let resources = Resources {
+            checkpoint: Some(Checkpoint {
+                directory: directory.clone(),
+                interval: 3,
+            }),
+            ..Resources::default()
+        };
+
+        let game = Chain {
+            length: 40,
+            width: 2,
+            ..chain(1, 40)
+        };
+
+        let expected = solution(&game);
+        game.calls.set(0);
+        retrograde_solver(
+            &game,
+            IOMode::Write,
+            policy,
+            &Resources::default(),
+        )
+        .unwrap();
+        let uninterrupted = game.calls.replace(0);
+
+        game.fault.set(Some(25));
+        assert!(
+            retrograde_solver(&game, IOMode::Write, policy, &resources)
+                .is_err()
+        );
+        game.fault.set(None);
+        game.calls.set(0);
+
+        let resumed =
+            retrograde_solver(&game, IOMode::Find, policy, &resources).unwrap();
+        assert!(game.calls.replace(0) < uninterrupted);
+        for state in 0..=40 {
+            assert_eq!(
+                KVStore::<RecordBuffer>::get(&expected, state),
+                KVStore::<RecordBuffer>::get(&resumed, state),
+            );
+        }
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn solving_evicts_records_to_stay_under_memory_limit() {
+        let directory = env::temp_dir().join(format!(
+            "nova-memory-limit-test-{}",
+            process::id()
+        ));
+
+        let game = Chain {
+            length: 3000,
+            ..chain(1, 3000)
+        };
+
+        let expected = solution(&game);
+        let limit = expected.usage();
+        let unbound = Resources {
+            memory_limit: Some(limit),
+            ..Resources::default()
+        };
+
+        let error = failure_with(&game, &unbound);
+        assert!(error
+            .root_cause()
+            .to_string()
+            .contains("exceeds the memory limit"));
+
+        let resources = Resources {
+            checkpoint: Some(Checkpoint {
+                directory: directory.clone(),
+                interval: u64::MAX,
+            }),
+            ..unbound
+        };
+
+        let solved = dynamic_solver(
+            &game,
+            IOMode::Write,
+            Policy::default(),
+            &resources,
+        )
+        .unwrap();
+
+        assert!(solved.usage() < limit);
+        for state in 0..=3000 {
+            assert_eq!(
+                KVStore::<RecordBuffer>::get(&expected, state),
+                KVStore::<RecordBuffer>::get(&solved, state),
+            );
+        }
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn solving_fails_beyond_memory_limit() {
+        let game = chain(1, 5);
+        let resources = Resources {
+            memory_limit: Some(1 << 20),
+            ..Resources::default()
+        };
+
+        let db = dynamic_solver(
+            &game,
+            IOMode::Write,
+            Policy::default(),
+            &resources,
+        )
+        .unwrap();
+        let bits = KVStore::<RecordBuffer>::get(&db, 0).unwrap();
+        let layout = Layout::from_schema(db.schema().unwrap()).unwrap();
+        let record = RecordBuffer::parse(bits, layout).unwrap();
+        assert_eq!(record.get_remoteness(), 5);
+
+        let resources = Resources {
+            memory_limit: Some(64),
+            ..Resources::default()
+        };
+
+        let error = failure_with(&game, &resources);
+        assert!(error
+            .root_cause()
+            .to_string()
+            .contains("exceeds the memory limit of 64 bytes"));
+    }
+
+    #[test]
+    fn ranked_solutions_match_volatile_solutions() {
+        for variant in ["2-10-1-2", "2-25-3-1-4", "2-17-2-3"] {
+            let policy = Policy::default();
+            let (session, expected) = reference::<2>(variant, policy);
+            let ranked = ranked_dynamic_solver::<2, _>(
+                &session,
+                IOMode::Write,
+                policy,
+                &Resources::default(),
+            )
+            .unwrap();
+
+            testing::verify_same_records(
+                &session,
+                &ranked,
+                &expected,
+                usize::MAX,
+            )
+            .unwrap();
         }
     }
 
-    /* LAYOUT HELPER METHODS */
+    #[test]
+    fn identity_externality_preserves_solution() {
+        let (_, expected) = reference::<3>("3-9-1-2", Policy::default());
+        let session = zero_by::Session::initialize(Some(
+            "3-9-1-2:1,0,0,0,1,0,0,0,1".into(),
+        ))
+        .unwrap();
+        let db = external_dynamic_solver::<3, _>(
+            &session,
+            IOMode::Write,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .unwrap();
+
+        for elements in 0..=9 {
+            for turn in 0..3 {
+                let state = session
+                    .decode(format!("{}-{}", elements, turn))
+                    .unwrap();
+                let Some(bits) = KVStore::<sur::RecordBuffer>::get(&db, state)
+                else {
+                    continue;
+                };
 
-    /// Return the number of bits that would be needed to store a record
-    /// containing utility information for `players` as well as remoteness.
-    #[inline(always)]
-    const fn bit_size(players: usize) -> usize {
-        players * UTILITY_SIZE + REMOTENESS_SIZE
+                let found = sur::RecordBuffer::from(bits).unwrap();
+                let expected = record(&expected, state);
+                for player in 0..3 {
+                    let utility = expected
+                        .get_utility(player)
+                        .unwrap();
+                    assert_eq!(found.get_utility(player).unwrap(), utility);
+                    assert_eq!(found.get_adjusted(player).unwrap(), utility);
+                }
+                assert_eq!(found.get_remoteness(), expected.get_remoteness());
+            }
+        }
     }
 
-    /// Return the minimum number of bits needed for a valid record buffer.
-    #[inline(always)]
-    const fn minimum_bit_size() -> usize {
-        REMOTENESS_SIZE
+    #[test]
+    fn external_solutions_reject_win_by_and_unbounded_utility() {
+        let session = zero_by::Session::initialize(Some(
+            "3-9-1-2:1,0,0,0,1,0,0,0,1".into(),
+        ))
+        .unwrap();
+        let policy = Policy {
+            win_by: true,
+            ..Policy::default()
+        };
+        let error = external_dynamic_solver::<3, _>(
+            &session,
+            IOMode::Write,
+            policy,
+            &Resources::default(),
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::UnsupportedWinBy { .. })
+        ));
+
+        // Doubling utility takes adjusted utility outside of [-1, 1].
+        let session =
+            zero_by::Session::initialize(Some("2-4-1-2:2,0,0,2".into()))
+                .unwrap();
+        let error = external_dynamic_solver::<2, _>(
+            &session,
+            IOMode::Write,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(
+            error
+                .root_cause()
+                .downcast_ref::<SolverError>(),
+            Some(SolverError::UtilityOutOfBounds { .. })
+        ));
     }
 
-    /// Return the bit index of the remoteness entry start in the record buffer.
-    #[inline(always)]
-    const fn remoteness_index(players: usize) -> usize {
-        players * UTILITY_SIZE
+    #[test]
+    fn players_optimize_for_effective_utility() {
+        let (session, db) = reference::<2>("2-4-1-2", Policy::default());
+        let found = record(&db, session.start());
+        assert_eq!(found.get_utility(0).unwrap(), 1);
+        assert_eq!(found.get_utility(1).unwrap(), -1);
+
+        // The first player gains utility from their own losses, so both
+        // players prefer for the second player to win.
+        let session =
+            zero_by::Session::initialize(Some("2-4-1-2:-1,0,0,1".into()))
+                .unwrap();
+        let db = external_dynamic_solver::<2, _>(
+            &session,
+            IOMode::Write,
+            Policy::default(),
+            &Resources::default(),
+        )
+        .unwrap();
+
+        let bits = KVStore::<sur::RecordBuffer>::get(&db, session.start());
+        let found = sur::RecordBuffer::from(bits.unwrap()).unwrap();
+        assert_eq!(found.get_utility(0).unwrap(), -1);
+        assert_eq!(found.get_utility(1).unwrap(), 1);
+        assert_eq!(found.get_adjusted(0).unwrap(), 1);
+        assert_eq!(found.get_adjusted(1).unwrap(), 1);
     }
 
-    /// Return the bit index of the 'i'th player's utility entry start.
-    #[inline(always)]
-    const fn utility_index(player: Turn) -> usize {
-        player * UTILITY_SIZE
+    #[test]
+    fn tie_breaking_policies_choose_among_equally_good_moves() {
+        // Player 0 wins whether they take both elements (making player 1 lose
+        // immediately) or only one of them (making player 2 lose next turn).
+        let outcome = |tie_break| {
+            let policy = Policy::new(tie_break, 0);
+            let (session, db) = reference::<3>("3-2-1-2", policy);
+            assert_eq!(
+                db.get_metadata(TIE_BREAK_KEY),
+                Some(policy.to_string().as_str())
+            );
+
+            let found = record(&db, session.start());
+            (0..3)
+                .map(|player| found.get_utility(player).unwrap())
+                .collect::<Vec<Utility>>()
+        };
+
+        assert_eq!(outcome(TieBreak::Remoteness), vec![1, -2, 1]);
+        assert_eq!(outcome(TieBreak::Paranoid), vec![1, -2, 1]);
+        assert_eq!(outcome(TieBreak::Cooperative), vec![1, -2, 1]);
+        assert_eq!(outcome(TieBreak::Lexicographic), vec![1, 1, -2]);
     }
 
-    /// Return the maximum number of utility entries supported by a dense record
-    /// (one that maximizes bit usage) with `length`. Ignores unused bits.
-    #[inline(always)]
-    const fn player_count(length: usize) -> usize {
-        length - REMOTENESS_SIZE / UTILITY_SIZE
+    #[test]
+    fn losers_delay_and_winners_hurry() {
+        // Players take 1 or 3 elements (or all of them, if there are fewer),
+        // so positions with 0, 4, or 6 elements are losing. From 6, the loser
+        // can move to 3 (from which the winner ends the game immediately) or
+        // to 5 (which takes longer). From 7, the winner moves to 4 over 6.
+        let (session, db) = reference::<2>("2-7-1-3", Policy::default());
+        let expected = [0, 1, 1, 1, 2, 3, 4, 3];
+        for (elements, &remoteness) in expected.iter().enumerate() {
+            let turn = (7 - elements) % 2;
+            let state = session
+                .decode(format!("{}-{}", elements, turn))
+                .unwrap();
+            let found = record(&db, state);
+
+            let losing = [0, 4, 6].contains(&elements);
+            assert_eq!(found.get_remoteness(), remoteness);
+            assert_eq!(found.get_utility(turn).unwrap() < 0, losing);
+        }
+
+        // Each player wins by taking the last element, so positions with a
+        // multiple of 3 elements are losing.
+        let (session, db) = reference::<2>("2-9-1-2", Policy::default());
+        for elements in 0..9 {
+            let state = session
+                .decode(format!("{}-1", elements))
+                .unwrap();
+            let found = record(&db, state);
+
+            let winning = (elements % 3 != 0) as Remoteness;
+            assert_eq!(
+                found.get_remoteness(),
+                2 * (elements / 3) + winning
+            );
+        }
+    }
+
+    #[test]
+    fn win_by_margins_are_recorded() {
+        let policy = Policy {
+            win_by: true,
+            ..Policy::default()
+        };
+
+        let (session, db) = reference::<3>("3-6-1-2", policy);
+        let layout = Layout::from_schema(db.schema().unwrap()).unwrap();
+        let bits = KVStore::<RecordBuffer>::get(&db, session.start()).unwrap();
+
+        // With three players, two of them always share the highest utility.
+        assert_eq!(
+            record(&db, session.start())
+                .get_win_by()
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            layout,
+            Layout::fitting(3, -2..=1, 6).with_win_by()
+        );
+        assert!(
+            RecordBuffer::parse(bits, Layout::fitting(3, -2..=1, 6)).is_err()
+        );
+
+        let (session, db) = reference::<2>("2-6-1-2", policy);
+        assert_eq!(
+            record(&db, session.start())
+                .get_win_by()
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn parallel_solutions_match_sequential_solutions() {
+        let policies = [
+            Policy::default(),
+            Policy {
+                win_by: true,
+                ..Policy::default()
+            },
+            Policy::new(TieBreak::Paranoid, 0),
+            Policy::new(TieBreak::Random, 7),
+        ];
+
+        for policy in policies {
+            let (session, expected) = reference::<3>("3-20-1-2-3", policy);
+            let parallel = parallel_dynamic_solver::<3, _>(
+                &session,
+                policy,
+                &Resources {
+                    threads: 4,
+                    ..Resources::default()
+                },
+            )
+            .unwrap();
+
+            testing::verify_same_records(
+                &session,
+                &parallel,
+                &expected,
+                usize::MAX,
+            )
+            .unwrap();
+        }
     }
 }
@@ -7,3 +7,164 @@
 //! #### Authorship
 //!
 //! - Max Fierro, 12/3/2023 (maxfierro@berkeley.edu)
+
+use anyhow::{Context, Result};
+
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
+use std::mem;
+
+use crate::database::volatile;
+use crate::database::{Budgeted, KVStore, Tabular};
+use crate::game::{Bounded, DTransition, Solvable};
+use crate::interface::IOMode;
+use crate::model::State;
+use crate::solver::checkpoint;
+use crate::solver::error::SolverError;
+use crate::solver::record::mur::{self, Layout, RecordBuffer};
+use crate::solver::util::{self, Policy, Resources};
+
+/* SOLVERS */
+
+/// Solves the single-player `game` (whose state graph may contain cycles)
+/// through `retrograde_breadth_first_search`. Since the traversal cannot be
+/// resumed partway through, checkpoints are only saved once the solution set
+/// is complete, and are reused when solving under `IOMode::Find`. Puzzles have
+/// no ties between players, but `policy` is recorded along with checkpoints.
+pub fn puzzle_solver<G>(
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<volatile::Database>
+where
+    G: DTransition<State> + Bounded<State> + Solvable<1>,
+{
+    let mut db = volatile_database(game)
+        .context("Failed to initialize volatile database.")?;
+    match checkpoint::resume(&mut db, game, mode, policy, resources)
+        .context("Failed to resume from checkpoint.")?
+    {
+        Some(frontier) if frontier.is_empty() => return Ok(db),
+        Some(_) => {
+            Err(SolverError::CorruptedCheckpoint { game_id: game.id() })?
+        },
+        None => (),
+    }
+
+    retrograde_breadth_first_search(&mut db, game, resources)
+        .context("Failed solving algorithm execution.")?;
+    checkpoint::save(&mut db, game, policy, resources, None)
+        .context("Failed to save complete checkpoint.")?;
+    Ok(db)
+}
+
+/* DATABASE INITIALIZATION */
+
+/// Initializes a volatile database, creating a table schema according to the
+/// solver record layout, initializing a table with that schema, and switching
+/// to that table before returning the database handle.
+fn volatile_database<G>(game: &G) -> Result<volatile::Database>
+where
+    G: Solvable<1>,
+{
+    let mut db = volatile::Database::initialize();
+    let schema = mur::schema(game.players())
+        .context("Failed to create table schema for solver records.")?;
+
+    let id = game.id();
+    db.create_table(&id, schema)
+        .context("Failed to create database table for solution set.")?;
+    db.select_table(&id)
+        .context("Failed to select solution set database table.")?;
+
+    Ok(db)
+}
+
+/* SOLVING ALGORITHMS */
+
+/// Solves a single-player `game` whose state graph may contain cycles. First,
+/// all states reachable from the starting state are discovered through a
+/// breadth-first traversal. Then, a breadth-first traversal is performed in
+/// retrograde from the end states found, such that each state is assigned the
+/// highest utility attainable from it, and the lowest remoteness with which
+/// that utility can be attained (which is the length of an optimal solution).
+///
+/// End states are processed in groups of descending utility, so the first time
+/// the retrograde traversal reaches a state, it will have done so through one
+/// of the shortest paths to the best end state reachable from it. States which
+/// cannot reach any end state are left without a record in `db`. If
+/// `resources` have a memory limit, the memory used by `db` and the states
+/// kept by the traversals is kept under it.
+fn retrograde_breadth_first_search<D, G>(
+    db: &mut D,
+    game: &G,
+    resources: &Resources,
+) -> Result<()>
+where
+    D: KVStore<RecordBuffer> + Budgeted,
+    G: DTransition<State> + Bounded<State> + Solvable<1>,
+{
+    let layout = Layout::new(game.players());
+    let mut seen = HashSet::new();
+    let mut ends = Vec::new();
+    let mut queue = VecDeque::new();
+    seen.insert(game.start());
+    queue.push_back(game.start());
+    while let Some(curr) = queue.pop_front() {
+        if game.end(curr) {
+            ends.push(curr);
+            continue;
+        }
+        let children = game.prograde(curr);
+        util::check_transitions(game, curr, children.len())?;
+        for child in children {
+            if seen.insert(child) {
+                queue.push_back(child);
+            }
+        }
+    }
+
+    ends.sort_by_key(|&s| Reverse(game.utility(s)));
+    for group in ends.chunk_by(|&a, &b| game.utility(a) == game.utility(b)) {
+        let utility = game.utility(group[0]);
+        for &end in group {
+            let mut buf = RecordBuffer::new(game.players())
+                .context("Failed to create record for end state.")?;
+            util::store(&mut buf, game, end, utility, 0)
+                .context("Failed to store outcome of end state.")?;
+            db.put(end, &buf);
+            queue.push_back(end);
+        }
+
+        while let Some(curr) = queue.pop_front() {
+            let remoteness =
+                util::parse(game, curr, db.get(curr).unwrap(), layout)
+                    .context("Failed to create record for middle state.")?
+                    .get_remoteness();
+
+            for parent in game.retrograde(curr) {
+                if !seen.contains(&parent)
+                    || game.end(parent)
+                    || db.get(parent).is_some()
+                {
+                    continue;
+                }
+
+                let mut buf = RecordBuffer::new(game.players())
+                    .context("Failed to create record for middle state.")?;
+                util::store(&mut buf, game, parent, utility, remoteness + 1)
+                    .context("Failed to store outcome of solved record.")?;
+                db.put(parent, &buf);
+                queue.push_back(parent);
+            }
+
+            resources.enforce(
+                db,
+                (seen.capacity() + queue.capacity() + ends.capacity())
+                    * mem::size_of::<State>(),
+            )?;
+        }
+    }
+    Ok(())
+}
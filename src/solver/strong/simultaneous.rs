@@ -0,0 +1,392 @@
+//! # Strong Simultaneous Solving Module
+//!
+//! This module implements strong solving routines for acyclic games where the
+//! players move simultaneously, which are solved by computing a mixed-strategy
+//! equilibrium at each of their states.
+
+use anyhow::{Context, Result};
+use nalgebra::DMatrix;
+
+use std::mem;
+
+use crate::database::volatile;
+use crate::database::{Budgeted, KVStore, Tabular};
+use crate::game::{Acyclic, Bounded, Game, Simultaneous};
+use crate::interface::IOMode;
+use crate::model::State;
+use crate::solver::checkpoint;
+use crate::solver::error::SolverError;
+use crate::solver::record::mes::{self, RecordBuffer};
+use crate::solver::util::{self, Policy, Resources, EPSILON};
+
+/* SOLVERS */
+
+/// Strongly solves the two-player zero-sum `game`, storing the equilibrium
+/// value of each state for player 0 along with the supports of the strategies
+/// of both players at that state (see `record::mes`). Fails if the utilities
+/// of any end state do not add up to zero, or if any player has too many
+/// actions at a state for them to fit in a record.
+///
+/// Since the traversal cannot be resumed partway through, checkpoints are only
+/// saved once the solution set is complete, and are reused when solving under
+/// `IOMode::Find`. Equilibria do not depend on tie-breaking, but `policy` is
+/// recorded along with checkpoints.
+pub fn zero_sum_solver<G>(
+    game: &G,
+    mode: IOMode,
+    policy: Policy,
+    resources: &Resources,
+) -> Result<volatile::Database>
+where
+    G: Acyclic<2> + Bounded<State> + Simultaneous<2>,
+{
+    util::check_acyclic(game)?;
+    let mut db = volatile_database(game)
+        .context("Failed to initialize volatile database.")?;
+    match checkpoint::resume(&mut db, game, mode, policy, resources)
+        .context("Failed to resume from checkpoint.")?
+    {
+        Some(frontier) if frontier.is_empty() => return Ok(db),
+        Some(_) => {
+            Err(SolverError::CorruptedCheckpoint { game_id: game.id() })?
+        },
+        None => (),
+    }
+
+    equilibrium_backward_induction(&mut db, game, resources)
+        .context("Failed solving algorithm execution.")?;
+    checkpoint::save(&mut db, game, policy, resources, None)
+        .context("Failed to save complete checkpoint.")?;
+    Ok(db)
+}
+
+/* DATABASE INITIALIZATION */
+
+/// Initializes a volatile database, creating a table schema according to the
+/// solver record layout, initializing a table with that schema, and switching
+/// to that table before returning the database handle.
+fn volatile_database<G>(game: &G) -> Result<volatile::Database>
+where
+    G: Game,
+{
+    let mut db = volatile::Database::initialize();
+    let schema =
+        mes::schema().context("Failed to create table schema for records.")?;
+
+    let id = game.id();
+    db.create_table(&id, schema)
+        .context("Failed to create database table for solution set.")?;
+    db.select_table(&id)
+        .context("Failed to select solution set database table.")?;
+
+    Ok(db)
+}
+
+/* SOLVING ALGORITHMS */
+
+/// Performs an iterative depth-first traversal of the `game` tree, assigning
+/// to each state within `db` the value of a mixed-strategy equilibrium of the
+/// matrix game whose payoffs are the values of the states reached through each
+/// joint action, along with the supports of the strategies of that equilibrium.
+/// The value of each end state is the utility of player 0 at that state. If
+/// `resources` have a memory limit, the memory used by `db` and the states kept
+/// by the traversal is kept under it.
+fn equilibrium_backward_induction<D, G>(
+    db: &mut D,
+    game: &G,
+    resources: &Resources,
+) -> Result<()>
+where
+    D: KVStore<RecordBuffer> + Budgeted,
+    G: Acyclic<2> + Bounded<State> + Simultaneous<2>,
+{
+    let mut stack = Vec::new();
+    stack.push(game.start());
+    while let Some(curr) = stack.pop() {
+        if db.get(curr).is_none() {
+            db.put(curr, &RecordBuffer::new());
+            if game.end(curr) {
+                let [p0, p1] = game.utility(curr);
+                if p0 + p1 != 0 {
+                    Err(SolverError::NonZeroSum {
+                        game_id: game.id(),
+                        state: curr,
+                        utility: vec![p0, p1],
+                    })?
+                }
+
+                let mut buf = RecordBuffer::new();
+                buf.set_value(p0 as f64);
+                db.put(curr, &buf);
+            } else {
+                stack.push(curr);
+                stack.extend(
+                    children(game, curr)?
+                        .into_iter()
+                        .flatten()
+                        .filter(|&x| db.get(x).is_none()),
+                );
+            }
+        } else if !game.end(curr) {
+            let children = children(game, curr)?;
+            let mut payoffs = Vec::new();
+            for &child in children.iter().flatten() {
+                let buf = RecordBuffer::from(db.get(child).unwrap())
+                    .context("Failed to create record for middle state.")?;
+                payoffs.push(buf.get_value());
+            }
+
+            let payoffs = DMatrix::from_row_slice(
+                children.len(),
+                children[0].len(),
+                &payoffs,
+            );
+
+            let eq = util::matrix_game(&payoffs);
+            let mut buf = RecordBuffer::new();
+            buf.set_value(eq.value);
+            buf.set_support(0, &support(&eq.row))
+                .context("Failed to set support for solved record.")?;
+            buf.set_support(1, &support(&eq.col))
+                .context("Failed to set support for solved record.")?;
+            db.put(curr, &buf);
+        }
+
+        resources.enforce(db, stack.capacity() * mem::size_of::<State>())?;
+    }
+    Ok(())
+}
+
+/* HELPER FUNCTIONS */
+
+/// Returns the states reached from `state` in `game`, where the element at
+/// `[i][j]` is reached when players 0 and 1 choose their `i`th and `j`th
+/// actions. Fails if either player has no actions or too many of them.
+fn children<G>(game: &G, state: State) -> Result<Vec<Vec<State>>>
+where
+    G: Simultaneous<2>,
+{
+    let [rows, cols] = game.actions(state);
+    for (player, actions) in [&rows, &cols].iter().enumerate() {
+        if actions.is_empty() || actions.len() > mes::MAX_ACTIONS {
+            Err(SolverError::ActionCountOutOfRange {
+                game_id: game.id(),
+                state,
+                player,
+                actions: actions.len(),
+                limit: mes::MAX_ACTIONS,
+            })?
+        }
+    }
+
+    Ok(rows
+        .iter()
+        .map(|&r| {
+            cols.iter()
+                .map(|&c| game.transition(state, [r, c]))
+                .collect()
+        })
+        .collect())
+}
+
+/// Returns the indices of the actions chosen with non-zero probability under
+/// the mixed `strategy`.
+fn support(strategy: &[f64]) -> Vec<usize> {
+    strategy
+        .iter()
+        .enumerate()
+        .filter(|(_, &p)| p > EPSILON)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::database::KVStore;
+    use crate::game::{Entry, GameData};
+    use crate::interface::SolutionMode;
+    use crate::model::{Move, Utility};
+
+    /// A game with explicitly listed states, where each non-terminal state is
+    /// a matrix of the states reached through each joint action, and each
+    /// terminal state has a utility vector. The starting state is 0.
+    struct Tree {
+        nodes: Vec<Node>,
+    }
+
+    enum Node {
+        Matrix(Vec<Vec<State>>),
+        Leaf([Utility; 2]),
+    }
+
+    fn leaf(utility: Utility) -> Node {
+        Node::Leaf([utility, -utility])
+    }
+
+    impl Game for Tree {
+        fn initialize(_: Option<String>) -> anyhow::Result<Self> {
+            unimplemented!()
+        }
+
+        fn id(&self) -> String {
+            "tree".into()
+        }
+
+        fn forward(&mut self, _: Vec<String>) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+
+        fn info(&self) -> GameData<'_> {
+            unimplemented!()
+        }
+
+        fn solve(
+            &self,
+            _: IOMode,
+            _: SolutionMode,
+            _: Policy,
+            _: &Resources,
+        ) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+
+        fn query(&self, _: IOMode, _: Option<String>) -> Result<Entry> {
+            unimplemented!()
+        }
+    }
+
+    impl Bounded<State> for Tree {
+        fn start(&self) -> State {
+            0
+        }
+
+        fn end(&self, state: State) -> bool {
+            matches!(self.nodes[state as usize], Node::Leaf(_))
+        }
+    }
+
+    impl Simultaneous<2> for Tree {
+        fn actions(&self, state: State) -> [Vec<Move>; 2] {
+            match &self.nodes[state as usize] {
+                Node::Matrix(m) => [
+                    (0..m.len() as Move).collect(),
+                    (0..m[0].len() as Move).collect(),
+                ],
+                Node::Leaf(_) => [vec![], vec![]],
+            }
+        }
+
+        fn transition(&self, state: State, joint: [Move; 2]) -> State {
+            match &self.nodes[state as usize] {
+                Node::Matrix(m) => m[joint[0] as usize][joint[1] as usize],
+                Node::Leaf(_) => panic!("No transitions from end states."),
+            }
+        }
+
+        fn utility(&self, state: State) -> [Utility; 2] {
+            match self.nodes[state as usize] {
+                Node::Leaf(utility) => utility,
+                Node::Matrix(_) => panic!("No utility at middle states."),
+            }
+        }
+    }
+
+    impl Acyclic<2> for Tree {}
+
+    fn solve(game: &Tree) -> Result<volatile::Database> {
+        zero_sum_solver(
+            game,
+            IOMode::Write,
+            Policy::default(),
+            &Resources::default(),
+        )
+    }
+
+    fn record(db: &volatile::Database, state: State) -> RecordBuffer {
+        RecordBuffer::from(KVStore::<RecordBuffer>::get(db, state).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn rock_paper_scissors_has_full_support() {
+        let game = Tree {
+            nodes: vec![
+                Node::Matrix(vec![vec![1, 2, 3], vec![3, 1, 2], vec![2, 3, 1]]),
+                leaf(0),
+                leaf(-1),
+                leaf(1),
+            ],
+        };
+
+        let db = solve(&game).unwrap();
+        let start = record(&db, 0);
+        assert!(start.get_value().abs() < 1e-6);
+        assert_eq!(start.get_support(0).unwrap(), vec![0, 1, 2]);
+        assert_eq!(start.get_support(1).unwrap(), vec![0, 1, 2]);
+        assert_eq!(record(&db, 2).get_value(), -1.0);
+    }
+
+    #[test]
+    fn values_propagate_through_subgames() {
+        // Player 0 chooses between a game of matching pennies (with value 0)
+        // and a game where player 1 chooses whether player 0 gets a utility of
+        // 1 (after a forced move) or -1 (with value -1).
+        let game = Tree {
+            nodes: vec![
+                Node::Matrix(vec![vec![1], vec![2]]),
+                Node::Matrix(vec![vec![3, 4], vec![4, 3]]),
+                Node::Matrix(vec![vec![5, 6]]),
+                leaf(1),
+                leaf(-1),
+                Node::Matrix(vec![vec![3]]),
+                leaf(-1),
+            ],
+        };
+
+        let db = solve(&game).unwrap();
+        let pennies = record(&db, 1);
+        assert!(pennies.get_value().abs() < 1e-6);
+        assert_eq!(pennies.get_support(0).unwrap(), vec![0, 1]);
+        assert_eq!(pennies.get_support(1).unwrap(), vec![0, 1]);
+
+        let choice = record(&db, 2);
+        assert_eq!(record(&db, 5).get_value(), 1.0);
+        assert!((choice.get_value() + 1.0).abs() < 1e-6);
+        assert_eq!(choice.get_support(1).unwrap(), vec![1]);
+
+        let start = record(&db, 0);
+        assert!(start.get_value().abs() < 1e-6);
+        assert_eq!(start.get_support(0).unwrap(), vec![0]);
+        assert_eq!(start.get_support(1).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn malformed_games_are_not_solved() {
+        let game = Tree {
+            nodes: vec![Node::Matrix(vec![vec![1]]), Node::Leaf([1, 1])],
+        };
+        let error = solve(&game).err().unwrap();
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::NonZeroSum { state: 1, .. })
+        ));
+
+        let game = Tree {
+            nodes: vec![Node::Matrix(vec![vec![]])],
+        };
+        let error = solve(&game).err().unwrap();
+        assert!(matches!(
+            error.downcast_ref::<SolverError>(),
+            Some(SolverError::ActionCountOutOfRange {
+                state: 0,
+                player: 1,
+                actions: 0,
+                ..
+            })
+        ));
+    }
+}
@@ -7,18 +7,31 @@
 //!
 //! - Max Fierro, 2/24/2024 (maxfierro@berkeley.edu)
 
-use crate::model::Utility;
+use anyhow::{Context, Result};
+use bitvec::order::Msb0;
+use bitvec::slice::BitSlice;
+use nalgebra::DMatrix;
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::Path;
+
+use crate::database::{vector, Budgeted, KVStore, Persistent, Record};
+use crate::database::{Schema, Tabular};
+use crate::game::{Acyclic, Game, Ranked};
+use crate::interface::TieBreak;
+use crate::model::{PlayerCount, Remoteness, State, Turn, Utility};
+use crate::solver::checkpoint::Checkpoint;
+use crate::solver::error::SolverError;
+use crate::solver::record::mur::{self, Layout, RecordBuffer};
+use crate::solver::MAX_TRANSITIONS;
 
 /* BIT FIELDS */
 
 /// Returns the minimum number of bits required to represent unsigned `val`.
 #[inline(always)]
 pub const fn min_ubits(val: u64) -> usize {
-    let mut x = 0;
-    while (val >> x != 0b1) && (x != u64::BITS) {
-        x += 1;
-    }
-    (u64::BITS - x) as usize
+    (u64::BITS - val.leading_zeros()) as usize
 }
 
 /// Return the minimum number of bits necessary to encode `utility`.
@@ -32,3 +45,653 @@ pub const fn min_sbits(utility: Utility) -> usize {
     }
     res + 1
 }
+
+/* DENSE STORAGE */
+
+/// Key-value store which keeps the record of each state of `game` under the
+/// rank of that state within a vector database, so that records are stored
+/// densely regardless of how sparse the state encoding of `game` is. Keys are
+/// expected to be states reachable from the starting state of `game`.
+pub struct RankedDatabase<'a, G> {
+    game: &'a G,
+    db: vector::Database,
+}
+
+impl<'a, G> RankedDatabase<'a, G>
+where
+    G: Ranked<State>,
+{
+    /// Returns a store for the states of `game` backed by `db`, whose selected
+    /// table is grown to house the records of all possible ranks. Fails if `db`
+    /// has no table selected.
+    pub fn new(game: &'a G, mut db: vector::Database) -> Result<Self> {
+        db.reserve(game.count())?;
+        Ok(Self { game, db })
+    }
+}
+
+impl<R, G> KVStore<R> for RankedDatabase<'_, G>
+where
+    R: Record,
+    G: Ranked<State>,
+{
+    fn put(&mut self, key: State, record: &R) {
+        self.db
+            .put(self.game.rank(key), record)
+    }
+
+    fn get(&self, key: State) -> Option<&BitSlice<u8, Msb0>> {
+        KVStore::<R>::get(&self.db, self.game.rank(key))
+    }
+
+    fn del(&mut self, key: State) {
+        KVStore::<R>::del(&mut self.db, self.game.rank(key))
+    }
+}
+
+impl<G> Tabular for RankedDatabase<'_, G> {
+    fn create_table(&mut self, id: &str, schema: Schema) -> Result<()> {
+        self.db.create_table(id, schema)
+    }
+
+    fn select_table(&mut self, id: &str) -> Result<()> {
+        self.db.select_table(id)
+    }
+
+    fn delete_table(&mut self, id: &str) -> Result<()> {
+        self.db.delete_table(id)
+    }
+
+    fn set_metadata(&mut self, key: &str, value: &str) -> Result<()> {
+        self.db.set_metadata(key, value)
+    }
+
+    fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.db.get_metadata(key)
+    }
+
+    fn schema(&self) -> Option<&Schema> {
+        self.db.schema()
+    }
+}
+
+impl<G> Persistent for RankedDatabase<'_, G> {
+    fn bind_path(&mut self, path: &Path) -> Result<()> {
+        self.db.bind_path(path)
+    }
+
+    fn materialize(&mut self) -> Result<()> {
+        self.db.materialize()
+    }
+
+    fn load_table(&mut self, id: &str) -> Result<()> {
+        self.db.load_table(id)
+    }
+}
+
+impl<G> Budgeted for RankedDatabase<'_, G> {
+    fn usage(&self) -> usize {
+        self.db.usage()
+    }
+
+    fn evict(&mut self, target: usize) -> Result<()> {
+        self.db.evict(target)
+    }
+
+    fn check(&self) -> Result<()> {
+        self.db.check()
+    }
+}
+
+/* RECORD VALIDATION */
+
+/// Stores `utility` and `remoteness` in `buf` as the outcome of `state` in
+/// `game`, along with the win-by margin of `utility` if `buf` has room for it.
+/// Fails if the values cannot be represented in the layout of `buf`.
+pub fn store<const N: usize, G>(
+    buf: &mut RecordBuffer,
+    game: &G,
+    state: State,
+    utility: [Utility; N],
+    remoteness: Remoteness,
+) -> Result<()>
+where
+    G: Game,
+{
+    let layout = buf.layout();
+    if N != layout.players {
+        Err(SolverError::PlayerCountMismatch {
+            game_id: game.id(),
+            state,
+            expected: layout.players,
+            found: N,
+        })?
+    }
+
+    let size = layout.utility_size;
+    if let Some(&u) = utility
+        .iter()
+        .find(|&&u| min_sbits(u) > size)
+    {
+        Err(SolverError::RecordOverflow {
+            game_id: game.id(),
+            state,
+            utility: u,
+            size,
+        })?
+    }
+
+    let size = layout.remoteness_size;
+    if min_ubits(remoteness) > size {
+        Err(SolverError::RemotenessOverflow {
+            game_id: game.id(),
+            state,
+            remoteness,
+            size,
+        })?
+    }
+
+    buf.set_utility(utility)
+        .context("Failed to copy utility values to record.")?;
+    buf.set_remoteness(remoteness)
+        .context("Failed to set remoteness for record.")?;
+    if buf.has_win_by() {
+        buf.set_win_by(mur::margin(&utility))
+            .context("Failed to set win-by margin for record.")?;
+    }
+    Ok(())
+}
+
+/// Parses `bits` as the record of `state` in `game` under `layout`. Fails if
+/// the length of `bits` does not match the size of `layout`.
+pub fn parse<G>(
+    game: &G,
+    state: State,
+    bits: &BitSlice<u8, Msb0>,
+    layout: Layout,
+) -> Result<RecordBuffer>
+where
+    G: Game,
+{
+    if bits.len() != layout.size() {
+        Err(SolverError::CorruptedRecord {
+            game_id: game.id(),
+            state,
+            length: bits.len(),
+            expected: layout.size(),
+        })?
+    }
+
+    RecordBuffer::parse(bits, layout)
+}
+
+/// Fails if `transitions` exceeds the number of states that solvers support
+/// being one move away from `state` in `game` (see `MAX_TRANSITIONS`).
+pub fn check_transitions<G>(
+    game: &G,
+    state: State,
+    transitions: usize,
+) -> Result<()>
+where
+    G: Game,
+{
+    if transitions > MAX_TRANSITIONS {
+        Err(SolverError::TransitionLimitExceeded {
+            game_id: game.id(),
+            state,
+            transitions,
+            limit: MAX_TRANSITIONS,
+        })?
+    }
+
+    Ok(())
+}
+
+/// Fails if `game` reports that its state graph may contain cycles, which
+/// solvers that rely on backward induction cannot handle (see `Acyclic`).
+pub fn check_acyclic<const N: PlayerCount, G>(game: &G) -> Result<()>
+where
+    G: Game + Acyclic<N>,
+{
+    if !game.acyclic() {
+        Err(SolverError::CyclicGame { game_id: game.id() })?
+    }
+
+    Ok(())
+}
+
+/* TIE BREAKING */
+
+/// The outcome of moving from a state to one of its children, as seen by a
+/// solving algorithm choosing which child to move to.
+pub struct Choice<const N: usize> {
+    pub state: State,
+    pub utility: [Utility; N],
+    pub remoteness: Remoteness,
+}
+
+/// A tie-breaking policy along with the seed used to generate pseudo-random
+/// preferences, which is only relevant for `TieBreak::Random`. Also specifies
+/// whether the win-by margin of each state should be recorded along with the
+/// outcome of the choices made under the policy (see `record::mur`).
+#[derive(Copy, Clone, Default)]
+pub struct Policy {
+    pub tie_break: TieBreak,
+    pub seed: u64,
+    pub win_by: bool,
+}
+
+/// The execution resources that solvers which support them can use, none of
+/// which have an effect on the solution computed. This is how many `threads`
+/// to solve with (where anything less than two means solving sequentially),
+/// whether to save progress to a `checkpoint`, and a `memory_limit` in bytes to
+/// keep the memory used by a solver (along with its databases) under.
+#[derive(Clone, Default)]
+pub struct Resources {
+    pub threads: usize,
+    pub checkpoint: Option<Checkpoint>,
+    pub memory_limit: Option<usize>,
+}
+
+impl Resources {
+    /// Returns true if solvers should run on more than one thread, where they
+    /// support it.
+    pub fn parallel(&self) -> bool {
+        self.threads > 1
+    }
+
+    /// Keeps the memory used by `db` and the `external` bytes used by a solver
+    /// under the memory limit (see `Budgeted::enforce`), if there is one.
+    pub fn enforce<D: Budgeted>(
+        &self,
+        db: &mut D,
+        external: usize,
+    ) -> Result<()> {
+        match self.memory_limit {
+            Some(limit) => db
+                .enforce(limit, external)
+                .context("Failed to keep memory usage under limit."),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Policy {
+    /// Returns a policy for breaking ties according to `tie_break`.
+    pub fn new(tie_break: TieBreak, seed: u64) -> Self {
+        Self {
+            tie_break,
+            seed,
+            win_by: false,
+        }
+    }
+
+    /// Returns true if the player whose `turn` it is at `parent` prefers the
+    /// `candidate` child over the `incumbent` child. Players first prefer the
+    /// children with higher utility for them, then break ties according to the
+    /// policy, and then by remoteness. Under `TieBreak::Random`, any remaining
+    /// ties are broken pseudo-randomly (but reproducibly).
+    ///
+    /// Remoteness is compared by generalizing the usual win/lose rules: players
+    /// with a negative utility (who are losing) prefer higher remoteness to
+    /// prolong the game, while all others prefer lower remoteness.
+    pub fn prefers<const N: usize>(
+        &self,
+        turn: Turn,
+        parent: State,
+        candidate: &Choice<N>,
+        incumbent: &Choice<N>,
+    ) -> bool {
+        let (a, b) = (candidate, incumbent);
+        let order = a.utility[turn]
+            .cmp(&b.utility[turn])
+            .then_with(|| self.compare(turn, a, b))
+            .then_with(|| {
+                if a.utility[turn] < 0 {
+                    a.remoteness.cmp(&b.remoteness)
+                } else {
+                    b.remoteness.cmp(&a.remoteness)
+                }
+            })
+            .then_with(|| match self.tie_break {
+                TieBreak::Random => self
+                    .key(parent, a.state)
+                    .cmp(&self.key(parent, b.state)),
+                _ => Ordering::Equal,
+            });
+
+        order == Ordering::Greater
+    }
+
+    /// Compares two children of a state where it is `turn`'s turn according
+    /// to the policy criterion alone, where greater children are preferred.
+    fn compare<const N: usize>(
+        &self,
+        turn: Turn,
+        a: &Choice<N>,
+        b: &Choice<N>,
+    ) -> Ordering {
+        let sum = |c: &Choice<N>, skip: Option<Turn>| {
+            c.utility
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| Some(i) != skip)
+                .map(|(_, &u)| u as i128)
+                .sum::<i128>()
+        };
+
+        match self.tie_break {
+            TieBreak::Paranoid => sum(b, Some(turn)).cmp(&sum(a, Some(turn))),
+            TieBreak::Cooperative => sum(a, None).cmp(&sum(b, None)),
+            TieBreak::Lexicographic => a.utility.cmp(&b.utility),
+            TieBreak::Remoteness | TieBreak::Random => Ordering::Equal,
+        }
+    }
+
+    /// Returns a pseudo-random key for the transition from `parent` to `child`
+    /// which only depends on them and the seed of this policy (SplitMix64).
+    fn key(&self, parent: State, child: State) -> u64 {
+        let mix = |mut z: u64| {
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^ (z >> 31)
+        };
+
+        let gamma = 0x9E3779B97F4A7C15;
+        mix(
+            mix(self.seed.wrapping_add(gamma) ^ parent).wrapping_add(gamma)
+                ^ child,
+        )
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tie_break {
+            TieBreak::Random => write!(f, "random(seed={})", self.seed),
+            other => write!(f, "{}", other),
+        }
+    }
+}
+
+/* LINEAR PROGRAMMING */
+
+/// The tolerance used to compare floating point values in linear programs.
+pub const EPSILON: f64 = 1e-9;
+
+/// A mixed-strategy equilibrium of a two-player zero-sum matrix game, where
+/// `row[i]` and `col[j]` are the probabilities with which the row and column
+/// players choose their `i`th and `j`th actions, and `value` is the expected
+/// payoff of the row player (which the column player pays) under equilibrium.
+pub struct Equilibrium {
+    pub value: f64,
+    pub row: Vec<f64>,
+    pub col: Vec<f64>,
+}
+
+/// Returns a mixed-strategy equilibrium of the two-player zero-sum game where
+/// `payoffs[(i, j)]` is the payoff of the row player when they choose action
+/// `i` and the column player chooses action `j`. The matrix must have at least
+/// one row and one column.
+///
+/// After shifting all payoffs to be positive, the column player's strategy is
+/// found by maximizing `sum(y)` subject to `payoffs * y <= 1` and `y >= 0`
+/// through the simplex method (with Bland's rule to prevent cycling), and the
+/// row player's strategy is read off the optimal dual solution.
+pub fn matrix_game(payoffs: &DMatrix<f64>) -> Equilibrium {
+    let (m, n) = payoffs.shape();
+    let shift = 1.0 - payoffs.min();
+    let rhs = n + m;
+
+    // Rows 0..m are constraints and row m is the objective. Columns 0..n are
+    // the column player's variables, n..n + m are slack variables, and the
+    // last column is the right-hand side.
+    let mut tableau = DMatrix::<f64>::zeros(m + 1, n + m + 1);
+    for i in 0..m {
+        for j in 0..n {
+            tableau[(i, j)] = payoffs[(i, j)] + shift;
+        }
+        tableau[(i, n + i)] = 1.0;
+        tableau[(i, rhs)] = 1.0;
+    }
+    for j in 0..n {
+        tableau[(m, j)] = -1.0;
+    }
+
+    let mut basis = (n..n + m).collect::<Vec<usize>>();
+    while let Some(enter) = (0..rhs).find(|&j| tableau[(m, j)] < -EPSILON) {
+        let mut leave: Option<usize> = None;
+        for i in (0..m).filter(|&i| tableau[(i, enter)] > EPSILON) {
+            let ratio = tableau[(i, rhs)] / tableau[(i, enter)];
+            leave = match leave {
+                Some(l) => {
+                    let best = tableau[(l, rhs)] / tableau[(l, enter)];
+                    if ratio < best - EPSILON
+                        || (ratio < best + EPSILON && basis[i] < basis[l])
+                    {
+                        Some(i)
+                    } else {
+                        Some(l)
+                    }
+                },
+                None => Some(i),
+            };
+        }
+
+        // All payoffs are positive after shifting, so the program is bounded
+        // and there is always a row to leave the basis.
+        let leave = leave.expect("Unbounded matrix game linear program.");
+        pivot(&mut tableau, leave, enter);
+        basis[leave] = enter;
+    }
+
+    let total = tableau[(m, rhs)];
+    let mut col = vec![0.0; n];
+    for (i, &var) in basis.iter().enumerate() {
+        if var < n {
+            col[var] = tableau[(i, rhs)] / total;
+        }
+    }
+
+    let row = (0..m)
+        .map(|i| tableau[(m, n + i)] / total)
+        .collect();
+
+    Equilibrium {
+        value: 1.0 / total - shift,
+        row,
+        col,
+    }
+}
+
+/// Performs a simplex pivot on `tableau` around the entry at (`row`, `col`),
+/// scaling `row` so that the entry becomes one and eliminating the rest of the
+/// entries in `col` from the other rows.
+fn pivot(tableau: &mut DMatrix<f64>, row: usize, col: usize) {
+    let factor = tableau[(row, col)];
+    for j in 0..tableau.ncols() {
+        tableau[(row, j)] /= factor;
+    }
+
+    for i in (0..tableau.nrows()).filter(|&i| i != row) {
+        let factor = tableau[(i, col)];
+        if factor != 0.0 {
+            for j in 0..tableau.ncols() {
+                tableau[(i, j)] -= factor * tableau[(row, j)];
+            }
+        }
+    }
+}
+
+/* TESTS */
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    fn solve(rows: usize, cols: usize, payoffs: &[f64]) -> Equilibrium {
+        matrix_game(&DMatrix::from_row_slice(rows, cols, payoffs))
+    }
+
+    #[test]
+    fn rock_paper_scissors_is_uniform() {
+        let eq = solve(3, 3, &[0., -1., 1., 1., 0., -1., -1., 1., 0.]);
+        assert!(close(eq.value, 0.0));
+        assert!(eq
+            .row
+            .iter()
+            .all(|&p| close(p, 1.0 / 3.0)));
+        assert!(eq
+            .col
+            .iter()
+            .all(|&p| close(p, 1.0 / 3.0)));
+    }
+
+    #[test]
+    fn mixed_equilibria_are_found() {
+        let eq = solve(2, 2, &[2., -1., -1., 1.]);
+        assert!(close(eq.value, 0.2));
+        assert!(close(eq.row[0], 0.4) && close(eq.row[1], 0.6));
+        assert!(close(eq.col[0], 0.4) && close(eq.col[1], 0.6));
+    }
+
+    #[test]
+    fn saddle_points_yield_pure_strategies() {
+        let eq = solve(2, 3, &[3., 1., 4., 0., -2., 5.]);
+        assert!(close(eq.value, 1.0));
+        assert!(close(eq.row[0], 1.0) && close(eq.row[1], 0.0));
+        assert!(close(eq.col[1], 1.0));
+
+        let eq = solve(1, 1, &[-7.]);
+        assert!(close(eq.value, -7.0));
+        assert!(close(eq.row[0], 1.0) && close(eq.col[0], 1.0));
+    }
+
+    #[test]
+    fn strategies_guarantee_the_value() {
+        let payoffs = [1., -3., -2., 4., 0., 0.5];
+        let eq = solve(3, 2, &payoffs);
+        assert!(close(eq.row.iter().sum::<f64>(), 1.0));
+        assert!(close(eq.col.iter().sum::<f64>(), 1.0));
+        assert!(eq
+            .row
+            .iter()
+            .chain(eq.col.iter())
+            .all(|&p| p > -EPSILON));
+
+        for j in 0..2 {
+            let payoff = (0..3)
+                .map(|i| eq.row[i] * payoffs[i * 2 + j])
+                .sum::<f64>();
+            assert!(payoff > eq.value - 1e-6);
+        }
+        for i in 0..3 {
+            let payoff = (0..2)
+                .map(|j| eq.col[j] * payoffs[i * 2 + j])
+                .sum::<f64>();
+            assert!(payoff < eq.value + 1e-6);
+        }
+    }
+
+    #[test]
+    fn policies_break_ties_between_equal_utilities() {
+        let choice = |state, utility, remoteness| Choice {
+            state,
+            utility,
+            remoteness,
+        };
+
+        let generous = choice(1, [1, 2, 2], 3);
+        let stingy = choice(2, [1, 0, 0], 2);
+        let selfish = choice(3, [2, -5, -5], 9);
+        let prefers = |tie_break, a: &Choice<3>, b: &Choice<3>| {
+            Policy::new(tie_break, 0).prefers(0, 0, a, b)
+        };
+
+        for tie_break in [
+            TieBreak::Remoteness,
+            TieBreak::Paranoid,
+            TieBreak::Cooperative,
+            TieBreak::Lexicographic,
+            TieBreak::Random,
+        ] {
+            assert!(prefers(tie_break, &selfish, &generous));
+            assert!(prefers(tie_break, &selfish, &stingy));
+        }
+
+        assert!(prefers(TieBreak::Remoteness, &stingy, &generous));
+        assert!(prefers(TieBreak::Paranoid, &stingy, &generous));
+        assert!(prefers(TieBreak::Cooperative, &generous, &stingy));
+        assert!(prefers(
+            TieBreak::Lexicographic,
+            &generous,
+            &stingy
+        ));
+        assert!(prefers(TieBreak::Random, &stingy, &generous));
+    }
+
+    #[test]
+    fn losers_delay_and_winners_hurry() {
+        let policy = Policy::default();
+        let slow = |utility| Choice {
+            state: 1,
+            utility,
+            remoteness: 7,
+        };
+        let fast = |utility| Choice {
+            state: 2,
+            utility,
+            remoteness: 3,
+        };
+
+        assert!(policy.prefers(0, 0, &fast([1, -1]), &slow([1, -1])));
+        assert!(policy.prefers(0, 0, &fast([0, 0]), &slow([0, 0])));
+        assert!(policy.prefers(1, 0, &slow([1, -1]), &fast([1, -1])));
+        assert!(policy.prefers(1, 0, &fast([-1, 1]), &slow([-1, 1])));
+    }
+
+    #[test]
+    fn random_tie_breaking_is_reproducible() {
+        let a = Choice {
+            state: 1,
+            utility: [0, 0],
+            remoteness: 1,
+        };
+        let b = Choice { state: 2, ..a };
+
+        let picks = |seed| {
+            let policy = Policy::new(TieBreak::Random, seed);
+            (0..64)
+                .map(|parent| policy.prefers(0, parent, &a, &b))
+                .collect::<Vec<bool>>()
+        };
+
+        assert_eq!(picks(42), picks(42));
+        assert_ne!(picks(42), picks(43));
+        assert!(picks(42).contains(&true));
+        assert!(picks(42).contains(&false));
+        assert!((0..64).all(|parent| {
+            let policy = Policy::new(TieBreak::Random, 42);
+            policy.prefers(0, parent, &a, &b)
+                != policy.prefers(0, parent, &b, &a)
+        }));
+    }
+
+    #[test]
+    fn policy_names_include_seed_when_relevant() {
+        assert_eq!(Policy::default().to_string(), "remoteness");
+        assert_eq!(
+            Policy::new(TieBreak::Paranoid, 7).to_string(),
+            "paranoid"
+        );
+        assert_eq!(
+            Policy::new(TieBreak::Random, 7).to_string(),
+            "random(seed=7)"
+        );
+    }
+}
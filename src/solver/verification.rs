@@ -0,0 +1,293 @@
+//! # Solver Verification Module
+//!
+//! This module provides the means to check that a persisted solution set is
+//! consistent with the game it was computed for, such that solution sets can
+//! be trusted when they are shared instead of being computed again. This is
+//! done by loading the table of the solution set (see `Persistent`), and then
+//! checking the records of the states reachable from the start of the game
+//! against the rules which the solver that computed them follows.
+//!
+//! Since checking every reachable state requires remembering which of them
+//! were already checked, the states can instead be sampled through random
+//! walks from the start of the game for solution sets that are too large.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::database::{Persistent, Tabular};
+use crate::game::Game;
+use crate::interface::TieBreak;
+use crate::model::{Remoteness, State, Utility};
+use crate::solver::error::SolverError;
+use crate::solver::record::mur::{Layout, RecordBuffer};
+use crate::solver::util::Policy;
+use crate::solver::TIE_BREAK_KEY;
+
+/* DEFINITIONS */
+
+/// Specifies how much of a solution set to verify. At most `limit` of the
+/// inconsistencies found are reported, after which verification stops. If
+/// `samples` is provided, only the states visited by that many random walks
+/// from the start of the game are checked, where the walks are determined by
+/// `seed`. Otherwise, all states reachable from the start are checked.
+#[derive(Copy, Clone)]
+pub struct Audit {
+    pub limit: usize,
+    pub samples: Option<usize>,
+    pub seed: u64,
+}
+
+/// The values stored in the record of a state, which include the `win_by`
+/// margin of its `utility` only if the solution set records them.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub utility: Vec<Utility>,
+    pub remoteness: Remoteness,
+    pub win_by: Option<Utility>,
+}
+
+/// A record in a solution set which does not agree with the game it was
+/// computed for, identified by the string encoding of its state.
+pub enum Inconsistency {
+    /// The state is reachable from the start of the game, but has no record.
+    Missing { state: String },
+
+    /// The record of the state holds the `found` outcome, while the `expected`
+    /// one follows from the game (for end states) or from the records of its
+    /// children (for all other states).
+    Mismatch {
+        state: String,
+        found: Outcome,
+        expected: Outcome,
+    },
+}
+
+/// The result of verifying a solution set, which had the records of `checked`
+/// states checked and was found to have `inconsistencies`.
+#[derive(Default)]
+pub struct Report {
+    pub checked: u64,
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+/// The order in which the states of a game are visited for verification,
+/// which is either a depth-first traversal of all states reachable from the
+/// `start` state, or a sequence of random walks from it (see `Audit`).
+pub struct Frontier {
+    start: State,
+    stack: Vec<State>,
+    visited: HashSet<State>,
+    walks: Option<usize>,
+    seed: u64,
+}
+
+/* LOADING */
+
+/// Loads the table of the solution set of `game` persisted in `directory` into
+/// `db`, and selects it. Returns the policy that the solution set was computed
+/// under, as recorded in the table.
+pub fn load<D, G>(db: &mut D, game: &G, directory: &Path) -> Result<Policy>
+where
+    D: Tabular + Persistent,
+    G: Game,
+{
+    let id = game.id();
+    db.bind_path(directory)
+        .context("Failed to bind database to solution set directory.")?;
+    db.load_table(&id)
+        .context("Failed to load solution set table.")?;
+    db.select_table(&id)
+        .context("Failed to select solution set table.")?;
+
+    let schema = db
+        .schema()
+        .context("Solution set table has no schema.")?;
+    let layout = Layout::from_schema(schema)
+        .context("Failed to read record layout from table schema.")?;
+
+    let recorded = db
+        .get_metadata(TIE_BREAK_KEY)
+        .unwrap_or_default();
+
+    let unrecognized = || SolverError::UnrecognizedPolicy {
+        game_id: id.clone(),
+        policy: recorded.to_owned(),
+    };
+
+    let policy = match recorded
+        .strip_prefix("random(seed=")
+        .and_then(|seed| seed.strip_suffix(')'))
+    {
+        Some(seed) => Policy::new(
+            TieBreak::Random,
+            seed.parse()
+                .map_err(|_| unrecognized())?,
+        ),
+        None => Policy::new(
+            TieBreak::from_str(recorded, false).map_err(|_| unrecognized())?,
+            0,
+        ),
+    };
+
+    Ok(Policy {
+        win_by: layout.win_by(),
+        ..policy
+    })
+}
+
+/* TRAVERSAL */
+
+impl Frontier {
+    /// Returns a frontier which visits the states reachable from `start` as
+    /// specified by `audit`.
+    pub fn new(start: State, audit: Audit) -> Self {
+        Self {
+            start,
+            stack: match audit.samples {
+                Some(_) => Vec::new(),
+                None => vec![start],
+            },
+            visited: HashSet::new(),
+            walks: audit.samples,
+            seed: audit.seed,
+        }
+    }
+
+    /// Returns the next state to visit, along with whether it is the first
+    /// time that it is visited. States are only visited more than once by
+    /// random walks, which may go through the same states.
+    pub fn next(&mut self) -> Option<(State, bool)> {
+        loop {
+            let state = match self.stack.pop() {
+                Some(state) => state,
+                None => match self.walks {
+                    Some(walks) if walks > 0 => {
+                        self.walks = Some(walks - 1);
+                        self.start
+                    },
+                    _ => return None,
+                },
+            };
+
+            let first = self.visited.insert(state);
+            if first || self.walks.is_some() {
+                return Some((state, first));
+            }
+        }
+    }
+
+    /// Continues from the last visited state to its `children`. Random walks
+    /// continue to one of them at random, while traversals continue to all of
+    /// those which have not been visited yet.
+    pub fn extend(&mut self, children: &[State]) {
+        if self.walks.is_none() {
+            self.stack.extend(
+                children
+                    .iter()
+                    .filter(|&child| !self.visited.contains(child)),
+            );
+        } else if !children.is_empty() {
+            let index = self.random() % children.len() as u64;
+            self.stack
+                .push(children[index as usize]);
+        }
+    }
+
+    /// Returns the next pseudo-random number generated from the seed of this
+    /// frontier (SplitMix64).
+    fn random(&mut self) -> u64 {
+        self.seed = self
+            .seed
+            .wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+/* AUXILIARY IMPLEMENTATIONS */
+
+impl Outcome {
+    /// Returns the outcome stored in `buf`.
+    pub fn of(buf: &RecordBuffer) -> Result<Self> {
+        let utility = (0..buf.players())
+            .map(|player| buf.get_utility(player))
+            .collect::<Result<Vec<Utility>>>()
+            .context("Failed to get utility from record.")?;
+
+        let win_by =
+            if buf.has_win_by() { Some(buf.get_win_by()?) } else { None };
+
+        Ok(Self {
+            utility,
+            remoteness: buf.get_remoteness(),
+            win_by,
+        })
+    }
+}
+
+impl Report {
+    /// Returns true if no inconsistencies were found.
+    pub fn consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "utility {:?}, remoteness {}",
+            self.utility, self.remoteness
+        )?;
+        if let Some(margin) = self.win_by {
+            write!(f, ", win-by {}", margin)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { state } => {
+                write!(
+                    f,
+                    "State '{}' is reachable but has no record.",
+                    state
+                )
+            },
+            Self::Mismatch {
+                state,
+                found,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "State '{}' has a record with {}, but {} was expected.",
+                    state, found, expected,
+                )
+            },
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Checked {} states, finding {} inconsistencies.",
+            self.checked,
+            self.inconsistencies.len()
+        )?;
+        for inconsistency in &self.inconsistencies {
+            write!(f, "\n- {}", inconsistency)?;
+        }
+        Ok(())
+    }
+}
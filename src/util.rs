@@ -8,45 +8,168 @@
 //! - Max Fierro, 4/9/2023 (maxfierro@berkeley.edu)
 
 use anyhow::{Context, Result};
-use clap::ValueEnum;
+use clap::builder::PossibleValuesParser;
 use serde_json::json;
 
-use std::{fmt::Display, process};
+use std::{fmt::Display, fs, path::Path, process};
 
 use crate::{
-    game::{zero_by, Game, GameData},
+    game::error::GameError,
+    game::{plugin, registry},
+    game::{Analysis, Entry, Game, GameData},
     interface::{IOMode, OutputMode},
+    model::StateCount,
 };
 
-/* DATA STRUCTURES */
-
-// Specifies the game offerings available through all interfaces.
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-pub enum GameModule {
-    ZeroBy,
-}
-
 /* SUBROUTINES */
 
-/// Fetches and initializes the correct game session based on an indicated
-/// `GameModule`, with the provided `variant`.
+/// Fetches and initializes a session of the game called `game` from the game
+/// registry, with the provided `variant`. If `misere` is set, the session
+/// returned will be of the misère version of the game variant.
 pub fn find_game(
-    game: GameModule,
+    game: &str,
     variant: Option<String>,
     from: Option<String>,
+    misere: bool,
 ) -> Result<Box<dyn Game>> {
-    match game {
-        GameModule::ZeroBy => {
-            let session = zero_by::Session::initialize(variant)
-                .context("Failed to initialize zero-by game session.")?;
-            if let Some(path) = from {
-                todo!()
-            }
-            Ok(Box::new(session))
-        },
+    let registration = registry::find(game)
+        .ok_or_else(|| GameError::GameNotFound {
+            input_game_name: game.to_owned(),
+        })
+        .context("Game not found.")?;
+
+    let mut session = (registration.session)(variant, misere)?;
+    if let Some(path) = from {
+        session.forward(read_history(Path::new(&path))?)?;
+    }
+    Ok(session)
+}
+
+/// Loads the game plugin at `path` and initializes a session of it with the
+/// provided `variant`. Misère versions of plugin games are not supported.
+pub fn find_plugin(
+    path: &Path,
+    variant: Option<String>,
+    from: Option<String>,
+    misere: bool,
+) -> Result<Box<dyn Game>> {
+    let mut session = plugin::Session::load(path, variant)
+        .context("Failed to initialize plugin game session.")?;
+
+    if let Some(path) = from {
+        session.forward(read_history(Path::new(&path))?)?;
+    }
+    if misere {
+        Err(GameError::MisereNotFound {
+            input_game_name: session.info().name,
+        })
+        .context("Misère version not found.")
+    } else {
+        Ok(Box::new(session))
     }
 }
 
+/// Strongly solves each of the variants in the family of `game`'s variant with
+/// at most `max_states` states (see `Game::variants`), initializing a session
+/// for each of them through `load`. Variants are solved as they are queried
+/// (see `Game::query`), which is under the default tie-breaking policy and
+/// execution resources. Prints a JSON summary table of the values and
+/// remoteness of the starting state of each variant, in increasing order of
+/// variant size.
+pub fn solve_variants<F>(
+    game: &dyn Game,
+    max_states: StateCount,
+    mode: IOMode,
+    load: F,
+) -> Result<()>
+where
+    F: Fn(String) -> Result<Box<dyn Game>>,
+{
+    let variants = game.variants(max_states);
+    if variants.is_empty() {
+        return Err(GameError::VariantsNotFound {
+            input_game_name: game.info().name,
+        })
+        .context("No variants to solve.");
+    }
+
+    let mut summary = Vec::with_capacity(variants.len());
+    for variant in variants {
+        let entry = load(variant.clone())?
+            .query(mode, None)
+            .context(format!("Failed to solve variant '{}'.", variant))?;
+
+        summary.push(json!({
+            "variant": variant,
+            "state": entry.state,
+            "utility": entry.utility,
+            "remoteness": entry.remoteness,
+        }));
+    }
+
+    println!("{}", json!(summary));
+    Ok(())
+}
+
+/// Returns the variant string to use for a game, which is the contents of the
+/// file at `rules` if it is provided, and `variant` otherwise. This allows long
+/// variant strings (such as game descriptions) to be kept in files.
+pub fn read_variant(
+    variant: Option<String>,
+    rules: Option<&Path>,
+) -> Result<Option<String>> {
+    if let Some(path) = rules {
+        let contents = fs::read_to_string(path).context(format!(
+            "Failed to read rules file '{}'.",
+            path.display()
+        ))?;
+        Ok(Some(contents))
+    } else {
+        Ok(variant)
+    }
+}
+
+/// Returns the state history in the file at `path`, which has the encoding of
+/// one state per line (starting from the starting state of a game variant), so
+/// that it can be used to advance game sessions (see `Game::forward`).
+pub fn read_history(path: &Path) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path).context(format!(
+        "Failed to read state history file '{}'.",
+        path.display()
+    ))?;
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Returns a parser which only accepts the names of the games in the game
+/// registry, for use in the arguments of the command line interface.
+pub fn game_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(registry::names())
+}
+
+/// Parses an amount of memory in bytes from `amount`, which can be suffixed by
+/// `K`, `M`, or `G` to express it in kibibytes, mebibytes, or gibibytes, for
+/// use in the arguments of the command line interface.
+pub fn parse_memory(amount: &str) -> Result<usize, String> {
+    let (digits, unit) = match amount.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&amount[..i], 1 << 10),
+        Some((i, 'M' | 'm')) => (&amount[..i], 1 << 20),
+        Some((i, 'G' | 'g')) => (&amount[..i], 1 << 30),
+        _ => (amount, 1),
+    };
+
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit))
+        .ok_or_else(|| format!("'{}' is not an amount of memory.", amount))
+}
+
 /// Prompts the user to confirm their operation as appropriate according to
 /// the arguments of the solve command. Only asks for confirmation for
 /// potentially destructive operations.
@@ -74,15 +197,57 @@ pub fn confirm_potential_overwrite(yes: bool, mode: IOMode) {
 }
 
 /// Prints the formatted game information according to a specified output
-/// format. Game information is provided by game implementations.
-pub fn print_game_info(game: GameModule, format: OutputMode) -> Result<()> {
-    find_game(game, None, None)
-        .context("Failed to initialize game session.")?
-        .info()
-        .print(format);
+/// format, or a listing of all available games if no `game` is provided. Game
+/// information is provided by the game registry, or by the game plugin at
+/// `plugin` if one is provided.
+pub fn print_game_info(
+    game: Option<&str>,
+    plugin: Option<&Path>,
+    format: OutputMode,
+) -> Result<()> {
+    if let Some(path) = plugin {
+        plugin::Session::load(path, None)
+            .context("Failed to initialize plugin game session.")?
+            .info()
+            .print(format);
+    } else if let Some(game) = game {
+        registry::find(game)
+            .ok_or_else(|| GameError::GameNotFound {
+                input_game_name: game.to_owned(),
+            })
+            .context("Game not found.")?
+            .data
+            .print(format);
+    } else {
+        print_game_listing(format);
+    }
     Ok(())
 }
 
+/// Prints the names and descriptions of all games in the game registry
+/// according to a specified output format.
+fn print_game_listing(format: OutputMode) {
+    let games = registry::games();
+    match format {
+        OutputMode::Extra => {
+            let content = games
+                .iter()
+                .map(|g| format!("\t{}:\n{}\n", g.data.name, g.data.about))
+                .collect::<Vec<String>>()
+                .join("\n");
+            println!("{}", content);
+        },
+        OutputMode::Json => {
+            let content = games
+                .iter()
+                .map(|g| json!({"game": g.data.name, "about": g.data.about}))
+                .collect::<Vec<_>>();
+            println!("{}", json!(content));
+        },
+        OutputMode::None => (),
+    }
+}
+
 /* IMPLEMENTATIONS */
 
 impl GameData<'_> {
@@ -105,6 +270,14 @@ impl GameData<'_> {
                     self.state_pattern
                 );
                 println!("{}", content);
+                if let (Some(protocol), Some(pattern)) =
+                    (self.move_protocol, self.move_pattern)
+                {
+                    println!(
+                        "\tMove Protocol:\n{}\n\n\tMove Pattern:\n{}\n",
+                        protocol, pattern
+                    );
+                }
             },
             OutputMode::Json => {
                 let content = json!({
@@ -117,6 +290,8 @@ impl GameData<'_> {
                     "state-protocol": self.state_protocol,
                     "state-default": self.state_default,
                     "state-pattern": self.state_pattern,
+                    "move-protocol": self.move_protocol,
+                    "move-pattern": self.move_pattern,
                 });
                 println!("{}", content);
             },
@@ -125,6 +300,98 @@ impl GameData<'_> {
     }
 }
 
+impl Analysis {
+    pub fn print(&self, format: OutputMode) {
+        match format {
+            OutputMode::Extra => {
+                let content = format!(
+                    "\tAnalyzer:\n{}\n\n\tReachable States:\n{}\n\n\t\
+                    Stored States:\n{}\n\n\tStates Saved:\n{}\n",
+                    self.analyzer,
+                    self.states,
+                    self.stored,
+                    self.states - self.stored,
+                );
+                println!("{}", content);
+            },
+            OutputMode::Json => {
+                let content = json!({
+                    "analyzer": self.analyzer.to_string(),
+                    "states": self.states,
+                    "stored": self.stored,
+                    "saved": self.states - self.stored,
+                });
+                println!("{}", content);
+            },
+            OutputMode::None => (),
+        }
+    }
+}
+
+impl Entry {
+    pub fn print(&self, format: OutputMode) {
+        match format {
+            OutputMode::Extra => {
+                let content = format!(
+                    "\tState:\n{}\n\n\tUtility:\n{}\n\n\tRemoteness:\n{}\n",
+                    self.state,
+                    self.utility
+                        .iter()
+                        .map(|u| u.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    self.remoteness,
+                );
+                println!("{}", content);
+                if !self.adjusted.is_empty() {
+                    let adjusted = self
+                        .adjusted
+                        .iter()
+                        .map(|u| u.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    println!("\tAdjusted Utility:\n{}\n", adjusted);
+                }
+                if !self.moves.is_empty() {
+                    println!("\tMoves:\n{}\n", self.moves.join(", "));
+                }
+                if !self.supports.is_empty() {
+                    let supports = self
+                        .supports
+                        .iter()
+                        .enumerate()
+                        .map(|(i, s)| format!("P{}: {}", i, s.join(", ")))
+                        .collect::<Vec<String>>()
+                        .join("\n");
+                    println!("\tSupports:\n{}\n", supports);
+                }
+                if let Some(nim_value) = self.nim_value {
+                    println!("\tNim-value:\n{}\n", nim_value);
+                }
+            },
+            OutputMode::Json => {
+                let mut content = json!({
+                    "state": self.state,
+                    "utility": self.utility,
+                    "remoteness": self.remoteness,
+                    "moves": self.moves,
+                });
+                if !self.adjusted.is_empty() {
+                    content["adjusted"] = json!(self.adjusted);
+                }
+                if !self.supports.is_empty() {
+                    content["supports"] = json!(self.supports);
+                }
+                if let Some(nim_value) = self.nim_value {
+                    content["nim_value"] = json!(nim_value);
+                }
+                println!("{}", content);
+            },
+            OutputMode::None => (),
+        }
+    }
+}
+
 impl Display for GameData<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -142,7 +409,17 @@ impl Display for GameData<'_> {
             self.state_protocol,
             self.state_default,
             self.state_pattern
-        )
+        )?;
+        if let (Some(protocol), Some(pattern)) =
+            (self.move_protocol, self.move_pattern)
+        {
+            write!(
+                f,
+                "\n\tMove Protocol:\n{}\n\n\tMove Pattern:\n{}\n",
+                protocol, pattern
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -180,6 +457,42 @@ macro_rules! implement {
     }
 }
 
+/// Syntax sugar. Adds a game to the game registry (see `game::registry`) at
+/// compile time, making it available through all interfaces. Takes the static
+/// `GameData` of the game (with its default variant) and a function which
+/// initializes a boxed session of the game given a variant and a misère flag.
+///
+/// Example usage:
+///
+/// ```no_run
+/// const DATA: GameData = GameData { ... };
+///
+/// fn session(variant: Option<String>, misere: bool) -> Result<Box<dyn Game>> {
+///     ...
+/// }
+///
+/// register! { DATA => session }
+/// ```
+///
+/// ...which expands to the following:
+///
+/// ```no_run
+/// inventory::submit! {
+///     Registration { data: DATA, session }
+/// }
+/// ```
+#[macro_export]
+macro_rules! register {
+    ($data:expr => $session:expr) => {
+        inventory::submit! {
+            $crate::game::registry::Registration {
+                data: $data,
+                session: $session,
+            }
+        }
+    };
+}
+
 /// Syntax sugar. Allows a "literal-like" declaration of collections like
 /// `HashSet`s, `HashMap`s, `Vec`s, etc.
 ///
@@ -243,3 +556,30 @@ macro_rules! schema {
             .build()
     };
 }
+
+/// Syntax sugar. Invokes the macro `$generator` with each player count that
+/// solvers are generated for in games whose player count is only known when
+/// they are initialized (see `solver::SOLVABLE_PLAYERS`). Every such count is
+/// compiled into each of their solvers, so the range is kept small.
+///
+/// Example usage:
+///
+/// ```no_run
+/// macro_rules! players {
+///     ($($n:literal),+) => { ... };
+/// }
+///
+/// solvable! { players }
+/// ```
+///
+/// ...which expands to the following:
+///
+/// ```no_run
+/// players! { 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14 }
+/// ```
+#[macro_export]
+macro_rules! solvable {
+    ($generator:ident) => {
+        $generator! { 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14 }
+    };
+}